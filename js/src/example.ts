@@ -95,6 +95,7 @@ const initUnlock = async () => {
     getProgramId(connection),
     // @ts-ignore
     Buffer.from(LOCK_SEED),
+    wallet.publicKey,
   );
 
   const tx = await signAndSendInstructions(connection, [], wallet, instruction);
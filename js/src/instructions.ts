@@ -1,9 +1,12 @@
 import {
+  AccountMeta,
   PublicKey,
+  SystemProgram,
   SYSVAR_RENT_PUBKEY,
   TransactionInstruction,
 } from '@solana/web3.js';
-import { CreateSchedule } from './state';
+import { CreateSchedule, Hook } from './state';
+import { Numberu32, Numberu64 } from './utils';
 
 export enum Instruction {
   Init,
@@ -50,6 +53,28 @@ export function createInitInstruction(
   });
 }
 
+// How the source token account owner of a `Create` instruction passes the allowlist of the
+// config: with its allowlist entry account, or with a Merkle proof against the allowlist root
+export type AllowlistAccess =
+  | { allowlistEntry: PublicKey }
+  | { proof: Array<Buffer> };
+
+// The referrer credited with a `Create` instruction: its referrer account and, while the config
+// pays a referral share of the deposit fee, its token account receiving the share
+export type Referral = {
+  referrer: PublicKey;
+  referrerAccount: PublicKey;
+  referrerTokenAccount: PublicKey | null;
+};
+
+// The position token minted by a `Create` instruction: its mint, with no supply, no decimals, no
+// freeze authority and the vesting account as mint authority, and the token account of the
+// beneficiary token account owner receiving it
+export type Position = {
+  mint: PublicKey;
+  tokenAccount: PublicKey;
+};
+
 export function createCreateInstruction(
   vestingProgramId: PublicKey,
   tokenProgramId: PublicKey,
@@ -58,12 +83,69 @@ export function createCreateInstruction(
   vestingTokenAccountKey: PublicKey,
   sourceTokenAccountOwnerKey: PublicKey,
   sourceTokenAccountKey: PublicKey,
+  beneficiaryTokenAccountKey: PublicKey,
+  configAccountKey: PublicKey,
+  statsAccountKey: PublicKey,
+  registryAccountKey: PublicKey,
+  receiptMintKey: PublicKey,
+  receiptTokenAccountKey: PublicKey,
   schedule: CreateSchedule,
   seeds: Array<Buffer | Uint8Array>,
+  multiSigners: Array<PublicKey> = [],
+  revocationAuthority: PublicKey | null = null,
+  keeperTip: Numberu64 = new Numberu64(0),
+  autoRenew: boolean = false,
+  hook: Hook | null = null,
+  allowlist: AllowlistAccess | null = null,
+  depositFeeTreasuryKey: PublicKey | null = null,
+  referral: Referral | null = null,
+  position: Position | null = null,
 ): TransactionInstruction {
   let buffers = [Buffer.from(Int8Array.from([1]).buffer), Buffer.concat(seeds)];
 
   buffers.push(schedule.toBuffer());
+  // Optional authority allowed to revoke the lock
+  if (revocationAuthority) {
+    buffers.push(Buffer.from([1]), revocationAuthority.toBuffer());
+  } else {
+    buffers.push(Buffer.from([0]));
+  }
+  // Lamports held by the vesting account on top of its rent, paid to the account releasing the
+  // lock once it matures
+  buffers.push(keeperTip.toBuffer());
+  // Whether the lock renews for another period at each maturity
+  buffers.push(Buffer.from([autoRenew ? 1 : 0]));
+  // Optional program invoked after the creation, initial unlock and unlock of the lock
+  if (hook) {
+    buffers.push(
+      Buffer.from([1]),
+      hook.programId.toBuffer(),
+      Buffer.from([hook.mode]),
+    );
+  } else {
+    buffers.push(Buffer.from([0]));
+  }
+  // Optional Merkle proof of the source token account owner against the allowlist root
+  if (allowlist && 'proof' in allowlist) {
+    buffers.push(
+      Buffer.from([1]),
+      new Numberu32(allowlist.proof.length).toBuffer(),
+      ...allowlist.proof,
+    );
+  } else {
+    buffers.push(Buffer.from([0]));
+  }
+  // Optional wallet credited with the lock
+  if (referral) {
+    buffers.push(Buffer.from([1]), referral.referrer.toBuffer());
+  } else {
+    buffers.push(Buffer.from([0]));
+  }
+  // Whether the position token of the lock is minted to the beneficiary
+  buffers.push(Buffer.from([position ? 1 : 0]));
+  // Number of signer accounts of a multisig source token account owner, following the other
+  // accounts
+  buffers.push(Buffer.from([multiSigners.length]));
 
   const data = Buffer.concat(buffers);
   const keys = [
@@ -89,7 +171,7 @@ export function createCreateInstruction(
     },
     {
       pubkey: sourceTokenAccountOwnerKey,
-      isSigner: true,
+      isSigner: multiSigners.length === 0,
       isWritable: false,
     },
     {
@@ -97,6 +179,97 @@ export function createCreateInstruction(
       isSigner: false,
       isWritable: true,
     },
+    {
+      pubkey: beneficiaryTokenAccountKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: statsAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: registryAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: receiptMintKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: receiptTokenAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    // Allowlist entry of the source token account owner
+    ...(allowlist && 'allowlistEntry' in allowlist
+      ? [
+          {
+            pubkey: allowlist.allowlistEntry,
+            isSigner: false,
+            isWritable: false,
+          },
+        ]
+      : []),
+    // Deposit fee treasury token account, while the config charges a deposit fee
+    ...(depositFeeTreasuryKey
+      ? [
+          {
+            pubkey: depositFeeTreasuryKey,
+            isSigner: false,
+            isWritable: true,
+          },
+        ]
+      : []),
+    // Referrer account, followed by the referrer token account while the config pays a referral
+    // share of the deposit fee
+    ...(referral
+      ? [
+          {
+            pubkey: referral.referrerAccount,
+            isSigner: false,
+            isWritable: true,
+          },
+        ]
+      : []),
+    ...(referral?.referrerTokenAccount
+      ? [
+          {
+            pubkey: referral.referrerTokenAccount,
+            isSigner: false,
+            isWritable: true,
+          },
+        ]
+      : []),
+    // Position mint, followed by the token account receiving the position token
+    ...(position
+      ? [
+          {
+            pubkey: position.mint,
+            isSigner: false,
+            isWritable: true,
+          },
+          {
+            pubkey: position.tokenAccount,
+            isSigner: false,
+            isWritable: true,
+          },
+        ]
+      : []),
+    // Signers of a multisig source token account owner
+    ...multiSigners.map(signer => ({
+      pubkey: signer,
+      isSigner: true,
+      isWritable: false,
+    })),
   ];
   return new TransactionInstruction({
     keys,
@@ -105,6 +278,57 @@ export function createCreateInstruction(
   });
 }
 
+// Appends the hook program account of a lock, its hook authority and the accounts forwarded to
+// its hook to a `Create`, `InitializeUnlock` or `Unlock` instruction, after all its other accounts
+export function withHook(
+  instruction: TransactionInstruction,
+  hookProgramId: PublicKey,
+  hookAuthority: PublicKey,
+  hookAccounts: Array<AccountMeta> = [],
+): TransactionInstruction {
+  instruction.keys.push(
+    {
+      pubkey: hookProgramId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: hookAuthority,
+      isSigner: false,
+      isWritable: false,
+    },
+    ...hookAccounts,
+  );
+  return instruction;
+}
+
+// The accounts of the holder of a position token, releasing its lock
+export type PositionHolder = {
+  positionMint: PublicKey;
+  positionTokenAccount: PublicKey;
+  holder: PublicKey;
+};
+
+function positionHolderKeys(positionHolder: PositionHolder) {
+  return [
+    {
+      pubkey: positionHolder.positionMint,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: positionHolder.positionTokenAccount,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: positionHolder.holder,
+      isSigner: true,
+      isWritable: false,
+    },
+  ];
+}
+
 export function createUnlockInstruction(
   vestingProgramId: PublicKey,
   tokenProgramId: PublicKey,
@@ -112,7 +336,11 @@ export function createUnlockInstruction(
   vestingAccountKey: PublicKey,
   vestingTokenAccountKey: PublicKey,
   destinationTokenAccountKey: PublicKey,
+  statsAccountKey: PublicKey,
+  registryAccountKey: PublicKey,
   seeds: Array<Buffer | Uint8Array>,
+  positionHolder: PositionHolder | null = null,
+  keeperKey: PublicKey | null = null,
 ): TransactionInstruction {
   const data = Buffer.concat([
     Buffer.from(Int8Array.from([2]).buffer),
@@ -145,7 +373,28 @@ export function createUnlockInstruction(
       isSigner: false,
       isWritable: true,
     },
+    {
+      pubkey: statsAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: registryAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
   ];
+  if (positionHolder) {
+    keys.push(...positionHolderKeys(positionHolder));
+  }
+  // The keeper receives the keeper tip of the lock
+  if (keeperKey) {
+    keys.push({
+      pubkey: keeperKey,
+      isSigner: false,
+      isWritable: true,
+    });
+  }
   return new TransactionInstruction({
     keys,
     programId: vestingProgramId,
@@ -160,7 +409,12 @@ export function createInitializeUnlockInstruction(
   vestingAccountKey: PublicKey,
   vestingTokenAccountKey: PublicKey,
   destinationTokenAccountKey: PublicKey,
+  statsAccountKey: PublicKey,
+  receiptMintKey: PublicKey,
+  receiptTokenAccountKey: PublicKey,
+  destinationTokenAccountOwnerKey: PublicKey,
   seeds: Array<Buffer | Uint8Array>,
+  positionHolder: PositionHolder | null = null,
 ): TransactionInstruction {
   const data = Buffer.concat([
     Buffer.from(Int8Array.from([3]).buffer),
@@ -193,7 +447,1446 @@ export function createInitializeUnlockInstruction(
       isSigner: false,
       isWritable: true,
     },
+    {
+      pubkey: statsAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: receiptMintKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: receiptTokenAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: destinationTokenAccountOwnerKey,
+      isSigner: true,
+      isWritable: false,
+    },
+  ];
+  if (positionHolder) {
+    keys.push(...positionHolderKeys(positionHolder));
+  }
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createRevokeInstruction(
+  vestingProgramId: PublicKey,
+  tokenProgramId: PublicKey,
+  clockSysvarId: PublicKey,
+  vestingAccountKey: PublicKey,
+  vestingTokenAccountKey: PublicKey,
+  destinationTokenAccountKey: PublicKey,
+  revocationAuthorityKey: PublicKey,
+  treasuryTokenAccountKey: PublicKey,
+  statsAccountKey: PublicKey,
+  registryAccountKey: PublicKey,
+  seeds: Array<Buffer | Uint8Array>,
+): TransactionInstruction {
+  const data = Buffer.concat([
+    Buffer.from(Int8Array.from([7]).buffer),
+    Buffer.concat(seeds),
+  ]);
+
+  const keys = [
+    {
+      pubkey: tokenProgramId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: clockSysvarId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: vestingAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: vestingTokenAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: destinationTokenAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: revocationAuthorityKey,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: treasuryTokenAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: statsAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: registryAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createEarlyUnlockInstruction(
+  vestingProgramId: PublicKey,
+  tokenProgramId: PublicKey,
+  clockSysvarId: PublicKey,
+  vestingAccountKey: PublicKey,
+  vestingTokenAccountKey: PublicKey,
+  destinationTokenAccountKey: PublicKey,
+  destinationTokenAccountOwnerKey: PublicKey,
+  configAccountKey: PublicKey,
+  penaltyTreasuryTokenAccountKey: PublicKey,
+  statsAccountKey: PublicKey,
+  registryAccountKey: PublicKey,
+  seeds: Array<Buffer | Uint8Array>,
+  positionHolder: PositionHolder | null = null,
+): TransactionInstruction {
+  const data = Buffer.concat([
+    Buffer.from(Int8Array.from([10]).buffer),
+    Buffer.concat(seeds),
+  ]);
+
+  const keys = [
+    {
+      pubkey: tokenProgramId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: clockSysvarId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: vestingAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: vestingTokenAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: destinationTokenAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: destinationTokenAccountOwnerKey,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: penaltyTreasuryTokenAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: statsAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: registryAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  if (positionHolder) {
+    keys.push(...positionHolderKeys(positionHolder));
+  }
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+// Stops the renewal of an auto-renewing lock during the notice period before its next maturity,
+// or the grace period after its last one, signed by the owner of its destination
+export function createOptOutRenewalInstruction(
+  vestingProgramId: PublicKey,
+  clockSysvarId: PublicKey,
+  vestingAccountKey: PublicKey,
+  destinationTokenAccountKey: PublicKey,
+  destinationTokenAccountOwnerKey: PublicKey,
+  seeds: Array<Buffer | Uint8Array>,
+  positionHolder: PositionHolder | null = null,
+): TransactionInstruction {
+  const data = Buffer.concat([
+    Buffer.from(Int8Array.from([26]).buffer),
+    Buffer.concat(seeds),
+  ]);
+
+  const keys = [
+    {
+      pubkey: clockSysvarId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: vestingAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: destinationTokenAccountKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: destinationTokenAccountOwnerKey,
+      isSigner: true,
+      isWritable: false,
+    },
+  ];
+  if (positionHolder) {
+    keys.push(...positionHolderKeys(positionHolder));
+  }
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export type BatchLock = {
+  vestingAccountKey: PublicKey;
+  vestingTokenAccountKey: PublicKey;
+  beneficiaryTokenAccountKey: PublicKey;
+  receiptTokenAccountKey: PublicKey;
+  registryAccountKey: PublicKey;
+  schedule: CreateSchedule;
+  seeds: Array<Buffer | Uint8Array>;
+};
+
+export function createCreateBatchInstruction(
+  vestingProgramId: PublicKey,
+  tokenProgramId: PublicKey,
+  clockSysvarId: PublicKey,
+  payerKey: PublicKey,
+  sourceTokenAccountOwnerKey: PublicKey,
+  sourceTokenAccountKey: PublicKey,
+  configAccountKey: PublicKey,
+  statsAccountKey: PublicKey,
+  receiptMintKey: PublicKey,
+  locks: Array<BatchLock>,
+  multiSigners: Array<PublicKey> = [],
+  depositFeeTreasuryKey: PublicKey | null = null,
+): TransactionInstruction {
+  let buffers = [
+    Buffer.from(Int8Array.from([4]).buffer),
+    new Numberu32(locks.length).toBuffer(),
+  ];
+  for (const lock of locks) {
+    buffers.push(Buffer.concat(lock.seeds), lock.schedule.toBuffer());
+  }
+
+  const data = Buffer.concat(buffers);
+  const keys = [
+    {
+      pubkey: SystemProgram.programId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: SYSVAR_RENT_PUBKEY,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: tokenProgramId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: clockSysvarId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: payerKey,
+      isSigner: true,
+      isWritable: true,
+    },
+    {
+      pubkey: sourceTokenAccountOwnerKey,
+      isSigner: multiSigners.length === 0,
+      isWritable: false,
+    },
+    {
+      pubkey: sourceTokenAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: statsAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: receiptMintKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    // Vesting, vesting token, beneficiary token, receipt token and registry accounts of every lock
+    ...locks.flatMap(lock => [
+      {
+        pubkey: lock.vestingAccountKey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: lock.vestingTokenAccountKey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: lock.beneficiaryTokenAccountKey,
+        isSigner: false,
+        isWritable: false,
+      },
+      {
+        pubkey: lock.receiptTokenAccountKey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: lock.registryAccountKey,
+        isSigner: false,
+        isWritable: true,
+      },
+    ]),
+    // Deposit fee treasury token account, while the config charges a deposit fee
+    ...(depositFeeTreasuryKey
+      ? [
+          {
+            pubkey: depositFeeTreasuryKey,
+            isSigner: false,
+            isWritable: true,
+          },
+        ]
+      : []),
+    // Signers of a multisig source token account owner
+    ...multiSigners.map(signer => ({
+      pubkey: signer,
+      isSigner: true,
+      isWritable: false,
+    })),
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createSetPausedInstruction(
+  vestingProgramId: PublicKey,
+  guardianKey: PublicKey,
+  configAccountKey: PublicKey,
+  paused: boolean,
+): TransactionInstruction {
+  const data = Buffer.from([11, paused ? 1 : 0]);
+
+  const keys = [
+    {
+      pubkey: guardianKey,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createSetEmergencyModeInstruction(
+  vestingProgramId: PublicKey,
+  adminKey: PublicKey,
+  configAccountKey: PublicKey,
+  enabled: boolean,
+): TransactionInstruction {
+  const data = Buffer.from([12, enabled ? 1 : 0]);
+
+  const keys = [
+    {
+      pubkey: adminKey,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createSetGuardianInstruction(
+  vestingProgramId: PublicKey,
+  adminKey: PublicKey,
+  configAccountKey: PublicKey,
+  guardianKey: PublicKey,
+): TransactionInstruction {
+  const data = Buffer.from([13]);
+
+  const keys = [
+    {
+      pubkey: adminKey,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: guardianKey,
+      isSigner: false,
+      isWritable: false,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createSetAllowlistInstruction(
+  vestingProgramId: PublicKey,
+  adminKey: PublicKey,
+  configAccountKey: PublicKey,
+  enabled: boolean,
+  root: Buffer,
+): TransactionInstruction {
+  const data = Buffer.concat([Buffer.from([27, enabled ? 1 : 0]), root]);
+
+  const keys = [
+    {
+      pubkey: adminKey,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createSetLimitsInstruction(
+  vestingProgramId: PublicKey,
+  adminKey: PublicKey,
+  configAccountKey: PublicKey,
+  maxTotalLocked: Numberu64,
+  maxLockedPerWallet: Numberu64,
+  minLockAmounts: Array<Numberu64>,
+): TransactionInstruction {
+  const data = Buffer.concat([
+    Buffer.from([30]),
+    maxTotalLocked.toBuffer(),
+    maxLockedPerWallet.toBuffer(),
+    ...minLockAmounts.map(amount => amount.toBuffer()),
+  ]);
+
+  const keys = [
+    {
+      pubkey: adminKey,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createSetDepositFeeInstruction(
+  vestingProgramId: PublicKey,
+  adminKey: PublicKey,
+  configAccountKey: PublicKey,
+  depositFeeTreasuryTokenAccountKey: PublicKey,
+  depositFeeBps: number,
+): TransactionInstruction {
+  const data = Buffer.alloc(3);
+  data.writeUInt8(31, 0);
+  data.writeUInt16LE(depositFeeBps, 1);
+
+  const keys = [
+    {
+      pubkey: adminKey,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: depositFeeTreasuryTokenAccountKey,
+      isSigner: false,
+      isWritable: false,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createSetReferralShareInstruction(
+  vestingProgramId: PublicKey,
+  adminKey: PublicKey,
+  configAccountKey: PublicKey,
+  referralShareBps: number,
+): TransactionInstruction {
+  const data = Buffer.alloc(3);
+  data.writeUInt8(33, 0);
+  data.writeUInt16LE(referralShareBps, 1);
+
+  const keys = [
+    {
+      pubkey: adminKey,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createAddAllowlistEntryInstruction(
+  systemProgramId: PublicKey,
+  vestingProgramId: PublicKey,
+  payerKey: PublicKey,
+  adminKey: PublicKey,
+  configAccountKey: PublicKey,
+  walletKey: PublicKey,
+  allowlistEntryKey: PublicKey,
+): TransactionInstruction {
+  const data = Buffer.from([28]);
+
+  const keys = [
+    {
+      pubkey: systemProgramId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: SYSVAR_RENT_PUBKEY,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: payerKey,
+      isSigner: true,
+      isWritable: true,
+    },
+    {
+      pubkey: adminKey,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: walletKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: allowlistEntryKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createRemoveAllowlistEntryInstruction(
+  vestingProgramId: PublicKey,
+  adminKey: PublicKey,
+  configAccountKey: PublicKey,
+  allowlistEntryKey: PublicKey,
+  recipientKey: PublicKey,
+): TransactionInstruction {
+  const data = Buffer.from([29]);
+
+  const keys = [
+    {
+      pubkey: adminKey,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: allowlistEntryKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: recipientKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createEmergencyUnlockInstruction(
+  vestingProgramId: PublicKey,
+  tokenProgramId: PublicKey,
+  clockSysvarId: PublicKey,
+  vestingAccountKey: PublicKey,
+  vestingTokenAccountKey: PublicKey,
+  destinationTokenAccountKey: PublicKey,
+  configAccountKey: PublicKey,
+  statsAccountKey: PublicKey,
+  registryAccountKey: PublicKey,
+  seeds: Array<Buffer | Uint8Array>,
+  positionHolder: PositionHolder | null = null,
+): TransactionInstruction {
+  const data = Buffer.concat([
+    Buffer.from(Int8Array.from([14]).buffer),
+    Buffer.concat(seeds),
+  ]);
+
+  const keys = [
+    {
+      pubkey: tokenProgramId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: clockSysvarId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: vestingAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: vestingTokenAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: destinationTokenAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: statsAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: registryAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  if (positionHolder) {
+    keys.push(...positionHolderKeys(positionHolder));
+  }
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createInitStatsInstruction(
+  vestingProgramId: PublicKey,
+  payerKey: PublicKey,
+  statsAccountKey: PublicKey,
+): TransactionInstruction {
+  const data = Buffer.from([15]);
+
+  const keys = [
+    {
+      pubkey: SystemProgram.programId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: SYSVAR_RENT_PUBKEY,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: payerKey,
+      isSigner: true,
+      isWritable: true,
+    },
+    {
+      pubkey: statsAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+// Returns the raw stats account data through the transaction return data
+export function createGetStatsInstruction(
+  vestingProgramId: PublicKey,
+  statsAccountKey: PublicKey,
+): TransactionInstruction {
+  const data = Buffer.from([16]);
+
+  const keys = [
+    {
+      pubkey: statsAccountKey,
+      isSigner: false,
+      isWritable: false,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+// Creates the position registry of the owner, does nothing if it already exists
+export function createInitRegistryInstruction(
+  vestingProgramId: PublicKey,
+  payerKey: PublicKey,
+  ownerKey: PublicKey,
+  registryAccountKey: PublicKey,
+): TransactionInstruction {
+  const data = Buffer.from([17]);
+
+  const keys = [
+    {
+      pubkey: SystemProgram.programId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: SYSVAR_RENT_PUBKEY,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: payerKey,
+      isSigner: true,
+      isWritable: true,
+    },
+    {
+      pubkey: ownerKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: registryAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+// Creates the referrer account of the referrer, does nothing if it already exists
+export function createInitReferrerInstruction(
+  vestingProgramId: PublicKey,
+  payerKey: PublicKey,
+  referrerKey: PublicKey,
+  referrerAccountKey: PublicKey,
+): TransactionInstruction {
+  const data = Buffer.from([32]);
+
+  const keys = [
+    {
+      pubkey: SystemProgram.programId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: SYSVAR_RENT_PUBKEY,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: payerKey,
+      isSigner: true,
+      isWritable: true,
+    },
+    {
+      pubkey: referrerKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: referrerAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createCloseInstruction(
+  vestingProgramId: PublicKey,
+  tokenProgramId: PublicKey,
+  vestingAccountKey: PublicKey,
+  vestingTokenAccountKey: PublicKey,
+  destinationTokenAccountKey: PublicKey,
+  destinationTokenAccountOwnerKey: PublicKey,
+  registryAccountKey: PublicKey,
+  seeds: Array<Buffer | Uint8Array>,
+): TransactionInstruction {
+  const data = Buffer.concat([
+    Buffer.from(Int8Array.from([18]).buffer),
+    Buffer.concat(seeds),
+  ]);
+
+  const keys = [
+    {
+      pubkey: tokenProgramId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: vestingAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: vestingTokenAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: destinationTokenAccountKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: destinationTokenAccountOwnerKey,
+      isSigner: true,
+      isWritable: true,
+    },
+    {
+      pubkey: registryAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export function createMintPositionInstruction(
+  vestingProgramId: PublicKey,
+  tokenProgramId: PublicKey,
+  vestingAccountKey: PublicKey,
+  destinationTokenAccountKey: PublicKey,
+  destinationTokenAccountOwnerKey: PublicKey,
+  positionMintKey: PublicKey,
+  positionTokenAccountKey: PublicKey,
+  seeds: Array<Buffer | Uint8Array>,
+): TransactionInstruction {
+  const data = Buffer.concat([
+    Buffer.from(Int8Array.from([19]).buffer),
+    Buffer.concat(seeds),
+  ]);
+
+  const keys = [
+    {
+      pubkey: tokenProgramId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: vestingAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: destinationTokenAccountKey,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: destinationTokenAccountOwnerKey,
+      isSigner: true,
+      isWritable: false,
+    },
+    {
+      pubkey: positionMintKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: positionTokenAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+// Creates the receipt mint, minting receipts 1:1 with the amount staked in unlocked stakes
+export function createInitReceiptMintInstruction(
+  vestingProgramId: PublicKey,
+  tokenProgramId: PublicKey,
+  payerKey: PublicKey,
+  receiptMintKey: PublicKey,
+  tokenMintKey: PublicKey,
+): TransactionInstruction {
+  const data = Buffer.from([20]);
+
+  const keys = [
+    {
+      pubkey: SystemProgram.programId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: SYSVAR_RENT_PUBKEY,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: tokenProgramId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: payerKey,
+      isSigner: true,
+      isWritable: true,
+    },
+    {
+      pubkey: receiptMintKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: tokenMintKey,
+      isSigner: false,
+      isWritable: false,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+// Moves `amount` tokens of a lock to a new lock with the same terms, released to the new
+// destination token account and recorded in the registry of its owner
+export function createSplitInstruction(
+  vestingProgramId: PublicKey,
+  tokenProgramId: PublicKey,
+  payerKey: PublicKey,
+  vestingAccountKey: PublicKey,
+  vestingTokenAccountKey: PublicKey,
+  newVestingAccountKey: PublicKey,
+  newVestingTokenAccountKey: PublicKey,
+  destinationTokenAccountKey: PublicKey,
+  destinationTokenAccountOwnerKey: PublicKey,
+  newDestinationTokenAccountKey: PublicKey,
+  statsAccountKey: PublicKey,
+  registryAccountKey: PublicKey,
+  newRegistryAccountKey: PublicKey,
+  configAccountKey: PublicKey,
+  seeds: Array<Buffer | Uint8Array>,
+  newSeeds: Array<Buffer | Uint8Array>,
+  amount: Numberu64,
+): TransactionInstruction {
+  const data = Buffer.concat([
+    Buffer.from(Int8Array.from([21]).buffer),
+    Buffer.concat(seeds),
+    Buffer.concat(newSeeds),
+    amount.toBuffer(),
+  ]);
+
+  const keys = [
+    {
+      pubkey: SystemProgram.programId,
+      isSigner: 0,
+      isWritable: 0,
+    },
+    {
+      pubkey: SYSVAR_RENT_PUBKEY,
+      isSigner: 0,
+      isWritable: 0,
+    },
+    {
+      pubkey: tokenProgramId,
+      isSigner: 0,
+      isWritable: 0,
+    },
+    {
+      pubkey: payerKey,
+      isSigner: 1,
+      isWritable: 1,
+    },
+    {
+      pubkey: vestingAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: vestingTokenAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: newVestingAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: newVestingTokenAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: destinationTokenAccountKey,
+      isSigner: 0,
+      isWritable: 0,
+    },
+    {
+      pubkey: destinationTokenAccountOwnerKey,
+      isSigner: 1,
+      isWritable: 0,
+    },
+    {
+      pubkey: newDestinationTokenAccountKey,
+      isSigner: 0,
+      isWritable: 0,
+    },
+    {
+      pubkey: statsAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: registryAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: newRegistryAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: 0,
+      isWritable: 0,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export type MergedLock = {
+  vestingAccountKey: PublicKey;
+  vestingTokenAccountKey: PublicKey;
+  seeds: Array<Buffer | Uint8Array>;
+};
+
+// Moves the tokens of locks with the same terms into a lock, closing their accounts
+export function createMergeInstruction(
+  vestingProgramId: PublicKey,
+  tokenProgramId: PublicKey,
+  vestingAccountKey: PublicKey,
+  vestingTokenAccountKey: PublicKey,
+  destinationTokenAccountKey: PublicKey,
+  destinationTokenAccountOwnerKey: PublicKey,
+  statsAccountKey: PublicKey,
+  registryAccountKey: PublicKey,
+  seeds: Array<Buffer | Uint8Array>,
+  sources: Array<MergedLock>,
+): TransactionInstruction {
+  const buffers = [
+    Buffer.from(Int8Array.from([22]).buffer),
+    Buffer.concat(seeds),
+    new Numberu32(sources.length).toBuffer(),
+  ];
+  for (const source of sources) {
+    buffers.push(Buffer.concat(source.seeds));
+  }
+  const data = Buffer.concat(buffers);
+
+  const keys = [
+    {
+      pubkey: tokenProgramId,
+      isSigner: 0,
+      isWritable: 0,
+    },
+    {
+      pubkey: vestingAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: vestingTokenAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: destinationTokenAccountKey,
+      isSigner: 0,
+      isWritable: 0,
+    },
+    {
+      pubkey: destinationTokenAccountOwnerKey,
+      isSigner: 1,
+      isWritable: 1,
+    },
+    {
+      pubkey: statsAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: registryAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+  ];
+  for (const source of sources) {
+    keys.push(
+      {
+        pubkey: source.vestingAccountKey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: source.vestingTokenAccountKey,
+        isSigner: false,
+        isWritable: true,
+      },
+    );
+  }
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+// Transfers a lock to a token account of another wallet. With `requireAccept`, the transfer only
+// takes effect once the owner of the new destination accepts it
+export function createTransferPositionInstruction(
+  vestingProgramId: PublicKey,
+  vestingAccountKey: PublicKey,
+  destinationTokenAccountKey: PublicKey,
+  destinationTokenAccountOwnerKey: PublicKey,
+  registryAccountKey: PublicKey,
+  newDestinationTokenAccountKey: PublicKey,
+  newRegistryAccountKey: PublicKey,
+  configAccountKey: PublicKey,
+  seeds: Array<Buffer | Uint8Array>,
+  requireAccept: boolean,
+): TransactionInstruction {
+  const data = Buffer.concat([
+    Buffer.from(Int8Array.from([23]).buffer),
+    Buffer.concat(seeds),
+    Buffer.from([requireAccept ? 1 : 0]),
+  ]);
+
+  const keys = [
+    {
+      pubkey: vestingAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: destinationTokenAccountKey,
+      isSigner: 0,
+      isWritable: 0,
+    },
+    {
+      pubkey: destinationTokenAccountOwnerKey,
+      isSigner: 1,
+      isWritable: 0,
+    },
+    {
+      pubkey: registryAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: newDestinationTokenAccountKey,
+      isSigner: 0,
+      isWritable: 0,
+    },
+    {
+      pubkey: newRegistryAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: 0,
+      isWritable: 0,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+// Accepts the pending transfer of a lock, signed by the owner of its new destination
+export function createAcceptPositionInstruction(
+  vestingProgramId: PublicKey,
+  vestingAccountKey: PublicKey,
+  destinationTokenAccountKey: PublicKey,
+  registryAccountKey: PublicKey,
+  newDestinationTokenAccountKey: PublicKey,
+  newDestinationTokenAccountOwnerKey: PublicKey,
+  newRegistryAccountKey: PublicKey,
+  configAccountKey: PublicKey,
+  seeds: Array<Buffer | Uint8Array>,
+): TransactionInstruction {
+  const data = Buffer.concat([
+    Buffer.from(Int8Array.from([24]).buffer),
+    Buffer.concat(seeds),
+  ]);
+
+  const keys = [
+    {
+      pubkey: vestingAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: destinationTokenAccountKey,
+      isSigner: 0,
+      isWritable: 0,
+    },
+    {
+      pubkey: registryAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: newDestinationTokenAccountKey,
+      isSigner: 0,
+      isWritable: 0,
+    },
+    {
+      pubkey: newDestinationTokenAccountOwnerKey,
+      isSigner: 1,
+      isWritable: 0,
+    },
+    {
+      pubkey: newRegistryAccountKey,
+      isSigner: 0,
+      isWritable: 1,
+    },
+    {
+      pubkey: configAccountKey,
+      isSigner: 0,
+      isWritable: 0,
+    },
+  ];
+  return new TransactionInstruction({
+    keys,
+    programId: vestingProgramId,
+    data,
+  });
+}
+
+export type UnlockedLock = {
+  vestingAccountKey: PublicKey;
+  vestingTokenAccountKey: PublicKey;
+  destinationTokenAccountKey: PublicKey;
+  registryAccountKey: PublicKey;
+  seeds: Array<Buffer | Uint8Array>;
+};
+
+// Releases the matured locks of a list to their destinations, skipping the others
+export function createUnlockManyInstruction(
+  vestingProgramId: PublicKey,
+  tokenProgramId: PublicKey,
+  clockSysvarId: PublicKey,
+  statsAccountKey: PublicKey,
+  keeperKey: PublicKey,
+  locks: Array<UnlockedLock>,
+): TransactionInstruction {
+  const buffers = [
+    Buffer.from(Int8Array.from([25]).buffer),
+    new Numberu32(locks.length).toBuffer(),
+  ];
+  for (const lock of locks) {
+    buffers.push(Buffer.concat(lock.seeds));
+  }
+  const data = Buffer.concat(buffers);
+
+  const keys = [
+    {
+      pubkey: tokenProgramId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: clockSysvarId,
+      isSigner: false,
+      isWritable: false,
+    },
+    {
+      pubkey: statsAccountKey,
+      isSigner: false,
+      isWritable: true,
+    },
+    {
+      pubkey: keeperKey,
+      isSigner: false,
+      isWritable: true,
+    },
   ];
+  for (const lock of locks) {
+    keys.push(
+      {
+        pubkey: lock.vestingAccountKey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: lock.vestingTokenAccountKey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: lock.destinationTokenAccountKey,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: lock.registryAccountKey,
+        isSigner: false,
+        isWritable: true,
+      },
+    );
+  }
   return new TransactionInstruction({
     keys,
     programId: vestingProgramId,
@@ -1,4 +1,5 @@
 import {
+  AccountMeta,
   PublicKey,
   SystemProgram,
   SYSVAR_CLOCK_PUBKEY,
@@ -7,16 +8,48 @@ import {
 } from '@solana/web3.js';
 import {
   createAssociatedTokenAccountInstruction,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMint2Instruction,
   TOKEN_PROGRAM_ID,
+  getAccount,
   getAssociatedTokenAddress,
+  getMinimumBalanceForRentExemptMint,
+  MINT_SIZE,
 } from '@solana/spl-token';
 import {
   createCreateInstruction,
   createInitInstruction,
   createUnlockInstruction,
   createInitializeUnlockInstruction,
+  createRevokeInstruction,
+  createEarlyUnlockInstruction,
+  createEmergencyUnlockInstruction,
+  createInitRegistryInstruction,
+  createInitReferrerInstruction,
+  createCloseInstruction,
+  createMintPositionInstruction,
+  createSplitInstruction,
+  createMergeInstruction,
+  createTransferPositionInstruction,
+  createAcceptPositionInstruction,
+  createUnlockManyInstruction,
+  createOptOutRenewalInstruction,
+  PositionHolder,
+  withHook,
+  AllowlistAccess,
+  Position,
+  Referral,
 } from './instructions';
-import { ContractInfo, CreateSchedule } from './state';
+import {
+  Config,
+  ContractInfo,
+  CreateSchedule,
+  Hook,
+  PositionRegistry,
+  Referrer,
+  Stats,
+} from './state';
+import { Numberu64 } from './utils';
 import bs58 from 'bs58';
 
 /**
@@ -46,7 +79,18 @@ export const DEVNET_TOKEN_MINT = new PublicKey(
  * @param payer The fee payer of the transaction
  * @param possibleSourceTokenPubkey The source token account (i.e where locked tokens are originating from), if null it defaults to the ATA
  * @param schedule The vesting schedule
- * @returns An array of `TransactionInstruction`
+ * @param sourceOwner The owner of the source token account, defaults to the payer
+ * @param multiSigners The signers of the source token account owner, if it is a multisig
+ * @param beneficiaryTokenPubkey The token account receiving the tokens on unlock, if null it defaults to the source token account
+ * @param revocationAuthority The authority allowed to revoke the lock, if null the lock is not revocable
+ * @param keeperTip Lamports deposited by the payer, paid to whoever releases the lock once it matures
+ * @param autoRenew Whether the lock renews for another period at each maturity, until its owner opts out with `optOutRenewal`
+ * @param hook The program invoked after the creation, initial unlock and unlock of the lock, if null the lock has no hook
+ * @param hookAccounts The accounts forwarded to the hook
+ * @param allowlistProof The Merkle proof of the source owner against the allowlist root, if null the allowlist entry of the source owner is used while the allowlist is enabled
+ * @param referrer The wallet credited with the lock, paid its share of the deposit fee to its associated token account, if null the lock has no referrer
+ * @param positionMint A new keypair address for the position mint of the lock, which signs the transaction, minting the position token to the beneficiary owner, if null the lock has no position token
+ * @returns An array of `TransactionInstruction`, creating the position registry of the beneficiary owner and, for an unlocked stake, its receipt token account if needed
  */
 export async function create(
   connection: Connection,
@@ -55,12 +99,23 @@ export async function create(
   payer: PublicKey,
   possibleSourceTokenPubkey: PublicKey | null,
   schedule: CreateSchedule,
+  sourceOwner: PublicKey = payer,
+  multiSigners: Array<PublicKey> = [],
+  beneficiaryTokenPubkey: PublicKey | null = null,
+  revocationAuthority: PublicKey | null = null,
+  keeperTip: Numberu64 = new Numberu64(0),
+  autoRenew: boolean = false,
+  hook: Hook | null = null,
+  hookAccounts: Array<AccountMeta> = [],
+  allowlistProof: Array<Buffer> | null = null,
+  referrer: PublicKey | null = null,
+  positionMint: PublicKey | null = null,
 ): Promise<Array<TransactionInstruction>> {
   // If no source token account was given, use the associated source account
   if (possibleSourceTokenPubkey == null) {
     possibleSourceTokenPubkey = await getAssociatedTokenAddress(
       isDevnetConnection(connection)? DEVNET_TOKEN_MINT : TOKEN_MINT,
-      payer,
+      sourceOwner,
       true,
     );
   }
@@ -92,6 +147,20 @@ export async function create(
     throw 'Contract already exists.';
   }
 
+  // The lock is recorded in the position registry of the beneficiary token account owner
+  const beneficiaryOwner = beneficiaryTokenPubkey
+    ? (await getAccount(connection, beneficiaryTokenPubkey)).owner
+    : sourceOwner;
+  const registryAccountKey = await getRegistryAddress(programId, beneficiaryOwner);
+
+  // Unlocked stakes mint their receipts to the beneficiary owner
+  const receiptMintKey = await getReceiptMintAddress(programId);
+  const receiptTokenAccountKey = await getAssociatedTokenAddress(
+    receiptMintKey,
+    beneficiaryOwner,
+    true,
+  );
+
   let instruction = [
     createInitInstruction(
       SystemProgram.programId,
@@ -106,32 +175,365 @@ export async function create(
       vestingAccountKey,
       isDevnetConnection(connection)? DEVNET_TOKEN_MINT : TOKEN_MINT,
     ),
-    createCreateInstruction(
+    createInitRegistryInstruction(
+      programId,
+      payer,
+      beneficiaryOwner,
+      registryAccountKey,
+    ),
+  ];
+  if (schedule.timeDelta.isZero()) {
+    instruction.push(
+      createAssociatedTokenAccountIdempotentInstruction(
+        payer,
+        receiptTokenAccountKey,
+        beneficiaryOwner,
+        receiptMintKey,
+      ),
+    );
+  }
+  // The keeper tip is held by the vesting account on top of its rent
+  if (!keeperTip.isZero()) {
+    instruction.push(
+      SystemProgram.transfer({
+        fromPubkey: payer,
+        toPubkey: vestingAccountKey,
+        lamports: keeperTip.toNumber(),
+      }),
+    );
+  }
+  // While the allowlist is enabled, the source owner passes it with a proof or its entry
+  const configAccountKey = await getConfigAddress(programId);
+  const configInfo = await connection.getAccountInfo(configAccountKey);
+  const config = configInfo ? Config.fromBuffer(configInfo.data) : undefined;
+  let allowlist: AllowlistAccess | null = null;
+  if (allowlistProof) {
+    allowlist = { proof: allowlistProof };
+  } else if (config?.isAllowlistEnabled) {
+    allowlist = {
+      allowlistEntry: await getAllowlistEntryAddress(programId, sourceOwner),
+    };
+  }
+
+  // The referrer account of the referrer is created if needed, and its associated token account
+  // receives its share of the deposit fee
+  let referral: Referral | null = null;
+  if (referrer) {
+    const referrerAccount = await getReferrerAddress(programId, referrer);
+    instruction.push(
+      createInitReferrerInstruction(programId, payer, referrer, referrerAccount),
+    );
+    referral = {
+      referrer,
+      referrerAccount,
+      referrerTokenAccount:
+        config && config.depositFeeBps > 0 && config.referralShareBps > 0
+          ? await getAssociatedTokenAddress(
+              isDevnetConnection(connection) ? DEVNET_TOKEN_MINT : TOKEN_MINT,
+              referrer,
+              true,
+            )
+          : null,
+    };
+  }
+
+  // The position mint is created with the vesting account as mint authority, and the position
+  // token minted to the associated token account of the beneficiary owner
+  let position: Position | null = null;
+  if (positionMint) {
+    position = {
+      mint: positionMint,
+      tokenAccount: await getAssociatedTokenAddress(positionMint, beneficiaryOwner),
+    };
+    instruction.push(
+      SystemProgram.createAccount({
+        fromPubkey: payer,
+        newAccountPubkey: positionMint,
+        lamports: await getMinimumBalanceForRentExemptMint(connection),
+        space: MINT_SIZE,
+        programId: TOKEN_PROGRAM_ID,
+      }),
+      createInitializeMint2Instruction(positionMint, 0, vestingAccountKey, null),
+      createAssociatedTokenAccountInstruction(
+        payer,
+        position.tokenAccount,
+        beneficiaryOwner,
+        positionMint,
+      ),
+    );
+  }
+
+  let createInstruction = createCreateInstruction(
+    programId,
+    TOKEN_PROGRAM_ID,
+    SYSVAR_CLOCK_PUBKEY,
+    vestingAccountKey,
+    vestingTokenAccountKey,
+    sourceOwner,
+    possibleSourceTokenPubkey,
+    beneficiaryTokenPubkey ?? possibleSourceTokenPubkey,
+    configAccountKey,
+    await getStatsAddress(programId),
+    registryAccountKey,
+    receiptMintKey,
+    receiptTokenAccountKey,
+    schedule,
+    [seedWord],
+    multiSigners,
+    revocationAuthority,
+    keeperTip,
+    autoRenew,
+    hook,
+    allowlist,
+    // While the config charges a deposit fee, its treasury receives the fee
+    config && config.depositFeeBps > 0 ? config.depositFeeTreasury : null,
+    referral,
+    position,
+  );
+  if (hook) {
+    createInstruction = withHook(
+      createInstruction,
+      hook.programId,
+      await getHookAuthorityAddress(programId, vestingAccountKey),
+      hookAccounts,
+    );
+  }
+  instruction.push(createInstruction);
+  return instruction;
+}
+
+/**
+ * This function can be used to unlock vested tokens
+ * @param connection The Solana RPC connection object
+ * @param programId The token vesting program ID
+ * @param seedWord Seed words used to derive the vesting account
+ * @param holder The holder of the position token of the lock, required if the lock has one
+ * @param keeper The account receiving the keeper tip of the lock, usually the fee payer
+ * @param hookAccounts The accounts forwarded to the hook of the lock, if it has one
+ * @returns An array of `TransactionInstruction`
+ */
+export async function unlock(
+  connection: Connection,
+  programId: PublicKey,
+  seedWord: Buffer | Uint8Array,
+  holder: PublicKey | null = null,
+  keeper: PublicKey | null = null,
+  hookAccounts: Array<AccountMeta> = [],
+): Promise<Array<TransactionInstruction>> {
+  seedWord = seedWord.slice(0, 31);
+  const [vestingAccountKey, bump] = await PublicKey.findProgramAddress(
+    [seedWord],
+    programId,
+  );
+  seedWord = Buffer.from(seedWord.toString('hex') + bump.toString(16), 'hex');
+
+  const vestingTokenAccountKey = await getAssociatedTokenAddress(
+    isDevnetConnection(connection)? DEVNET_TOKEN_MINT : TOKEN_MINT,
+    vestingAccountKey,
+    true,
+  );
+
+  const vestingInfo = await getContractInfo(connection, vestingAccountKey);
+  const [destinationTokenPubkey, positionHolder] = await releaseDestination(
+    connection,
+    vestingInfo,
+    holder,
+  );
+  const destinationOwner = (
+    await getAccount(connection, vestingInfo.destinationAddress)
+  ).owner;
+
+  let unlockInstruction = createUnlockInstruction(
+    programId,
+    TOKEN_PROGRAM_ID,
+    SYSVAR_CLOCK_PUBKEY,
+    vestingAccountKey,
+    vestingTokenAccountKey,
+    destinationTokenPubkey,
+    await getStatsAddress(programId),
+    await getRegistryAddress(programId, destinationOwner),
+    [seedWord],
+    positionHolder,
+    keeper,
+  );
+  if (vestingInfo.hook) {
+    unlockInstruction = withHook(
+      unlockInstruction,
+      vestingInfo.hook.programId,
+      await getHookAuthorityAddress(programId, vestingAccountKey),
+      hookAccounts,
+    );
+  }
+  let instruction = [unlockInstruction];
+
+  return instruction;
+}
+
+/**
+ * This function can be used to release the matured locks among several locks in one transaction,
+ * skipping the others. About seven locks fit in a transaction.
+ * @param connection The Solana RPC connection object
+ * @param programId The token vesting program ID
+ * @param seedWords Seed words used to derive the vesting accounts
+ * @param keeper The account receiving the keeper tips of the released locks, usually the fee payer
+ * @returns An array of `TransactionInstruction`
+ */
+export async function unlockMany(
+  connection: Connection,
+  programId: PublicKey,
+  seedWords: Array<Buffer | Uint8Array>,
+  keeper: PublicKey,
+): Promise<Array<TransactionInstruction>> {
+  const locks = [];
+  for (const seedWord of seedWords) {
+    const [vestingAccountKey, seeds] = await findVestingAccount(
+      programId,
+      seedWord,
+    );
+    const vestingInfo = await getContractInfo(connection, vestingAccountKey);
+    const destinationOwner = (
+      await getAccount(connection, vestingInfo.destinationAddress)
+    ).owner;
+    locks.push({
+      vestingAccountKey,
+      vestingTokenAccountKey: await getAssociatedTokenAddress(
+        isDevnetConnection(connection) ? DEVNET_TOKEN_MINT : TOKEN_MINT,
+        vestingAccountKey,
+        true,
+      ),
+      destinationTokenAccountKey: vestingInfo.destinationAddress,
+      registryAccountKey: await getRegistryAddress(programId, destinationOwner),
+      seeds: [seeds],
+    });
+  }
+
+  let instruction = [
+    createUnlockManyInstruction(
+      programId,
+      TOKEN_PROGRAM_ID,
+      SYSVAR_CLOCK_PUBKEY,
+      await getStatsAddress(programId),
+      keeper,
+      locks,
+    ),
+  ];
+
+  return instruction;
+}
+
+/**
+ * This function can be used to revoke a lock, releasing the vested tokens to its destination and
+ * the unvested ones to the treasury
+ * @param connection The Solana RPC connection object
+ * @param programId The token vesting program ID
+ * @param seedWord Seed words used to derive the vesting account
+ * @param revocationAuthority The revocation authority of the lock
+ * @param treasuryTokenPubkey The token account receiving the unvested tokens
+ * @returns An array of `TransactionInstruction`
+ */
+export async function revoke(
+  connection: Connection,
+  programId: PublicKey,
+  seedWord: Buffer | Uint8Array,
+  revocationAuthority: PublicKey,
+  treasuryTokenPubkey: PublicKey,
+): Promise<Array<TransactionInstruction>> {
+  seedWord = seedWord.slice(0, 31);
+  const [vestingAccountKey, bump] = await PublicKey.findProgramAddress(
+    [seedWord],
+    programId,
+  );
+  seedWord = Buffer.from(seedWord.toString('hex') + bump.toString(16), 'hex');
+
+  const vestingTokenAccountKey = await getAssociatedTokenAddress(
+    isDevnetConnection(connection)? DEVNET_TOKEN_MINT : TOKEN_MINT,
+    vestingAccountKey,
+    true,
+  );
+
+  const vestingInfo = await getContractInfo(connection, vestingAccountKey);
+  const destinationOwner = (
+    await getAccount(connection, vestingInfo.destinationAddress)
+  ).owner;
+
+  let instruction = [
+    createRevokeInstruction(
       programId,
       TOKEN_PROGRAM_ID,
       SYSVAR_CLOCK_PUBKEY,
       vestingAccountKey,
       vestingTokenAccountKey,
-      payer,
-      possibleSourceTokenPubkey,
-      schedule,
+      vestingInfo.destinationAddress,
+      revocationAuthority,
+      treasuryTokenPubkey,
+      await getStatsAddress(programId),
+      await getRegistryAddress(programId, destinationOwner),
       [seedWord],
     ),
   ];
+
   return instruction;
 }
 
 /**
- * This function can be used to unlock vested tokens
+ * This function can be used to stop the renewal of an auto-renewing lock during the notice period
+ * before its next maturity, when it is released instead, or during the grace period after its
+ * last maturity, when it is released at once
  * @param connection The Solana RPC connection object
  * @param programId The token vesting program ID
  * @param seedWord Seed words used to derive the vesting account
+ * @param destinationOwner The owner of the destination token account of the lock, or the holder of its position token
  * @returns An array of `TransactionInstruction`
  */
-export async function unlock(
+export async function optOutRenewal(
+  connection: Connection,
+  programId: PublicKey,
+  seedWord: Buffer | Uint8Array,
+  destinationOwner: PublicKey,
+): Promise<Array<TransactionInstruction>> {
+  seedWord = seedWord.slice(0, 31);
+  const [vestingAccountKey, bump] = await PublicKey.findProgramAddress(
+    [seedWord],
+    programId,
+  );
+  seedWord = Buffer.from(seedWord.toString('hex') + bump.toString(16), 'hex');
+
+  const vestingInfo = await getContractInfo(connection, vestingAccountKey);
+  const [destinationTokenPubkey, positionHolder] = await releaseDestination(
+    connection,
+    vestingInfo,
+    destinationOwner,
+  );
+
+  let instruction = [
+    createOptOutRenewalInstruction(
+      programId,
+      SYSVAR_CLOCK_PUBKEY,
+      vestingAccountKey,
+      destinationTokenPubkey,
+      destinationOwner,
+      [seedWord],
+      positionHolder,
+    ),
+  ];
+
+  return instruction;
+}
+
+/**
+ * This function can be used to unlock the tokens of a timed lock before its release time, paying a
+ * penalty to the treasury of the program config
+ * @param connection The Solana RPC connection object
+ * @param programId The token vesting program ID
+ * @param seedWord Seed words used to derive the vesting account
+ * @param destinationOwner The owner of the destination token account of the lock, or the holder of its position token
+ * @returns An array of `TransactionInstruction`
+ */
+export async function earlyUnlock(
   connection: Connection,
   programId: PublicKey,
   seedWord: Buffer | Uint8Array,
+  destinationOwner: PublicKey,
 ): Promise<Array<TransactionInstruction>> {
   seedWord = seedWord.slice(0, 31);
   const [vestingAccountKey, bump] = await PublicKey.findProgramAddress(
@@ -147,16 +549,32 @@ export async function unlock(
   );
 
   const vestingInfo = await getContractInfo(connection, vestingAccountKey);
+  const [destinationTokenPubkey, positionHolder] = await releaseDestination(
+    connection,
+    vestingInfo,
+    destinationOwner,
+  );
+  const configAccountKey = await getConfigAddress(programId);
+  const config = await getConfig(connection, configAccountKey);
+  const registryOwner = (
+    await getAccount(connection, vestingInfo.destinationAddress)
+  ).owner;
 
   let instruction = [
-    createUnlockInstruction(
+    createEarlyUnlockInstruction(
       programId,
       TOKEN_PROGRAM_ID,
       SYSVAR_CLOCK_PUBKEY,
       vestingAccountKey,
       vestingTokenAccountKey,
-      vestingInfo.destinationAddress,
+      destinationTokenPubkey,
+      destinationOwner,
+      configAccountKey,
+      config.penaltyTreasury,
+      await getStatsAddress(programId),
+      await getRegistryAddress(programId, registryOwner),
       [seedWord],
+      positionHolder,
     ),
   ];
 
@@ -164,17 +582,19 @@ export async function unlock(
 }
 
 /**
- * This function can be used to initialize the unlock of vested tokens
+ * This function can be used to unlock all the tokens of a lock while the program is in emergency
+ * mode, whatever its release time
  * @param connection The Solana RPC connection object
  * @param programId The token vesting program ID
  * @param seedWord Seed words used to derive the vesting account
- * @param mintAddress The mint of the vested tokens
+ * @param holder The holder of the position token of the lock, required if the lock has one
  * @returns An array of `TransactionInstruction`
  */
-export async function initializeUnlock(
+export async function emergencyUnlock(
   connection: Connection,
   programId: PublicKey,
   seedWord: Buffer | Uint8Array,
+  holder: PublicKey | null = null,
 ): Promise<Array<TransactionInstruction>> {
   seedWord = seedWord.slice(0, 31);
   const [vestingAccountKey, bump] = await PublicKey.findProgramAddress(
@@ -190,15 +610,73 @@ export async function initializeUnlock(
   );
 
   const vestingInfo = await getContractInfo(connection, vestingAccountKey);
+  const [destinationTokenPubkey, positionHolder] = await releaseDestination(
+    connection,
+    vestingInfo,
+    holder,
+  );
+  const destinationOwner = (
+    await getAccount(connection, vestingInfo.destinationAddress)
+  ).owner;
 
   let instruction = [
-    createInitializeUnlockInstruction(
+    createEmergencyUnlockInstruction(
       programId,
       TOKEN_PROGRAM_ID,
       SYSVAR_CLOCK_PUBKEY,
       vestingAccountKey,
       vestingTokenAccountKey,
+      destinationTokenPubkey,
+      await getConfigAddress(programId),
+      await getStatsAddress(programId),
+      await getRegistryAddress(programId, destinationOwner),
+      [seedWord],
+      positionHolder,
+    ),
+  ];
+
+  return instruction;
+}
+
+/**
+ * This function can be used to close a released or revoked lock, removing it from the position
+ * registry of the owner of its destination token account
+ * @param connection The Solana RPC connection object
+ * @param programId The token vesting program ID
+ * @param seedWord Seed words used to derive the vesting account
+ * @param destinationOwner The owner of the destination token account of the lock, receiving the rent of its accounts
+ * @returns An array of `TransactionInstruction`
+ */
+export async function close(
+  connection: Connection,
+  programId: PublicKey,
+  seedWord: Buffer | Uint8Array,
+  destinationOwner: PublicKey,
+): Promise<Array<TransactionInstruction>> {
+  seedWord = seedWord.slice(0, 31);
+  const [vestingAccountKey, bump] = await PublicKey.findProgramAddress(
+    [seedWord],
+    programId,
+  );
+  seedWord = Buffer.from(seedWord.toString('hex') + bump.toString(16), 'hex');
+
+  const vestingTokenAccountKey = await getAssociatedTokenAddress(
+    isDevnetConnection(connection)? DEVNET_TOKEN_MINT : TOKEN_MINT,
+    vestingAccountKey,
+    true,
+  );
+
+  const vestingInfo = await getContractInfo(connection, vestingAccountKey);
+
+  let instruction = [
+    createCloseInstruction(
+      programId,
+      TOKEN_PROGRAM_ID,
+      vestingAccountKey,
+      vestingTokenAccountKey,
       vestingInfo.destinationAddress,
+      destinationOwner,
+      await getRegistryAddress(programId, destinationOwner),
       [seedWord],
     ),
   ];
@@ -207,28 +685,671 @@ export async function initializeUnlock(
 }
 
 /**
- * This function can be used retrieve information about a vesting account
+ * Derives the vesting account of a seed word, returning it with the seeds signing for it
+ */
+async function findVestingAccount(
+  programId: PublicKey,
+  seedWord: Buffer | Uint8Array,
+): Promise<[PublicKey, Buffer]> {
+  seedWord = seedWord.slice(0, 31);
+  const [vestingAccountKey, bump] = await PublicKey.findProgramAddress(
+    [seedWord],
+    programId,
+  );
+  return [
+    vestingAccountKey,
+    Buffer.from(seedWord.toString('hex') + bump.toString(16), 'hex'),
+  ];
+}
+
+/**
+ * This function can be used to split an amount off a lock into a new lock with the same terms
  * @param connection The Solana RPC connection object
- * @param vestingAccountKey The vesting account public key
- * @returns A `ContractInfo` object
+ * @param programId The token vesting program ID
+ * @param seedWord Seed words used to derive the vesting account
+ * @param newSeedWord Seed words used to derive the new vesting account
+ * @param payer The fee payer of the transaction
+ * @param destinationOwner The owner of the lock
+ * @param amount The amount moved to the new lock, less than the amount of the lock
+ * @param newDestinationTokenPubkey The token account receiving the tokens of the new lock, if null it defaults to the destination of the lock
+ * @param newDestinationOwner The owner of the new destination token account, defaults to the destination owner
+ * @returns An array of `TransactionInstruction`, creating the vesting token account of the new lock and the position registry of the new destination owner if needed
  */
-export async function getContractInfo(
+export async function split(
   connection: Connection,
-  vestingAccountKey: PublicKey,
-): Promise<ContractInfo> {
-  console.log('Fetching contract ', vestingAccountKey.toBase58());
-  const vestingInfo = await connection.getAccountInfo(
+  programId: PublicKey,
+  seedWord: Buffer | Uint8Array,
+  newSeedWord: Buffer | Uint8Array,
+  payer: PublicKey,
+  destinationOwner: PublicKey,
+  amount: Numberu64,
+  newDestinationTokenPubkey: PublicKey | null = null,
+  newDestinationOwner: PublicKey = destinationOwner,
+): Promise<Array<TransactionInstruction>> {
+  const mint = isDevnetConnection(connection) ? DEVNET_TOKEN_MINT : TOKEN_MINT;
+  const [vestingAccountKey, seeds] = await findVestingAccount(
+    programId,
+    seedWord,
+  );
+  const [newVestingAccountKey, newSeeds] = await findVestingAccount(
+    programId,
+    newSeedWord,
+  );
+  const vestingTokenAccountKey = await getAssociatedTokenAddress(
+    mint,
     vestingAccountKey,
-    'single',
+    true,
   );
-  if (!vestingInfo) {
-    throw new Error('Vesting contract account is unavailable');
-  }
-  const info = ContractInfo.fromBuffer(vestingInfo!.data);
-  if (!info) {
-    throw new Error('Vesting contract account is not initialized');
-  }
-  return info!;
+  const newVestingTokenAccountKey = await getAssociatedTokenAddress(
+    mint,
+    newVestingAccountKey,
+    true,
+  );
+
+  const vestingInfo = await getContractInfo(connection, vestingAccountKey);
+  const newRegistryAccountKey = await getRegistryAddress(
+    programId,
+    newDestinationOwner,
+  );
+
+  let instruction = [
+    createAssociatedTokenAccountInstruction(
+      payer,
+      newVestingTokenAccountKey,
+      newVestingAccountKey,
+      mint,
+    ),
+    createInitRegistryInstruction(
+      programId,
+      payer,
+      newDestinationOwner,
+      newRegistryAccountKey,
+    ),
+    createSplitInstruction(
+      programId,
+      TOKEN_PROGRAM_ID,
+      payer,
+      vestingAccountKey,
+      vestingTokenAccountKey,
+      newVestingAccountKey,
+      newVestingTokenAccountKey,
+      vestingInfo.destinationAddress,
+      destinationOwner,
+      newDestinationTokenPubkey ?? vestingInfo.destinationAddress,
+      await getStatsAddress(programId),
+      await getRegistryAddress(programId, destinationOwner),
+      newRegistryAccountKey,
+      await getConfigAddress(programId),
+      [seeds],
+      [newSeeds],
+      amount,
+    ),
+  ];
+
+  return instruction;
+}
+
+/**
+ * This function can be used to merge locks with the same destination, tier and release time into
+ * a lock, closing their accounts
+ * @param connection The Solana RPC connection object
+ * @param programId The token vesting program ID
+ * @param seedWord Seed words used to derive the vesting account the locks are merged into
+ * @param sourceSeedWords Seed words used to derive the vesting accounts of the merged locks
+ * @param destinationOwner The owner of the destination token account of the locks, receiving the rent of the merged locks
+ * @returns An array of `TransactionInstruction`
+ */
+export async function merge(
+  connection: Connection,
+  programId: PublicKey,
+  seedWord: Buffer | Uint8Array,
+  sourceSeedWords: Array<Buffer | Uint8Array>,
+  destinationOwner: PublicKey,
+): Promise<Array<TransactionInstruction>> {
+  const mint = isDevnetConnection(connection) ? DEVNET_TOKEN_MINT : TOKEN_MINT;
+  const [vestingAccountKey, seeds] = await findVestingAccount(
+    programId,
+    seedWord,
+  );
+  const vestingTokenAccountKey = await getAssociatedTokenAddress(
+    mint,
+    vestingAccountKey,
+    true,
+  );
+  const sources = [];
+  for (const sourceSeedWord of sourceSeedWords) {
+    const [sourceVestingAccountKey, sourceSeeds] = await findVestingAccount(
+      programId,
+      sourceSeedWord,
+    );
+    sources.push({
+      vestingAccountKey: sourceVestingAccountKey,
+      vestingTokenAccountKey: await getAssociatedTokenAddress(
+        mint,
+        sourceVestingAccountKey,
+        true,
+      ),
+      seeds: [sourceSeeds],
+    });
+  }
+
+  const vestingInfo = await getContractInfo(connection, vestingAccountKey);
+
+  let instruction = [
+    createMergeInstruction(
+      programId,
+      TOKEN_PROGRAM_ID,
+      vestingAccountKey,
+      vestingTokenAccountKey,
+      vestingInfo.destinationAddress,
+      destinationOwner,
+      await getStatsAddress(programId),
+      await getRegistryAddress(programId, destinationOwner),
+      [seeds],
+      sources,
+    ),
+  ];
+
+  return instruction;
+}
+
+/**
+ * This function can be used to transfer a lock to a token account of another wallet
+ * @param connection The Solana RPC connection object
+ * @param programId The token vesting program ID
+ * @param seedWord Seed words used to derive the vesting account
+ * @param destinationOwner The owner of the lock
+ * @param newDestinationTokenPubkey The token account the lock is released to once transferred, the destination of the lock to cancel a pending transfer
+ * @param requireAccept Whether the owner of the new destination token account has to accept the transfer with `acceptPosition`
+ * @returns An array of `TransactionInstruction`, creating the position registry of the new owner if needed
+ */
+export async function transferPosition(
+  connection: Connection,
+  programId: PublicKey,
+  seedWord: Buffer | Uint8Array,
+  destinationOwner: PublicKey,
+  newDestinationTokenPubkey: PublicKey,
+  requireAccept: boolean = false,
+): Promise<Array<TransactionInstruction>> {
+  const [vestingAccountKey, seeds] = await findVestingAccount(
+    programId,
+    seedWord,
+  );
+  const vestingInfo = await getContractInfo(connection, vestingAccountKey);
+  const newDestinationOwner = (
+    await getAccount(connection, newDestinationTokenPubkey)
+  ).owner;
+  const newRegistryAccountKey = await getRegistryAddress(
+    programId,
+    newDestinationOwner,
+  );
+
+  let instruction = [];
+  if (!requireAccept) {
+    instruction.push(
+      createInitRegistryInstruction(
+        programId,
+        destinationOwner,
+        newDestinationOwner,
+        newRegistryAccountKey,
+      ),
+    );
+  }
+  instruction.push(
+    createTransferPositionInstruction(
+      programId,
+      vestingAccountKey,
+      vestingInfo.destinationAddress,
+      destinationOwner,
+      await getRegistryAddress(programId, destinationOwner),
+      newDestinationTokenPubkey,
+      newRegistryAccountKey,
+      await getConfigAddress(programId),
+      [seeds],
+      requireAccept,
+    ),
+  );
+
+  return instruction;
+}
+
+/**
+ * This function can be used to accept the pending transfer of a lock
+ * @param connection The Solana RPC connection object
+ * @param programId The token vesting program ID
+ * @param seedWord Seed words used to derive the vesting account
+ * @param newDestinationOwner The owner of the token account the lock is transferred to
+ * @returns An array of `TransactionInstruction`, creating the position registry of the new owner if needed
+ */
+export async function acceptPosition(
+  connection: Connection,
+  programId: PublicKey,
+  seedWord: Buffer | Uint8Array,
+  newDestinationOwner: PublicKey,
+): Promise<Array<TransactionInstruction>> {
+  const [vestingAccountKey, seeds] = await findVestingAccount(
+    programId,
+    seedWord,
+  );
+  const vestingInfo = await getContractInfo(connection, vestingAccountKey);
+  if (!vestingInfo.pendingDestination) {
+    throw new Error('The lock has no pending transfer');
+  }
+  const newRegistryAccountKey = await getRegistryAddress(
+    programId,
+    newDestinationOwner,
+  );
+
+  let instruction = [
+    createInitRegistryInstruction(
+      programId,
+      newDestinationOwner,
+      newDestinationOwner,
+      newRegistryAccountKey,
+    ),
+    createAcceptPositionInstruction(
+      programId,
+      vestingAccountKey,
+      vestingInfo.destinationAddress,
+      await getRegistryAddress(programId, vestingInfo.owner),
+      vestingInfo.pendingDestination,
+      newDestinationOwner,
+      newRegistryAccountKey,
+      await getConfigAddress(programId),
+      [seeds],
+    ),
+  ];
+
+  return instruction;
+}
+
+/**
+ * This function can be used to initialize the unlock of vested tokens
+ * @param connection The Solana RPC connection object
+ * @param programId The token vesting program ID
+ * @param seedWord Seed words used to derive the vesting account
+ * @param owner The owner of the destination token account, burning the receipts of the stake from its associated token account and holding the position token of the lock if it has one
+ * @param hookAccounts The accounts forwarded to the hook of the lock, if it has one
+ * @returns An array of `TransactionInstruction`
+ */
+export async function initializeUnlock(
+  connection: Connection,
+  programId: PublicKey,
+  seedWord: Buffer | Uint8Array,
+  owner: PublicKey,
+  hookAccounts: Array<AccountMeta> = [],
+): Promise<Array<TransactionInstruction>> {
+  seedWord = seedWord.slice(0, 31);
+  const [vestingAccountKey, bump] = await PublicKey.findProgramAddress(
+    [seedWord],
+    programId,
+  );
+  seedWord = Buffer.from(seedWord.toString('hex') + bump.toString(16), 'hex');
+
+  const vestingTokenAccountKey = await getAssociatedTokenAddress(
+    isDevnetConnection(connection)? DEVNET_TOKEN_MINT : TOKEN_MINT,
+    vestingAccountKey,
+    true,
+  );
+
+  const vestingInfo = await getContractInfo(connection, vestingAccountKey);
+  const [destinationTokenPubkey, positionHolder] = await releaseDestination(
+    connection,
+    vestingInfo,
+    owner,
+  );
+  const receiptMintKey = await getReceiptMintAddress(programId);
+
+  let initializeUnlockInstruction = createInitializeUnlockInstruction(
+    programId,
+    TOKEN_PROGRAM_ID,
+    SYSVAR_CLOCK_PUBKEY,
+    vestingAccountKey,
+    vestingTokenAccountKey,
+    destinationTokenPubkey,
+    await getStatsAddress(programId),
+    receiptMintKey,
+    await getAssociatedTokenAddress(receiptMintKey, owner, true),
+    owner,
+    [seedWord],
+    positionHolder,
+  );
+  if (vestingInfo.hook) {
+    initializeUnlockInstruction = withHook(
+      initializeUnlockInstruction,
+      vestingInfo.hook.programId,
+      await getHookAuthorityAddress(programId, vestingAccountKey),
+      hookAccounts,
+    );
+  }
+  let instruction = [initializeUnlockInstruction];
+
+  return instruction;
+}
+
+/**
+ * This function can be used to turn a lock into a position token, minted to the associated token
+ * account of the owner of its destination token account. The lock is then released to the holder
+ * of the token.
+ * @param connection The Solana RPC connection object
+ * @param programId The token vesting program ID
+ * @param seedWord Seed words used to derive the vesting account
+ * @param payer The fee payer of the transaction
+ * @param destinationOwner The owner of the destination token account of the lock
+ * @param positionMint A new keypair address for the position mint, which signs the transaction
+ * @returns An array of `TransactionInstruction`
+ */
+export async function mintPosition(
+  connection: Connection,
+  programId: PublicKey,
+  seedWord: Buffer | Uint8Array,
+  payer: PublicKey,
+  destinationOwner: PublicKey,
+  positionMint: PublicKey,
+): Promise<Array<TransactionInstruction>> {
+  seedWord = seedWord.slice(0, 31);
+  const [vestingAccountKey, bump] = await PublicKey.findProgramAddress(
+    [seedWord],
+    programId,
+  );
+  seedWord = Buffer.from(seedWord.toString('hex') + bump.toString(16), 'hex');
+
+  const vestingInfo = await getContractInfo(connection, vestingAccountKey);
+  const positionTokenAccountKey = await getAssociatedTokenAddress(
+    positionMint,
+    destinationOwner,
+  );
+
+  let instruction = [
+    SystemProgram.createAccount({
+      fromPubkey: payer,
+      newAccountPubkey: positionMint,
+      lamports: await getMinimumBalanceForRentExemptMint(connection),
+      space: MINT_SIZE,
+      programId: TOKEN_PROGRAM_ID,
+    }),
+    createInitializeMint2Instruction(positionMint, 0, vestingAccountKey, null),
+    createAssociatedTokenAccountInstruction(
+      payer,
+      positionTokenAccountKey,
+      destinationOwner,
+      positionMint,
+    ),
+    createMintPositionInstruction(
+      programId,
+      TOKEN_PROGRAM_ID,
+      vestingAccountKey,
+      vestingInfo.destinationAddress,
+      destinationOwner,
+      positionMint,
+      positionTokenAccountKey,
+      [seedWord],
+    ),
+  ];
+
+  return instruction;
+}
+
+/**
+ * Resolves the destination of a released lock: its destination address, or for a lock with a
+ * position token, the associated token account of the holder of the token
+ */
+async function releaseDestination(
+  connection: Connection,
+  vestingInfo: ContractInfo,
+  holder: PublicKey | null,
+): Promise<[PublicKey, PositionHolder | null]> {
+  if (!vestingInfo.positionMint) {
+    return [vestingInfo.destinationAddress, null];
+  }
+  if (!holder) {
+    throw 'The lock has a position token, its holder is required.';
+  }
+  const destinationTokenPubkey = await getAssociatedTokenAddress(
+    isDevnetConnection(connection)? DEVNET_TOKEN_MINT : TOKEN_MINT,
+    holder,
+    true,
+  );
+  const positionHolder = {
+    positionMint: vestingInfo.positionMint,
+    positionTokenAccount: await getAssociatedTokenAddress(
+      vestingInfo.positionMint,
+      holder,
+      true,
+    ),
+    holder,
+  };
+  return [destinationTokenPubkey, positionHolder];
+}
+
+/**
+ * This function can be used retrieve information about a vesting account
+ * @param connection The Solana RPC connection object
+ * @param vestingAccountKey The vesting account public key
+ * @returns A `ContractInfo` object
+ */
+export async function getContractInfo(
+  connection: Connection,
+  vestingAccountKey: PublicKey,
+): Promise<ContractInfo> {
+  console.log('Fetching contract ', vestingAccountKey.toBase58());
+  const vestingInfo = await connection.getAccountInfo(
+    vestingAccountKey,
+    'single',
+  );
+  if (!vestingInfo) {
+    throw new Error('Vesting contract account is unavailable');
+  }
+  const info = ContractInfo.fromBuffer(vestingInfo!.data);
+  if (!info) {
+    throw new Error('Vesting contract account is not initialized');
+  }
+  return info!;
+}
+
+/**
+ * This function can be used to derive the address of the config account of the program
+ * @param programId The token vesting program ID
+ * @returns The config account address
+ */
+export async function getConfigAddress(programId: PublicKey): Promise<PublicKey> {
+  const [configAccountKey] = await PublicKey.findProgramAddress(
+    [Buffer.from('config')],
+    programId,
+  );
+  return configAccountKey;
+}
+
+/**
+ * This function can be used to retrieve the config of the program
+ * @param connection The Solana RPC connection object
+ * @param configAccountKey The config account, derived from the "config" seed
+ * @returns A Config object
+ */
+export async function getConfig(
+  connection: Connection,
+  configAccountKey: PublicKey,
+): Promise<Config> {
+  const configInfo = await connection.getAccountInfo(
+    configAccountKey,
+    'single',
+  );
+  if (!configInfo) {
+    throw new Error('Config account is unavailable');
+  }
+  const config = Config.fromBuffer(configInfo!.data);
+  if (!config) {
+    throw new Error('Config account is not initialized');
+  }
+  return config!;
+}
+
+/**
+ * This function can be used to derive the address of the stats account of the program
+ * @param programId The token vesting program ID
+ * @returns The stats account address
+ */
+export async function getStatsAddress(programId: PublicKey): Promise<PublicKey> {
+  const [statsAccountKey] = await PublicKey.findProgramAddress(
+    [Buffer.from('stats')],
+    programId,
+  );
+  return statsAccountKey;
+}
+
+/**
+ * This function can be used to derive the address of the receipt mint of the program, minting
+ * receipts 1:1 with the amount staked in unlocked stakes
+ * @param programId The token vesting program ID
+ * @returns The receipt mint address
+ */
+export async function getReceiptMintAddress(
+  programId: PublicKey,
+): Promise<PublicKey> {
+  const [receiptMintKey] = await PublicKey.findProgramAddress(
+    [Buffer.from('receipt')],
+    programId,
+  );
+  return receiptMintKey;
+}
+
+/**
+ * This function can be used to retrieve the locked amounts of the program per tier
+ * @param connection The Solana RPC connection object
+ * @param statsAccountKey The stats account, derived from the "stats" seed
+ * @returns A Stats object
+ */
+export async function getStats(
+  connection: Connection,
+  statsAccountKey: PublicKey,
+): Promise<Stats> {
+  const statsInfo = await connection.getAccountInfo(statsAccountKey, 'single');
+  if (!statsInfo) {
+    throw new Error('Stats account is unavailable');
+  }
+  const stats = Stats.fromBuffer(statsInfo!.data);
+  if (!stats) {
+    throw new Error('Stats account is not initialized');
+  }
+  return stats!;
+}
+
+/**
+ * This function can be used to derive the address of the position registry of a wallet
+ * @param programId The token vesting program ID
+ * @param owner The wallet owning the destination token accounts of the locks
+ * @returns The position registry address
+ */
+export async function getRegistryAddress(
+  programId: PublicKey,
+  owner: PublicKey,
+): Promise<PublicKey> {
+  const [registryAccountKey] = await PublicKey.findProgramAddress(
+    [Buffer.from('registry'), owner.toBuffer()],
+    programId,
+  );
+  return registryAccountKey;
+}
+
+/**
+ * This function can be used to derive the address of the allowlist entry of a wallet
+ * @param programId The token vesting program ID
+ * @param wallet The allowlisted wallet
+ * @returns The allowlist entry address
+ */
+export async function getAllowlistEntryAddress(
+  programId: PublicKey,
+  wallet: PublicKey,
+): Promise<PublicKey> {
+  const [allowlistEntryKey] = await PublicKey.findProgramAddress(
+    [Buffer.from('allowlist'), wallet.toBuffer()],
+    programId,
+  );
+  return allowlistEntryKey;
+}
+
+/**
+ * This function can be used to derive the hook authority of a lock, signing the invocations of its hook
+ * @param programId The token vesting program ID
+ * @param vestingAccountKey The vesting account of the lock
+ * @returns The hook authority address
+ */
+export async function getHookAuthorityAddress(
+  programId: PublicKey,
+  vestingAccountKey: PublicKey,
+): Promise<PublicKey> {
+  const [hookAuthorityKey] = await PublicKey.findProgramAddress(
+    [Buffer.from('hook'), vestingAccountKey.toBuffer()],
+    programId,
+  );
+  return hookAuthorityKey;
+}
+
+/**
+ * This function can be used to find the referrer account of a wallet
+ * @param programId The token vesting program ID
+ * @param referrer The referrer
+ * @returns The referrer account address
+ */
+export async function getReferrerAddress(
+  programId: PublicKey,
+  referrer: PublicKey,
+): Promise<PublicKey> {
+  const [referrerAccountKey] = await PublicKey.findProgramAddress(
+    [Buffer.from('referrer'), referrer.toBuffer()],
+    programId,
+  );
+  return referrerAccountKey;
+}
+
+/**
+ * This function can be used to retrieve the totals credited to a referrer
+ * @param connection The Solana RPC connection object
+ * @param referrerAccountKey The referrer account, derived from the "referrer" seed and the referrer
+ * @returns A Referrer object
+ */
+export async function getReferrer(
+  connection: Connection,
+  referrerAccountKey: PublicKey,
+): Promise<Referrer> {
+  const referrerInfo = await connection.getAccountInfo(
+    referrerAccountKey,
+    'single',
+  );
+  if (!referrerInfo) {
+    throw new Error('Referrer account is unavailable');
+  }
+  const referrer = Referrer.fromBuffer(referrerInfo!.data);
+  if (!referrer) {
+    throw new Error('Referrer account is not initialized');
+  }
+  return referrer!;
+}
+
+/**
+ * This function can be used to list the open locks of a wallet, without `getProgramAccounts`
+ * @param connection The Solana RPC connection object
+ * @param programId The token vesting program ID
+ * @param owner The wallet owning the destination token accounts of the locks
+ * @returns A PositionRegistry object
+ */
+export async function getPositionRegistry(
+  connection: Connection,
+  programId: PublicKey,
+  owner: PublicKey,
+): Promise<PositionRegistry> {
+  const registryInfo = await connection.getAccountInfo(
+    await getRegistryAddress(programId, owner),
+    'single',
+  );
+  if (!registryInfo) {
+    throw new Error('Position registry account is unavailable');
+  }
+  const registry = PositionRegistry.fromBuffer(registryInfo!.data);
+  if (!registry) {
+    throw new Error('Position registry account is not initialized');
+  }
+  return registry!;
 }
 
 /**
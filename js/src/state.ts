@@ -43,21 +43,124 @@ export class Schedule {
   }
 }
 
+// The clock measuring the release time of a lock
+export enum LockMode {
+  Timestamp = 0,
+  Slot = 1,
+  Epoch = 2,
+}
+
+// Whether the instructions of a lock must invoke its hook, or the owner of the lock can leave out
+// its program account from the instructions it signs
+export enum HookMode {
+  Required = 0,
+  Skippable = 1,
+}
+
+// A program invoked after the creation, initial unlock and unlock of a lock
+export type Hook = {
+  programId: PublicKey;
+  mode: HookMode;
+};
+
+export const VESTING_SCHEDULE_HEADER_LEN = 270;
+export const VESTING_SCHEDULE_LEN = 16;
+
 export class VestingScheduleHeader {
   destinationAddress!: PublicKey;
   isInitialized!: boolean;
+  // The authority allowed to revoke the lock, null if the lock is not revocable
+  revocationAuthority!: PublicKey | null;
+  isRevoked!: boolean;
+  lockMode!: LockMode;
+  // Index of the lock duration of the lock, from 0 (unlock with cooldown) to 4 (12 months)
+  tier!: number;
+  // The mint of the position token whose holder releases the lock, null if the lock has none
+  positionMint!: PublicKey | null;
+  // The token account the lock is being transferred to, null if there is no pending transfer
+  pendingDestination!: PublicKey | null;
+  // The seeds of the vesting account, followed by its bump
+  seeds!: Buffer;
+  // Lamports paid to the account releasing the lock once it matures
+  keeperTip!: Numberu64;
+  // Whether the lock renews for another period at each maturity, until its owner opts out
+  autoRenew!: boolean;
+  // The hook of the lock, null if the lock has none
+  hook!: Hook | null;
+  // The wallet credited with the lock, null if the lock has no referrer
+  referrer!: PublicKey | null;
+  // The wallet owning the lock, who signs its transfers
+  owner!: PublicKey;
 
-  constructor(destinationAddress: PublicKey, isInitialized: boolean) {
+  constructor(
+    destinationAddress: PublicKey,
+    isInitialized: boolean,
+    revocationAuthority: PublicKey | null = null,
+    isRevoked: boolean = false,
+    lockMode: LockMode = LockMode.Timestamp,
+    tier: number = 0,
+    positionMint: PublicKey | null = null,
+    pendingDestination: PublicKey | null = null,
+    seeds: Buffer = Buffer.alloc(32),
+    keeperTip: Numberu64 = new Numberu64(0),
+    autoRenew: boolean = false,
+    hook: Hook | null = null,
+    referrer: PublicKey | null = null,
+    owner: PublicKey = PublicKey.default,
+  ) {
     this.destinationAddress = destinationAddress;
     this.isInitialized = isInitialized;
+    this.revocationAuthority = revocationAuthority;
+    this.isRevoked = isRevoked;
+    this.lockMode = lockMode;
+    this.tier = tier;
+    this.positionMint = positionMint;
+    this.pendingDestination = pendingDestination;
+    this.seeds = seeds;
+    this.keeperTip = keeperTip;
+    this.autoRenew = autoRenew;
+    this.hook = hook;
+    this.referrer = referrer;
+    this.owner = owner;
   }
 
   static fromBuffer(buf: Buffer): VestingScheduleHeader {
     const destinationAddress = new PublicKey(buf.slice(0, 32));
     const isInitialized = buf[32] == 1;
+    const revocationAuthority = new PublicKey(buf.slice(33, 65));
+    const isRevoked = buf[65] == 1;
+    const lockMode: LockMode = buf[66];
+    const tier = buf[67];
+    const positionMint = new PublicKey(buf.slice(68, 100));
+    const pendingDestination = new PublicKey(buf.slice(100, 132));
+    const seeds = buf.slice(132, 164);
+    const keeperTip = Numberu64.fromBuffer(buf.slice(164, 172));
+    const autoRenew = buf[172] == 1;
+    const hookProgram = new PublicKey(buf.slice(173, 205));
+    const hookMode: HookMode = buf[205];
+    const referrer = new PublicKey(buf.slice(206, 238));
+    const owner = new PublicKey(buf.slice(238, 270));
     const header: VestingScheduleHeader = {
       destinationAddress,
       isInitialized,
+      revocationAuthority: revocationAuthority.equals(PublicKey.default)
+        ? null
+        : revocationAuthority,
+      isRevoked,
+      lockMode,
+      tier,
+      positionMint: positionMint.equals(PublicKey.default) ? null : positionMint,
+      pendingDestination: pendingDestination.equals(PublicKey.default)
+        ? null
+        : pendingDestination,
+      seeds,
+      keeperTip,
+      autoRenew,
+      hook: hookProgram.equals(PublicKey.default)
+        ? null
+        : { programId: hookProgram, mode: hookMode },
+      referrer: referrer.equals(PublicKey.default) ? null : referrer,
+      owner,
     };
     return header;
   }
@@ -66,18 +169,350 @@ export class VestingScheduleHeader {
 export class ContractInfo {
   destinationAddress!: PublicKey;
   schedule!: Schedule;
+  revocationAuthority!: PublicKey | null;
+  isRevoked!: boolean;
+  // Unit of the release time of the schedule
+  lockMode!: LockMode;
+  // Mint of the position token whose holder releases the lock, null if the lock has none
+  positionMint!: PublicKey | null;
+  // Token account the lock is being transferred to, null if there is no pending transfer
+  pendingDestination!: PublicKey | null;
+  // Seeds of the vesting account, followed by its bump
+  seeds!: Buffer;
+  // Lamports paid to the account releasing the lock once it matures
+  keeperTip!: Numberu64;
+  // Whether the lock renews for another period at each maturity, until its owner opts out. The
+  // release time of the schedule is then the first maturity.
+  autoRenew!: boolean;
+  // Hook of the lock, null if the lock has none
+  hook!: Hook | null;
+  // Wallet credited with the lock, null if the lock has no referrer
+  referrer!: PublicKey | null;
+  // Wallet owning the lock, who signs its transfers
+  owner!: PublicKey;
 
-  constructor(destinationAddress: PublicKey, schedule: Schedule) {
+  constructor(
+    destinationAddress: PublicKey,
+    schedule: Schedule,
+    revocationAuthority: PublicKey | null = null,
+    isRevoked: boolean = false,
+    lockMode: LockMode = LockMode.Timestamp,
+    positionMint: PublicKey | null = null,
+    pendingDestination: PublicKey | null = null,
+    seeds: Buffer = Buffer.alloc(32),
+    keeperTip: Numberu64 = new Numberu64(0),
+    autoRenew: boolean = false,
+    hook: Hook | null = null,
+    referrer: PublicKey | null = null,
+    owner: PublicKey = PublicKey.default,
+  ) {
     this.destinationAddress = destinationAddress;
     this.schedule = schedule;
+    this.revocationAuthority = revocationAuthority;
+    this.isRevoked = isRevoked;
+    this.lockMode = lockMode;
+    this.positionMint = positionMint;
+    this.pendingDestination = pendingDestination;
+    this.seeds = seeds;
+    this.keeperTip = keeperTip;
+    this.autoRenew = autoRenew;
+    this.hook = hook;
+    this.referrer = referrer;
+    this.owner = owner;
   }
 
   static fromBuffer(buf: Buffer): ContractInfo | undefined {
-    const header = VestingScheduleHeader.fromBuffer(buf.slice(0, 33));
+    const header = VestingScheduleHeader.fromBuffer(
+      buf.slice(0, VESTING_SCHEDULE_HEADER_LEN),
+    );
     if (!header.isInitialized) {
       return undefined;
     }
-    const schedule = Schedule.fromBuffer(buf.slice(33, 49));
-    return new ContractInfo(header.destinationAddress, schedule);
+    const schedule = Schedule.fromBuffer(
+      buf.slice(
+        VESTING_SCHEDULE_HEADER_LEN,
+        VESTING_SCHEDULE_HEADER_LEN + VESTING_SCHEDULE_LEN,
+      ),
+    );
+    return new ContractInfo(
+      header.destinationAddress,
+      schedule,
+      header.revocationAuthority,
+      header.isRevoked,
+      header.lockMode,
+      header.positionMint,
+      header.pendingDestination,
+      header.seeds,
+      header.keeperTip,
+      header.autoRenew,
+      header.hook,
+      header.referrer,
+      header.owner,
+    );
+  }
+}
+
+export const CONFIG_LEN = 228;
+
+export class Config {
+  admin!: PublicKey;
+  // The token account receiving the early unlock penalties
+  penaltyTreasury!: PublicKey;
+  // The penalty of an early unlock with a whole 12-month lock ahead, in basis points
+  maxPenaltyBps!: number;
+  bump!: number;
+  isInitialized!: boolean;
+  // The key allowed to pause the creation of locks
+  guardian!: PublicKey;
+  isPaused!: boolean;
+  // Whether every lock can be unlocked, whatever its release time
+  isEmergencyMode!: boolean;
+  // Whether `Create` is restricted to the allowlisted source token account owners
+  isAllowlistEnabled!: boolean;
+  // Merkle root of the wallets allowlisted without an allowlist entry, all zeros for none
+  allowlistRoot!: Buffer;
+  // Maximum amount held by all the locks, 0 for no cap
+  maxTotalLocked!: Numberu64;
+  // Maximum amount of the positions of a wallet, 0 for no cap
+  maxLockedPerWallet!: Numberu64;
+  // Minimum amount of a new lock, by tier
+  minLockAmounts!: Array<Numberu64>;
+  // The token account receiving the deposit fees
+  depositFeeTreasury!: PublicKey;
+  // The fee taken from the amount of a new lock, in basis points
+  depositFeeBps!: number;
+  // The share of the deposit fee paid to the referrer of a new lock, in basis points of the fee
+  referralShareBps!: number;
+  // The clock measuring the release time of the locks of the pool
+  lockMode!: LockMode;
+
+  constructor(
+    admin: PublicKey,
+    penaltyTreasury: PublicKey,
+    maxPenaltyBps: number,
+    bump: number,
+    isInitialized: boolean,
+    guardian: PublicKey,
+    isPaused: boolean,
+    isEmergencyMode: boolean,
+    isAllowlistEnabled: boolean,
+    allowlistRoot: Buffer,
+    maxTotalLocked: Numberu64,
+    maxLockedPerWallet: Numberu64,
+    minLockAmounts: Array<Numberu64>,
+    depositFeeTreasury: PublicKey,
+    depositFeeBps: number,
+    referralShareBps: number,
+    lockMode: LockMode,
+  ) {
+    this.admin = admin;
+    this.penaltyTreasury = penaltyTreasury;
+    this.maxPenaltyBps = maxPenaltyBps;
+    this.bump = bump;
+    this.isInitialized = isInitialized;
+    this.guardian = guardian;
+    this.isPaused = isPaused;
+    this.isEmergencyMode = isEmergencyMode;
+    this.isAllowlistEnabled = isAllowlistEnabled;
+    this.allowlistRoot = allowlistRoot;
+    this.maxTotalLocked = maxTotalLocked;
+    this.maxLockedPerWallet = maxLockedPerWallet;
+    this.minLockAmounts = minLockAmounts;
+    this.depositFeeTreasury = depositFeeTreasury;
+    this.depositFeeBps = depositFeeBps;
+    this.referralShareBps = referralShareBps;
+    this.lockMode = lockMode;
+  }
+
+  static fromBuffer(buf: Buffer): Config | undefined {
+    const isInitialized = buf[67] == 1;
+    if (!isInitialized) {
+      return undefined;
+    }
+    return new Config(
+      new PublicKey(buf.slice(0, 32)),
+      new PublicKey(buf.slice(32, 64)),
+      buf.readUInt16LE(64),
+      buf[66],
+      isInitialized,
+      new PublicKey(buf.slice(68, 100)),
+      buf[100] == 1,
+      buf[101] == 1,
+      buf[102] == 1,
+      Buffer.from(buf.slice(103, 135)),
+      Numberu64.fromBuffer(buf.slice(135, 143)),
+      Numberu64.fromBuffer(buf.slice(143, 151)),
+      [0, 1, 2, 3, 4].map(tier =>
+        Numberu64.fromBuffer(buf.slice(151 + tier * 8, 159 + tier * 8)),
+      ),
+      new PublicKey(buf.slice(191, 223)),
+      buf.readUInt16LE(223),
+      buf.readUInt16LE(225),
+      buf[227],
+    );
+  }
+}
+
+export const REFERRER_LEN = 50;
+
+export class Referrer {
+  referrer!: PublicKey;
+  bump!: number;
+  isInitialized!: boolean;
+  // Amount locked by the locks credited to the referrer
+  totalLocked!: Numberu64;
+  // Amount locked by the locks credited to the referrer, weighted by their tier
+  weightedVolume!: Numberu64;
+
+  constructor(
+    referrer: PublicKey,
+    bump: number,
+    isInitialized: boolean,
+    totalLocked: Numberu64,
+    weightedVolume: Numberu64,
+  ) {
+    this.referrer = referrer;
+    this.bump = bump;
+    this.isInitialized = isInitialized;
+    this.totalLocked = totalLocked;
+    this.weightedVolume = weightedVolume;
+  }
+
+  static fromBuffer(buf: Buffer): Referrer | undefined {
+    const isInitialized = buf[33] == 1;
+    if (!isInitialized) {
+      return undefined;
+    }
+    return new Referrer(
+      new PublicKey(buf.slice(0, 32)),
+      buf[32],
+      isInitialized,
+      Numberu64.fromBuffer(buf.slice(34, 42)),
+      Numberu64.fromBuffer(buf.slice(42, 50)),
+    );
+  }
+}
+
+export const TIER_STATS_LEN = 24;
+export const STATS_LEN = 122;
+
+export class TierStats {
+  totalLocked!: Numberu64;
+  activeLocks!: Numberu64;
+  // Amount of the tier 0 locks whose unlock was initialized
+  pendingCooldown!: Numberu64;
+
+  constructor(
+    totalLocked: Numberu64,
+    activeLocks: Numberu64,
+    pendingCooldown: Numberu64,
+  ) {
+    this.totalLocked = totalLocked;
+    this.activeLocks = activeLocks;
+    this.pendingCooldown = pendingCooldown;
+  }
+
+  static fromBuffer(buf: Buffer): TierStats {
+    return new TierStats(
+      Numberu64.fromBuffer(buf.slice(0, 8)),
+      Numberu64.fromBuffer(buf.slice(8, 16)),
+      Numberu64.fromBuffer(buf.slice(16, 24)),
+    );
+  }
+}
+
+export class Stats {
+  // Statistics of the locks of every tier, indexed by tier
+  tiers!: Array<TierStats>;
+  bump!: number;
+  isInitialized!: boolean;
+
+  constructor(tiers: Array<TierStats>, bump: number, isInitialized: boolean) {
+    this.tiers = tiers;
+    this.bump = bump;
+    this.isInitialized = isInitialized;
+  }
+
+  static fromBuffer(buf: Buffer): Stats | undefined {
+    const tiersLen = STATS_LEN - 2;
+    const isInitialized = buf[tiersLen + 1] == 1;
+    if (!isInitialized) {
+      return undefined;
+    }
+    const tiers: Array<TierStats> = [];
+    for (let offset = 0; offset < tiersLen; offset += TIER_STATS_LEN) {
+      tiers.push(TierStats.fromBuffer(buf.slice(offset, offset + TIER_STATS_LEN)));
+    }
+    return new Stats(tiers, buf[tiersLen], isInitialized);
+  }
+}
+
+export const POSITION_LEN = 48;
+export const MAX_POSITIONS = 64;
+export const POSITION_REGISTRY_LEN = 43 + MAX_POSITIONS * POSITION_LEN;
+
+export class Position {
+  vestingAccount!: PublicKey;
+  // Index of the position among all the locks registered to the owner, never reused
+  index!: Numberu64;
+  // Amount of the lock when it was registered, counted against the per-wallet cap until it is
+  // closed
+  amount!: Numberu64;
+
+  constructor(vestingAccount: PublicKey, index: Numberu64, amount: Numberu64) {
+    this.vestingAccount = vestingAccount;
+    this.index = index;
+    this.amount = amount;
+  }
+
+  static fromBuffer(buf: Buffer): Position {
+    return new Position(
+      new PublicKey(buf.slice(0, 32)),
+      Numberu64.fromBuffer(buf.slice(32, 40)),
+      Numberu64.fromBuffer(buf.slice(40, 48)),
+    );
+  }
+}
+
+export class PositionRegistry {
+  owner!: PublicKey;
+  bump!: number;
+  isInitialized!: boolean;
+  // Index of the next registered position
+  nextIndex!: Numberu64;
+  // The open positions, in the order they were registered
+  positions!: Array<Position>;
+
+  constructor(
+    owner: PublicKey,
+    bump: number,
+    isInitialized: boolean,
+    nextIndex: Numberu64,
+    positions: Array<Position>,
+  ) {
+    this.owner = owner;
+    this.bump = bump;
+    this.isInitialized = isInitialized;
+    this.nextIndex = nextIndex;
+    this.positions = positions;
+  }
+
+  static fromBuffer(buf: Buffer): PositionRegistry | undefined {
+    const isInitialized = buf[33] == 1;
+    if (!isInitialized) {
+      return undefined;
+    }
+    const positions: Array<Position> = [];
+    for (let i = 0; i < buf[42]; i++) {
+      const offset = 43 + i * POSITION_LEN;
+      positions.push(Position.fromBuffer(buf.slice(offset, offset + POSITION_LEN)));
+    }
+    return new PositionRegistry(
+      new PublicKey(buf.slice(0, 32)),
+      buf[32],
+      isInitialized,
+      Numberu64.fromBuffer(buf.slice(34, 42)),
+      positions,
+    );
   }
 }
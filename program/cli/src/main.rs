@@ -0,0 +1,1415 @@
+//! Command line tool to manage token vesting locks.
+//!
+//! Every command that sends a transaction can be run in offline mode with `--sign-only` and
+//! `--blockhash`, in which case the signed transaction is printed instead of being sent.
+mod output;
+
+use std::{error::Error, time::Duration};
+
+use clap::{Args, Parser, Subcommand};
+use rand::Rng;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+    rpc_request::TokenAccountsFilter,
+};
+use solana_sdk::{
+    account::from_account,
+    clock::Clock,
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signer},
+    sysvar,
+    transaction::Transaction,
+};
+use spl_associated_token_account::get_associated_token_address;
+use token_vesting::{
+    hook::{Hook, HookMode},
+    instruction::{self, AllowlistAccess, CreateArgs, Referral, Schedule},
+    processor::TOKEN_MINT,
+    state::{VestingSchedule, VestingScheduleHeader},
+};
+use token_vesting_client::{
+    allowlist_entry_address, batch, hook_authority_address, instructions, referrer_address,
+    seed_from_word, tier_time_delta, ClientError, ContractInfo, LockAddresses, VestingClient,
+    TIERS,
+};
+
+use crate::output::{
+    print, CreatedBatch, CreatedLock, KeeperRound, Lock, LockList, OutputFormat, SignedTransaction,
+    StatsOutput, TransactionOutput,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "token-vesting-cli",
+    version,
+    about = "Manage token vesting locks"
+)]
+struct Cli {
+    /// RPC URL, defaults to the one of the Solana CLI config
+    #[arg(long, short = 'u', global = true)]
+    url: Option<String>,
+
+    /// Keypair owning the locked tokens, defaults to the one of the Solana CLI config
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<String>,
+
+    /// Fee payer of the transactions, defaults to the keypair
+    #[arg(long, global = true)]
+    fee_payer: Option<Pubkey>,
+
+    #[arg(long, env = "TOKEN_VESTING_PROGRAM_ID")]
+    program_id: Pubkey,
+
+    /// Mint of the locked tokens, defaults to the one the program was built with
+    #[arg(long, global = true)]
+    mint: Option<Pubkey>,
+
+    #[arg(long, value_enum, default_value_t = OutputFormat::Display, global = true)]
+    output: OutputFormat,
+
+    /// Only sign the transaction and print it, without any RPC request
+    #[arg(long, global = true, requires = "blockhash")]
+    sign_only: bool,
+
+    /// Recent blockhash to sign with in offline mode
+    #[arg(long, global = true)]
+    blockhash: Option<Hash>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
+enum Command {
+    /// Locks tokens from a token account, for its owner or for a beneficiary
+    Create {
+        /// Lock period in months: 0 (unlocked, with a 7 day withdrawal period), 3, 6, 9 or 12
+        #[arg(long, value_parser = parse_tier)]
+        tier: u64,
+
+        /// Amount to lock, in base units of the mint
+        #[arg(long)]
+        amount: u64,
+
+        /// Seed word of the lock (at least 31 bytes), a random one is generated by default
+        #[arg(long)]
+        seed: Option<String>,
+
+        /// Source token account, defaults to the owner's associated token account
+        #[arg(long)]
+        source: Option<Pubkey>,
+
+        /// Token account receiving the tokens on unlock, defaults to the source token account
+        #[arg(long)]
+        beneficiary: Option<Pubkey>,
+
+        /// Owner of the beneficiary token account, whose position registry records the lock.
+        /// Fetched from the cluster by default, required in offline mode.
+        #[arg(long, requires = "beneficiary")]
+        beneficiary_owner: Option<Pubkey>,
+
+        /// SPL multisig owning the source token account, instead of the keypair
+        #[arg(long, requires = "multisig_signer")]
+        multisig: Option<Pubkey>,
+
+        /// Signer of the multisig, repeated up to its threshold. Signers other than the keypair
+        /// sign the transaction printed by `--sign-only`.
+        #[arg(long, requires = "multisig")]
+        multisig_signer: Vec<Pubkey>,
+
+        /// Authority allowed to revoke the lock before it is unlocked, taking back the unvested
+        /// tokens. Locks are not revocable by default.
+        #[arg(long)]
+        revocation_authority: Option<Pubkey>,
+
+        /// Mint a position token for the lock to the owner of the beneficiary token account. The
+        /// lock is then released to the holder of the token.
+        #[arg(long, conflicts_with = "revocation_authority")]
+        position: bool,
+
+        /// Lamports deposited by the fee payer, paid to whoever releases the lock once it matures
+        #[arg(long, default_value_t = 0)]
+        keeper_tip: u64,
+
+        /// Renew the lock for another period at each maturity, until its owner opts out with
+        /// `opt-out-renewal`. Unlocked (tier 0) locks do not renew.
+        #[arg(long)]
+        auto_renew: bool,
+
+        /// Hook program invoked after the creation, initial unlock and unlock of the lock
+        #[arg(long)]
+        hook: Option<Pubkey>,
+
+        /// Whether the hook must be invoked: required, or skippable by the owner of the lock
+        /// leaving out its program account, which bypasses a failing hook
+        #[arg(long, value_parser = parse_hook_mode, default_value = "required", requires = "hook")]
+        hook_mode: HookMode,
+
+        /// Writable account forwarded to the hook, repeated for every account
+        #[arg(long, requires = "hook")]
+        hook_account: Vec<Pubkey>,
+
+        /// Node of the Merkle proof of the owner against the allowlist root, in hex, repeated for
+        /// every node. The owner's allowlist entry is used otherwise.
+        #[arg(long, value_parser = parse_hash)]
+        allowlist_proof: Vec<[u8; 32]>,
+
+        /// Pass the allowlist with the owner's allowlist entry. Detected from the config by
+        /// default, required in offline mode while the allowlist is enabled.
+        #[arg(long, conflicts_with = "allowlist_proof")]
+        allowlist_entry: bool,
+
+        /// Treasury token account receiving the deposit fee. Detected from the config by default,
+        /// required in offline mode while the config charges a deposit fee.
+        #[arg(long)]
+        deposit_fee_treasury: Option<Pubkey>,
+
+        /// Wallet credited with the lock
+        #[arg(long)]
+        referrer: Option<Pubkey>,
+
+        /// Token account of the referrer receiving its share of the deposit fee. Its associated
+        /// token account is detected from the config by default, required in offline mode while
+        /// the config pays a referral share.
+        #[arg(long, requires = "referrer")]
+        referrer_token_account: Option<Pubkey>,
+    },
+    /// Locks tokens for every line of a `beneficiary,tier,amount` CSV file, in as few
+    /// transactions as possible
+    CreateBatch {
+        /// CSV file of the locks, with the beneficiary wallet, the tier in months and the amount
+        #[arg(long)]
+        file: String,
+
+        /// Identifier the seeds of the locks are derived from. Running a batch again with the
+        /// same identifier only creates the missing locks.
+        #[arg(long)]
+        batch_id: String,
+
+        /// Source token account, defaults to the keypair's associated token account
+        #[arg(long)]
+        source: Option<Pubkey>,
+    },
+    /// Starts the withdrawal period of an unlocked (tier 0) lock, burning its receipts from the
+    /// keypair's associated token account
+    InitiateUnlock {
+        #[command(flatten)]
+        lock: LockArgs,
+
+        /// Hook program of the lock, fetched from the vesting account by default. Required in
+        /// offline mode for a lock with a hook.
+        #[arg(long)]
+        hook: Option<Pubkey>,
+
+        /// Writable account forwarded to the hook of the lock, repeated for every account
+        #[arg(long)]
+        hook_account: Vec<Pubkey>,
+    },
+    /// Releases the tokens of a matured lock to its destination, earning its keeper tip for the
+    /// fee payer
+    Unlock {
+        #[command(flatten)]
+        lock: LockArgs,
+
+        /// Owner of the destination token account recorded in the lock, whose position registry
+        /// records it. Fetched from the cluster by default, required in offline mode.
+        #[arg(long)]
+        destination_owner: Option<Pubkey>,
+
+        /// Hook program of the lock, fetched from the vesting account by default. Required in
+        /// offline mode for a lock with a hook.
+        #[arg(long)]
+        hook: Option<Pubkey>,
+
+        /// Writable account forwarded to the hook of the lock, repeated for every account
+        #[arg(long)]
+        hook_account: Vec<Pubkey>,
+    },
+    /// Releases the matured locks among several locks to their destinations in one transaction,
+    /// skipping the others. The fee payer earns their keeper tips.
+    UnlockMany {
+        /// Seed word of a lock, repeated for every lock
+        #[arg(long, required = true)]
+        seed: Vec<String>,
+    },
+    /// Scans the program for matured locks and releases them, earning their keeper tips for the
+    /// keypair
+    Keeper {
+        /// Minimum keeper tip of the released locks, in lamports
+        #[arg(long, default_value_t = 0)]
+        min_tip: u64,
+
+        /// Seconds between two scans
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+
+        /// Scans and releases the matured locks once, instead of running until interrupted
+        #[arg(long)]
+        once: bool,
+    },
+    /// Stops the renewal of an auto-renewing lock, with the keypair as owner of its destination
+    /// token account. Only possible in the 7 days before the release time, when the lock is
+    /// released instead of renewed, or in the 7 days after a maturity, when it is released at once.
+    OptOutRenewal(LockArgs),
+    /// Revokes a lock, with the keypair as revocation authority: the vested tokens are released
+    /// to its destination and the unvested ones to the treasury
+    Revoke {
+        #[command(flatten)]
+        lock: LockArgs,
+
+        /// Owner of the destination token account recorded in the lock, whose position registry
+        /// records it. Fetched from the cluster by default, required in offline mode.
+        #[arg(long)]
+        destination_owner: Option<Pubkey>,
+
+        /// Token account receiving the unvested tokens, defaults to the keypair's associated
+        /// token account
+        #[arg(long)]
+        treasury: Option<Pubkey>,
+    },
+    /// Closes a released or revoked lock, with the keypair as owner of its destination token
+    /// account, and removes it from the keypair's position registry
+    Close(LockArgs),
+    /// Transfers a lock to a token account of another wallet, with the keypair as owner of the
+    /// lock
+    TransferPosition {
+        #[command(flatten)]
+        lock: LockArgs,
+
+        /// Token account the lock is released to once transferred
+        #[arg(long)]
+        new_destination: Pubkey,
+
+        /// Owner of the new destination token account, whose position registry records the
+        /// lock. Fetched from the cluster by default, required in offline mode.
+        #[arg(long)]
+        new_destination_owner: Option<Pubkey>,
+
+        /// Only transfer the lock once the owner of the new destination token account accepts it
+        /// with `accept-position`. A transfer to the destination of the lock cancels a pending
+        /// transfer.
+        #[arg(long)]
+        require_accept: bool,
+    },
+    /// Accepts the pending transfer of a lock, with the keypair as owner of the token account it
+    /// is transferred to
+    AcceptPosition {
+        #[command(flatten)]
+        lock: LockArgs,
+
+        /// Owner of the lock, fetched from the vesting account by default. Required in offline
+        /// mode.
+        #[arg(long)]
+        owner: Option<Pubkey>,
+
+        /// Token account the lock is transferred to, fetched from the vesting account by
+        /// default. Required in offline mode.
+        #[arg(long)]
+        new_destination: Option<Pubkey>,
+    },
+    /// Splits an amount off a lock into a new lock with the same terms, with the keypair as owner
+    /// of the lock
+    Split {
+        #[command(flatten)]
+        lock: LockArgs,
+
+        /// Amount moved to the new lock, less than the amount of the lock
+        #[arg(long)]
+        amount: u64,
+
+        /// Seed word of the new lock (at least 31 bytes), a random one is generated by default
+        #[arg(long)]
+        new_seed: Option<String>,
+
+        /// Token account receiving the tokens of the new lock, defaults to the destination of
+        /// the lock
+        #[arg(long)]
+        new_destination: Option<Pubkey>,
+
+        /// Owner of the new destination token account, whose position registry records the new
+        /// lock. Fetched from the cluster by default, required in offline mode.
+        #[arg(long, requires = "new_destination")]
+        new_destination_owner: Option<Pubkey>,
+    },
+    /// Merges locks with the same destination, tier and release time into a lock, with the
+    /// keypair as owner of their destination token account. The merged locks are closed.
+    Merge {
+        #[command(flatten)]
+        lock: LockArgs,
+
+        /// Seed word of a lock merged into the lock, repeated for every merged lock
+        #[arg(long, required = true)]
+        source_seed: Vec<String>,
+    },
+    /// Displays a lock
+    Info {
+        /// Seed word of the lock
+        #[arg(long, required_unless_present = "address", conflicts_with = "address")]
+        seed: Option<String>,
+
+        /// Address of the vesting account
+        #[arg(long)]
+        address: Option<Pubkey>,
+    },
+    /// Lists the locks releasing tokens to a wallet's token accounts
+    List {
+        /// Wallet owning the destination token accounts, defaults to the keypair
+        #[arg(long)]
+        owner: Option<Pubkey>,
+    },
+    /// Displays the locked amounts of the program per tier
+    Stats,
+}
+
+#[derive(Args)]
+struct LockArgs {
+    /// Seed word of the lock
+    #[arg(long)]
+    seed: String,
+
+    /// Destination token account of the lock, fetched from the vesting account by default.
+    /// Required in offline mode.
+    #[arg(long)]
+    destination: Option<Pubkey>,
+
+    /// Position mint of the lock, fetched from the vesting account by default. Required in
+    /// offline mode for a lock with a position token, released to the keypair holding it.
+    #[arg(long)]
+    position_mint: Option<Pubkey>,
+}
+
+fn parse_tier(tier: &str) -> Result<u64, String> {
+    let months: u64 = tier.parse().map_err(|_| format!("invalid tier {}", tier))?;
+    tier_time_delta(months).ok_or_else(|| format!("tier should be one of {:?}", TIERS))
+}
+
+fn parse_hook_mode(hook_mode: &str) -> Result<HookMode, String> {
+    match hook_mode {
+        "required" => Ok(HookMode::Required),
+        "skippable" => Ok(HookMode::Skippable),
+        _ => Err(format!("invalid hook mode {}", hook_mode)),
+    }
+}
+
+fn parse_hash(hash: &str) -> Result<[u8; 32], String> {
+    let invalid = || format!("invalid hash {}, expected 64 hex characters", hash);
+    if hash.len() != 64 || !hash.is_ascii() {
+        return Err(invalid());
+    }
+    let mut bytes = [0; 32];
+    for (byte, i) in bytes.iter_mut().zip((0..64).step_by(2)) {
+        *byte = u8::from_str_radix(&hash[i..i + 2], 16).map_err(|_| invalid())?;
+    }
+    Ok(bytes)
+}
+
+fn random_seed_word() -> String {
+    // Same format as the seeds generated by the JS bindings
+    let mut rng = rand::thread_rng();
+    (0..64)
+        .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+        .collect()
+}
+
+struct Context {
+    rpc: RpcClient,
+    keypair: Keypair,
+    fee_payer: Pubkey,
+    program_id: Pubkey,
+    mint: Pubkey,
+    output: OutputFormat,
+    offline_blockhash: Option<Hash>,
+}
+
+impl Context {
+    fn new(cli: &Cli) -> Result<Self, Box<dyn Error>> {
+        let config = match solana_cli_config::CONFIG_FILE.as_ref() {
+            Some(config_file) => solana_cli_config::Config::load(config_file).unwrap_or_default(),
+            None => solana_cli_config::Config::default(),
+        };
+        let url = cli.url.clone().unwrap_or(config.json_rpc_url);
+        let keypair_path = cli.keypair.clone().unwrap_or(config.keypair_path);
+        let keypair = read_keypair_file(&keypair_path)
+            .map_err(|e| format!("Failed to read keypair {}: {}", keypair_path, e))?;
+        Ok(Self {
+            rpc: RpcClient::new_with_commitment(url, CommitmentConfig::confirmed()),
+            fee_payer: cli.fee_payer.unwrap_or_else(|| keypair.pubkey()),
+            keypair,
+            program_id: cli.program_id,
+            mint: cli.mint.unwrap_or(TOKEN_MINT),
+            output: cli.output,
+            offline_blockhash: if cli.sign_only { cli.blockhash } else { None },
+        })
+    }
+
+    fn client(&self) -> VestingClient<RpcClient> {
+        let mut client = VestingClient::new(
+            RpcClient::new_with_commitment(self.rpc.url(), self.rpc.commitment()),
+            self.program_id,
+        );
+        client.mint = self.mint;
+        client
+    }
+
+    /// Signs the instructions with the keypair, then sends them unless in offline mode
+    async fn process(
+        &self,
+        instructions: &[Instruction],
+    ) -> Result<TransactionOutput, Box<dyn Error>> {
+        self.process_with_signers(instructions, &[]).await
+    }
+
+    /// Signs the instructions with the keypair and the other signers, then sends them unless in
+    /// offline mode
+    async fn process_with_signers(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<TransactionOutput, Box<dyn Error>> {
+        let message = Message::new(instructions, Some(&self.fee_payer));
+        let mut transaction = Transaction::new_unsigned(message);
+        let blockhash = match self.offline_blockhash {
+            Some(blockhash) => blockhash,
+            None => self.rpc.get_latest_blockhash().await?,
+        };
+        transaction.try_partial_sign(&[&self.keypair], blockhash)?;
+        transaction.try_partial_sign(signers, blockhash)?;
+
+        if self.offline_blockhash.is_some() {
+            let signer_keys = transaction.message.signer_keys();
+            let (signers, absent_signers) =
+                signer_keys
+                    .iter()
+                    .zip(transaction.signatures.iter())
+                    .partition::<Vec<_>, _>(|(_, signature)| **signature != Default::default());
+            let signed = SignedTransaction {
+                transaction: base64::Engine::encode(
+                    &base64::engine::general_purpose::STANDARD,
+                    bincode::serialize(&transaction)?,
+                ),
+                signers: signers.iter().map(|(key, _)| key.to_string()).collect(),
+                absent_signers: absent_signers
+                    .iter()
+                    .map(|(key, _)| key.to_string())
+                    .collect(),
+            };
+            return Ok(TransactionOutput::Signed(signed));
+        }
+
+        if !transaction.is_signed() {
+            return Err("The transaction needs other signers, sign it with --sign-only".into());
+        }
+        let signature = self.rpc.send_and_confirm_transaction(&transaction).await?;
+        Ok(TransactionOutput::Sent {
+            signature: signature.to_string(),
+        })
+    }
+
+    /// Resolves the destination of a lock, from the command line or from the vesting account
+    async fn destination(
+        &self,
+        args: &LockArgs,
+        addresses: &LockAddresses,
+    ) -> Result<Pubkey, Box<dyn Error>> {
+        match (args.destination, self.offline_blockhash) {
+            (Some(destination), _) => Ok(destination),
+            (None, Some(_)) => Err("--destination is required in offline mode".into()),
+            (None, None) => Ok(self
+                .client()
+                .get_contract_info(&addresses.vesting_account)
+                .await?
+                .header
+                .destination_address),
+        }
+    }
+
+    /// The owner of the destination token account recorded in a lock, unless given
+    async fn destination_owner(
+        &self,
+        addresses: &LockAddresses,
+        destination_owner: Option<Pubkey>,
+    ) -> Result<Pubkey, Box<dyn Error>> {
+        match (destination_owner, self.offline_blockhash) {
+            (Some(destination_owner), _) => Ok(destination_owner),
+            (None, Some(_)) => Err("--destination-owner is required in offline mode".into()),
+            (None, None) => {
+                let mut client = self.client();
+                let info = client.get_contract_info(&addresses.vesting_account).await?;
+                Ok(client
+                    .token_account_owner(&info.header.destination_address)
+                    .await?)
+            }
+        }
+    }
+
+    /// Builds an instruction releasing a lock. A lock with a position token is released to the
+    /// associated token account of the keypair, which must hold the token.
+    async fn release_instruction(
+        &self,
+        args: &LockArgs,
+        addresses: &LockAddresses,
+        build: impl FnOnce(&Pubkey) -> Result<Instruction, ClientError>,
+    ) -> Result<Instruction, Box<dyn Error>> {
+        let position_mint = match (args.position_mint, self.offline_blockhash) {
+            (Some(position_mint), _) => Some(position_mint),
+            (None, Some(_)) => None,
+            (None, None) => self
+                .client()
+                .get_contract_info(&addresses.vesting_account)
+                .await?
+                .header
+                .position_mint(),
+        };
+        match position_mint {
+            Some(position_mint) => {
+                let holder = self.keypair.pubkey();
+                let destination = args
+                    .destination
+                    .unwrap_or_else(|| get_associated_token_address(&holder, &self.mint));
+                Ok(instructions::with_position_holder(
+                    build(&destination)?,
+                    &position_mint,
+                    &holder,
+                ))
+            }
+            None => Ok(build(&self.destination(args, addresses).await?)?),
+        }
+    }
+
+    /// Appends the hook program account of a lock with a hook, its hook authority and the
+    /// accounts forwarded to its hook to an instruction releasing it
+    async fn with_hook(
+        &self,
+        addresses: &LockAddresses,
+        hook: Option<Pubkey>,
+        hook_account: &[Pubkey],
+        instruction: Instruction,
+    ) -> Result<Instruction, Box<dyn Error>> {
+        let hook_program = match (hook, self.offline_blockhash) {
+            (Some(hook_program), _) => Some(hook_program),
+            (None, Some(_)) => None,
+            (None, None) => self
+                .client()
+                .get_contract_info(&addresses.vesting_account)
+                .await?
+                .header
+                .hook()
+                .map(|hook| hook.program_id),
+        };
+        Ok(match hook_program {
+            Some(hook_program) => instruction::with_hook(
+                instruction,
+                &hook_program,
+                &hook_authority_address(&self.program_id, &addresses.vesting_account),
+                &hook_accounts(hook_account),
+            ),
+            None => instruction,
+        })
+    }
+}
+
+/// The accounts forwarded to a hook, all writable
+fn hook_accounts(accounts: &[Pubkey]) -> Vec<AccountMeta> {
+    accounts
+        .iter()
+        .map(|account| AccountMeta::new(*account, false))
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn command_create(
+    context: &Context,
+    time_delta: u64,
+    amount: u64,
+    seed: Option<String>,
+    source: Option<Pubkey>,
+    beneficiary: Option<Pubkey>,
+    beneficiary_owner: Option<Pubkey>,
+    multisig: Option<Pubkey>,
+    multisig_signers: &[Pubkey],
+    revocation_authority: Option<Pubkey>,
+    position: bool,
+    keeper_tip: u64,
+    auto_renew: bool,
+    hook: Option<Hook>,
+    hook_account: &[Pubkey],
+    allowlist_proof: &[[u8; 32]],
+    allowlist_entry: bool,
+    deposit_fee_treasury: Option<Pubkey>,
+    referrer: Option<Pubkey>,
+    referrer_token_account: Option<Pubkey>,
+) -> Result<(), Box<dyn Error>> {
+    let seed = seed.unwrap_or_else(random_seed_word);
+    let addresses =
+        LockAddresses::find(&context.program_id, &context.mint, &seed_from_word(&seed)?);
+    if context.offline_blockhash.is_none()
+        && context
+            .rpc
+            .get_account_with_commitment(&addresses.vesting_account, context.rpc.commitment())
+            .await?
+            .value
+            .is_some()
+    {
+        return Err(format!("Lock {} already exists", addresses.vesting_account).into());
+    }
+    let owner = multisig.unwrap_or_else(|| context.keypair.pubkey());
+    let beneficiary_owner = match (beneficiary, beneficiary_owner, context.offline_blockhash) {
+        (None, _, _) => owner,
+        (Some(_), Some(beneficiary_owner), _) => beneficiary_owner,
+        (Some(_), None, Some(_)) => {
+            return Err("--beneficiary-owner is required in offline mode".into())
+        }
+        (Some(beneficiary), None, None) => {
+            context.client().token_account_owner(&beneficiary).await?
+        }
+    };
+    let allowlist = if !allowlist_proof.is_empty() {
+        Some(AllowlistAccess::Proof(allowlist_proof.to_vec()))
+    } else if allowlist_entry {
+        Some(AllowlistAccess::Entry(allowlist_entry_address(
+            &context.program_id,
+            &owner,
+        )))
+    } else if context.offline_blockhash.is_none() {
+        context.client().allowlist_access(&owner).await?
+    } else {
+        None
+    };
+    let deposit_fee_treasury = match deposit_fee_treasury {
+        Some(deposit_fee_treasury) => Some(deposit_fee_treasury),
+        None if context.offline_blockhash.is_none() => {
+            context.client().deposit_fee_treasury().await?
+        }
+        None => None,
+    };
+    let referral = match referrer {
+        Some(referrer)
+            if referrer_token_account.is_some() || context.offline_blockhash.is_some() =>
+        {
+            Some(Referral {
+                referrer,
+                referrer_account: referrer_address(&context.program_id, &referrer),
+                referrer_token_account,
+            })
+        }
+        Some(referrer) => {
+            let mut client = context.client();
+            client.referrer = Some(referrer);
+            client.referral().await?
+        }
+        None => None,
+    };
+    let multisig_signers: Vec<&Pubkey> = multisig_signers.iter().collect();
+    let position_mint = Keypair::new();
+    let instructions = instructions::lock(
+        &context.program_id,
+        &context.mint,
+        &context.fee_payer,
+        &owner,
+        &multisig_signers,
+        source,
+        beneficiary,
+        &beneficiary_owner,
+        &addresses,
+        Schedule { time_delta, amount },
+        CreateArgs {
+            revocation_authority,
+            keeper_tip,
+            auto_renew,
+            hook,
+            allowlist,
+            deposit_fee_treasury_token_account_key: deposit_fee_treasury,
+            referral,
+            position: position
+                .then(|| instructions::position(&beneficiary_owner, &position_mint.pubkey())),
+        },
+        &hook_accounts(hook_account),
+    )?;
+    let mut signers = vec![];
+    if position {
+        signers.push(&position_mint);
+    }
+    let created = CreatedLock {
+        seed,
+        vesting_account: addresses.vesting_account.to_string(),
+        vesting_token_account: addresses.vesting_token_account.to_string(),
+        position_mint: position.then(|| position_mint.pubkey().to_string()),
+        transaction: context
+            .process_with_signers(&instructions, &signers)
+            .await?,
+    };
+    print(&created, context.output);
+    Ok(())
+}
+
+async fn command_create_batch(
+    context: &Context,
+    file: &str,
+    batch_id: &str,
+    source: Option<Pubkey>,
+) -> Result<(), Box<dyn Error>> {
+    let csv = std::fs::read_to_string(file)
+        .map_err(|e| format!("Failed to read CSV file {}: {}", file, e))?;
+    let recipients = batch::parse_csv(&csv)?;
+    let locks = context
+        .client()
+        .lock_batch(
+            &context.keypair,
+            &context.keypair,
+            source,
+            batch_id.as_bytes(),
+            &recipients,
+        )
+        .await?;
+    print(
+        &CreatedBatch {
+            batch_id: batch_id.to_string(),
+            vesting_accounts: locks
+                .iter()
+                .map(|addresses| addresses.vesting_account.to_string())
+                .collect(),
+        },
+        context.output,
+    );
+    Ok(())
+}
+
+async fn command_initiate_unlock(
+    context: &Context,
+    args: &LockArgs,
+    hook: Option<Pubkey>,
+    hook_account: &[Pubkey],
+) -> Result<(), Box<dyn Error>> {
+    let addresses = LockAddresses::find(
+        &context.program_id,
+        &context.mint,
+        &seed_from_word(&args.seed)?,
+    );
+    let instruction = context
+        .release_instruction(args, &addresses, |destination| {
+            instructions::initialize_unlock(
+                &context.program_id,
+                &addresses,
+                destination,
+                &context.keypair.pubkey(),
+            )
+        })
+        .await?;
+    let instruction = context
+        .with_hook(&addresses, hook, hook_account, instruction)
+        .await?;
+    print(&context.process(&[instruction]).await?, context.output);
+    Ok(())
+}
+
+async fn command_unlock(
+    context: &Context,
+    args: &LockArgs,
+    destination_owner: Option<Pubkey>,
+    hook: Option<Pubkey>,
+    hook_account: &[Pubkey],
+) -> Result<(), Box<dyn Error>> {
+    let addresses = LockAddresses::find(
+        &context.program_id,
+        &context.mint,
+        &seed_from_word(&args.seed)?,
+    );
+    let destination_owner = context
+        .destination_owner(&addresses, destination_owner)
+        .await?;
+    let instruction = context
+        .release_instruction(args, &addresses, |destination| {
+            instructions::unlock(
+                &context.program_id,
+                &addresses,
+                destination,
+                &destination_owner,
+            )
+        })
+        .await?;
+    let instruction = instruction::with_keeper(instruction, &context.fee_payer);
+    let instruction = context
+        .with_hook(&addresses, hook, hook_account, instruction)
+        .await?;
+    print(&context.process(&[instruction]).await?, context.output);
+    Ok(())
+}
+
+async fn command_unlock_many(context: &Context, seeds: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut client = context.client();
+    let mut locks = vec![];
+    for seed in seeds {
+        let addresses =
+            LockAddresses::find(&context.program_id, &context.mint, &seed_from_word(seed)?);
+        let info = client.get_contract_info(&addresses.vesting_account).await?;
+        let destination_owner = client
+            .token_account_owner(&info.header.destination_address)
+            .await?;
+        locks.push((
+            addresses,
+            info.header.destination_address,
+            destination_owner,
+        ));
+    }
+    let instruction = instructions::unlock_many(&context.program_id, &context.fee_payer, &locks)?;
+    print(&context.process(&[instruction]).await?, context.output);
+    Ok(())
+}
+
+async fn command_keeper(
+    context: &Context,
+    min_tip: u64,
+    interval: u64,
+    once: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut result = Ok(());
+    context
+        .client()
+        .run_keeper(
+            &context.keypair,
+            min_tip,
+            Duration::from_secs(interval),
+            |round| match round {
+                Ok(locks) => {
+                    let round = KeeperRound {
+                        vesting_accounts: locks
+                            .iter()
+                            .map(|lock| lock.addresses.vesting_account.to_string())
+                            .collect(),
+                        keeper_tips: locks.iter().map(|lock| lock.keeper_tip).sum(),
+                    };
+                    print(&round, context.output);
+                    !once
+                }
+                // A failed round is retried by the next one
+                Err(e) if !once => {
+                    eprintln!("Keeper round failed: {}", e);
+                    true
+                }
+                Err(e) => {
+                    result = Err(e);
+                    false
+                }
+            },
+        )
+        .await;
+    Ok(result?)
+}
+
+async fn command_opt_out_renewal(context: &Context, args: &LockArgs) -> Result<(), Box<dyn Error>> {
+    let addresses = LockAddresses::find(
+        &context.program_id,
+        &context.mint,
+        &seed_from_word(&args.seed)?,
+    );
+    let instruction = context
+        .release_instruction(args, &addresses, |destination| {
+            instructions::opt_out_renewal(
+                &context.program_id,
+                &addresses,
+                destination,
+                &context.keypair.pubkey(),
+            )
+        })
+        .await?;
+    print(&context.process(&[instruction]).await?, context.output);
+    Ok(())
+}
+
+async fn command_revoke(
+    context: &Context,
+    args: &LockArgs,
+    destination_owner: Option<Pubkey>,
+    treasury: Option<Pubkey>,
+) -> Result<(), Box<dyn Error>> {
+    let addresses = LockAddresses::find(
+        &context.program_id,
+        &context.mint,
+        &seed_from_word(&args.seed)?,
+    );
+    let destination = context.destination(args, &addresses).await?;
+    let treasury = treasury
+        .unwrap_or_else(|| get_associated_token_address(&context.keypair.pubkey(), &context.mint));
+    let destination_owner = context
+        .destination_owner(&addresses, destination_owner)
+        .await?;
+    let instruction = instructions::revoke(
+        &context.program_id,
+        &addresses,
+        &destination,
+        &destination_owner,
+        &context.keypair.pubkey(),
+        &treasury,
+    )?;
+    print(&context.process(&[instruction]).await?, context.output);
+    Ok(())
+}
+
+async fn command_close(context: &Context, args: &LockArgs) -> Result<(), Box<dyn Error>> {
+    let addresses = LockAddresses::find(
+        &context.program_id,
+        &context.mint,
+        &seed_from_word(&args.seed)?,
+    );
+    let destination = context.destination(args, &addresses).await?;
+    let instruction = instructions::close(
+        &context.program_id,
+        &addresses,
+        &destination,
+        &context.keypair.pubkey(),
+    )?;
+    print(&context.process(&[instruction]).await?, context.output);
+    Ok(())
+}
+
+async fn command_transfer_position(
+    context: &Context,
+    args: &LockArgs,
+    new_destination: Pubkey,
+    new_destination_owner: Option<Pubkey>,
+    require_accept: bool,
+) -> Result<(), Box<dyn Error>> {
+    let addresses = LockAddresses::find(
+        &context.program_id,
+        &context.mint,
+        &seed_from_word(&args.seed)?,
+    );
+    let destination = context.destination(args, &addresses).await?;
+    let new_destination_owner = match (new_destination_owner, context.offline_blockhash) {
+        (Some(new_destination_owner), _) => new_destination_owner,
+        (None, Some(_)) => return Err("--new-destination-owner is required in offline mode".into()),
+        (None, None) => {
+            context
+                .client()
+                .token_account_owner(&new_destination)
+                .await?
+        }
+    };
+    let instructions = instructions::transfer_position(
+        &context.program_id,
+        &addresses,
+        &destination,
+        &context.keypair.pubkey(),
+        &new_destination,
+        &new_destination_owner,
+        require_accept,
+    )?;
+    print(&context.process(&instructions).await?, context.output);
+    Ok(())
+}
+
+async fn command_accept_position(
+    context: &Context,
+    args: &LockArgs,
+    owner: Option<Pubkey>,
+    new_destination: Option<Pubkey>,
+) -> Result<(), Box<dyn Error>> {
+    let addresses = LockAddresses::find(
+        &context.program_id,
+        &context.mint,
+        &seed_from_word(&args.seed)?,
+    );
+    let (destination, owner, new_destination) = match (
+        args.destination,
+        owner,
+        new_destination,
+        context.offline_blockhash,
+    ) {
+        (Some(destination), Some(owner), Some(new_destination), _) => {
+            (destination, owner, new_destination)
+        }
+        (_, _, _, Some(_)) => {
+            return Err(
+                "--destination, --owner and --new-destination are required in offline mode".into(),
+            )
+        }
+        (destination, owner, new_destination, None) => {
+            let mut client = context.client();
+            let info = client.get_contract_info(&addresses.vesting_account).await?;
+            let destination = destination.unwrap_or(info.header.destination_address);
+            let owner = owner.unwrap_or(info.header.owner);
+            let new_destination = new_destination
+                .or_else(|| info.header.pending_destination())
+                .ok_or(ClientError::NoPendingTransfer(addresses.vesting_account))?;
+            (destination, owner, new_destination)
+        }
+    };
+    let instructions = instructions::accept_position(
+        &context.program_id,
+        &addresses,
+        &destination,
+        &owner,
+        &new_destination,
+        &context.keypair.pubkey(),
+    )?;
+    print(&context.process(&instructions).await?, context.output);
+    Ok(())
+}
+
+async fn command_split(
+    context: &Context,
+    args: &LockArgs,
+    amount: u64,
+    new_seed: Option<String>,
+    new_destination: Option<Pubkey>,
+    new_destination_owner: Option<Pubkey>,
+) -> Result<(), Box<dyn Error>> {
+    let addresses = LockAddresses::find(
+        &context.program_id,
+        &context.mint,
+        &seed_from_word(&args.seed)?,
+    );
+    let new_seed = new_seed.unwrap_or_else(random_seed_word);
+    let new_addresses = LockAddresses::find(
+        &context.program_id,
+        &context.mint,
+        &seed_from_word(&new_seed)?,
+    );
+    let destination = context.destination(args, &addresses).await?;
+    let owner = context.keypair.pubkey();
+    let (new_destination, new_destination_owner) = match (
+        new_destination,
+        new_destination_owner,
+        context.offline_blockhash,
+    ) {
+        (None, _, _) => (destination, owner),
+        (Some(new_destination), Some(new_destination_owner), _) => {
+            (new_destination, new_destination_owner)
+        }
+        (Some(_), None, Some(_)) => {
+            return Err("--new-destination-owner is required in offline mode".into())
+        }
+        (Some(new_destination), None, None) => (
+            new_destination,
+            context
+                .client()
+                .token_account_owner(&new_destination)
+                .await?,
+        ),
+    };
+    let instructions = instructions::split(
+        &context.program_id,
+        &context.mint,
+        &context.fee_payer,
+        &addresses,
+        &new_addresses,
+        &destination,
+        &owner,
+        &new_destination,
+        &new_destination_owner,
+        amount,
+    )?;
+    let created = CreatedLock {
+        seed: new_seed,
+        vesting_account: new_addresses.vesting_account.to_string(),
+        vesting_token_account: new_addresses.vesting_token_account.to_string(),
+        position_mint: None,
+        transaction: context.process(&instructions).await?,
+    };
+    print(&created, context.output);
+    Ok(())
+}
+
+async fn command_merge(
+    context: &Context,
+    args: &LockArgs,
+    source_seeds: &[String],
+) -> Result<(), Box<dyn Error>> {
+    let addresses = LockAddresses::find(
+        &context.program_id,
+        &context.mint,
+        &seed_from_word(&args.seed)?,
+    );
+    let sources = source_seeds
+        .iter()
+        .map(|seed| {
+            Ok(LockAddresses::find(
+                &context.program_id,
+                &context.mint,
+                &seed_from_word(seed)?,
+            ))
+        })
+        .collect::<Result<Vec<_>, ClientError>>()?;
+    let destination = context.destination(args, &addresses).await?;
+    let instruction = instructions::merge(
+        &context.program_id,
+        &addresses,
+        &sources,
+        &destination,
+        &context.keypair.pubkey(),
+    )?;
+    print(&context.process(&[instruction]).await?, context.output);
+    Ok(())
+}
+
+async fn command_info(
+    context: &Context,
+    seed: Option<String>,
+    address: Option<Pubkey>,
+) -> Result<(), Box<dyn Error>> {
+    let vesting_account = match (address, seed) {
+        (Some(address), _) => address,
+        (None, Some(seed)) => {
+            LockAddresses::find(&context.program_id, &context.mint, &seed_from_word(&seed)?)
+                .vesting_account
+        }
+        (None, None) => unreachable!("clap requires either --seed or --address"),
+    };
+    let info = context.client().get_contract_info(&vesting_account).await?;
+    let clock = fetch_clock(context).await?;
+    print(&Lock::new(&vesting_account, &info, &clock), context.output);
+    Ok(())
+}
+
+/// Fetches the clock the release times of the locks are compared with
+async fn fetch_clock(context: &Context) -> Result<Clock, Box<dyn Error>> {
+    let account = context.rpc.get_account(&sysvar::clock::id()).await?;
+    Ok(from_account(&account).ok_or("Invalid clock sysvar account")?)
+}
+
+async fn command_list(context: &Context, owner: Option<Pubkey>) -> Result<(), Box<dyn Error>> {
+    let owner = owner.unwrap_or_else(|| context.keypair.pubkey());
+    let token_accounts = context
+        .rpc
+        .get_token_accounts_by_owner(&owner, TokenAccountsFilter::Mint(context.mint))
+        .await?;
+
+    let clock = fetch_clock(context).await?;
+    let mut locks = vec![];
+    for token_account in token_accounts {
+        let token_account: Pubkey = token_account.pubkey.parse()?;
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize((VestingScheduleHeader::LEN + VestingSchedule::LEN) as u64),
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, &token_account.to_bytes())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = context
+            .rpc
+            .get_program_accounts_with_config(&context.program_id, config)
+            .await?;
+        for (vesting_account, account) in accounts {
+            let info = ContractInfo::unpack(&account.data)?;
+            if info.header.is_initialized {
+                locks.push(Lock::new(&vesting_account, &info, &clock));
+            }
+        }
+    }
+    print(
+        &LockList {
+            owner: owner.to_string(),
+            locks,
+        },
+        context.output,
+    );
+    Ok(())
+}
+
+async fn command_stats(context: &Context) -> Result<(), Box<dyn Error>> {
+    let stats = context.client().get_stats().await?;
+    print(&StatsOutput::new(&stats), context.output);
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    let context = Context::new(&cli)?;
+    match cli.command {
+        Command::Create {
+            tier,
+            amount,
+            seed,
+            source,
+            beneficiary,
+            beneficiary_owner,
+            multisig,
+            ref multisig_signer,
+            revocation_authority,
+            position,
+            keeper_tip,
+            auto_renew,
+            hook,
+            hook_mode,
+            ref hook_account,
+            ref allowlist_proof,
+            allowlist_entry,
+            deposit_fee_treasury,
+            referrer,
+            referrer_token_account,
+        } => {
+            command_create(
+                &context,
+                tier,
+                amount,
+                seed,
+                source,
+                beneficiary,
+                beneficiary_owner,
+                multisig,
+                multisig_signer,
+                revocation_authority,
+                position,
+                keeper_tip,
+                auto_renew,
+                hook.map(|program_id| Hook {
+                    program_id,
+                    mode: hook_mode,
+                }),
+                hook_account,
+                allowlist_proof,
+                allowlist_entry,
+                deposit_fee_treasury,
+                referrer,
+                referrer_token_account,
+            )
+            .await
+        }
+        Command::CreateBatch {
+            ref file,
+            ref batch_id,
+            source,
+        } => {
+            if context.offline_blockhash.is_some() {
+                return Err("create-batch requires RPC access".into());
+            }
+            command_create_batch(&context, file, batch_id, source).await
+        }
+        Command::InitiateUnlock {
+            ref lock,
+            hook,
+            ref hook_account,
+        } => command_initiate_unlock(&context, lock, hook, hook_account).await,
+        Command::Unlock {
+            ref lock,
+            destination_owner,
+            hook,
+            ref hook_account,
+        } => command_unlock(&context, lock, destination_owner, hook, hook_account).await,
+        Command::UnlockMany { ref seed } => {
+            if context.offline_blockhash.is_some() {
+                return Err("unlock-many requires RPC access".into());
+            }
+            command_unlock_many(&context, seed).await
+        }
+        Command::Keeper {
+            min_tip,
+            interval,
+            once,
+        } => {
+            if context.offline_blockhash.is_some() {
+                return Err("keeper requires RPC access".into());
+            }
+            command_keeper(&context, min_tip, interval, once).await
+        }
+        Command::OptOutRenewal(ref args) => command_opt_out_renewal(&context, args).await,
+        Command::Revoke {
+            ref lock,
+            destination_owner,
+            treasury,
+        } => command_revoke(&context, lock, destination_owner, treasury).await,
+        Command::Close(ref args) => command_close(&context, args).await,
+        Command::TransferPosition {
+            ref lock,
+            new_destination,
+            new_destination_owner,
+            require_accept,
+        } => {
+            command_transfer_position(
+                &context,
+                lock,
+                new_destination,
+                new_destination_owner,
+                require_accept,
+            )
+            .await
+        }
+        Command::AcceptPosition {
+            ref lock,
+            owner,
+            new_destination,
+        } => command_accept_position(&context, lock, owner, new_destination).await,
+        Command::Split {
+            ref lock,
+            amount,
+            new_seed,
+            new_destination,
+            new_destination_owner,
+        } => {
+            command_split(
+                &context,
+                lock,
+                amount,
+                new_seed,
+                new_destination,
+                new_destination_owner,
+            )
+            .await
+        }
+        Command::Merge {
+            ref lock,
+            ref source_seed,
+        } => command_merge(&context, lock, source_seed).await,
+        Command::Info { seed, address } => {
+            if context.offline_blockhash.is_some() {
+                return Err("info requires RPC access".into());
+            }
+            command_info(&context, seed, address).await
+        }
+        Command::List { owner } => {
+            if context.offline_blockhash.is_some() {
+                return Err("list requires RPC access".into());
+            }
+            command_list(&context, owner).await
+        }
+        Command::Stats => {
+            if context.offline_blockhash.is_some() {
+                return Err("stats requires RPC access".into());
+            }
+            command_stats(&context).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_parse_tier() {
+        assert_eq!(parse_tier("0"), Ok(0));
+        assert_eq!(parse_tier("3"), Ok(7_776_000));
+        assert_eq!(parse_tier("12"), Ok(31_104_000));
+        assert!(parse_tier("1").is_err());
+        assert!(parse_tier("three").is_err());
+    }
+
+    #[test]
+    fn test_parse_hash() {
+        let hash = [0xab; 32];
+        assert_eq!(parse_hash(&"ab".repeat(32)), Ok(hash));
+        assert!(parse_hash(&"ab".repeat(31)).is_err());
+        assert!(parse_hash(&"zz".repeat(32)).is_err());
+        assert!(parse_hash(&"é".repeat(32)).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+use std::fmt;
+
+use clap::ValueEnum;
+use serde::Serialize;
+use solana_sdk::{clock::Clock, pubkey::Pubkey};
+use token_vesting::state::{LockMode, Stats};
+use token_vesting_client::{ContractInfo, TIERS};
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    Display,
+    Json,
+}
+
+pub fn print<T: Serialize + fmt::Display>(output: &T, format: OutputFormat) {
+    match format {
+        OutputFormat::Display => println!("{}", output),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(output).unwrap()),
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedLock {
+    pub seed: String,
+    pub vesting_account: String,
+    pub vesting_token_account: String,
+    pub position_mint: Option<String>,
+    #[serde(flatten)]
+    pub transaction: TransactionOutput,
+}
+
+impl fmt::Display for CreatedLock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Seed: {}", self.seed)?;
+        writeln!(f, "Vesting account: {}", self.vesting_account)?;
+        writeln!(f, "Vesting token account: {}", self.vesting_token_account)?;
+        if let Some(position_mint) = &self.position_mint {
+            writeln!(f, "Position mint: {}", position_mint)?;
+        }
+        write!(f, "{}", self.transaction)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedBatch {
+    pub batch_id: String,
+    pub vesting_accounts: Vec<String>,
+}
+
+impl fmt::Display for CreatedBatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} lock(s) created by batch {}",
+            self.vesting_accounts.len(),
+            self.batch_id
+        )?;
+        for vesting_account in &self.vesting_accounts {
+            write!(f, "\n{}", vesting_account)?;
+        }
+        Ok(())
+    }
+}
+
+/// A transaction that was either sent, or only signed in offline mode
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum TransactionOutput {
+    Sent { signature: String },
+    Signed(SignedTransaction),
+}
+
+impl fmt::Display for TransactionOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionOutput::Sent { signature } => write!(f, "Signature: {}", signature),
+            TransactionOutput::Signed(signed) => write!(f, "{}", signed),
+        }
+    }
+}
+
+/// A transaction signed in offline mode, serialized with bincode and encoded in base64
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedTransaction {
+    pub transaction: String,
+    pub signers: Vec<String>,
+    pub absent_signers: Vec<String>,
+}
+
+impl fmt::Display for SignedTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Transaction: {}", self.transaction)?;
+        writeln!(f, "Signers: {}", self.signers.join(", "))?;
+        write!(f, "Absent signers: {}", self.absent_signers.join(", "))
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lock {
+    pub vesting_account: String,
+    pub destination_address: String,
+    pub owner: String,
+    pub release_time: u64,
+    pub lock_mode: String,
+    pub amount: u64,
+    pub revocation_authority: Option<String>,
+    pub position_mint: Option<String>,
+    pub pending_destination: Option<String>,
+    pub keeper_tip: u64,
+    pub auto_renew: bool,
+    pub referrer: Option<String>,
+    pub status: LockStatus,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LockStatus {
+    /// Unlocked stake whose withdrawal period has not been initialized
+    Staked,
+    Locked,
+    Releasable,
+    Claimed,
+    Revoked,
+}
+
+impl Lock {
+    pub fn new(vesting_account: &Pubkey, info: &ContractInfo, clock: &Clock) -> Self {
+        let lock_mode = info.header.lock_mode;
+        let now = lock_mode.now(clock);
+        // The release time of an auto-renewing lock rolls forward at each maturity
+        let release_time = info.header.release_time(&info.schedule, now);
+        let status = if info.header.is_revoked {
+            LockStatus::Revoked
+        } else if info.schedule.amount == 0 {
+            LockStatus::Claimed
+        } else if info.schedule.release_time == 0 {
+            LockStatus::Staked
+        } else if now < release_time {
+            LockStatus::Locked
+        } else {
+            LockStatus::Releasable
+        };
+        Self {
+            vesting_account: vesting_account.to_string(),
+            destination_address: info.header.destination_address.to_string(),
+            owner: info.header.owner.to_string(),
+            release_time,
+            lock_mode: match lock_mode {
+                LockMode::Timestamp => "timestamp",
+                LockMode::Slot => "slot",
+                LockMode::Epoch => "epoch",
+            }
+            .to_string(),
+            amount: info.schedule.amount,
+            revocation_authority: info
+                .header
+                .revocation_authority()
+                .map(|authority| authority.to_string()),
+            position_mint: info
+                .header
+                .position_mint()
+                .map(|position_mint| position_mint.to_string()),
+            pending_destination: info
+                .header
+                .pending_destination()
+                .map(|pending_destination| pending_destination.to_string()),
+            keeper_tip: info.header.keeper_tip,
+            auto_renew: info.header.auto_renew,
+            referrer: info.header.referrer().map(|referrer| referrer.to_string()),
+            status,
+        }
+    }
+}
+
+impl fmt::Display for Lock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Vesting account: {}", self.vesting_account)?;
+        writeln!(f, "Destination: {}", self.destination_address)?;
+        writeln!(f, "Owner: {}", self.owner)?;
+        writeln!(
+            f,
+            "Release time: {} ({})",
+            self.release_time, self.lock_mode
+        )?;
+        writeln!(f, "Amount: {}", self.amount)?;
+        if let Some(revocation_authority) = &self.revocation_authority {
+            writeln!(f, "Revocation authority: {}", revocation_authority)?;
+        }
+        if let Some(position_mint) = &self.position_mint {
+            writeln!(f, "Position mint: {}", position_mint)?;
+        }
+        if let Some(pending_destination) = &self.pending_destination {
+            writeln!(f, "Pending transfer to: {}", pending_destination)?;
+        }
+        if self.keeper_tip > 0 {
+            writeln!(f, "Keeper tip: {} lamports", self.keeper_tip)?;
+        }
+        if self.auto_renew {
+            writeln!(f, "Renews at the release time, unless opted out")?;
+        }
+        if let Some(referrer) = &self.referrer {
+            writeln!(f, "Referrer: {}", referrer)?;
+        }
+        write!(f, "Status: {:?}", self.status)
+    }
+}
+
+/// The locks released by a round of the keeper
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeeperRound {
+    pub vesting_accounts: Vec<String>,
+    pub keeper_tips: u64,
+}
+
+impl fmt::Display for KeeperRound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} lock(s) released, earning {} lamports",
+            self.vesting_accounts.len(),
+            self.keeper_tips
+        )?;
+        for vesting_account in &self.vesting_accounts {
+            write!(f, "\n{}", vesting_account)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockList {
+    pub owner: String,
+    pub locks: Vec<Lock>,
+}
+
+impl fmt::Display for LockList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} lock(s) owned by {}", self.locks.len(), self.owner)?;
+        for lock in &self.locks {
+            write!(f, "\n\n{}", lock)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TierStatsOutput {
+    pub months: u64,
+    pub total_locked: u64,
+    pub active_locks: u64,
+    pub pending_cooldown: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsOutput {
+    pub tiers: Vec<TierStatsOutput>,
+}
+
+impl StatsOutput {
+    pub fn new(stats: &Stats) -> Self {
+        Self {
+            tiers: TIERS
+                .iter()
+                .zip(stats.tiers.iter())
+                .map(|(&months, tier)| TierStatsOutput {
+                    months,
+                    total_locked: tier.total_locked,
+                    active_locks: tier.active_locks,
+                    pending_cooldown: tier.pending_cooldown,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for StatsOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, tier) in self.tiers.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{} month(s): {} locked in {} lock(s), {} pending cooldown",
+                tier.months, tier.total_locked, tier.active_locks, tier.pending_cooldown
+            )?;
+        }
+        Ok(())
+    }
+}
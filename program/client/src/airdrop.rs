@@ -0,0 +1,154 @@
+//! Off-chain side of the locked airdrops: the Merkle tree of the entries, the proofs of each
+//! recipient and the addresses of the airdrop accounts. The same trees hash the allowlisted
+//! wallets.
+use solana_sdk::pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address;
+use token_vesting::{merkle, processor::AIRDROP_SEED};
+
+use crate::{ClientError, LockAddresses, SEED_LEN};
+
+/// An entry of an airdrop: the wallet claims `amount` tokens into a lock of `time_delta` seconds
+#[derive(Clone, Debug, PartialEq)]
+pub struct AirdropEntry {
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub time_delta: u64,
+}
+
+impl AirdropEntry {
+    pub fn leaf(&self) -> [u8; 32] {
+        merkle::leaf_hash(&self.wallet, self.amount, self.time_delta)
+    }
+}
+
+/// Merkle tree of the entries of an airdrop or of the allowlisted wallets, hashed the way the
+/// program verifies the proofs.
+///
+/// A node without sibling is paired with itself.
+#[derive(Clone, Debug)]
+pub struct MerkleTree {
+    /// Levels of the tree, from the leaves to the root
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    pub fn new(entries: &[AirdropEntry]) -> Result<Self, ClientError> {
+        Self::from_leaves(entries.iter().map(AirdropEntry::leaf).collect())
+    }
+
+    /// Tree of the allowlisted wallets, for the allowlist root of the config
+    pub fn allowlist(wallets: &[Pubkey]) -> Result<Self, ClientError> {
+        Self::from_leaves(wallets.iter().map(merkle::allowlist_leaf_hash).collect())
+    }
+
+    fn from_leaves(leaves: Vec<[u8; 32]>) -> Result<Self, ClientError> {
+        if leaves.is_empty() {
+            return Err(ClientError::EmptyAirdrop);
+        }
+        let mut levels = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let level = levels.last().unwrap();
+            let parents = level
+                .chunks(2)
+                .map(|pair| merkle::node_hash(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+                .collect();
+            levels.push(parents);
+        }
+        Ok(Self { levels })
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.levels.last().unwrap()[0]
+    }
+
+    /// Proof of the entry at the given index, in the order of the entries given to `new` or of
+    /// the wallets given to `allowlist`
+    pub fn proof(&self, index: usize) -> Option<Vec<[u8; 32]>> {
+        if index >= self.levels[0].len() {
+            return None;
+        }
+        let mut index = index;
+        let mut proof = vec![];
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = level.get(index ^ 1).unwrap_or(&level[index]);
+            proof.push(*sibling);
+            index /= 2;
+        }
+        Some(proof)
+    }
+}
+
+/// The addresses of an airdrop, all derived from its seeds
+#[derive(Clone, Debug, PartialEq)]
+pub struct AirdropAddresses {
+    /// The user seed followed by the bump of the airdrop account
+    pub seeds: [u8; 32],
+    pub airdrop_account: Pubkey,
+    /// The associated token account of the airdrop account, funding the claims
+    pub vault_token_account: Pubkey,
+}
+
+impl AirdropAddresses {
+    /// Derives the addresses of an airdrop from a user chosen seed
+    pub fn find(program_id: &Pubkey, mint: &Pubkey, seed: &[u8; SEED_LEN]) -> Self {
+        let (airdrop_account, bump) =
+            Pubkey::find_program_address(&[AIRDROP_SEED, seed], program_id);
+        let mut seeds = [0u8; 32];
+        seeds[..SEED_LEN].copy_from_slice(seed);
+        seeds[SEED_LEN] = bump;
+        Self {
+            seeds,
+            airdrop_account,
+            vault_token_account: get_associated_token_address(&airdrop_account, mint),
+        }
+    }
+
+    /// Addresses of the lock a wallet claims its entry into
+    pub fn claim_lock(&self, program_id: &Pubkey, mint: &Pubkey, wallet: &Pubkey) -> LockAddresses {
+        LockAddresses::find(
+            program_id,
+            mint,
+            &merkle::claim_seed(&self.airdrop_account, wallet),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(count: u64) -> Vec<AirdropEntry> {
+        (0..count)
+            .map(|i| AirdropEntry {
+                wallet: Pubkey::new_unique(),
+                amount: i + 1,
+                time_delta: 7_776_000,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_merkle_tree_proofs() {
+        for count in 1..=9 {
+            let entries = entries(count);
+            let tree = MerkleTree::new(&entries).unwrap();
+            for (index, entry) in entries.iter().enumerate() {
+                let proof = tree.proof(index).unwrap();
+                assert!(merkle::verify(&proof, &tree.root(), entry.leaf()));
+
+                let mut wrong_amount = entry.clone();
+                wrong_amount.amount += 1;
+                assert!(!merkle::verify(&proof, &tree.root(), wrong_amount.leaf()));
+            }
+            assert_eq!(tree.proof(entries.len()), None);
+        }
+    }
+
+    #[test]
+    fn test_merkle_tree_empty() {
+        assert!(matches!(
+            MerkleTree::new(&[]),
+            Err(ClientError::EmptyAirdrop)
+        ));
+    }
+}
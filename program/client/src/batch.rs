@@ -0,0 +1,317 @@
+//! Creation of many locks from a CSV file, split into `CreateBatch` transactions.
+//!
+//! Every CSV line is `beneficiary,tier,amount`, where the beneficiary is a wallet receiving the
+//! tokens in its associated token account, and the tier is the lock period in months. Empty lines,
+//! lines starting with `#` and a `beneficiary,tier,amount` header are ignored.
+use std::str::FromStr;
+
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction, hash::hashv, instruction::Instruction,
+    message::Message, packet::PACKET_DATA_SIZE, pubkey::Pubkey, signature::SIGNATURE_BYTES, sysvar,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use token_vesting::instruction::{self, BatchEntry, BatchLock, Schedule};
+
+use crate::{
+    config_address, receipt_mint_address, registry_address, stats_address, tier_time_delta,
+    ClientError, LockAddresses, SEED_LEN,
+};
+
+/// Compute units requested for every lock of a batch, including the creation of the associated
+/// token accounts and of the position registry
+pub const COMPUTE_UNITS_PER_LOCK: u32 = 90_000;
+
+/// Upper bound of the locks of a single transaction, to stay below the compute limit
+pub const MAX_LOCKS_PER_TRANSACTION: usize = 15;
+
+const CSV_HEADER: &str = "beneficiary,tier,amount";
+
+/// A lock to create, read from a CSV line
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchRecipient {
+    /// Wallet receiving the tokens in its associated token account
+    pub beneficiary: Pubkey,
+    pub time_delta: u64,
+    pub amount: u64,
+}
+
+/// A transaction creating some of the locks of a batch
+#[derive(Clone, Debug)]
+pub struct BatchTransaction {
+    pub instructions: Vec<Instruction>,
+    /// The locks created by the transaction, with the index of their recipient
+    pub locks: Vec<(usize, LockAddresses)>,
+}
+
+/// Parses the recipients of a batch from CSV
+pub fn parse_csv(csv: &str) -> Result<Vec<BatchRecipient>, ClientError> {
+    let mut recipients = vec![];
+    for (index, line) in csv.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.replace(' ', "") == CSV_HEADER {
+            continue;
+        }
+        let invalid = |reason: &str| ClientError::InvalidCsv {
+            line: index + 1,
+            reason: reason.to_string(),
+        };
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != 3 {
+            return Err(invalid("expected beneficiary,tier,amount"));
+        }
+        let beneficiary =
+            Pubkey::from_str(fields[0]).map_err(|_| invalid("invalid beneficiary"))?;
+        let time_delta = fields[1]
+            .parse()
+            .ok()
+            .and_then(tier_time_delta)
+            .ok_or_else(|| invalid("invalid tier"))?;
+        let amount = fields[2].parse().map_err(|_| invalid("invalid amount"))?;
+        recipients.push(BatchRecipient {
+            beneficiary,
+            time_delta,
+            amount,
+        });
+    }
+    Ok(recipients)
+}
+
+/// Seed of the lock of a batch recipient. Seeds are derived from the batch id, so that a batch
+/// interrupted midway can be sent again without creating its locks twice.
+pub fn batch_seed(batch_id: &[u8], index: usize) -> [u8; SEED_LEN] {
+    let hash = hashv(&[b"batch", batch_id, &(index as u64).to_le_bytes()]);
+    let mut seed = [0u8; SEED_LEN];
+    seed.copy_from_slice(&hash.as_ref()[..SEED_LEN]);
+    seed
+}
+
+/// Splits the recipients of a batch into transactions, each as large as the packet size and the
+/// compute limit allow.
+///
+/// Every transaction creates the associated token accounts of its vesting accounts and
+/// beneficiaries, the receipt associated token accounts of the beneficiaries of unlocked stakes
+/// and the position registries of the beneficiaries, then the locks themselves with a single `CreateBatch` instruction. The payer and
+/// the source owner (or its multisig signers) must sign every transaction. The deposit fee
+/// treasury of the config is passed while the config charges a deposit fee.
+#[allow(clippy::too_many_arguments)]
+pub fn batch_transactions(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    payer: &Pubkey,
+    source_owner: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    source_token_account: Option<Pubkey>,
+    deposit_fee_treasury: Option<Pubkey>,
+    batch_id: &[u8],
+    recipients: &[BatchRecipient],
+) -> Result<Vec<BatchTransaction>, ClientError> {
+    let source_token_account =
+        source_token_account.unwrap_or_else(|| get_associated_token_address(source_owner, mint));
+    let receipt_mint = receipt_mint_address(program_id);
+    let build = |locks: &[(usize, LockAddresses)]| -> Result<Vec<Instruction>, ClientError> {
+        let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            COMPUTE_UNITS_PER_LOCK * locks.len() as u32,
+        )];
+        let mut batch_locks = vec![];
+        let mut entries = vec![];
+        for (index, addresses) in locks {
+            let recipient = &recipients[*index];
+            instructions.push(create_associated_token_account_idempotent(
+                payer,
+                &addresses.vesting_account,
+                mint,
+                &spl_token::id(),
+            ));
+            instructions.push(create_associated_token_account_idempotent(
+                payer,
+                &recipient.beneficiary,
+                mint,
+                &spl_token::id(),
+            ));
+            if recipient.time_delta == 0 {
+                instructions.push(create_associated_token_account_idempotent(
+                    payer,
+                    &recipient.beneficiary,
+                    &receipt_mint,
+                    &spl_token::id(),
+                ));
+            }
+            let registry_account = registry_address(program_id, &recipient.beneficiary);
+            instructions.push(instruction::init_registry(
+                program_id,
+                payer,
+                &recipient.beneficiary,
+                &registry_account,
+            )?);
+            batch_locks.push(BatchLock {
+                vesting_account_key: addresses.vesting_account,
+                vesting_token_account_key: addresses.vesting_token_account,
+                beneficiary_token_account_key: get_associated_token_address(
+                    &recipient.beneficiary,
+                    mint,
+                ),
+                receipt_token_account_key: get_associated_token_address(
+                    &recipient.beneficiary,
+                    &receipt_mint,
+                ),
+                registry_account_key: registry_account,
+            });
+            entries.push(BatchEntry {
+                seeds: addresses.seeds,
+                schedule: Schedule {
+                    time_delta: recipient.time_delta,
+                    amount: recipient.amount,
+                },
+            });
+        }
+        instructions.push(instruction::create_batch(
+            program_id,
+            &spl_token::id(),
+            &sysvar::clock::id(),
+            payer,
+            source_owner,
+            &source_token_account,
+            &config_address(program_id),
+            &stats_address(program_id),
+            &receipt_mint,
+            deposit_fee_treasury.as_ref(),
+            signer_pubkeys,
+            &batch_locks,
+            entries,
+        )?);
+        Ok(instructions)
+    };
+
+    let fits = |locks: &[(usize, LockAddresses)]| -> Result<bool, ClientError> {
+        Ok(locks.len() <= MAX_LOCKS_PER_TRANSACTION
+            && transaction_size(&build(locks)?, payer) <= PACKET_DATA_SIZE)
+    };
+
+    let mut transactions = vec![];
+    let mut locks: Vec<(usize, LockAddresses)> = vec![];
+    for index in 0..recipients.len() {
+        let addresses = LockAddresses::find(program_id, mint, &batch_seed(batch_id, index));
+        locks.push((index, addresses));
+        if !fits(&locks)? {
+            // Send the previous locks in their own transaction, and start a new one
+            let last = locks.pop().unwrap();
+            if !locks.is_empty() {
+                transactions.push(BatchTransaction {
+                    instructions: build(&locks)?,
+                    locks: std::mem::take(&mut locks),
+                });
+            }
+            locks.push(last);
+            if !fits(&locks)? {
+                return Err(ClientError::TransactionTooLarge);
+            }
+        }
+    }
+    if !locks.is_empty() {
+        transactions.push(BatchTransaction {
+            instructions: build(&locks)?,
+            locks,
+        });
+    }
+    Ok(transactions)
+}
+
+/// Size of the serialized transaction, once signed
+fn transaction_size(instructions: &[Instruction], payer: &Pubkey) -> usize {
+    let message = Message::new(instructions, Some(payer));
+    let signatures = usize::from(message.header.num_required_signatures);
+    // The signature count is encoded as a compact u16, a single byte below 128
+    1 + signatures * SIGNATURE_BYTES + message.serialize().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let beneficiary = Pubkey::new_unique();
+        let csv = format!(
+            "beneficiary,tier,amount\n# comment\n\n{},3,100\n {} , 0 , 5 \n",
+            beneficiary, beneficiary
+        );
+        assert_eq!(
+            parse_csv(&csv).unwrap(),
+            vec![
+                BatchRecipient {
+                    beneficiary,
+                    time_delta: 7_776_000,
+                    amount: 100,
+                },
+                BatchRecipient {
+                    beneficiary,
+                    time_delta: 0,
+                    amount: 5,
+                },
+            ]
+        );
+
+        assert!(matches!(
+            parse_csv(&format!("{},4,100", beneficiary)),
+            Err(ClientError::InvalidCsv { line: 1, .. })
+        ));
+        assert!(matches!(
+            parse_csv(&format!("\n{},3", beneficiary)),
+            Err(ClientError::InvalidCsv { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse_csv("not a key,3,100"),
+            Err(ClientError::InvalidCsv { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_batch_transactions() {
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let recipients: Vec<BatchRecipient> = (0..50)
+            .map(|amount| BatchRecipient {
+                beneficiary: Pubkey::new_unique(),
+                time_delta: 0,
+                amount,
+            })
+            .collect();
+
+        let transactions = batch_transactions(
+            &program_id,
+            &mint,
+            &payer,
+            &owner,
+            &[],
+            None,
+            None,
+            b"test",
+            &recipients,
+        )
+        .unwrap();
+        assert!(transactions.len() > 1);
+
+        // Every recipient gets exactly one lock, in order
+        let indexes: Vec<usize> = transactions
+            .iter()
+            .flat_map(|transaction| transaction.locks.iter().map(|(index, _)| *index))
+            .collect();
+        assert_eq!(indexes, (0..50).collect::<Vec<_>>());
+
+        for transaction in &transactions {
+            assert!(transaction_size(&transaction.instructions, &payer) <= PACKET_DATA_SIZE);
+            assert!(transaction.locks.len() <= MAX_LOCKS_PER_TRANSACTION);
+        }
+
+        // Seeds only depend on the batch id and the index
+        assert_eq!(
+            transactions[0].locks[0].1,
+            LockAddresses::find(&program_id, &mint, &batch_seed(b"test", 0))
+        );
+        assert_ne!(batch_seed(b"test", 0), batch_seed(b"other", 0));
+    }
+}
@@ -0,0 +1,37 @@
+use solana_sdk::{program_error::ProgramError, pubkey::Pubkey, signer::SignerError};
+use thiserror::Error;
+
+/// Errors that may be returned by the token vesting client.
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("RPC request failed: {0}")]
+    Rpc(String),
+    #[error(transparent)]
+    Program(#[from] ProgramError),
+    #[error(transparent)]
+    Signer(#[from] SignerError),
+    #[error("Vesting contract {0} already exists")]
+    AlreadyExists(Pubkey),
+    #[error("Account {0} is unavailable")]
+    AccountNotFound(Pubkey),
+    #[error("Vesting contract {0} is not initialized")]
+    NotInitialized(Pubkey),
+    #[error("Invalid vesting seeds")]
+    InvalidSeeds,
+    #[error("Invalid CSV line {line}: {reason}")]
+    InvalidCsv { line: usize, reason: String },
+    #[error("A single lock does not fit in a transaction")]
+    TransactionTooLarge,
+    #[error("An airdrop needs at least one entry")]
+    EmptyAirdrop,
+    #[error("Airdrop entry {0} does not exist")]
+    EntryNotFound(usize),
+    #[error("Vesting contract {0} has no pending transfer")]
+    NoPendingTransfer(Pubkey),
+}
+
+impl From<solana_client::client_error::ClientError> for ClientError {
+    fn from(e: solana_client::client_error::ClientError) -> Self {
+        ClientError::Rpc(e.to_string())
+    }
+}
@@ -0,0 +1,676 @@
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction, system_program, sysvar,
+};
+use spl_associated_token_account::{
+    get_associated_token_address,
+    instruction::{create_associated_token_account, create_associated_token_account_idempotent},
+};
+use token_vesting::instruction::{self, CreateArgs, MergedLock, Position, Schedule, UnlockedLock};
+
+use crate::{
+    airdrop::{AirdropAddresses, AirdropEntry},
+    config_address,
+    error::ClientError,
+    hook_authority_address, receipt_mint_address, registry_address, stats_address, LockAddresses,
+};
+
+/// Instructions creating a lock: the vesting account, its associated token account, the position
+/// registry of the beneficiary owner if it does not exist yet, the receipt associated token
+/// account of the beneficiary owner for an unlocked stake, and the `Create` instruction
+/// transferring the tokens from the source account.
+///
+/// If no source token account is given, the owner's associated token account is used. The tokens
+/// are released to the beneficiary token account, which defaults to the source token account and
+/// must be owned by `beneficiary_owner`.
+/// When the owner is an SPL multisig, `signer_pubkeys` lists the multisig signers. The keeper tip
+/// of `args` is transferred from the payer to the vesting account, for the account releasing the
+/// lock once it matures. A lock with a hook invokes its hook program with the hook accounts after
+/// its creation. A lock with a referral credits the referrer, whose referrer account is created
+/// if it does not exist yet. A lock with a position creates its position mint, which must sign,
+/// and mints the position token to the associated token account of the beneficiary owner.
+#[allow(clippy::too_many_arguments)]
+pub fn lock(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    payer: &Pubkey,
+    source_owner: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
+    source_token_account: Option<Pubkey>,
+    beneficiary_token_account: Option<Pubkey>,
+    beneficiary_owner: &Pubkey,
+    addresses: &LockAddresses,
+    schedule: Schedule,
+    args: CreateArgs,
+    hook_accounts: &[AccountMeta],
+) -> Result<Vec<Instruction>, ClientError> {
+    let source_token_account =
+        source_token_account.unwrap_or_else(|| get_associated_token_address(source_owner, mint));
+    let beneficiary_token_account = beneficiary_token_account.unwrap_or(source_token_account);
+    let registry_account = registry_address(program_id, beneficiary_owner);
+    let receipt_mint = receipt_mint_address(program_id);
+    let mut instructions = vec![
+        instruction::init(
+            &system_program::id(),
+            &sysvar::rent::id(),
+            program_id,
+            payer,
+            &addresses.vesting_account,
+            addresses.seeds,
+        )?,
+        create_associated_token_account(payer, &addresses.vesting_account, mint, &spl_token::id()),
+        instruction::init_registry(program_id, payer, beneficiary_owner, &registry_account)?,
+    ];
+    if schedule.time_delta == 0 {
+        instructions.push(create_associated_token_account_idempotent(
+            payer,
+            beneficiary_owner,
+            &receipt_mint,
+            &spl_token::id(),
+        ));
+    }
+    if let Some(referral) = &args.referral {
+        instructions.push(instruction::init_referrer(
+            program_id,
+            payer,
+            &referral.referrer,
+            &referral.referrer_account,
+        )?);
+    }
+    if let Some(position) = &args.position {
+        instructions.extend(create_position_mint(
+            payer,
+            beneficiary_owner,
+            addresses,
+            &position.mint,
+        )?);
+    }
+    if args.keeper_tip > 0 {
+        instructions.push(system_instruction::transfer(
+            payer,
+            &addresses.vesting_account,
+            args.keeper_tip,
+        ));
+    }
+    let hook = args.hook;
+    let create = instruction::create(
+        program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &addresses.vesting_account,
+        &addresses.vesting_token_account,
+        source_owner,
+        &source_token_account,
+        &beneficiary_token_account,
+        &config_address(program_id),
+        &stats_address(program_id),
+        &registry_account,
+        &receipt_mint,
+        &get_associated_token_address(beneficiary_owner, &receipt_mint),
+        signer_pubkeys,
+        schedule,
+        addresses.seeds,
+        args,
+    )?;
+    instructions.push(match hook {
+        Some(hook) => instruction::with_hook(
+            create,
+            &hook.program_id,
+            &hook_authority_address(program_id, &addresses.vesting_account),
+            hook_accounts,
+        ),
+        None => create,
+    });
+    Ok(instructions)
+}
+
+/// Instruction starting the withdrawal period of an "unlocked" (0 time delta) lock, burning the
+/// receipts from the associated token account of the destination owner
+pub fn initialize_unlock(
+    program_id: &Pubkey,
+    addresses: &LockAddresses,
+    destination_token_account: &Pubkey,
+    destination_owner: &Pubkey,
+) -> Result<Instruction, ClientError> {
+    let receipt_mint = receipt_mint_address(program_id);
+    Ok(instruction::initialize_unlock(
+        program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &addresses.vesting_account,
+        &addresses.vesting_token_account,
+        destination_token_account,
+        &stats_address(program_id),
+        &receipt_mint,
+        &get_associated_token_address(destination_owner, &receipt_mint),
+        destination_owner,
+        addresses.seeds,
+    )?)
+}
+
+/// Instruction releasing the tokens of a matured lock to its destination. The lock is released
+/// in the position registry of `registry_owner`, the owner of the destination token account of
+/// the lock.
+pub fn unlock(
+    program_id: &Pubkey,
+    addresses: &LockAddresses,
+    destination_token_account: &Pubkey,
+    registry_owner: &Pubkey,
+) -> Result<Instruction, ClientError> {
+    Ok(instruction::unlock(
+        program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &addresses.vesting_account,
+        &addresses.vesting_token_account,
+        destination_token_account,
+        &stats_address(program_id),
+        &registry_address(program_id, registry_owner),
+        addresses.seeds,
+    )?)
+}
+
+/// Instruction releasing the matured locks of a list, each given with its destination token
+/// account and the owner of that account. The other locks are skipped, the keeper tips of the
+/// released ones are paid to the keeper account.
+pub fn unlock_many(
+    program_id: &Pubkey,
+    keeper: &Pubkey,
+    locks: &[(LockAddresses, Pubkey, Pubkey)],
+) -> Result<Instruction, ClientError> {
+    let locks: Vec<UnlockedLock> = locks
+        .iter()
+        .map(
+            |(addresses, destination_token_account, destination_owner)| UnlockedLock {
+                vesting_account_key: addresses.vesting_account,
+                vesting_token_account_key: addresses.vesting_token_account,
+                destination_token_account_key: *destination_token_account,
+                registry_account_key: registry_address(program_id, destination_owner),
+                seeds: addresses.seeds,
+            },
+        )
+        .collect();
+    Ok(instruction::unlock_many(
+        program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &stats_address(program_id),
+        keeper,
+        &locks,
+    )?)
+}
+
+/// Instruction revoking a lock: the vested tokens are released to its destination, owned by
+/// `destination_owner`, and the unvested ones to the treasury token account
+pub fn revoke(
+    program_id: &Pubkey,
+    addresses: &LockAddresses,
+    destination_token_account: &Pubkey,
+    destination_owner: &Pubkey,
+    revocation_authority: &Pubkey,
+    treasury_token_account: &Pubkey,
+) -> Result<Instruction, ClientError> {
+    Ok(instruction::revoke(
+        program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &addresses.vesting_account,
+        &addresses.vesting_token_account,
+        destination_token_account,
+        revocation_authority,
+        treasury_token_account,
+        &stats_address(program_id),
+        &registry_address(program_id, destination_owner),
+        addresses.seeds,
+    )?)
+}
+
+/// Instruction releasing the tokens of a lock before its release time, minus the early unlock
+/// penalty. The owner of the destination token account must sign. The lock is released in the
+/// position registry of `registry_owner`, the owner of the destination token account of the
+/// lock.
+pub fn early_unlock(
+    program_id: &Pubkey,
+    addresses: &LockAddresses,
+    destination_token_account: &Pubkey,
+    destination_owner: &Pubkey,
+    penalty_treasury_token_account: &Pubkey,
+    registry_owner: &Pubkey,
+) -> Result<Instruction, ClientError> {
+    Ok(instruction::early_unlock(
+        program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &addresses.vesting_account,
+        &addresses.vesting_token_account,
+        destination_token_account,
+        destination_owner,
+        &config_address(program_id),
+        penalty_treasury_token_account,
+        &stats_address(program_id),
+        &registry_address(program_id, registry_owner),
+        addresses.seeds,
+    )?)
+}
+
+/// Instruction stopping the renewal of an auto-renewing lock during the renewal notice period or
+/// the grace period after a maturity.
+/// The owner of the destination token account must sign.
+pub fn opt_out_renewal(
+    program_id: &Pubkey,
+    addresses: &LockAddresses,
+    destination_token_account: &Pubkey,
+    destination_owner: &Pubkey,
+) -> Result<Instruction, ClientError> {
+    Ok(instruction::opt_out_renewal(
+        program_id,
+        &sysvar::clock::id(),
+        &addresses.vesting_account,
+        destination_token_account,
+        destination_owner,
+        addresses.seeds,
+    )?)
+}
+
+/// Instruction releasing all the tokens of a lock to its destination while the program is in
+/// emergency mode. The lock is released in the position registry of `registry_owner`, the owner
+/// of the destination token account of the lock.
+pub fn emergency_unlock(
+    program_id: &Pubkey,
+    addresses: &LockAddresses,
+    destination_token_account: &Pubkey,
+    registry_owner: &Pubkey,
+) -> Result<Instruction, ClientError> {
+    Ok(instruction::emergency_unlock(
+        program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &addresses.vesting_account,
+        &addresses.vesting_token_account,
+        destination_token_account,
+        &config_address(program_id),
+        &stats_address(program_id),
+        &registry_address(program_id, registry_owner),
+        addresses.seeds,
+    )?)
+}
+
+/// Instruction closing a released or revoked lock, signed by the owner of its destination token
+/// account
+pub fn close(
+    program_id: &Pubkey,
+    addresses: &LockAddresses,
+    destination_token_account: &Pubkey,
+    destination_owner: &Pubkey,
+) -> Result<Instruction, ClientError> {
+    Ok(instruction::close(
+        program_id,
+        &spl_token::id(),
+        &addresses.vesting_account,
+        &addresses.vesting_token_account,
+        destination_token_account,
+        destination_owner,
+        &registry_address(program_id, destination_owner),
+        addresses.seeds,
+    )?)
+}
+
+/// Instructions transferring a lock to a token account of another wallet, signed by the owner of
+/// the lock. Unless the new owner has to accept the transfer, the position
+/// registry of the new owner is created if needed.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_position(
+    program_id: &Pubkey,
+    addresses: &LockAddresses,
+    destination_token_account: &Pubkey,
+    owner: &Pubkey,
+    new_destination_token_account: &Pubkey,
+    new_destination_owner: &Pubkey,
+    require_accept: bool,
+) -> Result<Vec<Instruction>, ClientError> {
+    let new_registry_account = registry_address(program_id, new_destination_owner);
+    let mut instructions = vec![];
+    if !require_accept {
+        instructions.push(instruction::init_registry(
+            program_id,
+            owner,
+            new_destination_owner,
+            &new_registry_account,
+        )?);
+    }
+    instructions.push(instruction::transfer_position(
+        program_id,
+        &addresses.vesting_account,
+        destination_token_account,
+        owner,
+        &registry_address(program_id, owner),
+        new_destination_token_account,
+        &new_registry_account,
+        &config_address(program_id),
+        addresses.seeds,
+        require_accept,
+    )?);
+    Ok(instructions)
+}
+
+/// Instructions accepting the pending transfer of a lock owned by `owner` to
+/// `new_destination_token_account`, signed by its owner, whose position registry is created if
+/// needed
+pub fn accept_position(
+    program_id: &Pubkey,
+    addresses: &LockAddresses,
+    destination_token_account: &Pubkey,
+    owner: &Pubkey,
+    new_destination_token_account: &Pubkey,
+    new_destination_owner: &Pubkey,
+) -> Result<Vec<Instruction>, ClientError> {
+    let new_registry_account = registry_address(program_id, new_destination_owner);
+    Ok(vec![
+        instruction::init_registry(
+            program_id,
+            new_destination_owner,
+            new_destination_owner,
+            &new_registry_account,
+        )?,
+        instruction::accept_position(
+            program_id,
+            &addresses.vesting_account,
+            destination_token_account,
+            &registry_address(program_id, owner),
+            new_destination_token_account,
+            new_destination_owner,
+            &new_registry_account,
+            &config_address(program_id),
+            addresses.seeds,
+        )?,
+    ])
+}
+
+/// Instructions splitting an amount off a lock into a new lock: the associated token account of
+/// the new vesting account, and the `Split` instruction. The new lock is released to the new
+/// destination token account, owned by `new_destination_owner`, and recorded in their position
+/// registry, created if needed.
+#[allow(clippy::too_many_arguments)]
+pub fn split(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    payer: &Pubkey,
+    addresses: &LockAddresses,
+    new_addresses: &LockAddresses,
+    destination_token_account: &Pubkey,
+    owner: &Pubkey,
+    new_destination_token_account: &Pubkey,
+    new_destination_owner: &Pubkey,
+    amount: u64,
+) -> Result<Vec<Instruction>, ClientError> {
+    Ok(vec![
+        create_associated_token_account_idempotent(
+            payer,
+            &new_addresses.vesting_account,
+            mint,
+            &spl_token::id(),
+        ),
+        instruction::init_registry(
+            program_id,
+            payer,
+            new_destination_owner,
+            &registry_address(program_id, new_destination_owner),
+        )?,
+        instruction::split(
+            program_id,
+            &spl_token::id(),
+            payer,
+            &addresses.vesting_account,
+            &addresses.vesting_token_account,
+            &new_addresses.vesting_account,
+            &new_addresses.vesting_token_account,
+            destination_token_account,
+            owner,
+            new_destination_token_account,
+            &stats_address(program_id),
+            &registry_address(program_id, owner),
+            &registry_address(program_id, new_destination_owner),
+            &config_address(program_id),
+            addresses.seeds,
+            new_addresses.seeds,
+            amount,
+        )?,
+    ])
+}
+
+/// Instruction merging locks into the lock at `addresses`, closing their accounts
+pub fn merge(
+    program_id: &Pubkey,
+    addresses: &LockAddresses,
+    sources: &[LockAddresses],
+    destination_token_account: &Pubkey,
+    destination_owner: &Pubkey,
+) -> Result<Instruction, ClientError> {
+    let sources: Vec<MergedLock> = sources
+        .iter()
+        .map(|source| MergedLock {
+            vesting_account_key: source.vesting_account,
+            vesting_token_account_key: source.vesting_token_account,
+            seeds: source.seeds,
+        })
+        .collect();
+    Ok(instruction::merge(
+        program_id,
+        &spl_token::id(),
+        &addresses.vesting_account,
+        &addresses.vesting_token_account,
+        destination_token_account,
+        destination_owner,
+        &stats_address(program_id),
+        &registry_address(program_id, destination_owner),
+        addresses.seeds,
+        &sources,
+    )?)
+}
+
+/// Instructions turning a lock into a position token: the one-of-one position mint, the
+/// associated token account of the owner of the destination token account, and the
+/// `MintPosition` instruction minting the token to it. The mint account must sign.
+///
+/// From then on, the lock is released to the holder of the token. The instructions can follow
+/// the ones of `lock` in the same transaction.
+pub fn mint_position(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    destination_owner: &Pubkey,
+    addresses: &LockAddresses,
+    destination_token_account: &Pubkey,
+    position_mint: &Pubkey,
+) -> Result<Vec<Instruction>, ClientError> {
+    let mut instructions =
+        create_position_mint(payer, destination_owner, addresses, position_mint)?;
+    instructions.push(instruction::mint_position(
+        program_id,
+        &spl_token::id(),
+        &addresses.vesting_account,
+        destination_token_account,
+        destination_owner,
+        position_mint,
+        &get_associated_token_address(destination_owner, position_mint),
+        addresses.seeds,
+    )?);
+    Ok(instructions)
+}
+
+/// The position token of a lock: its position mint and the associated token account of the
+/// holder receiving the token
+pub fn position(holder: &Pubkey, position_mint: &Pubkey) -> Position {
+    Position {
+        mint: *position_mint,
+        token_account: get_associated_token_address(holder, position_mint),
+    }
+}
+
+/// Instructions creating the one-of-one position mint of a lock, mintable by its vesting account,
+/// and the associated token account of the holder receiving the position token
+fn create_position_mint(
+    payer: &Pubkey,
+    holder: &Pubkey,
+    addresses: &LockAddresses,
+    position_mint: &Pubkey,
+) -> Result<Vec<Instruction>, ClientError> {
+    let mint_len = spl_token::state::Mint::LEN;
+    Ok(vec![
+        system_instruction::create_account(
+            payer,
+            position_mint,
+            Rent::default().minimum_balance(mint_len),
+            mint_len as u64,
+            &spl_token::id(),
+        ),
+        spl_token::instruction::initialize_mint2(
+            &spl_token::id(),
+            position_mint,
+            &addresses.vesting_account,
+            None,
+            0,
+        )?,
+        create_associated_token_account(payer, holder, position_mint, &spl_token::id()),
+    ])
+}
+
+/// Adds the accounts of the holder of a position token to an instruction releasing its lock. The
+/// token is held by the associated token account of the holder, who signs.
+pub fn with_position_holder(
+    instruction: Instruction,
+    position_mint: &Pubkey,
+    holder: &Pubkey,
+) -> Instruction {
+    instruction::with_position_holder(
+        instruction,
+        position_mint,
+        &get_associated_token_address(holder, position_mint),
+        holder,
+    )
+}
+
+/// Instructions creating an airdrop: the associated token account of the airdrop account, the
+/// `CreateAirdrop` instruction and the transfer of `amount` tokens to the vault.
+///
+/// If no source token account is given, the authority's associated token account is used.
+pub fn create_airdrop(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    source_token_account: Option<Pubkey>,
+    addresses: &AirdropAddresses,
+    merkle_root: [u8; 32],
+    amount: u64,
+) -> Result<Vec<Instruction>, ClientError> {
+    let source_token_account =
+        source_token_account.unwrap_or_else(|| get_associated_token_address(authority, mint));
+    Ok(vec![
+        create_associated_token_account(
+            authority,
+            &addresses.airdrop_account,
+            mint,
+            &spl_token::id(),
+        ),
+        instruction::create_airdrop(
+            program_id,
+            authority,
+            &addresses.airdrop_account,
+            &addresses.vault_token_account,
+            &config_address(program_id),
+            addresses.seeds,
+            merkle_root,
+        )?,
+        spl_token::instruction::transfer(
+            &spl_token::id(),
+            &source_token_account,
+            &addresses.vault_token_account,
+            authority,
+            &[],
+            amount,
+        )?,
+    ])
+}
+
+/// Instructions claiming an airdrop entry into a lock: the associated token accounts of the
+/// vesting account, of the destination and, for an unlocked stake, the receipt associated token
+/// account of the claimant, the position registry of the destination owner if it does not exist
+/// yet, and the `ClaimIntoLock` instruction.
+///
+/// The tokens are released to the destination token account, which defaults to the claimant's
+/// associated token account and must be owned by `destination_owner`. The deposit fee treasury of the config is passed while the config
+/// charges a deposit fee.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_into_lock(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    airdrop: &AirdropAddresses,
+    lock: &LockAddresses,
+    entry: &AirdropEntry,
+    proof: Vec<[u8; 32]>,
+    destination_token_account: Option<Pubkey>,
+    destination_owner: &Pubkey,
+    deposit_fee_treasury: Option<Pubkey>,
+) -> Result<Vec<Instruction>, ClientError> {
+    let mut instructions = vec![create_associated_token_account_idempotent(
+        &entry.wallet,
+        &lock.vesting_account,
+        mint,
+        &spl_token::id(),
+    )];
+    let destination_token_account = match destination_token_account {
+        Some(destination_token_account) => destination_token_account,
+        None => {
+            instructions.push(create_associated_token_account_idempotent(
+                &entry.wallet,
+                &entry.wallet,
+                mint,
+                &spl_token::id(),
+            ));
+            get_associated_token_address(&entry.wallet, mint)
+        }
+    };
+    let receipt_mint = receipt_mint_address(program_id);
+    if entry.time_delta == 0 {
+        instructions.push(create_associated_token_account_idempotent(
+            &entry.wallet,
+            &entry.wallet,
+            &receipt_mint,
+            &spl_token::id(),
+        ));
+    }
+    let registry_account = registry_address(program_id, destination_owner);
+    instructions.push(instruction::init_registry(
+        program_id,
+        &entry.wallet,
+        destination_owner,
+        &registry_account,
+    )?);
+    instructions.push(instruction::claim_into_lock(
+        program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &entry.wallet,
+        &airdrop.airdrop_account,
+        &airdrop.vault_token_account,
+        &lock.vesting_account,
+        &lock.vesting_token_account,
+        &destination_token_account,
+        &config_address(program_id),
+        &stats_address(program_id),
+        &receipt_mint,
+        &get_associated_token_address(&entry.wallet, &receipt_mint),
+        &registry_account,
+        deposit_fee_treasury.as_ref(),
+        airdrop.seeds,
+        lock.seeds,
+        Schedule {
+            time_delta: entry.time_delta,
+            amount: entry.amount,
+        },
+        proof,
+    )?);
+    Ok(instructions)
+}
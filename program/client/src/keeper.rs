@@ -0,0 +1,159 @@
+//! Keeper releasing matured locks for their keeper tips.
+//!
+//! `Unlock` is permissionless: once a lock matures, anyone can release it to its destination and
+//! earn the keeper tip deposited when the lock was created. A keeper periodically scans the
+//! vesting accounts of the program and releases the matured ones with `UnlockMany`.
+use solana_sdk::{
+    account::{from_account, Account},
+    clock::Clock,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+    sysvar,
+    transaction::Transaction,
+};
+use std::{cmp::Reverse, time::Duration};
+use token_vesting::state::{VestingSchedule, VestingScheduleHeader};
+
+use crate::{
+    error::ClientError, instructions, rpc::VestingRpc, ContractInfo, LockAddresses, VestingClient,
+};
+
+/// Number of locks released by a single `UnlockMany` transaction
+pub const LOCKS_PER_TRANSACTION: usize = 7;
+
+/// A matured lock that anyone can release
+#[derive(Clone, Debug, PartialEq)]
+pub struct MaturedLock {
+    pub addresses: LockAddresses,
+    pub destination_token_account: Pubkey,
+    /// Lamports earned by releasing the lock
+    pub keeper_tip: u64,
+}
+
+/// Selects the locks a keeper can release among the accounts of the program: the matured locks
+/// without a position token or a hook that were neither released nor revoked, paying a
+/// keeper tip of at least `min_keeper_tip`. The best paid locks come first.
+pub fn matured_locks(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    accounts: &[(Pubkey, Account)],
+    clock: &Clock,
+    min_keeper_tip: u64,
+) -> Vec<MaturedLock> {
+    let mut locks: Vec<MaturedLock> = accounts
+        .iter()
+        .filter(|(_, account)| account.owner == *program_id)
+        .filter_map(|(vesting_account, account)| {
+            let ContractInfo { header, schedule } = ContractInfo::unpack(&account.data).ok()?;
+            let now = header.lock_mode.now(clock);
+            if !header.is_initialized
+                || header.is_revoked
+                || header.position_mint().is_some()
+                || header.hook().is_some()
+                || header.keeper_tip < min_keeper_tip
+                || schedule.amount == 0
+                || schedule.release_time == 0
+                || now < header.release_time(&schedule, now)
+            {
+                return None;
+            }
+            let addresses = LockAddresses::from_seeds(program_id, mint, header.seeds).ok()?;
+            if addresses.vesting_account != *vesting_account {
+                return None;
+            }
+            Some(MaturedLock {
+                addresses,
+                destination_token_account: header.destination_address,
+                keeper_tip: header.keeper_tip,
+            })
+        })
+        .collect();
+    locks.sort_by_key(|lock| Reverse(lock.keeper_tip));
+    locks
+}
+
+impl<C: VestingRpc> VestingClient<C> {
+    /// Fetches the clock the release times of the locks are compared with
+    pub async fn get_clock(&mut self) -> Result<Clock, ClientError> {
+        let account = self
+            .rpc
+            .get_account(&sysvar::clock::id())
+            .await?
+            .ok_or_else(|| ClientError::AccountNotFound(sysvar::clock::id()))?;
+        from_account(&account).ok_or_else(|| ClientError::Rpc("Invalid clock sysvar".to_string()))
+    }
+
+    /// Scans the vesting accounts of the program for the locks a keeper can release
+    pub async fn find_matured_locks(
+        &mut self,
+        min_keeper_tip: u64,
+    ) -> Result<Vec<MaturedLock>, ClientError> {
+        let data_size = VestingScheduleHeader::LEN + VestingSchedule::LEN;
+        let accounts = self
+            .rpc
+            .get_program_accounts(&self.program_id, data_size)
+            .await?;
+        let clock = self.get_clock().await?;
+        Ok(matured_locks(
+            &self.program_id,
+            &self.mint,
+            &accounts,
+            &clock,
+            min_keeper_tip,
+        ))
+    }
+
+    /// Releases matured locks with `UnlockMany`, `LOCKS_PER_TRANSACTION` locks per transaction.
+    /// The keeper pays the fees and earns the keeper tips.
+    pub async fn release_matured_locks(
+        &mut self,
+        keeper: &dyn Signer,
+        locks: &[MaturedLock],
+    ) -> Result<Vec<Signature>, ClientError> {
+        let mut signatures = vec![];
+        for locks in locks.chunks(LOCKS_PER_TRANSACTION) {
+            let mut unlocked = vec![];
+            for lock in locks {
+                let destination_owner = self
+                    .token_account_owner(&lock.destination_token_account)
+                    .await?;
+                unlocked.push((
+                    lock.addresses.clone(),
+                    lock.destination_token_account,
+                    destination_owner,
+                ));
+            }
+            let instruction =
+                instructions::unlock_many(&self.program_id, &keeper.pubkey(), &unlocked)?;
+            let transaction = Transaction::new_with_payer(&[instruction], Some(&keeper.pubkey()));
+            signatures.push(self.sign_and_send(transaction, &[keeper]).await?);
+        }
+        Ok(signatures)
+    }
+
+    /// Runs a keeper: every `interval`, releases the matured locks paying a keeper tip of at
+    /// least `min_keeper_tip`. `on_round` receives the locks released by every round, or its
+    /// error, and the keeper carries on with the next round until `on_round` returns false.
+    pub async fn run_keeper(
+        &mut self,
+        keeper: &dyn Signer,
+        min_keeper_tip: u64,
+        interval: Duration,
+        mut on_round: impl FnMut(Result<Vec<MaturedLock>, ClientError>) -> bool,
+    ) {
+        loop {
+            let round = match self.find_matured_locks(min_keeper_tip).await {
+                Ok(locks) => self
+                    .release_matured_locks(keeper, &locks)
+                    .await
+                    .map(|_| locks),
+                Err(e) => Err(e),
+            };
+            if !on_round(round) {
+                return;
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
@@ -0,0 +1,1264 @@
+//! Rust client for the token vesting program.
+//!
+//! `VestingClient` wraps an RPC connection and exposes high-level async functions to lock tokens,
+//! initiate and complete their unlock and fetch contract information. The `instructions` module
+//! builds the same instructions without any network access, and the `keeper` module releases
+//! matured locks for their keeper tips.
+pub mod airdrop;
+pub mod batch;
+pub mod error;
+pub mod instructions;
+pub mod keeper;
+pub mod rpc;
+
+use solana_sdk::{
+    bpf_loader_upgradeable,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    signer::signers::Signers,
+    transaction::Transaction,
+};
+use spl_associated_token_account::get_associated_token_address;
+use std::{collections::BTreeMap, convert::TryInto};
+use token_vesting::{
+    hook::Hook,
+    instruction::{self, AllowlistAccess, CreateArgs, Referral, Schedule},
+    processor::{
+        ALLOWLIST_SEED, CONFIG_SEED, HOOK_AUTHORITY_SEED, RECEIPT_SEED, REFERRER_SEED,
+        REGISTRY_SEED, STATS_SEED, TOKEN_MINT,
+    },
+    state::{
+        unpack_schedule, Config, LockMode, PositionRegistry, Referrer, Stats, VestingSchedule,
+        VestingScheduleHeader,
+    },
+};
+
+pub use crate::{
+    airdrop::{AirdropAddresses, AirdropEntry, MerkleTree},
+    batch::BatchRecipient,
+    error::ClientError,
+    keeper::MaturedLock,
+    rpc::VestingRpc,
+};
+
+/// Length of the seed chosen by the user, the last byte of the seeds is the PDA bump
+pub const SEED_LEN: usize = 31;
+
+/// Lock periods supported by the program, in months
+pub const TIERS: [u64; 5] = [0, 3, 6, 9, 12];
+
+/// The program counts a month as 30 days
+pub const SECONDS_PER_MONTH: u64 = 30 * 86400;
+
+/// Converts a lock period in months to the time delta of its schedule
+pub fn tier_time_delta(months: u64) -> Option<u64> {
+    if TIERS.contains(&months) {
+        Some(months * SECONDS_PER_MONTH)
+    } else {
+        None
+    }
+}
+
+/// Converts a seed word, like the ones generated by the JS bindings, to a lock seed.
+///
+/// Only the first `SEED_LEN` bytes of the word are used.
+pub fn seed_from_word(word: &str) -> Result<[u8; SEED_LEN], ClientError> {
+    word.as_bytes()
+        .get(..SEED_LEN)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(ClientError::InvalidSeeds)
+}
+
+/// Address of the program config account
+pub fn config_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id).0
+}
+
+/// Address of the program data account of the program, holding its upgrade authority
+pub fn program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+/// Address of the program stats account
+pub fn stats_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[STATS_SEED], program_id).0
+}
+
+/// Address of the position registry of a wallet
+pub fn registry_address(program_id: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[REGISTRY_SEED, owner.as_ref()], program_id).0
+}
+
+/// Address of the receipt mint, minting receipts for the amount staked in unlocked stakes
+pub fn receipt_mint_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[RECEIPT_SEED], program_id).0
+}
+
+/// Address of the allowlist entry of a wallet
+pub fn allowlist_entry_address(program_id: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ALLOWLIST_SEED, wallet.as_ref()], program_id).0
+}
+
+/// Address of the referrer account of a wallet
+pub fn referrer_address(program_id: &Pubkey, referrer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[REFERRER_SEED, referrer.as_ref()], program_id).0
+}
+
+/// Address of the hook authority of a lock, signing the invocations of its hook
+pub fn hook_authority_address(program_id: &Pubkey, vesting_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[HOOK_AUTHORITY_SEED, vesting_account.as_ref()], program_id).0
+}
+
+/// The addresses of a lock, all derived from its seeds
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockAddresses {
+    /// The user seed followed by the bump of the vesting account
+    pub seeds: [u8; 32],
+    pub vesting_account: Pubkey,
+    /// The associated token account of the vesting account
+    pub vesting_token_account: Pubkey,
+}
+
+impl LockAddresses {
+    /// Derives the addresses of a new lock from a user chosen seed
+    pub fn find(program_id: &Pubkey, mint: &Pubkey, seed: &[u8; SEED_LEN]) -> Self {
+        let (vesting_account, bump) = Pubkey::find_program_address(&[seed], program_id);
+        let mut seeds = [0u8; 32];
+        seeds[..SEED_LEN].copy_from_slice(seed);
+        seeds[SEED_LEN] = bump;
+        Self::with_vesting_account(mint, seeds, vesting_account)
+    }
+
+    /// Derives the addresses of an existing lock from its full seeds
+    pub fn from_seeds(
+        program_id: &Pubkey,
+        mint: &Pubkey,
+        seeds: [u8; 32],
+    ) -> Result<Self, ClientError> {
+        let vesting_account = Pubkey::create_program_address(&[&seeds], program_id)
+            .map_err(|_| ClientError::InvalidSeeds)?;
+        Ok(Self::with_vesting_account(mint, seeds, vesting_account))
+    }
+
+    fn with_vesting_account(mint: &Pubkey, seeds: [u8; 32], vesting_account: Pubkey) -> Self {
+        Self {
+            seeds,
+            vesting_account,
+            vesting_token_account: get_associated_token_address(&vesting_account, mint),
+        }
+    }
+}
+
+/// The optional terms of a lock created by `VestingClient::lock`, none of them by default
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LockOptions {
+    /// The token account the tokens are locked from, the associated token account of the source
+    /// owner by default
+    pub source_token_account: Option<Pubkey>,
+    /// The token account the tokens are released to, the source token account by default
+    pub beneficiary_token_account: Option<Pubkey>,
+    /// The authority allowed to revoke the lock until it is unlocked
+    pub revocation_authority: Option<Pubkey>,
+    /// Lamports deposited by the payer, paid to whoever releases the lock once it matures
+    pub keeper_tip: u64,
+    /// Whether the lock renews for another period at each maturity until its owner opts out
+    pub auto_renew: bool,
+    /// The hook program invoked after the creation, initial unlock and unlock of the lock
+    pub hook: Option<Hook>,
+    /// Whether the position token of the lock is minted to the beneficiary owner, on a new
+    /// position mint
+    pub position: bool,
+}
+
+/// The decoded state of a vesting account
+#[derive(Debug, PartialEq)]
+pub struct ContractInfo {
+    pub header: VestingScheduleHeader,
+    pub schedule: VestingSchedule,
+}
+
+impl ContractInfo {
+    pub fn unpack(data: &[u8]) -> Result<Self, ClientError> {
+        if data.len() < VestingScheduleHeader::LEN + VestingSchedule::LEN {
+            return Err(ProgramError::InvalidAccountData.into());
+        }
+        let header = VestingScheduleHeader::unpack_from_slice(&data[..VestingScheduleHeader::LEN])?;
+        let schedule = unpack_schedule(&data[VestingScheduleHeader::LEN..])?;
+        Ok(Self { header, schedule })
+    }
+}
+
+/// High-level client of the token vesting program
+pub struct VestingClient<C> {
+    pub rpc: C,
+    pub program_id: Pubkey,
+    pub mint: Pubkey,
+    /// Accounts forwarded to the hook of a lock, by hook program
+    pub hook_accounts: BTreeMap<Pubkey, Vec<AccountMeta>>,
+    /// Proofs of the wallets allowlisted by the allowlist root of the config, by wallet. The
+    /// other wallets use their allowlist entry.
+    pub allowlist_proofs: BTreeMap<Pubkey, Vec<[u8; 32]>>,
+    /// Wallet credited with the locks created by the client, if any
+    pub referrer: Option<Pubkey>,
+}
+
+impl<C: VestingRpc> VestingClient<C> {
+    /// Creates a client for the token mint the program was built with
+    pub fn new(rpc: C, program_id: Pubkey) -> Self {
+        Self {
+            rpc,
+            program_id,
+            mint: TOKEN_MINT,
+            hook_accounts: BTreeMap::new(),
+            allowlist_proofs: BTreeMap::new(),
+            referrer: None,
+        }
+    }
+
+    pub fn lock_addresses(&self, seed: &[u8; SEED_LEN]) -> LockAddresses {
+        LockAddresses::find(&self.program_id, &self.mint, seed)
+    }
+
+    pub fn lock_addresses_from_seeds(&self, seeds: [u8; 32]) -> Result<LockAddresses, ClientError> {
+        LockAddresses::from_seeds(&self.program_id, &self.mint, seeds)
+    }
+
+    /// Locks tokens of the source owner with the terms of `options`. A lock with a hook forwards
+    /// its hook program the accounts registered in `hook_accounts`. While the allowlist is
+    /// enabled, the source owner passes it with its proof from `allowlist_proofs`, or its
+    /// allowlist entry.
+    ///
+    /// The lock is recorded in the position registry of the beneficiary token account owner,
+    /// created along with the lock if needed. While the config charges a deposit fee, the fee is
+    /// taken from the schedule amount and the lock holds the rest. The lock is credited to the
+    /// `referrer` of the client, if any, which receives its referral share of the fee.
+    pub async fn lock(
+        &mut self,
+        payer: &dyn Signer,
+        source_owner: &dyn Signer,
+        seed: &[u8; SEED_LEN],
+        schedule: Schedule,
+        options: LockOptions,
+    ) -> Result<LockAddresses, ClientError> {
+        self.lock_with_signers(
+            payer,
+            &source_owner.pubkey(),
+            &[source_owner],
+            seed,
+            schedule,
+            options,
+        )
+        .await
+    }
+
+    /// Locks tokens from a source token account owned by an SPL multisig, signed by enough of
+    /// its signers to reach the multisig threshold
+    pub async fn lock_multisig(
+        &mut self,
+        payer: &dyn Signer,
+        multisig_owner: &Pubkey,
+        signers: &[&dyn Signer],
+        seed: &[u8; SEED_LEN],
+        schedule: Schedule,
+        options: LockOptions,
+    ) -> Result<LockAddresses, ClientError> {
+        self.lock_with_signers(payer, multisig_owner, signers, seed, schedule, options)
+            .await
+    }
+
+    async fn lock_with_signers(
+        &mut self,
+        payer: &dyn Signer,
+        source_owner: &Pubkey,
+        signers: &[&dyn Signer],
+        seed: &[u8; SEED_LEN],
+        schedule: Schedule,
+        options: LockOptions,
+    ) -> Result<LockAddresses, ClientError> {
+        let addresses = self.lock_addresses(seed);
+        if self
+            .rpc
+            .get_account(&addresses.vesting_account)
+            .await?
+            .is_some()
+        {
+            return Err(ClientError::AlreadyExists(addresses.vesting_account));
+        }
+        // A single owner signs as the owner itself, a multisig through its signers
+        let signer_pubkeys: Vec<Pubkey> = signers
+            .iter()
+            .map(|signer| signer.pubkey())
+            .filter(|pubkey| pubkey != source_owner)
+            .collect();
+        let signer_pubkeys: Vec<&Pubkey> = signer_pubkeys.iter().collect();
+        let beneficiary_owner = match options.beneficiary_token_account {
+            Some(beneficiary_token_account) => {
+                self.token_account_owner(&beneficiary_token_account).await?
+            }
+            None => *source_owner,
+        };
+        let position_mint = Keypair::new();
+        let args = CreateArgs {
+            revocation_authority: options.revocation_authority,
+            keeper_tip: options.keeper_tip,
+            auto_renew: options.auto_renew,
+            hook: options.hook,
+            allowlist: self.allowlist_access(source_owner).await?,
+            deposit_fee_treasury_token_account_key: self.deposit_fee_treasury().await?,
+            referral: self.referral().await?,
+            position: options
+                .position
+                .then(|| instructions::position(&beneficiary_owner, &position_mint.pubkey())),
+        };
+        let instructions = instructions::lock(
+            &self.program_id,
+            &self.mint,
+            &payer.pubkey(),
+            source_owner,
+            &signer_pubkeys,
+            options.source_token_account,
+            options.beneficiary_token_account,
+            &beneficiary_owner,
+            &addresses,
+            schedule,
+            args,
+            options.hook.map_or(&[][..], |hook| {
+                self.forwarded_hook_accounts(&hook.program_id)
+            }),
+        )?;
+        let transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+        let mut all_signers = vec![payer];
+        all_signers.extend_from_slice(signers);
+        if options.position {
+            all_signers.push(&position_mint);
+        }
+        self.sign_and_send(transaction, &all_signers).await?;
+        Ok(addresses)
+    }
+
+    /// Creates a lock for every recipient of a batch, in as few transactions as possible.
+    ///
+    /// Transactions whose locks already exist are skipped, so an interrupted batch can be sent
+    /// again with the same batch id.
+    pub async fn lock_batch(
+        &mut self,
+        payer: &dyn Signer,
+        source_owner: &dyn Signer,
+        source_token_account: Option<Pubkey>,
+        batch_id: &[u8],
+        recipients: &[BatchRecipient],
+    ) -> Result<Vec<LockAddresses>, ClientError> {
+        let deposit_fee_treasury = self.deposit_fee_treasury().await?;
+        let transactions = batch::batch_transactions(
+            &self.program_id,
+            &self.mint,
+            &payer.pubkey(),
+            &source_owner.pubkey(),
+            &[],
+            source_token_account,
+            deposit_fee_treasury,
+            batch_id,
+            recipients,
+        )?;
+        let mut locks = vec![];
+        for batch_transaction in transactions {
+            let (_, first_lock) = &batch_transaction.locks[0];
+            if self
+                .rpc
+                .get_account(&first_lock.vesting_account)
+                .await?
+                .is_none()
+            {
+                let transaction = Transaction::new_with_payer(
+                    &batch_transaction.instructions,
+                    Some(&payer.pubkey()),
+                );
+                self.sign_and_send(transaction, &[payer, source_owner])
+                    .await?;
+            }
+            locks.extend(
+                batch_transaction
+                    .locks
+                    .into_iter()
+                    .map(|(_, addresses)| addresses),
+            );
+        }
+        Ok(locks)
+    }
+
+    pub fn airdrop_addresses(&self, seed: &[u8; SEED_LEN]) -> AirdropAddresses {
+        AirdropAddresses::find(&self.program_id, &self.mint, seed)
+    }
+
+    /// Creates an airdrop of the given entries, funded with their total amount from the source
+    /// token account, which defaults to the authority's associated token account.
+    pub async fn create_airdrop(
+        &mut self,
+        authority: &dyn Signer,
+        source_token_account: Option<Pubkey>,
+        seed: &[u8; SEED_LEN],
+        entries: &[AirdropEntry],
+    ) -> Result<AirdropAddresses, ClientError> {
+        let tree = MerkleTree::new(entries)?;
+        let amount = entries
+            .iter()
+            .try_fold(0u64, |total, entry| total.checked_add(entry.amount))
+            .ok_or(ProgramError::InvalidArgument)?;
+        let addresses = self.airdrop_addresses(seed);
+        let instructions = instructions::create_airdrop(
+            &self.program_id,
+            &self.mint,
+            &authority.pubkey(),
+            source_token_account,
+            &addresses,
+            tree.root(),
+            amount,
+        )?;
+        let transaction = Transaction::new_with_payer(&instructions, Some(&authority.pubkey()));
+        self.sign_and_send(transaction, &[authority]).await?;
+        Ok(addresses)
+    }
+
+    /// Claims the entry of an airdrop at the given index into a lock. The claimant must be the
+    /// wallet of the entry, and pays for the lock accounts.
+    pub async fn claim_into_lock(
+        &mut self,
+        claimant: &dyn Signer,
+        airdrop: &AirdropAddresses,
+        entries: &[AirdropEntry],
+        index: usize,
+        destination_token_account: Option<Pubkey>,
+    ) -> Result<LockAddresses, ClientError> {
+        let tree = MerkleTree::new(entries)?;
+        let proof = tree.proof(index).ok_or(ClientError::EntryNotFound(index))?;
+        let entry = &entries[index];
+        let addresses = airdrop.claim_lock(&self.program_id, &self.mint, &entry.wallet);
+        if self
+            .rpc
+            .get_account(&addresses.vesting_account)
+            .await?
+            .is_some()
+        {
+            return Err(ClientError::AlreadyExists(addresses.vesting_account));
+        }
+        let destination_owner = match destination_token_account {
+            Some(destination_token_account) => {
+                self.token_account_owner(&destination_token_account).await?
+            }
+            None => entry.wallet,
+        };
+        let deposit_fee_treasury = self.deposit_fee_treasury().await?;
+        let instructions = instructions::claim_into_lock(
+            &self.program_id,
+            &self.mint,
+            airdrop,
+            &addresses,
+            entry,
+            proof,
+            destination_token_account,
+            &destination_owner,
+            deposit_fee_treasury,
+        )?;
+        let transaction = Transaction::new_with_payer(&instructions, Some(&claimant.pubkey()));
+        self.sign_and_send(transaction, &[claimant]).await?;
+        Ok(addresses)
+    }
+
+    /// Starts the withdrawal period of an "unlocked" (0 time delta) lock. The owner of its
+    /// destination burns the receipts of the stake from their associated token account, and must
+    /// hold the position token of the lock, if it has one.
+    pub async fn initialize_unlock(
+        &mut self,
+        payer: &dyn Signer,
+        owner: &dyn Signer,
+        seeds: [u8; 32],
+    ) -> Result<Signature, ClientError> {
+        let addresses = self.lock_addresses_from_seeds(seeds)?;
+        let info = self.get_contract_info(&addresses.vesting_account).await?;
+        let instruction = self.release_instruction(&info, &owner.pubkey(), |destination| {
+            instructions::initialize_unlock(
+                &self.program_id,
+                &addresses,
+                destination,
+                &owner.pubkey(),
+            )
+        })?;
+        let instruction = self.with_hook(&addresses, &info, instruction);
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        self.sign_and_send(transaction, &[payer, owner]).await
+    }
+
+    /// Releases the tokens of a matured lock to its destination. A lock with a position token is
+    /// released to the associated token account of the payer, who must hold the token. The payer
+    /// earns the keeper tip of the lock.
+    pub async fn unlock(
+        &mut self,
+        payer: &dyn Signer,
+        seeds: [u8; 32],
+    ) -> Result<Signature, ClientError> {
+        let addresses = self.lock_addresses_from_seeds(seeds)?;
+        let info = self.get_contract_info(&addresses.vesting_account).await?;
+        let registry_owner = self
+            .token_account_owner(&info.header.destination_address)
+            .await?;
+        let instruction = self.release_instruction(&info, &payer.pubkey(), |destination| {
+            instructions::unlock(&self.program_id, &addresses, destination, &registry_owner)
+        })?;
+        let instruction = instruction::with_keeper(instruction, &payer.pubkey());
+        let instruction = self.with_hook(&addresses, &info, instruction);
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        self.sign_and_send(transaction, &[payer]).await
+    }
+
+    /// Releases the matured locks of a list in a single transaction, skipping the others. About
+    /// seven locks fit in a transaction. The payer earns the keeper tips of the released locks.
+    pub async fn unlock_many(
+        &mut self,
+        payer: &dyn Signer,
+        seeds: &[[u8; 32]],
+    ) -> Result<Signature, ClientError> {
+        let mut locks = vec![];
+        for seeds in seeds {
+            let addresses = self.lock_addresses_from_seeds(*seeds)?;
+            let info = self.get_contract_info(&addresses.vesting_account).await?;
+            let destination_owner = self
+                .token_account_owner(&info.header.destination_address)
+                .await?;
+            locks.push((
+                addresses,
+                info.header.destination_address,
+                destination_owner,
+            ));
+        }
+        let instruction = instructions::unlock_many(&self.program_id, &payer.pubkey(), &locks)?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        self.sign_and_send(transaction, &[payer]).await
+    }
+
+    /// Revokes a lock: its vested tokens are released to the destination and the unvested ones to
+    /// the treasury token account, which defaults to the authority's associated token account
+    pub async fn revoke(
+        &mut self,
+        revocation_authority: &dyn Signer,
+        seeds: [u8; 32],
+        treasury_token_account: Option<Pubkey>,
+    ) -> Result<Signature, ClientError> {
+        let addresses = self.lock_addresses_from_seeds(seeds)?;
+        let info = self.get_contract_info(&addresses.vesting_account).await?;
+        let treasury_token_account = treasury_token_account.unwrap_or_else(|| {
+            get_associated_token_address(&revocation_authority.pubkey(), &self.mint)
+        });
+        let destination_owner = self
+            .token_account_owner(&info.header.destination_address)
+            .await?;
+        let instruction = instructions::revoke(
+            &self.program_id,
+            &addresses,
+            &info.header.destination_address,
+            &destination_owner,
+            &revocation_authority.pubkey(),
+            &treasury_token_account,
+        )?;
+        let transaction =
+            Transaction::new_with_payer(&[instruction], Some(&revocation_authority.pubkey()));
+        self.sign_and_send(transaction, &[revocation_authority])
+            .await
+    }
+
+    /// Stops the renewal of an auto-renewing lock, released at its next maturity instead, or at
+    /// its last one during the grace period following it. The owner of the destination token
+    /// account signs and pays for the transaction during the renewal notice or grace period, or
+    /// for a lock with a position token, its holder.
+    pub async fn opt_out_renewal(
+        &mut self,
+        destination_owner: &dyn Signer,
+        seeds: [u8; 32],
+    ) -> Result<Signature, ClientError> {
+        let addresses = self.lock_addresses_from_seeds(seeds)?;
+        let info = self.get_contract_info(&addresses.vesting_account).await?;
+        let instruction =
+            self.release_instruction(&info, &destination_owner.pubkey(), |destination| {
+                instructions::opt_out_renewal(
+                    &self.program_id,
+                    &addresses,
+                    destination,
+                    &destination_owner.pubkey(),
+                )
+            })?;
+        let transaction =
+            Transaction::new_with_payer(&[instruction], Some(&destination_owner.pubkey()));
+        self.sign_and_send(transaction, &[destination_owner]).await
+    }
+
+    /// Releases the tokens of a lock before its release time, minus the early unlock penalty. The
+    /// owner of the destination token account signs and pays for the transaction, or for a lock
+    /// with a position token, its holder.
+    pub async fn early_unlock(
+        &mut self,
+        destination_owner: &dyn Signer,
+        seeds: [u8; 32],
+    ) -> Result<Signature, ClientError> {
+        let addresses = self.lock_addresses_from_seeds(seeds)?;
+        let info = self.get_contract_info(&addresses.vesting_account).await?;
+        let config = self.get_config().await?;
+        let registry_owner = self
+            .token_account_owner(&info.header.destination_address)
+            .await?;
+        let instruction =
+            self.release_instruction(&info, &destination_owner.pubkey(), |destination| {
+                instructions::early_unlock(
+                    &self.program_id,
+                    &addresses,
+                    destination,
+                    &destination_owner.pubkey(),
+                    &config.penalty_treasury,
+                    &registry_owner,
+                )
+            })?;
+        let transaction =
+            Transaction::new_with_payer(&[instruction], Some(&destination_owner.pubkey()));
+        self.sign_and_send(transaction, &[destination_owner]).await
+    }
+
+    /// Releases all the tokens of a lock to its destination while the program is in emergency
+    /// mode, whatever its release time. The payer must hold the position token of the lock, if it
+    /// has one.
+    pub async fn emergency_unlock(
+        &mut self,
+        payer: &dyn Signer,
+        seeds: [u8; 32],
+    ) -> Result<Signature, ClientError> {
+        let addresses = self.lock_addresses_from_seeds(seeds)?;
+        let info = self.get_contract_info(&addresses.vesting_account).await?;
+        let registry_owner = self
+            .token_account_owner(&info.header.destination_address)
+            .await?;
+        let instruction = self.release_instruction(&info, &payer.pubkey(), |destination| {
+            instructions::emergency_unlock(
+                &self.program_id,
+                &addresses,
+                destination,
+                &registry_owner,
+            )
+        })?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        self.sign_and_send(transaction, &[payer]).await
+    }
+
+    /// Creates the program config, with the signer as admin and the guardian allowed to pause
+    /// the creation of locks, whose release times are then measured in the given lock mode. The
+    /// signer must be the upgrade authority of the program.
+    pub async fn init_config(
+        &mut self,
+        admin: &dyn Signer,
+        penalty_treasury_token_account: &Pubkey,
+        guardian: &Pubkey,
+        max_penalty_bps: u16,
+        lock_mode: LockMode,
+    ) -> Result<Signature, ClientError> {
+        let instruction = instruction::init_config(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            penalty_treasury_token_account,
+            guardian,
+            &program_data_address(&self.program_id),
+            max_penalty_bps,
+            lock_mode,
+        )?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&admin.pubkey()));
+        self.sign_and_send(transaction, &[admin]).await
+    }
+
+    /// Updates the early unlock penalty and its treasury
+    pub async fn update_penalty(
+        &mut self,
+        admin: &dyn Signer,
+        penalty_treasury_token_account: &Pubkey,
+        max_penalty_bps: u16,
+    ) -> Result<Signature, ClientError> {
+        let instruction = instruction::update_penalty(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            penalty_treasury_token_account,
+            max_penalty_bps,
+        )?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&admin.pubkey()));
+        self.sign_and_send(transaction, &[admin]).await
+    }
+
+    /// Pauses or resumes the creation of locks, signed by the guardian
+    pub async fn set_paused(
+        &mut self,
+        guardian: &dyn Signer,
+        paused: bool,
+    ) -> Result<Signature, ClientError> {
+        let instruction = instruction::set_paused(
+            &self.program_id,
+            &guardian.pubkey(),
+            &config_address(&self.program_id),
+            paused,
+        )?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&guardian.pubkey()));
+        self.sign_and_send(transaction, &[guardian]).await
+    }
+
+    /// Enters or leaves the emergency mode, signed by the admin
+    pub async fn set_emergency_mode(
+        &mut self,
+        admin: &dyn Signer,
+        enabled: bool,
+    ) -> Result<Signature, ClientError> {
+        let instruction = instruction::set_emergency_mode(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            enabled,
+        )?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&admin.pubkey()));
+        self.sign_and_send(transaction, &[admin]).await
+    }
+
+    /// Replaces the guardian, signed by the admin
+    pub async fn set_guardian(
+        &mut self,
+        admin: &dyn Signer,
+        guardian: &Pubkey,
+    ) -> Result<Signature, ClientError> {
+        let instruction = instruction::set_guardian(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            guardian,
+        )?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&admin.pubkey()));
+        self.sign_and_send(transaction, &[admin]).await
+    }
+
+    /// Enables or disables the allowlist gating the creation of locks, and sets the Merkle root
+    /// of the wallets allowlisted without an entry
+    pub async fn set_allowlist(
+        &mut self,
+        admin: &dyn Signer,
+        enabled: bool,
+        root: [u8; 32],
+    ) -> Result<Signature, ClientError> {
+        let instruction = instruction::set_allowlist(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            enabled,
+            root,
+        )?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&admin.pubkey()));
+        self.sign_and_send(transaction, &[admin]).await
+    }
+
+    /// Allowlists a wallet by creating its allowlist entry, signed by the admin
+    pub async fn add_allowlist_entry(
+        &mut self,
+        payer: &dyn Signer,
+        admin: &dyn Signer,
+        wallet: &Pubkey,
+    ) -> Result<Signature, ClientError> {
+        let instruction = instruction::add_allowlist_entry(
+            &self.program_id,
+            &payer.pubkey(),
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            wallet,
+            &allowlist_entry_address(&self.program_id, wallet),
+        )?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        self.sign_and_send(transaction, &[payer, admin]).await
+    }
+
+    /// Removes the allowlist entry of a wallet, sending its rent to the recipient
+    pub async fn remove_allowlist_entry(
+        &mut self,
+        admin: &dyn Signer,
+        wallet: &Pubkey,
+        recipient: &Pubkey,
+    ) -> Result<Signature, ClientError> {
+        let instruction = instruction::remove_allowlist_entry(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            &allowlist_entry_address(&self.program_id, wallet),
+            recipient,
+        )?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&admin.pubkey()));
+        self.sign_and_send(transaction, &[admin]).await
+    }
+
+    /// Sets the caps on the amounts held by all the locks and by the positions of a wallet, and
+    /// the minimum amount of a new lock by tier. A cap of 0 lifts it.
+    pub async fn set_limits(
+        &mut self,
+        admin: &dyn Signer,
+        max_total_locked: u64,
+        max_locked_per_wallet: u64,
+        min_lock_amounts: [u64; 5],
+    ) -> Result<Signature, ClientError> {
+        let instruction = instruction::set_limits(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            max_total_locked,
+            max_locked_per_wallet,
+            min_lock_amounts,
+        )?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&admin.pubkey()));
+        self.sign_and_send(transaction, &[admin]).await
+    }
+
+    /// Sets the fee taken from the amount of new locks, in basis points, and the token account
+    /// receiving it. A fee of 0 lifts it.
+    pub async fn set_deposit_fee(
+        &mut self,
+        admin: &dyn Signer,
+        deposit_fee_treasury_token_account: &Pubkey,
+        deposit_fee_bps: u16,
+    ) -> Result<Signature, ClientError> {
+        let instruction = instruction::set_deposit_fee(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            deposit_fee_treasury_token_account,
+            deposit_fee_bps,
+        )?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&admin.pubkey()));
+        self.sign_and_send(transaction, &[admin]).await
+    }
+
+    /// Sets the share of the deposit fee of a new lock paid to its referrer, in basis points of
+    /// the fee
+    pub async fn set_referral_share(
+        &mut self,
+        admin: &dyn Signer,
+        referral_share_bps: u16,
+    ) -> Result<Signature, ClientError> {
+        let instruction = instruction::set_referral_share(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            referral_share_bps,
+        )?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&admin.pubkey()));
+        self.sign_and_send(transaction, &[admin]).await
+    }
+
+    /// Creates the stats account of the program, which must exist before any lock is created
+    pub async fn init_stats(&mut self, payer: &dyn Signer) -> Result<Signature, ClientError> {
+        let instruction = instruction::init_stats(
+            &self.program_id,
+            &payer.pubkey(),
+            &stats_address(&self.program_id),
+        )?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        self.sign_and_send(transaction, &[payer]).await
+    }
+
+    /// Creates the receipt mint of the program, which must exist before any lock is created
+    pub async fn init_receipt_mint(
+        &mut self,
+        payer: &dyn Signer,
+    ) -> Result<Signature, ClientError> {
+        let instruction = instruction::init_receipt_mint(
+            &self.program_id,
+            &spl_token::id(),
+            &payer.pubkey(),
+            &receipt_mint_address(&self.program_id),
+            &self.mint,
+        )?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        self.sign_and_send(transaction, &[payer]).await
+    }
+
+    /// Creates the position registry of a wallet, if it does not exist yet
+    pub async fn init_registry(
+        &mut self,
+        payer: &dyn Signer,
+        owner: &Pubkey,
+    ) -> Result<Signature, ClientError> {
+        let instruction = instruction::init_registry(
+            &self.program_id,
+            &payer.pubkey(),
+            owner,
+            &registry_address(&self.program_id, owner),
+        )?;
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        self.sign_and_send(transaction, &[payer]).await
+    }
+
+    /// Fetches the position registry of a wallet, listing its open locks
+    pub async fn get_registry(&mut self, owner: &Pubkey) -> Result<PositionRegistry, ClientError> {
+        let registry_address = registry_address(&self.program_id, owner);
+        let account = self
+            .rpc
+            .get_account(&registry_address)
+            .await?
+            .ok_or(ClientError::AccountNotFound(registry_address))?;
+        Ok(PositionRegistry::unpack(&account.data)?)
+    }
+
+    /// The totals of the locks referred by a wallet
+    pub async fn get_referrer(&mut self, referrer: &Pubkey) -> Result<Referrer, ClientError> {
+        let referrer_address = referrer_address(&self.program_id, referrer);
+        let account = self
+            .rpc
+            .get_account(&referrer_address)
+            .await?
+            .ok_or(ClientError::AccountNotFound(referrer_address))?;
+        Ok(Referrer::unpack(&account.data)?)
+    }
+
+    /// Closes a released or revoked lock, removing it from the position registry of the owner
+    /// of its destination token account, who signs and receives the rent of its accounts
+    pub async fn close(
+        &mut self,
+        destination_owner: &dyn Signer,
+        seeds: [u8; 32],
+    ) -> Result<Signature, ClientError> {
+        let addresses = self.lock_addresses_from_seeds(seeds)?;
+        let info = self.get_contract_info(&addresses.vesting_account).await?;
+        let instruction = instructions::close(
+            &self.program_id,
+            &addresses,
+            &info.header.destination_address,
+            &destination_owner.pubkey(),
+        )?;
+        let transaction =
+            Transaction::new_with_payer(&[instruction], Some(&destination_owner.pubkey()));
+        self.sign_and_send(transaction, &[destination_owner]).await
+    }
+
+    /// Transfers a lock to a token account of another wallet, signed by the owner of the lock.
+    /// With `require_accept`, the transfer only takes effect once the owner of the new
+    /// destination token account accepts it with `accept_position`.
+    pub async fn transfer_position(
+        &mut self,
+        owner: &dyn Signer,
+        seeds: [u8; 32],
+        new_destination_token_account: Pubkey,
+        require_accept: bool,
+    ) -> Result<Signature, ClientError> {
+        let addresses = self.lock_addresses_from_seeds(seeds)?;
+        let info = self.get_contract_info(&addresses.vesting_account).await?;
+        let new_destination_owner = self
+            .token_account_owner(&new_destination_token_account)
+            .await?;
+        let instructions = instructions::transfer_position(
+            &self.program_id,
+            &addresses,
+            &info.header.destination_address,
+            &owner.pubkey(),
+            &new_destination_token_account,
+            &new_destination_owner,
+            require_accept,
+        )?;
+        let transaction = Transaction::new_with_payer(&instructions, Some(&owner.pubkey()));
+        self.sign_and_send(transaction, &[owner]).await
+    }
+
+    /// Accepts the pending transfer of a lock, signed by the owner of the token account it is
+    /// transferred to
+    pub async fn accept_position(
+        &mut self,
+        new_destination_owner: &dyn Signer,
+        seeds: [u8; 32],
+    ) -> Result<Signature, ClientError> {
+        let addresses = self.lock_addresses_from_seeds(seeds)?;
+        let info = self.get_contract_info(&addresses.vesting_account).await?;
+        let new_destination_token_account = info
+            .header
+            .pending_destination()
+            .ok_or(ClientError::NoPendingTransfer(addresses.vesting_account))?;
+        let instructions = instructions::accept_position(
+            &self.program_id,
+            &addresses,
+            &info.header.destination_address,
+            &info.header.owner,
+            &new_destination_token_account,
+            &new_destination_owner.pubkey(),
+        )?;
+        let transaction =
+            Transaction::new_with_payer(&instructions, Some(&new_destination_owner.pubkey()));
+        self.sign_and_send(transaction, &[new_destination_owner])
+            .await
+    }
+
+    /// Splits an amount off a lock into a new lock with the same terms, released to the new
+    /// destination token account or by default to the same destination. The owner of the lock
+    /// signs.
+    pub async fn split(
+        &mut self,
+        payer: &dyn Signer,
+        owner: &dyn Signer,
+        seeds: [u8; 32],
+        new_seed: &[u8; SEED_LEN],
+        amount: u64,
+        new_destination_token_account: Option<Pubkey>,
+    ) -> Result<LockAddresses, ClientError> {
+        let addresses = self.lock_addresses_from_seeds(seeds)?;
+        let new_addresses = self.lock_addresses(new_seed);
+        if self
+            .rpc
+            .get_account(&new_addresses.vesting_account)
+            .await?
+            .is_some()
+        {
+            return Err(ClientError::AlreadyExists(new_addresses.vesting_account));
+        }
+        let info = self.get_contract_info(&addresses.vesting_account).await?;
+        let destination_token_account = info.header.destination_address;
+        let (new_destination_token_account, new_destination_owner) =
+            match new_destination_token_account {
+                Some(new_destination_token_account) => (
+                    new_destination_token_account,
+                    self.token_account_owner(&new_destination_token_account)
+                        .await?,
+                ),
+                None => (destination_token_account, owner.pubkey()),
+            };
+        let instructions = instructions::split(
+            &self.program_id,
+            &self.mint,
+            &payer.pubkey(),
+            &addresses,
+            &new_addresses,
+            &destination_token_account,
+            &owner.pubkey(),
+            &new_destination_token_account,
+            &new_destination_owner,
+            amount,
+        )?;
+        let transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+        self.sign_and_send(transaction, &[payer, owner]).await?;
+        Ok(new_addresses)
+    }
+
+    /// Merges locks with the same destination, tier and release time into the lock of `seeds`.
+    /// The owner of the destination token account signs, and receives the rent of the merged
+    /// locks.
+    pub async fn merge(
+        &mut self,
+        destination_owner: &dyn Signer,
+        seeds: [u8; 32],
+        source_seeds: &[[u8; 32]],
+    ) -> Result<Signature, ClientError> {
+        let addresses = self.lock_addresses_from_seeds(seeds)?;
+        let sources = source_seeds
+            .iter()
+            .map(|seeds| self.lock_addresses_from_seeds(*seeds))
+            .collect::<Result<Vec<_>, _>>()?;
+        let info = self.get_contract_info(&addresses.vesting_account).await?;
+        let instruction = instructions::merge(
+            &self.program_id,
+            &addresses,
+            &sources,
+            &info.header.destination_address,
+            &destination_owner.pubkey(),
+        )?;
+        let transaction =
+            Transaction::new_with_payer(&[instruction], Some(&destination_owner.pubkey()));
+        self.sign_and_send(transaction, &[destination_owner]).await
+    }
+
+    /// Turns a lock into a position token, minted to the associated token account of the owner
+    /// of its destination token account, who signs. Returns the position token account.
+    pub async fn mint_position(
+        &mut self,
+        payer: &dyn Signer,
+        destination_owner: &dyn Signer,
+        seeds: [u8; 32],
+        position_mint: &dyn Signer,
+    ) -> Result<Pubkey, ClientError> {
+        let addresses = self.lock_addresses_from_seeds(seeds)?;
+        let info = self.get_contract_info(&addresses.vesting_account).await?;
+        let instructions = instructions::mint_position(
+            &self.program_id,
+            &payer.pubkey(),
+            &destination_owner.pubkey(),
+            &addresses,
+            &info.header.destination_address,
+            &position_mint.pubkey(),
+        )?;
+        let transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+        self.sign_and_send(transaction, &[payer, destination_owner, position_mint])
+            .await?;
+        Ok(get_associated_token_address(
+            &destination_owner.pubkey(),
+            &position_mint.pubkey(),
+        ))
+    }
+
+    /// Fetches the owner of a token account
+    pub async fn token_account_owner(
+        &mut self,
+        token_account: &Pubkey,
+    ) -> Result<Pubkey, ClientError> {
+        let account = self
+            .rpc
+            .get_account(token_account)
+            .await?
+            .ok_or(ClientError::AccountNotFound(*token_account))?;
+        Ok(spl_token::state::Account::unpack(&account.data)?.owner)
+    }
+
+    /// Fetches and decodes the program stats, by tier
+    pub async fn get_stats(&mut self) -> Result<Stats, ClientError> {
+        let stats_address = stats_address(&self.program_id);
+        let account = self
+            .rpc
+            .get_account(&stats_address)
+            .await?
+            .ok_or(ClientError::AccountNotFound(stats_address))?;
+        Ok(Stats::unpack(&account.data)?)
+    }
+
+    /// Fetches and decodes the program config
+    pub async fn get_config(&mut self) -> Result<Config, ClientError> {
+        let config_address = config_address(&self.program_id);
+        let account = self
+            .rpc
+            .get_account(&config_address)
+            .await?
+            .ok_or(ClientError::AccountNotFound(config_address))?;
+        Ok(Config::unpack(&account.data)?)
+    }
+
+    /// Fetches and decodes a vesting account
+    pub async fn get_contract_info(
+        &mut self,
+        vesting_account: &Pubkey,
+    ) -> Result<ContractInfo, ClientError> {
+        let account = self
+            .rpc
+            .get_account(vesting_account)
+            .await?
+            .ok_or(ClientError::AccountNotFound(*vesting_account))?;
+        let info = ContractInfo::unpack(&account.data)?;
+        if !info.header.is_initialized {
+            return Err(ClientError::NotInitialized(*vesting_account));
+        }
+        Ok(info)
+    }
+
+    /// Builds an instruction releasing a lock to its destination, or for a lock with a position
+    /// token, to the associated token account of the holder
+    fn release_instruction(
+        &self,
+        info: &ContractInfo,
+        holder: &Pubkey,
+        build: impl FnOnce(&Pubkey) -> Result<Instruction, ClientError>,
+    ) -> Result<Instruction, ClientError> {
+        match info.header.position_mint() {
+            Some(position_mint) => Ok(instructions::with_position_holder(
+                build(&get_associated_token_address(holder, &self.mint))?,
+                &position_mint,
+                holder,
+            )),
+            None => build(&info.header.destination_address),
+        }
+    }
+
+    /// How a source owner passes the allowlist, none while it is disabled: with its proof from
+    /// `allowlist_proofs`, otherwise with its allowlist entry
+    pub async fn allowlist_access(
+        &mut self,
+        source_owner: &Pubkey,
+    ) -> Result<Option<AllowlistAccess>, ClientError> {
+        let config_address = config_address(&self.program_id);
+        let is_allowlist_enabled = match self.rpc.get_account(&config_address).await? {
+            Some(account) => Config::unpack(&account.data)?.is_allowlist_enabled,
+            None => false,
+        };
+        if !is_allowlist_enabled {
+            return Ok(None);
+        }
+        Ok(Some(match self.allowlist_proofs.get(source_owner) {
+            Some(proof) => AllowlistAccess::Proof(proof.clone()),
+            None => AllowlistAccess::Entry(allowlist_entry_address(&self.program_id, source_owner)),
+        }))
+    }
+
+    /// The treasury token account receiving the deposit fee of new locks, none while the config
+    /// charges no deposit fee
+    pub async fn deposit_fee_treasury(&mut self) -> Result<Option<Pubkey>, ClientError> {
+        let config_address = config_address(&self.program_id);
+        Ok(match self.rpc.get_account(&config_address).await? {
+            Some(account) => {
+                let config = Config::unpack(&account.data)?;
+                (config.deposit_fee_bps > 0).then_some(config.deposit_fee_treasury)
+            }
+            None => None,
+        })
+    }
+
+    /// The referral of the locks created by the client, none without a `referrer`. The referrer
+    /// token account, its associated token account, is only passed while the config charges a
+    /// deposit fee with a referral share.
+    pub async fn referral(&mut self) -> Result<Option<Referral>, ClientError> {
+        let referrer = match self.referrer {
+            Some(referrer) => referrer,
+            None => return Ok(None),
+        };
+        let config_address = config_address(&self.program_id);
+        let pays_referral_share = match self.rpc.get_account(&config_address).await? {
+            Some(account) => {
+                let config = Config::unpack(&account.data)?;
+                config.deposit_fee_bps > 0 && config.referral_share_bps > 0
+            }
+            None => false,
+        };
+        Ok(Some(Referral {
+            referrer,
+            referrer_account: referrer_address(&self.program_id, &referrer),
+            referrer_token_account: pays_referral_share
+                .then(|| get_associated_token_address(&referrer, &self.mint)),
+        }))
+    }
+
+    /// The accounts registered for a hook program
+    fn forwarded_hook_accounts(&self, hook_program_id: &Pubkey) -> &[AccountMeta] {
+        self.hook_accounts
+            .get(hook_program_id)
+            .map_or(&[], |accounts| accounts.as_slice())
+    }
+
+    /// Appends the hook program account of a lock with a hook, its hook authority and its
+    /// registered accounts to an instruction releasing it
+    fn with_hook(
+        &self,
+        addresses: &LockAddresses,
+        info: &ContractInfo,
+        instruction: Instruction,
+    ) -> Instruction {
+        match info.header.hook() {
+            Some(hook) => instruction::with_hook(
+                instruction,
+                &hook.program_id,
+                &hook_authority_address(&self.program_id, &addresses.vesting_account),
+                self.forwarded_hook_accounts(&hook.program_id),
+            ),
+            None => instruction,
+        }
+    }
+
+    async fn sign_and_send<T: Signers + ?Sized>(
+        &mut self,
+        mut transaction: Transaction,
+        signers: &T,
+    ) -> Result<Signature, ClientError> {
+        let recent_blockhash = self.rpc.get_latest_blockhash().await?;
+        transaction.try_sign(signers, recent_blockhash)?;
+        let signature = transaction.signatures[0];
+        self.rpc.send_transaction(transaction).await?;
+        Ok(signature)
+    }
+}
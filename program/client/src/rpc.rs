@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::RpcFilterType,
+};
+use solana_sdk::{account::Account, hash::Hash, pubkey::Pubkey, transaction::Transaction};
+
+use crate::error::ClientError;
+
+/// The subset of a Solana RPC connection used by the client.
+///
+/// It is implemented for the nonblocking `RpcClient`, and can be implemented for a
+/// `solana-program-test` `BanksClient` wrapper in tests.
+#[async_trait]
+pub trait VestingRpc {
+    /// Fetches an account, returns `None` if it does not exist
+    async fn get_account(&mut self, address: &Pubkey) -> Result<Option<Account>, ClientError>;
+
+    /// Fetches the accounts owned by a program whose data has the given size
+    async fn get_program_accounts(
+        &mut self,
+        program_id: &Pubkey,
+        data_size: usize,
+    ) -> Result<Vec<(Pubkey, Account)>, ClientError>;
+
+    async fn get_latest_blockhash(&mut self) -> Result<Hash, ClientError>;
+
+    /// Sends a signed transaction and waits for its confirmation
+    async fn send_transaction(&mut self, transaction: Transaction) -> Result<(), ClientError>;
+}
+
+#[async_trait]
+impl VestingRpc for RpcClient {
+    async fn get_account(&mut self, address: &Pubkey) -> Result<Option<Account>, ClientError> {
+        let response = self
+            .get_account_with_commitment(address, self.commitment())
+            .await?;
+        Ok(response.value)
+    }
+
+    async fn get_program_accounts(
+        &mut self,
+        program_id: &Pubkey,
+        data_size: usize,
+    ) -> Result<Vec<(Pubkey, Account)>, ClientError> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::DataSize(data_size as u64)]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        Ok(self
+            .get_program_accounts_with_config(program_id, config)
+            .await?)
+    }
+
+    async fn get_latest_blockhash(&mut self) -> Result<Hash, ClientError> {
+        Ok(RpcClient::get_latest_blockhash(self).await?)
+    }
+
+    async fn send_transaction(&mut self, transaction: Transaction) -> Result<(), ClientError> {
+        self.send_and_confirm_transaction(&transaction).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,857 @@
+use async_trait::async_trait;
+use solana_program_test::{processor, BanksClient, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
+    hash::Hash,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use spl_associated_token_account::get_associated_token_address;
+use std::{collections::BTreeSet, time::Duration};
+use token_vesting::{
+    hook::{Hook, HookMode},
+    instruction::Schedule,
+    processor::{Processor, TOKEN_MINT},
+};
+use token_vesting_client::{
+    batch::{batch_transactions, BatchRecipient},
+    instructions,
+    keeper::matured_locks,
+    program_data_address, AirdropEntry, ClientError, ContractInfo, LockAddresses, LockOptions,
+    MerkleTree, VestingClient, VestingRpc,
+};
+use token_vesting_hook_example::{instruction::init_tally, state::Tally};
+
+/// A `BanksClient`, remembering the accounts of the transactions it sends to emulate
+/// `getProgramAccounts`
+struct Banks(BanksClient, BTreeSet<Pubkey>);
+
+#[async_trait]
+impl VestingRpc for Banks {
+    async fn get_account(&mut self, address: &Pubkey) -> Result<Option<Account>, ClientError> {
+        self.0
+            .get_account(*address)
+            .await
+            .map_err(|e| ClientError::Rpc(e.to_string()))
+    }
+
+    async fn get_program_accounts(
+        &mut self,
+        program_id: &Pubkey,
+        data_size: usize,
+    ) -> Result<Vec<(Pubkey, Account)>, ClientError> {
+        let mut accounts = vec![];
+        for address in self.1.clone() {
+            if let Some(account) = self.get_account(&address).await? {
+                if account.owner == *program_id && account.data.len() == data_size {
+                    accounts.push((address, account));
+                }
+            }
+        }
+        Ok(accounts)
+    }
+
+    async fn get_latest_blockhash(&mut self) -> Result<Hash, ClientError> {
+        self.0
+            .get_latest_blockhash()
+            .await
+            .map_err(|e| ClientError::Rpc(e.to_string()))
+    }
+
+    async fn send_transaction(&mut self, transaction: Transaction) -> Result<(), ClientError> {
+        self.1
+            .extend(transaction.message.account_keys.iter().copied());
+        self.0
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| ClientError::Rpc(e.to_string()))
+    }
+}
+
+struct TestEnv {
+    context: ProgramTestContext,
+    client: VestingClient<Banks>,
+    owner: Keypair,
+    source_token_account: Pubkey,
+}
+
+async fn setup(amount: u64) -> TestEnv {
+    let owner = Keypair::new();
+    let owner_pubkey = owner.pubkey();
+    start(ProgramTest::default(), owner, &owner_pubkey, amount).await
+}
+
+/// Sets up a source token account owned by an `m` of `n` SPL multisig, returning the multisig
+/// and its signers
+async fn setup_multisig(amount: u64, m: u8, n: u8) -> (TestEnv, Pubkey, Vec<Keypair>) {
+    let multisig = Pubkey::new_unique();
+    let signers: Vec<Keypair> = (0..n).map(|_| Keypair::new()).collect();
+    let mut signer_pubkeys = [Pubkey::default(); spl_token::instruction::MAX_SIGNERS];
+    for (signer_pubkey, signer) in signer_pubkeys.iter_mut().zip(&signers) {
+        *signer_pubkey = signer.pubkey();
+    }
+    let mut program_test = ProgramTest::default();
+    program_test.add_account(
+        multisig,
+        packed_account(spl_token::state::Multisig {
+            m,
+            n,
+            is_initialized: true,
+            signers: signer_pubkeys,
+        }),
+    );
+    let env = start(program_test, Keypair::new(), &multisig, amount).await;
+    (env, multisig, signers)
+}
+
+/// Adds the program, the mint and a funded source token account, then starts the test validator
+/// and creates the stats account
+async fn start(
+    program_test: ProgramTest,
+    owner: Keypair,
+    source_owner: &Pubkey,
+    amount: u64,
+) -> TestEnv {
+    let mut env = start_without_stats(program_test, owner, source_owner, amount).await;
+    let payer = env.context.payer.insecure_clone();
+    env.client.init_stats(&payer).await.unwrap();
+    env
+}
+
+/// Adds the program, the mint and a funded source token account, then starts the test validator
+async fn start_without_stats(
+    mut program_test: ProgramTest,
+    owner: Keypair,
+    source_owner: &Pubkey,
+    amount: u64,
+) -> TestEnv {
+    let program_id = Pubkey::new_unique();
+    let source_token_account = get_associated_token_address(source_owner, &TOKEN_MINT);
+
+    program_test.add_program(
+        "token_vesting",
+        program_id,
+        processor!(Processor::process_instruction),
+    );
+    program_test.add_account(
+        TOKEN_MINT,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::Some(Pubkey::new_unique()),
+            supply: amount,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(
+        source_token_account,
+        packed_account(spl_token::state::Account {
+            mint: TOKEN_MINT,
+            owner: *source_owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..spl_token::state::Account::default()
+        }),
+    );
+
+    let mut context = program_test.start_with_context().await;
+    set_upgrade_authority(&mut context, &program_id).await;
+    let mut client = VestingClient::new(
+        Banks(context.banks_client.clone(), BTreeSet::new()),
+        program_id,
+    );
+    client.init_receipt_mint(&context.payer).await.unwrap();
+    TestEnv {
+        context,
+        client,
+        owner,
+        source_token_account,
+    }
+}
+
+/// Makes the payer the upgrade authority of the program, as if it deployed it
+async fn set_upgrade_authority(context: &mut ProgramTestContext, program_id: &Pubkey) {
+    let program_data = Account::new_data(
+        1_000_000_000,
+        &UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(context.payer.pubkey()),
+        },
+        &bpf_loader_upgradeable::id(),
+    )
+    .unwrap();
+    set_funded_account(context, &program_data_address(program_id), program_data).await;
+}
+
+/// Sets an account in the bank. Its lamports come from the payer, keeping the capitalization
+/// of the bank.
+async fn set_funded_account(context: &mut ProgramTestContext, address: &Pubkey, account: Account) {
+    let payer = context.payer.pubkey();
+    let mut payer_account = context
+        .banks_client
+        .get_account(payer)
+        .await
+        .unwrap()
+        .unwrap();
+    payer_account.lamports -= account.lamports;
+    context.set_account(&payer, &payer_account.into());
+    context.set_account(address, &account.into());
+}
+
+fn packed_account<T: Pack>(state: T) -> Account {
+    let mut data = vec![0; T::LEN];
+    state.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::id(),
+        ..Account::default()
+    }
+}
+
+async fn token_balance(env: &mut TestEnv, token_account: &Pubkey) -> u64 {
+    let account = env
+        .context
+        .banks_client
+        .get_account(*token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+/// Sends lamports from the payer, for wallets paying for their own transactions
+async fn fund(env: &mut TestEnv, wallet: &Pubkey) {
+    let payer = &env.context.payer;
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &payer.pubkey(),
+            wallet,
+            1_000_000_000,
+        )],
+        Some(&payer.pubkey()),
+        &[payer],
+        env.context.last_blockhash,
+    );
+    env.context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+/// Moves the clock forward, warping to a later slot so that retried transactions get a new
+/// blockhash
+async fn warp_forward(env: &mut TestEnv, seconds: i64) {
+    let clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    env.context.warp_to_slot(clock.slot + 100).unwrap();
+    let mut warped_clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    warped_clock.unix_timestamp = clock.unix_timestamp + seconds;
+    env.context.set_sysvar(&warped_clock);
+}
+
+#[tokio::test]
+async fn test_lock_and_unlock_timed() {
+    let mut env = setup(100).await;
+    let payer = env.context.payer.insecure_clone();
+    let seed = [3u8; 31];
+
+    let addresses = env
+        .client
+        .lock(
+            &payer,
+            &env.owner,
+            &seed,
+            Schedule {
+                time_delta: 7_776_000,
+                amount: 60,
+            },
+            LockOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut env, &addresses.vesting_token_account).await,
+        60
+    );
+
+    let info = env
+        .client
+        .get_contract_info(&addresses.vesting_account)
+        .await
+        .unwrap();
+    assert_eq!(info.header.destination_address, env.source_token_account);
+    assert_eq!(info.schedule.amount, 60);
+    assert_ne!(info.schedule.release_time, 0);
+
+    // The same seed can not be used twice
+    assert!(matches!(
+        env.client
+            .lock(
+                &payer,
+                &env.owner,
+                &seed,
+                Schedule {
+                    time_delta: 7_776_000,
+                    amount: 10,
+                },
+                LockOptions::default()
+            )
+            .await,
+        Err(ClientError::AlreadyExists(_))
+    ));
+
+    // The lock has not matured yet
+    assert!(env.client.unlock(&payer, addresses.seeds).await.is_err());
+
+    warp_forward(&mut env, 7_776_000).await;
+    env.client.unlock(&payer, addresses.seeds).await.unwrap();
+
+    let source_token_account = env.source_token_account;
+    assert_eq!(token_balance(&mut env, &source_token_account).await, 100);
+    assert_eq!(
+        token_balance(&mut env, &addresses.vesting_token_account).await,
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_get_contract_info_missing_account() {
+    let mut env = setup(0).await;
+    let addresses = env.client.lock_addresses(&[5u8; 31]);
+    assert!(matches!(
+        env.client
+            .get_contract_info(&addresses.vesting_account)
+            .await,
+        Err(ClientError::AccountNotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_lock_multisig_owner() {
+    let (mut env, multisig, signers) = setup_multisig(100, 2, 3).await;
+    let payer = env.context.payer.insecure_clone();
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 40,
+    };
+
+    // One signer is below the multisig threshold
+    assert!(env
+        .client
+        .lock_multisig(
+            &payer,
+            &multisig,
+            &[&signers[0]],
+            &[6u8; 31],
+            schedule.clone(),
+            LockOptions::default()
+        )
+        .await
+        .is_err());
+
+    let addresses = env
+        .client
+        .lock_multisig(
+            &payer,
+            &multisig,
+            &[&signers[0], &signers[2]],
+            &[6u8; 31],
+            schedule,
+            LockOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut env, &addresses.vesting_token_account).await,
+        40
+    );
+    let source_token_account = env.source_token_account;
+    assert_eq!(token_balance(&mut env, &source_token_account).await, 60);
+
+    let info = env
+        .client
+        .get_contract_info(&addresses.vesting_account)
+        .await
+        .unwrap();
+    assert_eq!(info.header.destination_address, env.source_token_account);
+}
+
+#[tokio::test]
+async fn test_lock_batch() {
+    let mut env = setup(1_000).await;
+    let payer = env.context.payer.insecure_clone();
+    let recipients: Vec<BatchRecipient> = (1..=7)
+        .map(|i| BatchRecipient {
+            beneficiary: Pubkey::new_unique(),
+            time_delta: if i % 2 == 0 { 0 } else { 15_552_000 },
+            amount: i * 10,
+        })
+        .collect();
+
+    // The batch is split over several transactions
+    let transactions = batch_transactions(
+        &env.client.program_id,
+        &TOKEN_MINT,
+        &payer.pubkey(),
+        &env.owner.pubkey(),
+        &[],
+        None,
+        None,
+        b"grants",
+        &recipients,
+    )
+    .unwrap();
+    assert!(transactions.len() > 1);
+
+    let locks = env
+        .client
+        .lock_batch(&payer, &env.owner, None, b"grants", &recipients)
+        .await
+        .unwrap();
+    assert_eq!(locks.len(), recipients.len());
+    for (recipient, addresses) in recipients.iter().zip(&locks) {
+        let info: ContractInfo = env
+            .client
+            .get_contract_info(&addresses.vesting_account)
+            .await
+            .unwrap();
+        assert_eq!(
+            info.header.destination_address,
+            get_associated_token_address(&recipient.beneficiary, &TOKEN_MINT)
+        );
+        assert_eq!(info.schedule.amount, recipient.amount);
+        assert_eq!(info.schedule.release_time == 0, recipient.time_delta == 0);
+        assert_eq!(
+            token_balance(&mut env, &addresses.vesting_token_account).await,
+            recipient.amount
+        );
+    }
+    let source_token_account = env.source_token_account;
+    assert_eq!(
+        token_balance(&mut env, &source_token_account).await,
+        1_000 - 280
+    );
+    // Every lock is registered for its recipient
+    let registry = env
+        .client
+        .get_registry(&recipients[0].beneficiary)
+        .await
+        .unwrap();
+    assert_eq!(
+        registry.open_positions()[0].vesting_account,
+        locks[0].vesting_account
+    );
+
+    // Sending the batch again does not lock the tokens twice
+    let locks_again = env
+        .client
+        .lock_batch(&payer, &env.owner, None, b"grants", &recipients)
+        .await
+        .unwrap();
+    assert_eq!(locks_again, locks);
+    assert_eq!(
+        token_balance(&mut env, &source_token_account).await,
+        1_000 - 280
+    );
+}
+
+#[tokio::test]
+async fn test_airdrop_claim_into_lock() {
+    let mut env = setup(1_000).await;
+    let owner_pubkey = env.owner.pubkey();
+    fund(&mut env, &owner_pubkey).await;
+    let claimants: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    for claimant in &claimants {
+        fund(&mut env, &claimant.pubkey()).await;
+    }
+    let entries: Vec<AirdropEntry> = claimants
+        .iter()
+        .zip([100, 200, 300])
+        .map(|(claimant, amount)| AirdropEntry {
+            wallet: claimant.pubkey(),
+            amount,
+            time_delta: 7_776_000,
+        })
+        .collect();
+
+    let airdrop = env
+        .client
+        .create_airdrop(&env.owner, None, &[9u8; 31], &entries)
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut env, &airdrop.vault_token_account).await,
+        600
+    );
+
+    let addresses = env
+        .client
+        .claim_into_lock(&claimants[1], &airdrop, &entries, 1, None)
+        .await
+        .unwrap();
+    let destination = get_associated_token_address(&claimants[1].pubkey(), &TOKEN_MINT);
+    let info = env
+        .client
+        .get_contract_info(&addresses.vesting_account)
+        .await
+        .unwrap();
+    assert_eq!(info.header.destination_address, destination);
+    assert_eq!(info.schedule.amount, 200);
+    assert_eq!(
+        token_balance(&mut env, &airdrop.vault_token_account).await,
+        400
+    );
+    let registry = env
+        .client
+        .get_registry(&claimants[1].pubkey())
+        .await
+        .unwrap();
+    assert_eq!(
+        registry.open_positions()[0].vesting_account,
+        addresses.vesting_account
+    );
+
+    // An entry can only be claimed once
+    assert!(matches!(
+        env.client
+            .claim_into_lock(&claimants[1], &airdrop, &entries, 1, None)
+            .await,
+        Err(ClientError::AlreadyExists(_))
+    ));
+
+    // Nor claimed again into the vesting account of another bump seed
+    let mut seeds = addresses.seeds;
+    let other_bump_lock = (0..addresses.seeds[31])
+        .rev()
+        .find_map(|bump| {
+            seeds[31] = bump;
+            LockAddresses::from_seeds(&env.client.program_id, &TOKEN_MINT, seeds).ok()
+        })
+        .unwrap();
+    let instructions = instructions::claim_into_lock(
+        &env.client.program_id,
+        &TOKEN_MINT,
+        &airdrop,
+        &other_bump_lock,
+        &entries[1],
+        MerkleTree::new(&entries).unwrap().proof(1).unwrap(),
+        None,
+        &entries[1].wallet,
+        None,
+    )
+    .unwrap();
+    let blockhash = env.context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&claimants[1].pubkey()),
+        &[&claimants[1]],
+        blockhash,
+    );
+    assert!(env
+        .context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .is_err());
+    assert_eq!(
+        token_balance(&mut env, &airdrop.vault_token_account).await,
+        400
+    );
+
+    // The claimed amount must match the entry
+    let mut inflated = entries.clone();
+    inflated[0].amount = 500;
+    assert!(env
+        .client
+        .claim_into_lock(&claimants[0], &airdrop, &inflated, 0, None)
+        .await
+        .is_err());
+
+    // Only the wallet of the entry can claim it
+    assert!(env
+        .client
+        .claim_into_lock(&claimants[0], &airdrop, &entries, 2, None)
+        .await
+        .is_err());
+
+    // The claimed lock is released like any other lock
+    warp_forward(&mut env, 7_776_000).await;
+    env.client
+        .unlock(&claimants[1], addresses.seeds)
+        .await
+        .unwrap();
+    assert_eq!(token_balance(&mut env, &destination).await, 200);
+}
+
+/// Lamports held by a vesting account
+async fn vesting_lamports(env: &mut TestEnv, addresses: &LockAddresses) -> u64 {
+    env.context
+        .banks_client
+        .get_balance(addresses.vesting_account)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_keeper_tip() {
+    let mut env = setup(100).await;
+    let payer = env.context.payer.insecure_clone();
+    let source_token_account = env.source_token_account;
+    let keeper = Keypair::new();
+    fund(&mut env, &keeper.pubkey()).await;
+
+    let mut locks = vec![];
+    for (seed, time_delta, keeper_tip) in [
+        ([1u8; 31], 7_776_000, 1_000_000),
+        ([2u8; 31], 7_776_000, 0),
+        ([3u8; 31], 15_552_000, 2_000_000),
+    ] {
+        let addresses = env
+            .client
+            .lock(
+                &payer,
+                &env.owner,
+                &seed,
+                Schedule {
+                    time_delta,
+                    amount: 10,
+                },
+                LockOptions {
+                    keeper_tip,
+                    ..LockOptions::default()
+                },
+            )
+            .await
+            .unwrap();
+        locks.push(addresses);
+    }
+
+    // The vesting accounts hold their keeper tip on top of their rent, and their seeds
+    let rent = vesting_lamports(&mut env, &locks[1]).await;
+    assert_eq!(
+        vesting_lamports(&mut env, &locks[0]).await,
+        rent + 1_000_000
+    );
+    let info = env
+        .client
+        .get_contract_info(&locks[0].vesting_account)
+        .await
+        .unwrap();
+    assert_eq!(info.header.keeper_tip, 1_000_000);
+    assert_eq!(info.header.seeds, locks[0].seeds);
+
+    // Nothing has matured yet
+    assert!(env.client.find_matured_locks(0).await.unwrap().is_empty());
+
+    // Matured locks are found by their keeper tip, the best paid first
+    warp_forward(&mut env, 7_776_000).await;
+    let matured = env.client.find_matured_locks(0).await.unwrap();
+    assert_eq!(
+        matured
+            .iter()
+            .map(|lock| (lock.addresses.clone(), lock.keeper_tip))
+            .collect::<Vec<_>>(),
+        vec![(locks[0].clone(), 1_000_000), (locks[1].clone(), 0)]
+    );
+    let matured = env.client.find_matured_locks(1).await.unwrap();
+    assert_eq!(matured.len(), 1);
+
+    // The keeper earns the tips of the locks it releases
+    let keeper_balance = env
+        .context
+        .banks_client
+        .get_balance(keeper.pubkey())
+        .await
+        .unwrap();
+    let mut rounds = 0;
+    env.client
+        .run_keeper(&keeper, 1, Duration::from_millis(1), |round| {
+            assert_eq!(round.unwrap().len(), 1 - rounds);
+            rounds += 1;
+            rounds < 2
+        })
+        .await;
+    assert_eq!(token_balance(&mut env, &source_token_account).await, 80);
+    assert_eq!(vesting_lamports(&mut env, &locks[0]).await, rent);
+    assert_eq!(
+        env.context
+            .banks_client
+            .get_balance(keeper.pubkey())
+            .await
+            .unwrap(),
+        keeper_balance + 1_000_000 - 5_000
+    );
+
+    // Anyone can release a matured lock with `Unlock` and earn its tip
+    warp_forward(&mut env, 7_776_000).await;
+    let clock: Clock = env.context.banks_client.get_sysvar().await.unwrap();
+    let mut accounts = vec![];
+    for addresses in &locks {
+        let account = env
+            .context
+            .banks_client
+            .get_account(addresses.vesting_account)
+            .await
+            .unwrap()
+            .unwrap();
+        accounts.push((addresses.vesting_account, account));
+    }
+    let matured = matured_locks(&env.client.program_id, &TOKEN_MINT, &accounts, &clock, 0);
+    assert_eq!(matured.len(), 2);
+    assert_eq!(matured[0].addresses, locks[2]);
+    env.client.unlock(&keeper, locks[2].seeds).await.unwrap();
+    assert_eq!(token_balance(&mut env, &source_token_account).await, 90);
+    assert_eq!(vesting_lamports(&mut env, &locks[2]).await, rent);
+    assert_eq!(
+        env.context
+            .banks_client
+            .get_balance(keeper.pubkey())
+            .await
+            .unwrap(),
+        keeper_balance + 3_000_000 - 10_000
+    );
+}
+
+async fn get_tally(env: &mut TestEnv, tally_account: &Pubkey) -> Tally {
+    let account = env
+        .context
+        .banks_client
+        .get_account(*tally_account)
+        .await
+        .unwrap()
+        .unwrap();
+    Tally::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_hooks() {
+    let hook_program = Pubkey::new_unique();
+    let tally_account = Pubkey::new_unique();
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "token_vesting_hook_example",
+        hook_program,
+        processor!(token_vesting_hook_example::processor::Processor::process_instruction),
+    );
+    program_test.add_account(
+        tally_account,
+        Account {
+            lamports: 1_000_000_000,
+            data: vec![0; Tally::LEN],
+            owner: hook_program,
+            ..Account::default()
+        },
+    );
+    let owner = Keypair::new();
+    let owner_pubkey = owner.pubkey();
+    let mut env = start(program_test, owner, &owner_pubkey, 100).await;
+    let payer = env.context.payer.insecure_clone();
+    fund(&mut env, &owner_pubkey).await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[init_tally(
+            &hook_program,
+            &tally_account,
+            &env.client.program_id,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        env.context.last_blockhash,
+    );
+    env.context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    env.client.hook_accounts.insert(
+        hook_program,
+        token_vesting_hook_example::instruction::hook_accounts(&tally_account),
+    );
+
+    // The hook is invoked after the creation, initial unlock and unlock of a lock
+    let staked = lock_tokens(
+        &mut env,
+        1,
+        Schedule {
+            time_delta: 0,
+            amount: 40,
+        },
+        LockOptions {
+            hook: Some(Hook {
+                program_id: hook_program,
+                mode: HookMode::Required,
+            }),
+            ..LockOptions::default()
+        },
+    )
+    .await
+    .unwrap();
+    let tally = get_tally(&mut env, &tally_account).await;
+    assert_eq!((tally.creates, tally.locked), (1, 40));
+    env.client
+        .initialize_unlock(&payer, &env.owner, staked.seeds)
+        .await
+        .unwrap();
+    assert_eq!(
+        get_tally(&mut env, &tally_account).await.initialize_unlocks,
+        1
+    );
+
+    // Keepers skip hooked locks, the client forwards the hook accounts of the lock
+    warp_forward(&mut env, 604_800).await;
+    assert!(env.client.find_matured_locks(0).await.unwrap().is_empty());
+    env.client.unlock(&payer, staked.seeds).await.unwrap();
+    let tally = get_tally(&mut env, &tally_account).await;
+    assert_eq!((tally.unlocks, tally.locked), (1, 0));
+
+    // Without the hook accounts of its hook program, the hook of a lock fails
+    let timed = lock_tokens(
+        &mut env,
+        2,
+        Schedule {
+            time_delta: 7_776_000,
+            amount: 60,
+        },
+        LockOptions {
+            hook: Some(Hook {
+                program_id: hook_program,
+                mode: HookMode::Skippable,
+            }),
+            ..LockOptions::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(get_tally(&mut env, &tally_account).await.locked, 60);
+    warp_forward(&mut env, 7_776_000).await;
+    assert!(env.client.find_matured_locks(0).await.unwrap().is_empty());
+    let hook_accounts = env.client.hook_accounts.remove(&hook_program).unwrap();
+    assert!(env.client.unlock(&payer, timed.seeds).await.is_err());
+    env.client.hook_accounts.insert(hook_program, hook_accounts);
+    env.client.unlock(&payer, timed.seeds).await.unwrap();
+    let tally = get_tally(&mut env, &tally_account).await;
+    assert_eq!((tally.unlocks, tally.locked), (2, 0));
+    let source_token_account = env.source_token_account;
+    assert_eq!(token_balance(&mut env, &source_token_account).await, 100);
+}
+
+/// Locks tokens of the owner of the environment, paid by the payer of the environment
+async fn lock_tokens(
+    env: &mut TestEnv,
+    seed: u8,
+    schedule: Schedule,
+    options: LockOptions,
+) -> Result<LockAddresses, ClientError> {
+    let payer = env.context.payer.insecure_clone();
+    env.client
+        .lock(&payer, &env.owner, &[seed; 31], schedule, options)
+        .await
+}
@@ -0,0 +1,73 @@
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use std::convert::TryInto;
+use token_vesting::hook::{HookPayload, HOOK_DISCRIMINATOR};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum HookExampleInstruction {
+    /// Initializes a tally account, allocated with `Tally::LEN` bytes and owned by the program
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The tally account
+    InitTally { vesting_program: Pubkey },
+    /// Tallies a lifecycle event of a lock, invoked by the vesting program
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[signer]` The hook authority of the lock
+    ///   1. `[]` The vesting account of the lock
+    ///   2. `[writable]` The tally account, forwarded by the vesting program
+    Hook(HookPayload),
+}
+
+impl HookExampleInstruction {
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.starts_with(&HOOK_DISCRIMINATOR) {
+            return Ok(Self::Hook(HookPayload::unpack(input)?));
+        }
+        match input.split_first() {
+            Some((0, rest)) if rest.len() == 32 => Ok(Self::InitTally {
+                vesting_program: Pubkey::new_from_array(rest.try_into().unwrap()),
+            }),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+
+    pub fn pack(&self) -> Vec<u8> {
+        match self {
+            Self::InitTally { vesting_program } => {
+                let mut buf = vec![0];
+                buf.extend_from_slice(vesting_program.as_ref());
+                buf
+            }
+            Self::Hook(payload) => payload.pack(),
+        }
+    }
+}
+
+/// Creates an `InitTally` instruction
+pub fn init_tally(
+    hook_program_id: &Pubkey,
+    tally_account_key: &Pubkey,
+    vesting_program_id: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *hook_program_id,
+        accounts: vec![AccountMeta::new(*tally_account_key, false)],
+        data: HookExampleInstruction::InitTally {
+            vesting_program: *vesting_program_id,
+        }
+        .pack(),
+    }
+}
+
+/// The accounts forwarded to the hook by the instructions of the vesting program
+pub fn hook_accounts(tally_account_key: &Pubkey) -> Vec<AccountMeta> {
+    vec![AccountMeta::new(*tally_account_key, false)]
+}
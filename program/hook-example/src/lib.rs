@@ -0,0 +1,11 @@
+//! Sample hook program for the token vesting program.
+//!
+//! It tallies the lifecycle events of the locks registering it as their hook in a `Tally`
+//! account, forwarded by the instructions of the vesting program after the hook program account.
+//! Integrators, such as a rewards distributor, can start from it to react to the locks.
+#[cfg(not(feature = "no-entrypoint"))]
+pub mod entrypoint;
+
+pub mod instruction;
+pub mod processor;
+pub mod state;
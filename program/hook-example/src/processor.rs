@@ -0,0 +1,106 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use token_vesting::{
+    hook::{HookEvent, HookPayload},
+    processor::HOOK_AUTHORITY_SEED,
+};
+
+use crate::{instruction::HookExampleInstruction, state::Tally};
+
+pub struct Processor {}
+
+impl Processor {
+    pub fn process_init_tally(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        vesting_program: Pubkey,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let tally_account = next_account_info(accounts_iter)?;
+
+        if tally_account.owner != program_id {
+            msg!("The tally account should be owned by the hook program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut data = tally_account.data.borrow_mut();
+        if data.len() != Tally::LEN || data[0] != 0 {
+            msg!("The tally account is invalid or already initialized");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        Tally {
+            is_initialized: true,
+            vesting_program,
+            ..Tally::default()
+        }
+        .pack_into_slice(&mut data);
+        Ok(())
+    }
+
+    pub fn process_hook(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        payload: HookPayload,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let hook_authority = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let tally_account = next_account_info(accounts_iter)?;
+
+        if tally_account.owner != program_id {
+            msg!("The tally account should be owned by the hook program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let mut tally = Tally::unpack(&tally_account.data.borrow())?;
+
+        // Only the vesting program can sign for the hook authority of its vesting accounts, which
+        // authenticates the payload
+        let (hook_authority_key, _) = Pubkey::find_program_address(
+            &[HOOK_AUTHORITY_SEED, payload.vesting_account.as_ref()],
+            &tally.vesting_program,
+        );
+        if !hook_authority.is_signer
+            || *hook_authority.key != hook_authority_key
+            || *vesting_account.key != payload.vesting_account
+            || *vesting_account.owner != tally.vesting_program
+        {
+            msg!("The hook should be invoked by the vesting program for the vesting account");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        match payload.event {
+            HookEvent::Create => {
+                tally.creates += 1;
+                tally.locked += payload.amount;
+            }
+            HookEvent::InitializeUnlock => tally.initialize_unlocks += 1,
+            HookEvent::Unlock => {
+                tally.unlocks += 1;
+                tally.locked = tally.locked.saturating_sub(payload.amount);
+            }
+        }
+        Tally::pack(tally, &mut tally_account.data.borrow_mut())
+    }
+
+    pub fn process_instruction(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
+    ) -> ProgramResult {
+        match HookExampleInstruction::unpack(instruction_data)? {
+            HookExampleInstruction::InitTally { vesting_program } => {
+                msg!("Instruction: Init Tally");
+                Self::process_init_tally(program_id, accounts, vesting_program)
+            }
+            HookExampleInstruction::Hook(payload) => {
+                msg!("Instruction: Hook {:?}", payload.event);
+                Self::process_hook(program_id, accounts, payload)
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+use std::convert::TryInto;
+
+/// The lifecycle events of the locks hooked to the program
+#[derive(Debug, Default, PartialEq)]
+pub struct Tally {
+    pub is_initialized: bool,
+    /// The vesting program whose locks are tallied
+    pub vesting_program: Pubkey,
+    pub creates: u64,
+    pub initialize_unlocks: u64,
+    pub unlocks: u64,
+    /// Amount of tokens locked by the hooked locks and not released yet
+    pub locked: u64,
+}
+
+impl Sealed for Tally {}
+
+impl IsInitialized for Tally {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Tally {
+    const LEN: usize = 65;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[0] = self.is_initialized as u8;
+        dst[1..33].copy_from_slice(self.vesting_program.as_ref());
+        dst[33..41].copy_from_slice(&self.creates.to_le_bytes());
+        dst[41..49].copy_from_slice(&self.initialize_unlocks.to_le_bytes());
+        dst[49..57].copy_from_slice(&self.unlocks.to_le_bytes());
+        dst[57..65].copy_from_slice(&self.locked.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self {
+            is_initialized: src[0] == 1,
+            vesting_program: Pubkey::new_from_array(src[1..33].try_into().unwrap()),
+            creates: u64::from_le_bytes(src[33..41].try_into().unwrap()),
+            initialize_unlocks: u64::from_le_bytes(src[41..49].try_into().unwrap()),
+            unlocks: u64::from_le_bytes(src[49..57].try_into().unwrap()),
+            locked: u64::from_le_bytes(src[57..65].try_into().unwrap()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tally_packing() {
+        let tally = Tally {
+            is_initialized: true,
+            vesting_program: Pubkey::new_unique(),
+            creates: 3,
+            initialize_unlocks: 1,
+            unlocks: 2,
+            locked: 40,
+        };
+        let mut packed = [0u8; Tally::LEN];
+        tally.pack_into_slice(&mut packed);
+        assert_eq!(tally, Tally::unpack_from_slice(&packed).unwrap());
+    }
+}
@@ -8,6 +8,15 @@ pub enum VestingError {
     // Invalid instruction
     #[error("Invalid Instruction")]
     InvalidInstruction,
+    // The amount of a new lock is below the minimum of its tier
+    #[error("Amount Below Minimum")]
+    AmountBelowMinimum,
+    // The new lock would exceed the amount all the locks can hold
+    #[error("Pool Cap Exceeded")]
+    PoolCapExceeded,
+    // The new lock would exceed the amount the locks of its beneficiary can hold
+    #[error("Wallet Cap Exceeded")]
+    WalletCapExceeded,
 }
 
 impl From<VestingError> for ProgramError {
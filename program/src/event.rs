@@ -0,0 +1,234 @@
+//! Events logged by the program on changes of its config and allowlist, and on emergency
+//! unlocks.
+//!
+//! Events are logged with `sol_log_data`, and appear base64 encoded in the `Program data:` lines
+//! of the transaction logs. Their data is a tag byte followed by their fields, little endian.
+use solana_program::{log::sol_log_data, program_error::ProgramError, pubkey::Pubkey};
+use std::convert::TryInto;
+
+use crate::error::VestingError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VestingEvent {
+    /// The guardian paused or resumed the creation of locks
+    PauseSet { paused: bool },
+    /// The admin entered or left the emergency mode
+    EmergencyModeSet { enabled: bool },
+    /// The admin replaced the guardian
+    GuardianSet { guardian: Pubkey },
+    /// A lock was released in emergency mode
+    EmergencyUnlock {
+        vesting_account: Pubkey,
+        amount: u64,
+    },
+    /// The admin enabled or disabled the allowlist, and set its Merkle root
+    AllowlistSet { enabled: bool, root: [u8; 32] },
+    /// The admin added a wallet to the allowlist
+    AllowlistEntryAdded { wallet: Pubkey },
+    /// The admin removed a wallet from the allowlist
+    AllowlistEntryRemoved { wallet: Pubkey },
+    /// The admin set the caps on the locked amounts and the minimum amounts of new locks
+    LimitsSet {
+        max_total_locked: u64,
+        max_locked_per_wallet: u64,
+        min_lock_amounts: [u64; 5],
+    },
+    /// The admin set the deposit fee and its treasury
+    DepositFeeSet {
+        treasury: Pubkey,
+        deposit_fee_bps: u16,
+    },
+    /// The admin set the share of the deposit fee paid to the referrers
+    ReferralShareSet { referral_share_bps: u16 },
+}
+
+impl VestingEvent {
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        match self {
+            &Self::PauseSet { paused } => {
+                buf.push(0);
+                buf.push(paused as u8);
+            }
+            &Self::EmergencyModeSet { enabled } => {
+                buf.push(1);
+                buf.push(enabled as u8);
+            }
+            Self::GuardianSet { guardian } => {
+                buf.push(2);
+                buf.extend_from_slice(guardian.as_ref());
+            }
+            Self::EmergencyUnlock {
+                vesting_account,
+                amount,
+            } => {
+                buf.push(3);
+                buf.extend_from_slice(vesting_account.as_ref());
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            Self::AllowlistSet { enabled, root } => {
+                buf.push(4);
+                buf.push(*enabled as u8);
+                buf.extend_from_slice(root);
+            }
+            Self::AllowlistEntryAdded { wallet } => {
+                buf.push(5);
+                buf.extend_from_slice(wallet.as_ref());
+            }
+            Self::AllowlistEntryRemoved { wallet } => {
+                buf.push(6);
+                buf.extend_from_slice(wallet.as_ref());
+            }
+            Self::LimitsSet {
+                max_total_locked,
+                max_locked_per_wallet,
+                min_lock_amounts,
+            } => {
+                buf.push(7);
+                buf.extend_from_slice(&max_total_locked.to_le_bytes());
+                buf.extend_from_slice(&max_locked_per_wallet.to_le_bytes());
+                for min_lock_amount in min_lock_amounts {
+                    buf.extend_from_slice(&min_lock_amount.to_le_bytes());
+                }
+            }
+            Self::DepositFeeSet {
+                treasury,
+                deposit_fee_bps,
+            } => {
+                buf.push(8);
+                buf.extend_from_slice(treasury.as_ref());
+                buf.extend_from_slice(&deposit_fee_bps.to_le_bytes());
+            }
+            Self::ReferralShareSet { referral_share_bps } => {
+                buf.push(9);
+                buf.extend_from_slice(&referral_share_bps.to_le_bytes());
+            }
+        }
+        buf
+    }
+
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        use VestingError::InvalidInstruction;
+        let (&tag, rest) = input.split_first().ok_or(InvalidInstruction)?;
+        let flag = || match rest.first() {
+            Some(0) => Ok(false),
+            Some(1) => Ok(true),
+            _ => Err(InvalidInstruction),
+        };
+        let pubkey = || {
+            rest.get(..32)
+                .and_then(|slice| slice.try_into().ok())
+                .map(Pubkey::new_from_array)
+                .ok_or(InvalidInstruction)
+        };
+        let amount = |offset: usize| {
+            rest.get(offset..offset + 8)
+                .and_then(|slice| slice.try_into().ok())
+                .map(u64::from_le_bytes)
+                .ok_or(InvalidInstruction)
+        };
+        Ok(match tag {
+            0 => Self::PauseSet { paused: flag()? },
+            1 => Self::EmergencyModeSet { enabled: flag()? },
+            2 => Self::GuardianSet {
+                guardian: pubkey()?,
+            },
+            3 => Self::EmergencyUnlock {
+                vesting_account: pubkey()?,
+                amount: amount(32)?,
+            },
+            4 => Self::AllowlistSet {
+                enabled: flag()?,
+                root: rest
+                    .get(1..33)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(InvalidInstruction)?,
+            },
+            5 => Self::AllowlistEntryAdded { wallet: pubkey()? },
+            6 => Self::AllowlistEntryRemoved { wallet: pubkey()? },
+            7 => {
+                let mut min_lock_amounts = [0; 5];
+                for (i, min_lock_amount) in min_lock_amounts.iter_mut().enumerate() {
+                    *min_lock_amount = amount(16 + 8 * i)?;
+                }
+                Self::LimitsSet {
+                    max_total_locked: amount(0)?,
+                    max_locked_per_wallet: amount(8)?,
+                    min_lock_amounts,
+                }
+            }
+            8 => Self::DepositFeeSet {
+                treasury: pubkey()?,
+                deposit_fee_bps: rest
+                    .get(32..34)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u16::from_le_bytes)
+                    .ok_or(InvalidInstruction)?,
+            },
+            9 => Self::ReferralShareSet {
+                referral_share_bps: rest
+                    .get(..2)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u16::from_le_bytes)
+                    .ok_or(InvalidInstruction)?,
+            },
+            _ => return Err(InvalidInstruction.into()),
+        })
+    }
+
+    /// Logs the event in the transaction logs
+    pub fn emit(&self) {
+        sol_log_data(&[&self.pack()]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_packing() {
+        for event in [
+            VestingEvent::PauseSet { paused: true },
+            VestingEvent::EmergencyModeSet { enabled: false },
+            VestingEvent::GuardianSet {
+                guardian: Pubkey::new_unique(),
+            },
+            VestingEvent::EmergencyUnlock {
+                vesting_account: Pubkey::new_unique(),
+                amount: 42,
+            },
+            VestingEvent::AllowlistSet {
+                enabled: true,
+                root: [7; 32],
+            },
+            VestingEvent::AllowlistEntryAdded {
+                wallet: Pubkey::new_unique(),
+            },
+            VestingEvent::AllowlistEntryRemoved {
+                wallet: Pubkey::new_unique(),
+            },
+            VestingEvent::LimitsSet {
+                max_total_locked: 1_000_000,
+                max_locked_per_wallet: 10_000,
+                min_lock_amounts: [1, 2, 3, 4, 5],
+            },
+            VestingEvent::DepositFeeSet {
+                treasury: Pubkey::new_unique(),
+                deposit_fee_bps: 50,
+            },
+            VestingEvent::ReferralShareSet {
+                referral_share_bps: 2_000,
+            },
+        ] {
+            assert_eq!(event, VestingEvent::unpack(&event.pack()).unwrap());
+        }
+        assert!(VestingEvent::unpack(&[0, 2]).is_err());
+        assert!(VestingEvent::unpack(&[3; 33]).is_err());
+        assert!(VestingEvent::unpack(&[4, 1]).is_err());
+        assert!(VestingEvent::unpack(&[7; 55]).is_err());
+        assert!(VestingEvent::unpack(&[8; 33]).is_err());
+        assert!(VestingEvent::unpack(&[9, 1]).is_err());
+        assert!(VestingEvent::unpack(&[10]).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+//! Hooks: programs invoked by the vesting program after the lifecycle events of a lock.
+//!
+//! A lock created with a hook invokes its hook program after `Create`, `InitializeUnlock` and
+//! `Unlock`. The instruction data of the hook is `HOOK_DISCRIMINATOR` followed by a
+//! `HookPayload`, little endian. Its accounts are the hook authority of the lock, as a signer, the
+//! vesting account, and the accounts following the hook authority in the instruction of the
+//! vesting program. The hook authority is the address derived from `HOOK_AUTHORITY_SEED` and the
+//! vesting account: hooks authenticate the payload by checking that it signed, as only the vesting
+//! program can sign for it. Unlike the vesting account, it has no authority over the tokens of
+//! the lock, so a hook cannot move them.
+//!
+//! The runtime does not let a program recover from a failed invocation: a failing hook fails the
+//! whole transaction. The hook mode of the lock sets whether the owner of the lock can leave out
+//! the hook program account, skipping a failing hook. The instructions nobody signs for the owner
+//! always invoke the hook.
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use std::convert::TryInto;
+
+use crate::error::VestingError;
+
+/// Prefix of the instruction data of hooks, telling them apart from the other instructions of
+/// the hook program
+pub const HOOK_DISCRIMINATOR: [u8; 8] = *b"vesthook";
+
+/// Whether the instructions of a lock must invoke its hook
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HookMode {
+    /// The hook program account is required, a failing hook blocks the lock
+    Required,
+    /// The hook is skipped when its program account is left out by an instruction signed by the
+    /// owner of the lock, so that the owner can bypass a failing hook: `Create`,
+    /// `InitializeUnlock`, and `Unlock` signed by the position token holder or by the owner
+    /// passed as the keeper account. `UnlockMany` skips the lock.
+    Skippable,
+}
+
+impl HookMode {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Required),
+            1 => Some(Self::Skippable),
+            _ => None,
+        }
+    }
+}
+
+/// The hook registered by a lock
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hook {
+    pub program_id: Pubkey,
+    pub mode: HookMode,
+}
+
+/// The lifecycle events of a lock notified to its hook
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HookEvent {
+    /// The lock was created
+    Create,
+    /// The withdrawal period of an unlocked stake started
+    InitializeUnlock,
+    /// The tokens of the lock were released to its destination
+    Unlock,
+}
+
+/// The payload passed to the hook of a lock
+#[derive(Clone, Debug, PartialEq)]
+pub struct HookPayload {
+    pub event: HookEvent,
+    pub vesting_account: Pubkey,
+    /// The destination spl-token account of the lock
+    pub destination_address: Pubkey,
+    /// Index of the lock period of the lock in `TIER_TIME_DELTAS`
+    pub tier: u8,
+    /// The amount locked, started withdrawing or released by the event
+    pub amount: u64,
+    /// The release time of the lock, in the unit of its lock mode, 0 for an unlocked stake
+    /// before its withdrawal period
+    pub release_time: u64,
+}
+
+impl HookPayload {
+    /// Length of a packed payload, discriminator included
+    pub const LEN: usize = 90;
+
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::LEN);
+        buf.extend_from_slice(&HOOK_DISCRIMINATOR);
+        buf.push(self.event as u8);
+        buf.extend_from_slice(self.vesting_account.as_ref());
+        buf.extend_from_slice(self.destination_address.as_ref());
+        buf.push(self.tier);
+        buf.extend_from_slice(&self.amount.to_le_bytes());
+        buf.extend_from_slice(&self.release_time.to_le_bytes());
+        buf
+    }
+
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        use VestingError::InvalidInstruction;
+        let rest = input
+            .strip_prefix(&HOOK_DISCRIMINATOR[..])
+            .ok_or(InvalidInstruction)?;
+        if rest.len() != Self::LEN - HOOK_DISCRIMINATOR.len() {
+            return Err(InvalidInstruction.into());
+        }
+        let event = match rest[0] {
+            0 => HookEvent::Create,
+            1 => HookEvent::InitializeUnlock,
+            2 => HookEvent::Unlock,
+            _ => return Err(InvalidInstruction.into()),
+        };
+        Ok(Self {
+            event,
+            vesting_account: Pubkey::new_from_array(rest[1..33].try_into().unwrap()),
+            destination_address: Pubkey::new_from_array(rest[33..65].try_into().unwrap()),
+            tier: rest[65],
+            amount: u64::from_le_bytes(rest[66..74].try_into().unwrap()),
+            release_time: u64::from_le_bytes(rest[74..82].try_into().unwrap()),
+        })
+    }
+
+    /// The instruction invoking the hook program, signed by the hook authority of the lock and
+    /// followed by the forwarded accounts
+    pub fn instruction(
+        &self,
+        hook_program_id: &Pubkey,
+        hook_authority: &Pubkey,
+        accounts: &[AccountMeta],
+    ) -> Instruction {
+        let mut metas = vec![
+            AccountMeta::new_readonly(*hook_authority, true),
+            AccountMeta::new_readonly(self.vesting_account, false),
+        ];
+        metas.extend_from_slice(accounts);
+        Instruction {
+            program_id: *hook_program_id,
+            accounts: metas,
+            data: self.pack(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_packing() {
+        let payload = HookPayload {
+            event: HookEvent::InitializeUnlock,
+            vesting_account: Pubkey::new_unique(),
+            destination_address: Pubkey::new_unique(),
+            tier: 0,
+            amount: 42,
+            release_time: 1_700_604_800,
+        };
+        let packed = payload.pack();
+        assert_eq!(packed.len(), HookPayload::LEN);
+        assert_eq!(payload, HookPayload::unpack(&packed).unwrap());
+
+        // Payloads without the discriminator, truncated or of an unknown event are rejected
+        assert!(HookPayload::unpack(&packed[8..]).is_err());
+        assert!(HookPayload::unpack(&packed[..packed.len() - 1]).is_err());
+        let mut unknown_event = packed;
+        unknown_event[8] = 3;
+        assert!(HookPayload::unpack(&unknown_event).is_err());
+        assert_eq!(HookMode::from_u8(2), None);
+    }
+}
@@ -1,12 +1,19 @@
-use crate::error::VestingError;
+use crate::{
+    error::VestingError,
+    hook::{Hook, HookMode},
+    state::LockMode,
+};
 
 use solana_program::{
     instruction::{AccountMeta, Instruction},
     msg,
     program_error::ProgramError,
     pubkey::Pubkey,
+    system_program, sysvar,
 };
+use spl_token::instruction::MAX_SIGNERS;
 
+use shank::ShankInstruction;
 use std::convert::TryInto;
 use std::mem::size_of;
 
@@ -17,7 +24,7 @@ use arbitrary::Arbitrary;
 impl Arbitrary for VestingInstruction {
     fn arbitrary(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<Self> {
         let seeds: [u8; 32] = u.arbitrary()?;
-        let choice = u.choose(&[0, 1, 2, 3])?;
+        let choice = u.choose(&[0, 1, 2, 3, 4])?;
         match choice {
             0 => {
                 return Ok(Self::Init { seeds });
@@ -29,10 +36,15 @@ impl Arbitrary for VestingInstruction {
                 return Ok(Self::Create {
                     seeds,
                     schedule: schedule,
+                    options: CreateOptions::default(),
                 });
             }
             2 => return Ok(Self::Unlock { seeds }),
-            _ => return Ok(Self::InitializeUnlock { seeds }),
+            3 => return Ok(Self::InitializeUnlock { seeds }),
+            _ => {
+                let entries: Vec<BatchEntry> = u.arbitrary()?;
+                return Ok(Self::CreateBatch { entries });
+            }
         }
     }
 }
@@ -48,8 +60,47 @@ pub struct Schedule {
 
 pub const SCHEDULE_SIZE: usize = 16;
 
+/// A lock created by `CreateBatch`, its beneficiary is passed through the accounts
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
 #[repr(C)]
 #[derive(Clone, Debug, PartialEq)]
+pub struct BatchEntry {
+    pub seeds: [u8; 32],
+    pub schedule: Schedule,
+}
+
+pub const BATCH_ENTRY_SIZE: usize = 32 + SCHEDULE_SIZE;
+
+/// Number of accounts passed for every entry of a `CreateBatch` instruction
+pub const BATCH_ENTRY_ACCOUNTS: usize = 5;
+
+/// The optional terms of a `Create` instruction, none of them by default. Each of them may be
+/// omitted from the instruction data along with the ones after it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CreateOptions {
+    // The authority allowed to revoke the lock, if any
+    pub revocation_authority: Option<Pubkey>,
+    // Lamports paid to the account releasing the lock once it matures
+    pub keeper_tip: u64,
+    // Whether the lock renews for another period at each maturity
+    pub auto_renew: bool,
+    // The program invoked after the lifecycle events of the lock, if any
+    pub hook: Option<Hook>,
+    // Merkle proof of the source token account owner against the allowlist root of the config,
+    // instead of its allowlist entry
+    pub allowlist_proof: Option<Vec<[u8; 32]>>,
+    // The wallet credited with the creation of the lock, if any
+    pub referrer: Option<Pubkey>,
+    // Whether the position token of the lock is minted to the beneficiary
+    pub position: bool,
+    // The number of signer accounts of a multisig source token account owner, 0 for a single
+    // owner
+    pub multisig_signers: u8,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq, ShankInstruction)]
+#[rustfmt::skip]
 pub enum VestingInstruction {
     /// Initializes an empty program account for the token_vesting program
     ///
@@ -58,26 +109,98 @@ pub enum VestingInstruction {
     ///   * Single owner
     ///   0. `[]` The system program account
     ///   1. `[]` The sysvar Rent account
-    ///   1. `[signer]` The fee payer account
-    ///   1. `[]` The vesting account
+    ///   2. `[writable, signer]` The fee payer account
+    ///   3. `[writable]` The vesting account
+    #[account(0, name = "system_program", desc = "The system program account")]
+    #[account(1, name = "rent", desc = "The sysvar Rent account")]
+    #[account(2, writable, signer, name = "payer", desc = "The fee payer account")]
+    #[account(3, writable, name = "vesting_account", desc = "The vesting account")]
     Init {
         // The seed used to derive the vesting accounts address
         seeds: [u8; 32],
     },
     /// Creates a new vesting schedule contract
     ///
+    /// The release time is measured with the lock mode of the config, in unix timestamps while the
+    /// config account is not initialized. A keeper tip is paid from lamports transferred to the
+    /// vesting account on top of its rent, before this instruction. A lock with a hook invokes it
+    /// once created. While the config charges a deposit fee, the fee is transferred to its treasury
+    /// and the lock holds the rest of the schedule amount. A lock with a referrer credits it in its
+    /// referrer account, and pays the referral share of the deposit fee to its spl-token account. A
+    /// lock created with its position token mints it to the beneficiary spl-token account owner, as
+    /// `MintPosition`.
+    ///
     /// Accounts expected by this instruction:
     ///
     ///   * Single owner
     ///   0. `[]` The spl-token program account
     ///   1. `[]` The clock sysvar account
-    ///   1. `[writable]` The vesting account
-    ///   2. `[writable]` The vesting spl-token account
-    ///   3. `[signer]` The source spl-token account owner
-    ///   4. `[writable]` The source spl-token account
+    ///   2. `[writable]` The vesting account
+    ///   3. `[writable]` The vesting spl-token account
+    ///   4. `[signer]` The source spl-token account owner
+    ///   5. `[writable]` The source spl-token account
+    ///   6. `[]` The beneficiary spl-token account, receiving the tokens on unlock
+    ///   7. `[]` The config account, which may not be initialized
+    ///   8. `[writable]` The stats account, which may not be initialized
+    ///   9. `[writable]` The position registry of the beneficiary spl-token account owner, which
+    ///      may not be initialized
+    ///   10. `[writable]` The receipt mint
+    ///   11. `[writable]` The receipt spl-token account of the beneficiary spl-token account
+    ///       owner, receiving the receipts of an unlocked stake
+    ///
+    ///   * Followed by, in this order, those of the accounts that apply
+    ///   * `[]` The allowlist entry of the source spl-token account owner, while the allowlist of
+    ///     the config is enabled and no allowlist proof is given
+    ///   * `[writable]` The deposit fee treasury spl-token account of the config, while the config
+    ///     charges a deposit fee
+    ///   * `[writable]` The referrer account, for a lock with a referrer
+    ///   * `[writable]` The referrer spl-token account, receiving the referral share of the
+    ///     deposit fee, for a lock with a referrer while the config charges a deposit fee with a
+    ///     referral share
+    ///   * `[writable]` The position mint, with no supply, no decimals, no freeze authority and
+    ///     the vesting account as mint authority, for a lock created with its position token
+    ///   * `[writable]` The spl-token account of the beneficiary spl-token account owner
+    ///     receiving the position token, for a lock created with its position token
+    ///
+    ///   * Multisignature owner
+    ///   0. `[]` The spl-token program account
+    ///   1. `[]` The clock sysvar account
+    ///   2. `[writable]` The vesting account
+    ///   3. `[writable]` The vesting spl-token account
+    ///   4. `[]` The source spl-token account multisignature owner
+    ///   5. `[writable]` The source spl-token account
+    ///   6. `[]` The beneficiary spl-token account, receiving the tokens on unlock
+    ///   7. `[]` The config account, which may not be initialized
+    ///   8. `[writable]` The stats account, which may not be initialized
+    ///   9. `[writable]` The position registry of the beneficiary spl-token account owner, which
+    ///      may not be initialized
+    ///   10. `[writable]` The receipt mint
+    ///   11. `[writable]` The receipt spl-token account of the beneficiary spl-token account
+    ///       owner, receiving the receipts of an unlocked stake
+    ///
+    ///   * Followed by the same accounts as for a single owner, then
+    ///   * ..M `[signer]` M signer accounts, M being the `multisig_signers` argument
+    ///
+    ///   * Followed by, for a lock with a hook
+    ///   * `[]` The hook program account
+    ///   * `[]` The hook authority of the lock
+    ///   * The accounts forwarded to the hook program
+    #[account(0, name = "token_program", desc = "The spl-token program account")]
+    #[account(1, name = "clock", desc = "The clock sysvar account")]
+    #[account(2, writable, name = "vesting_account", desc = "The vesting account")]
+    #[account(3, writable, name = "vesting_token_account", desc = "The vesting spl-token account")]
+    #[account(4, name = "source_token_account_owner", desc = "The source spl-token account owner, signing unless it is a multisig whose `multisig_signers` signer accounts follow the other accounts")]
+    #[account(5, writable, name = "source_token_account", desc = "The source spl-token account")]
+    #[account(6, name = "beneficiary_token_account", desc = "The beneficiary spl-token account, receiving the tokens on unlock")]
+    #[account(7, name = "config_account", desc = "The config account, which may not be initialized")]
+    #[account(8, writable, name = "stats_account", desc = "The stats account, which may not be initialized")]
+    #[account(9, writable, name = "registry_account", desc = "The position registry of the beneficiary spl-token account owner, which may not be initialized")]
+    #[account(10, writable, name = "receipt_mint", desc = "The receipt mint")]
+    #[account(11, writable, name = "receipt_token_account", desc = "The receipt spl-token account of the beneficiary spl-token account owner, receiving the receipts of an unlocked stake")]
     Create {
         seeds: [u8; 32],
         schedule: Schedule,
+        options: CreateOptions,
     },
     /// Unlocks a simple vesting contract (SVC) - can only be invoked by the program itself
     /// Accounts expected by this instruction:
@@ -85,21 +208,802 @@ pub enum VestingInstruction {
     ///   * Single owner
     ///   0. `[]` The spl-token program account
     ///   1. `[]` The clock sysvar account
-    ///   1. `[writable]` The vesting account
-    ///   2. `[writable]` The vesting spl-token account
-    ///   3. `[writable]` The destination spl-token account
+    ///   2. `[writable]` The vesting account
+    ///   3. `[writable]` The vesting spl-token account
+    ///   4. `[writable]` The destination spl-token account
+    ///   5. `[writable]` The stats account, which may not be initialized
+    ///   6. `[writable]` The position registry of the destination spl-token account owner, which
+    ///      may not be initialized. For a lock with a position token, the position registry of
+    ///      the wallet which minted it.
+    ///
+    ///   * Followed by, for a lock with a position token released to an account of its holder
+    ///   7. `[writable]` The position mint
+    ///   8. `[writable]` The spl-token account holding the position token
+    ///   9. `[signer]` The position token holder, owning the destination spl-token account
+    ///
+    ///   * Optionally followed by
+    ///   7. or 10. `[writable]` The keeper account, receiving the keeper tip of the lock. The
+    ///      owner of the lock signs as the keeper account to leave out a skippable hook.
+    ///
+    ///   * Followed by, for a lock with a hook
+    ///   * `[]` The hook program account, which a lock with a skippable hook may leave out when
+    ///     the position token holder or the owner of the lock signs
+    ///   * `[]` The hook authority of the lock, left out along with the hook program account
+    ///   * The accounts forwarded to the hook program
+    ///
+    ///   * A vesting account of the first layout (49 bytes) only takes accounts 0 to 4, any
+    ///     following account is ignored
+    #[account(0, name = "token_program", desc = "The spl-token program account")]
+    #[account(1, name = "clock", desc = "The clock sysvar account")]
+    #[account(2, writable, name = "vesting_account", desc = "The vesting account")]
+    #[account(3, writable, name = "vesting_token_account", desc = "The vesting spl-token account")]
+    #[account(4, writable, name = "destination_token_account", desc = "The destination spl-token account")]
+    #[account(5, writable, name = "stats_account", desc = "The stats account, which may not be initialized")]
+    #[account(6, writable, name = "registry_account", desc = "The position registry of the destination spl-token account owner, which may not be initialized")]
     Unlock { seeds: [u8; 32] },
 
     /// Initializes the unlocking period - can only be invoked by the program itself
+    /// The owner of the destination spl-token account burns the receipts of the stake.
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[]` The spl-token program account
+    ///   1. `[]` The clock sysvar account
+    ///   2. `[writable]` The vesting account
+    ///   3. `[writable]` The vesting spl-token account
+    ///   4. `[writable]` The destination spl-token account
+    ///   5. `[writable]` The stats account, which may not be initialized
+    ///   6. `[writable]` The receipt mint
+    ///   7. `[writable]` The receipt spl-token account burning the receipts
+    ///   8. `[signer]` The destination spl-token account owner, owning the receipt spl-token account
+    ///
+    ///   * Followed by, for a lock with a position token released to an account of its holder
+    ///   9. `[writable]` The position mint
+    ///   10. `[writable]` The spl-token account holding the position token
+    ///   11. `[signer]` The position token holder, owning the destination spl-token account
+    ///
+    ///   * Followed by, for a lock with a hook
+    ///   * `[]` The hook program account, which a lock with a skippable hook may leave out, as
+    ///     the destination spl-token account owner signs
+    ///   * `[]` The hook authority of the lock, left out along with the hook program account
+    ///   * The accounts forwarded to the hook program
+    ///
+    ///   * A vesting account of the first layout (49 bytes), having no receipts, only takes
+    ///     accounts 0 to 4, any following account is ignored
+    #[account(0, name = "token_program", desc = "The spl-token program account")]
+    #[account(1, name = "clock", desc = "The clock sysvar account")]
+    #[account(2, writable, name = "vesting_account", desc = "The vesting account")]
+    #[account(3, writable, name = "vesting_token_account", desc = "The vesting spl-token account")]
+    #[account(4, writable, name = "destination_token_account", desc = "The destination spl-token account")]
+    #[account(5, writable, name = "stats_account", desc = "The stats account, which may not be initialized")]
+    #[account(6, writable, name = "receipt_mint", desc = "The receipt mint")]
+    #[account(7, writable, name = "receipt_token_account", desc = "The receipt spl-token account burning the receipts")]
+    #[account(8, signer, name = "destination_token_account_owner", desc = "The destination spl-token account owner, owning the receipt spl-token account")]
+    InitializeUnlock { seeds: [u8; 32] },
+
+    /// Creates several vesting accounts and their schedules from the same source, each
+    /// releasing the tokens to its own beneficiary. The vesting spl-token accounts must exist,
+    /// the locks are recorded in the position registries of the beneficiaries which have one.
+    /// While the config charges a deposit fee, the fee is taken from the amount of every entry.
+    ///
     /// Accounts expected by this instruction:
     ///
     ///   * Single owner
+    ///   0. `[]` The system program account
+    ///   1. `[]` The sysvar Rent account
+    ///   2. `[]` The spl-token program account
+    ///   3. `[]` The clock sysvar account
+    ///   4. `[writable, signer]` The fee payer account
+    ///   5. `[signer]` The source spl-token account owner
+    ///   6. `[writable]` The source spl-token account
+    ///   7. `[]` The config account, which may not be initialized
+    ///   8. `[writable]` The stats account, which may not be initialized
+    ///   9. `[writable]` The receipt mint
+    ///   10. ..10+5N The accounts of each of the N entries:
+    ///       * `[writable]` The vesting account
+    ///       * `[writable]` The vesting spl-token account
+    ///       * `[]` The beneficiary spl-token account
+    ///       * `[writable]` The receipt spl-token account of the beneficiary spl-token account
+    ///         owner, receiving the receipts of an unlocked stake
+    ///       * `[writable]` The position registry of the beneficiary spl-token account owner,
+    ///         which may not be initialized
+    ///   * `[writable]` The deposit fee treasury spl-token account of the config, while the config
+    ///     charges a deposit fee
+    ///
+    ///   * Multisignature owner
+    ///   0. `[]` The system program account
+    ///   1. `[]` The sysvar Rent account
+    ///   2. `[]` The spl-token program account
+    ///   3. `[]` The clock sysvar account
+    ///   4. `[writable, signer]` The fee payer account
+    ///   5. `[]` The source spl-token account multisignature owner
+    ///   6. `[writable]` The source spl-token account
+    ///   7. `[]` The config account, which may not be initialized
+    ///   8. `[writable]` The stats account, which may not be initialized
+    ///   9. `[writable]` The receipt mint
+    ///   10. ..10+5N+M The accounts of the N entries and the deposit fee treasury as above,
+    ///       followed by M signer accounts, M being the `multisig_signers` argument
+    #[account(0, name = "system_program", desc = "The system program account")]
+    #[account(1, name = "rent", desc = "The sysvar Rent account")]
+    #[account(2, name = "token_program", desc = "The spl-token program account")]
+    #[account(3, name = "clock", desc = "The clock sysvar account")]
+    #[account(4, writable, signer, name = "payer", desc = "The fee payer account")]
+    #[account(5, name = "source_token_account_owner", desc = "The source spl-token account owner, signing unless it is a multisig")]
+    #[account(6, writable, name = "source_token_account", desc = "The source spl-token account")]
+    #[account(7, name = "config_account", desc = "The config account, which may not be initialized")]
+    #[account(8, writable, name = "stats_account", desc = "The stats account, which may not be initialized")]
+    #[account(9, writable, name = "receipt_mint", desc = "The receipt mint, followed by the vesting, vesting spl-token, beneficiary spl-token, receipt spl-token and position registry accounts of every entry, then the deposit fee treasury while the config charges a deposit fee and the multisig signers")]
+    CreateBatch {
+        entries: Vec<BatchEntry>,
+        // The number of signer accounts of a multisig source token account owner, 0 by default
+        // for a single owner
+        multisig_signers: u8,
+    },
+
+    /// Creates a locked airdrop, whose recipients claim their tokens into locks with
+    /// `ClaimIntoLock`. The vault spl-token account must be owned by the airdrop account, and
+    /// funded by the authority. While the allowlist of the config is enabled, only the admin
+    /// creates airdrops.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[]` The sysvar Rent account
+    ///   2. `[writable, signer]` The airdrop authority, paying for the airdrop account
+    ///   3. `[writable]` The airdrop account
+    ///   4. `[]` The vault spl-token account
+    ///   5. `[]` The config account, which may not be initialized
+    #[account(0, name = "system_program", desc = "The system program account")]
+    #[account(1, name = "rent", desc = "The sysvar Rent account")]
+    #[account(2, writable, signer, name = "authority", desc = "The airdrop authority, paying for the airdrop account")]
+    #[account(3, writable, name = "airdrop_account", desc = "The airdrop account")]
+    #[account(4, name = "vault_token_account", desc = "The vault spl-token account")]
+    #[account(5, name = "config_account", desc = "The config account, which may not be initialized")]
+    CreateAirdrop {
+        // The seeds used to derive the airdrop account address, after the "airdrop" prefix
+        seeds: [u8; 32],
+        merkle_root: [u8; 32],
+    },
+
+    /// Claims an airdrop entry into a new vesting account, funded from the airdrop vault. The
+    /// vesting spl-token account must exist, the lock is recorded in the position registry of the
+    /// destination owner if it has one.
+    /// While the allowlist of the config is enabled, only the airdrops of the admin are claimed.
+    /// While the config charges a deposit fee, the fee is taken from the claimed amount.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[]` The sysvar Rent account
+    ///   2. `[]` The spl-token program account
+    ///   3. `[]` The clock sysvar account
+    ///   4. `[writable, signer]` The claimant wallet, paying for the vesting account
+    ///   5. `[]` The airdrop account
+    ///   6. `[writable]` The vault spl-token account
+    ///   7. `[writable]` The vesting account
+    ///   8. `[writable]` The vesting spl-token account
+    ///   9. `[]` The destination spl-token account
+    ///   10. `[]` The config account, which may not be initialized
+    ///   11. `[writable]` The stats account, which may not be initialized
+    ///   12. `[writable]` The receipt mint
+    ///   13. `[writable]` The receipt spl-token account of the claimant, receiving the receipts
+    ///       of an unlocked stake
+    ///   14. `[writable]` The position registry of the destination spl-token account owner, which
+    ///       may not be initialized
+    ///   15. `[writable]` The deposit fee treasury spl-token account of the config, while the
+    ///       config charges a deposit fee
+    #[account(0, name = "system_program", desc = "The system program account")]
+    #[account(1, name = "rent", desc = "The sysvar Rent account")]
+    #[account(2, name = "token_program", desc = "The spl-token program account")]
+    #[account(3, name = "clock", desc = "The clock sysvar account")]
+    #[account(4, writable, signer, name = "claimant", desc = "The claimant wallet, paying for the vesting account")]
+    #[account(5, name = "airdrop_account", desc = "The airdrop account")]
+    #[account(6, writable, name = "vault_token_account", desc = "The vault spl-token account")]
+    #[account(7, writable, name = "vesting_account", desc = "The vesting account")]
+    #[account(8, writable, name = "vesting_token_account", desc = "The vesting spl-token account")]
+    #[account(9, name = "destination_token_account", desc = "The destination spl-token account")]
+    #[account(10, name = "config_account", desc = "The config account, which may not be initialized")]
+    #[account(11, writable, name = "stats_account", desc = "The stats account, which may not be initialized")]
+    #[account(12, writable, name = "receipt_mint", desc = "The receipt mint")]
+    #[account(13, writable, name = "receipt_token_account", desc = "The receipt spl-token account of the claimant, receiving the receipts of an unlocked stake")]
+    #[account(14, writable, name = "registry_account", desc = "The position registry of the destination spl-token account owner, which may not be initialized")]
+    ClaimIntoLock {
+        airdrop_seeds: [u8; 32],
+        // The seeds of the vesting account, derived from the airdrop and the claimant
+        seeds: [u8; 32],
+        schedule: Schedule,
+        proof: Vec<[u8; 32]>,
+    },
+    /// Revokes a lock created with a revocation authority. The vested tokens are released to the
+    /// destination and the unvested ones are returned to the treasury, after which the lock can
+    /// not be unlocked anymore.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The spl-token program account
+    ///   1. `[]` The clock sysvar account
+    ///   2. `[writable]` The vesting account
+    ///   3. `[writable]` The vesting spl-token account
+    ///   4. `[writable]` The destination spl-token account
+    ///   5. `[signer]` The revocation authority
+    ///   6. `[writable]` The treasury spl-token account, receiving the unvested tokens
+    ///   7. `[writable]` The stats account, which may not be initialized
+    ///   8. `[writable]` The position registry of the destination spl-token account owner, which
+    ///      may not be initialized
+    #[account(0, name = "token_program", desc = "The spl-token program account")]
+    #[account(1, name = "clock", desc = "The clock sysvar account")]
+    #[account(2, writable, name = "vesting_account", desc = "The vesting account")]
+    #[account(3, writable, name = "vesting_token_account", desc = "The vesting spl-token account")]
+    #[account(4, writable, name = "destination_token_account", desc = "The destination spl-token account")]
+    #[account(5, signer, name = "revocation_authority", desc = "The revocation authority")]
+    #[account(6, writable, name = "treasury_token_account", desc = "The treasury spl-token account, receiving the unvested tokens")]
+    #[account(7, writable, name = "stats_account", desc = "The stats account, which may not be initialized")]
+    #[account(8, writable, name = "registry_account", desc = "The position registry of the destination spl-token account owner, which may not be initialized")]
+    Revoke { seeds: [u8; 32] },
+    /// Creates the program config, with the signer as admin. The signer must be the upgrade
+    /// authority of the program. The lock mode of the config is the one of every lock of the
+    /// pool from then on.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[]` The sysvar Rent account
+    ///   2. `[writable, signer]` The admin account, paying for the config account
+    ///   3. `[writable]` The config account
+    ///   4. `[]` The penalty treasury spl-token account
+    ///   5. `[]` The guardian account, allowed to pause the creation of locks
+    ///   6. `[]` The program data account of the program, holding its upgrade authority
+    #[account(0, name = "system_program", desc = "The system program account")]
+    #[account(1, name = "rent", desc = "The sysvar Rent account")]
+    #[account(2, writable, signer, name = "admin", desc = "The admin account, paying for the config account")]
+    #[account(3, writable, name = "config_account", desc = "The config account")]
+    #[account(4, name = "penalty_treasury_token_account", desc = "The penalty treasury spl-token account")]
+    #[account(5, name = "guardian", desc = "The guardian account, allowed to pause the creation of locks")]
+    #[account(6, name = "program_data_account", desc = "The program data account of the program, holding its upgrade authority")]
+    InitConfig {
+        // Penalty charged on a lock released a whole 12 months early, in basis points
+        max_penalty_bps: u16,
+        // The clock measuring the release time of the locks of the pool, unix timestamps by
+        // default
+        lock_mode: LockMode,
+    },
+    /// Updates the early unlock penalty and its treasury
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` The admin account
+    ///   1. `[writable]` The config account
+    ///   2. `[]` The penalty treasury spl-token account
+    #[account(0, signer, name = "admin", desc = "The admin account")]
+    #[account(1, writable, name = "config_account", desc = "The config account")]
+    #[account(2, name = "penalty_treasury_token_account", desc = "The penalty treasury spl-token account")]
+    UpdatePenalty { max_penalty_bps: u16 },
+    /// Releases the tokens of a lock before its release time, minus a penalty sent to the
+    /// penalty treasury. Revocable locks can not be released early.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The spl-token program account
+    ///   1. `[]` The clock sysvar account
+    ///   2. `[writable]` The vesting account
+    ///   3. `[writable]` The vesting spl-token account
+    ///   4. `[writable]` The destination spl-token account
+    ///   5. `[signer]` The destination spl-token account owner
+    ///   6. `[]` The config account
+    ///   7. `[writable]` The penalty treasury spl-token account
+    ///   8. `[writable]` The stats account, which may not be initialized
+    ///   9. `[writable]` The position registry of the destination spl-token account owner, which
+    ///      may not be initialized. For a lock with a position token, the position registry of
+    ///      the wallet which minted it.
+    ///
+    ///   * Followed by, for a lock with a position token released to an account of its holder
+    ///   10. `[writable]` The position mint
+    ///   11. `[writable]` The spl-token account holding the position token
+    ///   12. `[signer]` The position token holder, owning the destination spl-token account
+    #[account(0, name = "token_program", desc = "The spl-token program account")]
+    #[account(1, name = "clock", desc = "The clock sysvar account")]
+    #[account(2, writable, name = "vesting_account", desc = "The vesting account")]
+    #[account(3, writable, name = "vesting_token_account", desc = "The vesting spl-token account")]
+    #[account(4, writable, name = "destination_token_account", desc = "The destination spl-token account")]
+    #[account(5, signer, name = "destination_token_account_owner", desc = "The destination spl-token account owner")]
+    #[account(6, name = "config_account", desc = "The config account")]
+    #[account(7, writable, name = "penalty_treasury_token_account", desc = "The penalty treasury spl-token account")]
+    #[account(8, writable, name = "stats_account", desc = "The stats account, which may not be initialized")]
+    #[account(9, writable, name = "registry_account", desc = "The position registry of the destination spl-token account owner, which may not be initialized")]
+    EarlyUnlock { seeds: [u8; 32] },
+    /// Pauses or resumes the creation of locks
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` The guardian account
+    ///   1. `[writable]` The config account
+    #[account(0, signer, name = "guardian", desc = "The guardian account")]
+    #[account(1, writable, name = "config_account", desc = "The config account")]
+    SetPaused { paused: bool },
+    /// Enters or leaves the emergency mode, in which every lock can be released with
+    /// `EmergencyUnlock` whatever its release time
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` The admin account
+    ///   1. `[writable]` The config account
+    #[account(0, signer, name = "admin", desc = "The admin account")]
+    #[account(1, writable, name = "config_account", desc = "The config account")]
+    SetEmergencyMode { enabled: bool },
+    /// Replaces the guardian of the config
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` The admin account
+    ///   1. `[writable]` The config account
+    ///   2. `[]` The new guardian account
+    #[account(0, signer, name = "admin", desc = "The admin account")]
+    #[account(1, writable, name = "config_account", desc = "The config account")]
+    #[account(2, name = "guardian", desc = "The new guardian account")]
+    SetGuardian,
+    /// Releases all the tokens of a lock to its destination while the program is in emergency
+    /// mode, whatever its release time
+    ///
+    /// Accounts expected by this instruction:
+    ///
     ///   0. `[]` The spl-token program account
     ///   1. `[]` The clock sysvar account
+    ///   2. `[writable]` The vesting account
+    ///   3. `[writable]` The vesting spl-token account
+    ///   4. `[writable]` The destination spl-token account
+    ///   5. `[]` The config account
+    ///   6. `[writable]` The stats account, which may not be initialized
+    ///   7. `[writable]` The position registry of the destination spl-token account owner, which
+    ///      may not be initialized. For a lock with a position token, the position registry of
+    ///      the wallet which minted it.
+    ///
+    ///   * Followed by, for a lock with a position token released to an account of its holder
+    ///   8. `[writable]` The position mint
+    ///   9. `[writable]` The spl-token account holding the position token
+    ///   10. `[signer]` The position token holder, owning the destination spl-token account
+    #[account(0, name = "token_program", desc = "The spl-token program account")]
+    #[account(1, name = "clock", desc = "The clock sysvar account")]
+    #[account(2, writable, name = "vesting_account", desc = "The vesting account")]
+    #[account(3, writable, name = "vesting_token_account", desc = "The vesting spl-token account")]
+    #[account(4, writable, name = "destination_token_account", desc = "The destination spl-token account")]
+    #[account(5, name = "config_account", desc = "The config account")]
+    #[account(6, writable, name = "stats_account", desc = "The stats account, which may not be initialized")]
+    #[account(7, writable, name = "registry_account", desc = "The position registry of the destination spl-token account owner, which may not be initialized")]
+    EmergencyUnlock { seeds: [u8; 32] },
+    /// Creates the stats account, counting the locks created from then on. Until it exists,
+    /// locks are not counted, and the pool can not be capped.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[]` The sysvar Rent account
+    ///   2. `[writable, signer]` The fee payer account
+    ///   3. `[writable]` The stats account
+    #[account(0, name = "system_program", desc = "The system program account")]
+    #[account(1, name = "rent", desc = "The sysvar Rent account")]
+    #[account(2, writable, signer, name = "payer", desc = "The fee payer account")]
+    #[account(3, writable, name = "stats_account", desc = "The stats account")]
+    InitStats,
+    /// Returns the stats account data through the return data of the transaction, without
+    /// modifying any account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The stats account
+    #[account(0, name = "stats_account", desc = "The stats account")]
+    GetStats,
+    /// Creates the position registry of a wallet, listing the locks created for its token
+    /// accounts. Does nothing if the registry already exists.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[]` The sysvar Rent account
+    ///   2. `[writable, signer]` The fee payer account
+    ///   3. `[]` The owner of the registry
+    ///   4. `[writable]` The position registry account
+    #[account(0, name = "system_program", desc = "The system program account")]
+    #[account(1, name = "rent", desc = "The sysvar Rent account")]
+    #[account(2, writable, signer, name = "payer", desc = "The fee payer account")]
+    #[account(3, name = "owner", desc = "The owner of the registry")]
+    #[account(4, writable, name = "registry_account", desc = "The position registry account")]
+    InitRegistry,
+    /// Closes a lock whose tokens have all been released or revoked, removing it from the
+    /// position registry. The rent of the vesting account and of the vesting spl-token account
+    /// goes to the owner of the destination spl-token account, who signs.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The spl-token program account
     ///   1. `[writable]` The vesting account
     ///   2. `[writable]` The vesting spl-token account
-    ///   3. `[writable]` The destination spl-token account
-    InitializeUnlock { seeds: [u8; 32] },
+    ///   3. `[]` The destination spl-token account
+    ///   4. `[writable, signer]` The destination spl-token account owner
+    ///   5. `[writable]` The position registry of the owner, which may not be initialized
+    #[account(0, name = "token_program", desc = "The spl-token program account")]
+    #[account(1, writable, name = "vesting_account", desc = "The vesting account")]
+    #[account(2, writable, name = "vesting_token_account", desc = "The vesting spl-token account")]
+    #[account(3, name = "destination_token_account", desc = "The destination spl-token account")]
+    #[account(4, writable, signer, name = "destination_token_account_owner", desc = "The destination spl-token account owner")]
+    #[account(5, writable, name = "registry_account", desc = "The position registry of the owner, which may not be initialized")]
+    Close { seeds: [u8; 32] },
+    /// Mints the position token of a lock to a spl-token account, then removes the mint
+    /// authority. From then on, the lock is released to the holder of the token, who signs, and
+    /// the token is burnt when the lock is released. Revocable locks can not have a position
+    /// token. The lock stays in the position registry of the owner. As only the holder releases
+    /// the lock, `UnlockMany` skips it and its keeper tip goes to the keeper account the holder
+    /// passes to `Unlock`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The spl-token program account
+    ///   1. `[writable]` The vesting account
+    ///   2. `[]` The destination spl-token account
+    ///   3. `[signer]` The destination spl-token account owner
+    ///   4. `[writable]` The position mint, with no supply, no decimals, no freeze authority and
+    ///      the vesting account as mint authority
+    ///   5. `[writable]` The spl-token account receiving the position token
+    #[account(0, name = "token_program", desc = "The spl-token program account")]
+    #[account(1, writable, name = "vesting_account", desc = "The vesting account")]
+    #[account(2, name = "destination_token_account", desc = "The destination spl-token account")]
+    #[account(3, signer, name = "destination_token_account_owner", desc = "The destination spl-token account owner")]
+    #[account(4, writable, name = "position_mint", desc = "The position mint, with no supply, no decimals, no freeze authority and the vesting account as mint authority")]
+    #[account(5, writable, name = "position_token_account", desc = "The spl-token account receiving the position token")]
+    MintPosition { seeds: [u8; 32] },
+    /// Creates the receipt mint, minting receipts 1:1 with the amount staked in unlocked stakes
+    /// and burning them when their withdrawal is initialized. The mint is its own mint
+    /// authority and has the decimals of the locked token. It must exist before any lock is
+    /// created.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[]` The sysvar Rent account
+    ///   2. `[]` The spl-token program account
+    ///   3. `[writable, signer]` The fee payer account
+    ///   4. `[writable]` The receipt mint
+    ///   5. `[]` The locked token mint
+    #[account(0, name = "system_program", desc = "The system program account")]
+    #[account(1, name = "rent", desc = "The sysvar Rent account")]
+    #[account(2, name = "token_program", desc = "The spl-token program account")]
+    #[account(3, writable, signer, name = "payer", desc = "The fee payer account")]
+    #[account(4, writable, name = "receipt_mint", desc = "The receipt mint")]
+    #[account(5, name = "token_mint", desc = "The locked token mint")]
+    InitReceiptMint,
+    /// Splits an amount off a lock into a new lock with the same tier, lock mode, release time
+    /// and revocation authority, released to the same or another destination. The tokens move
+    /// between the vesting spl-token accounts, the new one must exist. The split is signed by the
+    /// owner of the lock, and the new lock belongs to the owner of its destination. The receipts
+    /// of an unlocked stake stay with their holder, so an unlocked stake is only split to another
+    /// wallet once its withdrawal is initialized. Both locks keep the minimum of their tier, and
+    /// the new lock moves within the wallet cap of its new owner.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[]` The sysvar Rent account
+    ///   2. `[]` The spl-token program account
+    ///   3. `[writable, signer]` The fee payer account
+    ///   4. `[writable]` The vesting account
+    ///   5. `[writable]` The vesting spl-token account
+    ///   6. `[writable]` The new vesting account
+    ///   7. `[writable]` The new vesting spl-token account
+    ///   8. `[]` The destination spl-token account
+    ///   9. `[signer]` The owner of the lock
+    ///   10. `[]` The destination spl-token account of the new lock
+    ///   11. `[writable]` The stats account, which may not be initialized
+    ///   12. `[writable]` The position registry of the owner, which may not be initialized
+    ///   13. `[writable]` The position registry of the owner of the new destination, the same
+    ///       account as the position registry of the owner for a split within a wallet
+    ///   14. `[]` The config account, which may not be initialized
+    #[account(0, name = "system_program", desc = "The system program account")]
+    #[account(1, name = "rent", desc = "The sysvar Rent account")]
+    #[account(2, name = "token_program", desc = "The spl-token program account")]
+    #[account(3, writable, signer, name = "payer", desc = "The fee payer account")]
+    #[account(4, writable, name = "vesting_account", desc = "The vesting account")]
+    #[account(5, writable, name = "vesting_token_account", desc = "The vesting spl-token account")]
+    #[account(6, writable, name = "new_vesting_account", desc = "The new vesting account")]
+    #[account(7, writable, name = "new_vesting_token_account", desc = "The new vesting spl-token account")]
+    #[account(8, name = "destination_token_account", desc = "The destination spl-token account")]
+    #[account(9, signer, name = "owner", desc = "The owner of the lock")]
+    #[account(10, name = "new_destination_token_account", desc = "The destination spl-token account of the new lock")]
+    #[account(11, writable, name = "stats_account", desc = "The stats account, which may not be initialized")]
+    #[account(12, writable, name = "registry_account", desc = "The position registry of the owner, which may not be initialized")]
+    #[account(13, writable, name = "new_registry_account", desc = "The position registry of the owner of the new destination")]
+    #[account(14, name = "config_account", desc = "The config account, which may not be initialized")]
+    Split {
+        seeds: [u8; 32],
+        // The seeds of the new vesting account
+        new_seeds: [u8; 32],
+        amount: u64,
+    },
+    /// Merges locks into a lock with the same destination, tier, lock mode, release time and
+    /// revocation authority. Their tokens move to its vesting spl-token account, then their
+    /// accounts are closed and removed from the position registry, their rent going to the
+    /// owner of the destination spl-token account, who signs.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The spl-token program account
+    ///   1. `[writable]` The vesting account
+    ///   2. `[writable]` The vesting spl-token account
+    ///   3. `[]` The destination spl-token account
+    ///   4. `[writable, signer]` The destination spl-token account owner
+    ///   5. `[writable]` The stats account, which may not be initialized
+    ///   6. `[writable]` The position registry of the owner, which may not be initialized
+    ///   7. ..7+2N The accounts of each of the N merged locks:
+    ///      * `[writable]` The vesting account
+    ///      * `[writable]` The vesting spl-token account
+    #[account(0, name = "token_program", desc = "The spl-token program account")]
+    #[account(1, writable, name = "vesting_account", desc = "The vesting account")]
+    #[account(2, writable, name = "vesting_token_account", desc = "The vesting spl-token account")]
+    #[account(3, name = "destination_token_account", desc = "The destination spl-token account")]
+    #[account(4, writable, signer, name = "destination_token_account_owner", desc = "The destination spl-token account owner")]
+    #[account(5, writable, name = "stats_account", desc = "The stats account, which may not be initialized")]
+    #[account(6, writable, name = "registry_account", desc = "The position registry of the owner, which may not be initialized, followed by the vesting and vesting spl-token accounts of every merged lock")]
+    Merge {
+        seeds: [u8; 32],
+        // The seeds of the vesting accounts of the merged locks
+        source_seeds: Vec<[u8; 32]>,
+    },
+    /// Transfers a lock to a spl-token account of another wallet, signed by the owner of the
+    /// lock. The owner of the new destination becomes the owner of the lock, which moves to their
+    /// position registry, within the minimum of its tier and the wallet cap of the config. With
+    /// `require_accept`, the transfer only takes effect once the new owner accepts it with
+    /// `AcceptPosition`, and the registries are left unchanged. A transfer to the destination of
+    /// the lock cancels a pending transfer. Locks with a position token are transferred with
+    /// their token, and unlocked stakes once their withdrawal is initialized, their receipts
+    /// staying with their holder.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The vesting account
+    ///   1. `[]` The destination spl-token account
+    ///   2. `[signer]` The owner of the lock
+    ///   3. `[writable]` The position registry of the owner, which may not be initialized
+    ///   4. `[]` The new destination spl-token account
+    ///   5. `[writable]` The position registry of the owner of the new destination, which may
+    ///      only be left uninitialized with `require_accept`
+    ///   6. `[]` The config account, which may not be initialized
+    #[account(0, writable, name = "vesting_account", desc = "The vesting account")]
+    #[account(1, name = "destination_token_account", desc = "The destination spl-token account")]
+    #[account(2, signer, name = "owner", desc = "The owner of the lock")]
+    #[account(3, writable, name = "registry_account", desc = "The position registry of the owner, which may not be initialized")]
+    #[account(4, name = "new_destination_token_account", desc = "The new destination spl-token account")]
+    #[account(5, writable, name = "new_registry_account", desc = "The position registry of the owner of the new destination, which may only be left uninitialized with require_accept")]
+    #[account(6, name = "config_account", desc = "The config account, which may not be initialized")]
+    TransferPosition {
+        seeds: [u8; 32],
+        // Whether the owner of the new destination has to accept the transfer
+        require_accept: bool,
+    },
+    /// Accepts the pending transfer of a lock, signed by the owner of the spl-token account it
+    /// is transferred to, who becomes the owner of the lock. The lock moves to the position
+    /// registry of the new owner, within the minimum of its tier and the wallet cap of the config.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The vesting account
+    ///   1. `[]` The destination spl-token account
+    ///   2. `[writable]` The position registry of the owner of the lock, which may not be
+    ///      initialized
+    ///   3. `[]` The new destination spl-token account, the pending destination of the lock
+    ///   4. `[signer]` The new destination spl-token account owner
+    ///   5. `[writable]` The position registry of the new owner
+    ///   6. `[]` The config account, which may not be initialized
+    #[account(0, writable, name = "vesting_account", desc = "The vesting account")]
+    #[account(1, name = "destination_token_account", desc = "The destination spl-token account")]
+    #[account(2, writable, name = "registry_account", desc = "The position registry of the owner of the lock, which may not be initialized")]
+    #[account(3, name = "new_destination_token_account", desc = "The new destination spl-token account, the pending destination of the lock")]
+    #[account(4, signer, name = "new_destination_token_account_owner", desc = "The new destination spl-token account owner")]
+    #[account(5, writable, name = "new_registry_account", desc = "The position registry of the new owner")]
+    #[account(6, name = "config_account", desc = "The config account, which may not be initialized")]
+    AcceptPosition { seeds: [u8; 32] },
+    /// Releases every matured lock of a list to its destination, like `Unlock`. Locks that can
+    /// not be released yet, were already released or revoked, or have a position token or a hook
+    /// are skipped. The keeper tips of the released locks are paid to the keeper account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The spl-token program account
+    ///   1. `[]` The clock sysvar account
+    ///   2. `[writable]` The stats account, which may not be initialized
+    ///   3. `[writable]` The keeper account, receiving the keeper tips of the released locks
+    ///   4. ..4+4N The accounts of each of the N locks:
+    ///      * `[writable]` The vesting account
+    ///      * `[writable]` The vesting spl-token account
+    ///      * `[writable]` The destination spl-token account
+    ///      * `[writable]` The position registry of the destination spl-token account owner,
+    ///        which may not be initialized
+    #[account(0, name = "token_program", desc = "The spl-token program account")]
+    #[account(1, name = "clock", desc = "The clock sysvar account")]
+    #[account(2, writable, name = "stats_account", desc = "The stats account, which may not be initialized")]
+    #[account(3, writable, name = "keeper_account", desc = "The keeper account, receiving the keeper tips of the released locks, followed by the vesting, vesting spl-token, destination spl-token and position registry accounts of every lock")]
+    UnlockMany {
+        // The seeds of the vesting accounts of the locks
+        seeds: Vec<[u8; 32]>,
+    },
+    /// Stops the renewal of an auto-renewing lock, which is then released at its next
+    /// maturity. The owner of the destination spl-token account signs during the renewal notice
+    /// period preceding that maturity, or during the grace period following the last maturity,
+    /// the lock being then released at the last maturity.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The clock sysvar account
+    ///   1. `[writable]` The vesting account
+    ///   2. `[]` The destination spl-token account
+    ///   3. `[signer]` The destination spl-token account owner
+    ///
+    ///   * Followed by, for a lock with a position token, the accounts of its holder, owning the
+    ///     destination spl-token account
+    ///   4. `[]` The position mint
+    ///   5. `[]` The spl-token account holding the position token
+    ///   6. `[signer]` The position token holder
+    #[account(0, name = "clock", desc = "The clock sysvar account")]
+    #[account(1, writable, name = "vesting_account", desc = "The vesting account")]
+    #[account(2, name = "destination_token_account", desc = "The destination spl-token account")]
+    #[account(3, signer, name = "destination_token_account_owner", desc = "The destination spl-token account owner")]
+    OptOutRenewal { seeds: [u8; 32] },
+    /// Enables or disables the allowlist restricting `Create` to the wallets with an allowlist
+    /// entry or proven against the allowlist root, and replaces the root
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` The admin account
+    ///   1. `[writable]` The config account
+    #[account(0, signer, name = "admin", desc = "The admin account")]
+    #[account(1, writable, name = "config_account", desc = "The config account")]
+    SetAllowlist {
+        is_enabled: bool,
+        // Merkle root of the allowlisted wallets, all zeros for none
+        root: [u8; 32],
+    },
+    /// Creates the allowlist entry of a wallet
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[]` The sysvar Rent account
+    ///   2. `[writable, signer]` The fee payer account
+    ///   3. `[signer]` The admin account
+    ///   4. `[]` The config account
+    ///   5. `[]` The allowlisted wallet
+    ///   6. `[writable]` The allowlist entry account
+    #[account(0, name = "system_program", desc = "The system program account")]
+    #[account(1, name = "rent", desc = "The sysvar Rent account")]
+    #[account(2, writable, signer, name = "payer", desc = "The fee payer account")]
+    #[account(3, signer, name = "admin", desc = "The admin account")]
+    #[account(4, name = "config_account", desc = "The config account")]
+    #[account(5, name = "wallet", desc = "The allowlisted wallet")]
+    #[account(6, writable, name = "allowlist_entry", desc = "The allowlist entry account")]
+    AddAllowlistEntry,
+    /// Closes the allowlist entry of a wallet, sending its rent to the recipient account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` The admin account
+    ///   1. `[]` The config account
+    ///   2. `[writable]` The allowlist entry account
+    ///   3. `[writable]` The recipient of the rent
+    #[account(0, signer, name = "admin", desc = "The admin account")]
+    #[account(1, name = "config_account", desc = "The config account")]
+    #[account(2, writable, name = "allowlist_entry", desc = "The allowlist entry account")]
+    #[account(3, writable, name = "recipient", desc = "The recipient of the rent")]
+    RemoveAllowlistEntry,
+    /// Sets the caps on the amounts held by all the locks and by the positions of a wallet, and
+    /// the minimum amount of a new lock of each tier
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` The admin account
+    ///   1. `[writable]` The config account
+    #[account(0, signer, name = "admin", desc = "The admin account")]
+    #[account(1, writable, name = "config_account", desc = "The config account")]
+    SetLimits {
+        // Maximum amount held by all the locks, 0 for no cap
+        max_total_locked: u64,
+        // Maximum amount of the positions of a wallet, 0 for no cap
+        max_locked_per_wallet: u64,
+        // Minimum amount of a new lock, by tier
+        min_lock_amounts: [u64; 5],
+    },
+    /// Sets the fee taken from the amount of new locks and its treasury
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` The admin account
+    ///   1. `[writable]` The config account
+    ///   2. `[]` The deposit fee treasury spl-token account
+    #[account(0, signer, name = "admin", desc = "The admin account")]
+    #[account(1, writable, name = "config_account", desc = "The config account")]
+    #[account(2, name = "deposit_fee_treasury_token_account", desc = "The deposit fee treasury spl-token account")]
+    SetDepositFee {
+        // Fee taken from the amount of a new lock, in basis points
+        deposit_fee_bps: u16,
+    },
+    /// Creates the referrer account of a wallet, crediting it with the locks it refers. Does
+    /// nothing if the referrer account already exists.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` The system program account
+    ///   1. `[]` The sysvar Rent account
+    ///   2. `[writable, signer]` The fee payer account
+    ///   3. `[]` The referrer
+    ///   4. `[writable]` The referrer account
+    #[account(0, name = "system_program", desc = "The system program account")]
+    #[account(1, name = "rent", desc = "The sysvar Rent account")]
+    #[account(2, writable, signer, name = "payer", desc = "The fee payer account")]
+    #[account(3, name = "referrer", desc = "The referrer")]
+    #[account(4, writable, name = "referrer_account", desc = "The referrer account")]
+    InitReferrer,
+    /// Sets the share of the deposit fee of a new lock paid to its referrer
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` The admin account
+    ///   1. `[writable]` The config account
+    #[account(0, signer, name = "admin", desc = "The admin account")]
+    #[account(1, writable, name = "config_account", desc = "The config account")]
+    SetReferralShare {
+        // Share of the deposit fee paid to the referrer, in basis points of the fee
+        referral_share_bps: u16,
+    },
+}
+
+/// The referrer of a `Create` instruction
+#[derive(Clone, Debug, PartialEq)]
+pub struct Referral {
+    pub referrer: Pubkey,
+    /// The referrer account of the referrer
+    pub referrer_account: Pubkey,
+    /// The spl-token account of the referrer receiving the referral share of the deposit fee,
+    /// only while the config charges a deposit fee with a referral share
+    pub referrer_token_account: Option<Pubkey>,
+}
+
+/// The position token minted by a `Create` instruction
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    /// The position mint, with no supply, no decimals, no freeze authority and the vesting
+    /// account as mint authority
+    pub mint: Pubkey,
+    /// The spl-token account of the beneficiary spl-token account owner receiving the token
+    pub token_account: Pubkey,
+}
+
+/// How the source token account owner of a `Create` instruction proves that it is allowlisted,
+/// while the allowlist of the config is enabled
+#[derive(Clone, Debug, PartialEq)]
+pub enum AllowlistAccess {
+    /// The allowlist entry account of the owner
+    Entry(Pubkey),
+    /// A Merkle proof of the owner against the allowlist root of the config
+    Proof(Vec<[u8; 32]>),
+}
+
+/// The optional terms and accounts of a `Create` instruction, none of them by default
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CreateArgs {
+    /// The authority allowed to revoke the lock
+    pub revocation_authority: Option<Pubkey>,
+    /// Lamports paid to the account releasing the lock once it matures, transferred to the
+    /// vesting account before the instruction
+    pub keeper_tip: u64,
+    /// Whether the lock renews for another period at each maturity
+    pub auto_renew: bool,
+    /// The program invoked after the lifecycle events of the lock
+    pub hook: Option<Hook>,
+    /// How the source spl-token account owner proves that it is allowlisted
+    pub allowlist: Option<AllowlistAccess>,
+    /// The deposit fee treasury spl-token account of the config, while the config charges a
+    /// deposit fee
+    pub deposit_fee_treasury_token_account_key: Option<Pubkey>,
+    /// The referrer credited with the lock
+    pub referral: Option<Referral>,
+    /// The position token minted to the beneficiary
+    pub position: Option<Position>,
 }
 
 impl VestingInstruction {
@@ -131,9 +1035,178 @@ impl VestingInstruction {
                     .map(u64::from_le_bytes)
                     .ok_or(InvalidInstruction)?;
                 let schedule = Schedule { time_delta, amount };
+                // The revocation authority is optional, and may be omitted altogether
+                let (revocation_authority, offset) = match rest.get(offset + 16) {
+                    None | Some(0) => (None, offset + 17),
+                    Some(1) => (
+                        Some(
+                            rest.get(offset + 17..offset + 49)
+                                .and_then(|slice| slice.try_into().ok())
+                                .map(Pubkey::new_from_array)
+                                .ok_or(InvalidInstruction)?,
+                        ),
+                        offset + 49,
+                    ),
+                    Some(_) => return Err(InvalidInstruction.into()),
+                };
+                let keeper_tip = match rest.get(offset..) {
+                    None | Some([]) => 0,
+                    Some(keeper_tip) => keeper_tip
+                        .get(..8)
+                        .and_then(|slice| slice.try_into().ok())
+                        .map(u64::from_le_bytes)
+                        .ok_or(InvalidInstruction)?,
+                };
+                let auto_renew = match rest.get(offset + 8) {
+                    None | Some(0) => false,
+                    Some(1) => true,
+                    Some(_) => return Err(InvalidInstruction.into()),
+                };
+                let hook = match rest.get(offset + 9) {
+                    None | Some(0) => None,
+                    Some(1) => Some(Hook {
+                        program_id: rest
+                            .get(offset + 10..offset + 42)
+                            .and_then(|slice| slice.try_into().ok())
+                            .map(Pubkey::new_from_array)
+                            .ok_or(InvalidInstruction)?,
+                        mode: rest
+                            .get(offset + 42)
+                            .and_then(|&mode| HookMode::from_u8(mode))
+                            .ok_or(InvalidInstruction)?,
+                    }),
+                    Some(_) => return Err(InvalidInstruction.into()),
+                };
+                let offset = offset + if hook.is_some() { 43 } else { 10 };
+                let (allowlist_proof, offset) = match rest.get(offset) {
+                    None | Some(0) => (None, offset + 1),
+                    Some(1) => {
+                        let count = rest
+                            .get(offset + 1..offset + 5)
+                            .and_then(|slice| slice.try_into().ok())
+                            .map(u32::from_le_bytes)
+                            .ok_or(InvalidInstruction)? as usize;
+                        (
+                            Some(
+                                rest.get(offset + 5..offset + 5 + count * 32)
+                                    .ok_or(InvalidInstruction)?
+                                    .chunks_exact(32)
+                                    .map(|node| node.try_into().unwrap())
+                                    .collect(),
+                            ),
+                            offset + 5 + count * 32,
+                        )
+                    }
+                    Some(_) => return Err(InvalidInstruction.into()),
+                };
+                let referrer = match rest.get(offset) {
+                    None | Some(0) => None,
+                    Some(1) => Some(
+                        rest.get(offset + 1..offset + 33)
+                            .and_then(|slice| slice.try_into().ok())
+                            .map(Pubkey::new_from_array)
+                            .ok_or(InvalidInstruction)?,
+                    ),
+                    Some(_) => return Err(InvalidInstruction.into()),
+                };
+                let offset = offset + if referrer.is_some() { 33 } else { 1 };
+                let position = match rest.get(offset) {
+                    None | Some(0) => false,
+                    Some(1) => true,
+                    Some(_) => return Err(InvalidInstruction.into()),
+                };
+                let multisig_signers = rest.get(offset + 1).copied().unwrap_or(0);
                 Self::Create {
                     seeds,
                     schedule,
+                    options: CreateOptions {
+                        revocation_authority,
+                        keeper_tip,
+                        auto_renew,
+                        hook,
+                        allowlist_proof,
+                        referrer,
+                        position,
+                        multisig_signers,
+                    },
+                }
+            }
+            4 => {
+                let count = rest
+                    .get(..4)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u32::from_le_bytes)
+                    .ok_or(InvalidInstruction)? as usize;
+                let entries_data = rest
+                    .get(4..4 + count * BATCH_ENTRY_SIZE)
+                    .ok_or(InvalidInstruction)?;
+                let entries = entries_data
+                    .chunks_exact(BATCH_ENTRY_SIZE)
+                    .map(|entry| {
+                        let seeds: [u8; 32] = entry[..32].try_into().unwrap();
+                        let time_delta = u64::from_le_bytes(entry[32..40].try_into().unwrap());
+                        let amount = u64::from_le_bytes(entry[40..48].try_into().unwrap());
+                        BatchEntry {
+                            seeds,
+                            schedule: Schedule { time_delta, amount },
+                        }
+                    })
+                    .collect();
+                let multisig_signers = rest
+                    .get(4 + count * BATCH_ENTRY_SIZE)
+                    .copied()
+                    .unwrap_or(0);
+                Self::CreateBatch {
+                    entries,
+                    multisig_signers,
+                }
+            }
+            5 => {
+                let seeds: [u8; 32] = rest
+                    .get(..32)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(InvalidInstruction)?;
+                let merkle_root: [u8; 32] = rest
+                    .get(32..64)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(InvalidInstruction)?;
+                Self::CreateAirdrop { seeds, merkle_root }
+            }
+            6 => {
+                let airdrop_seeds: [u8; 32] = rest
+                    .get(..32)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(InvalidInstruction)?;
+                let seeds: [u8; 32] = rest
+                    .get(32..64)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(InvalidInstruction)?;
+                let time_delta = rest
+                    .get(64..72)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+                let amount = rest
+                    .get(72..80)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+                let count = rest
+                    .get(80..84)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u32::from_le_bytes)
+                    .ok_or(InvalidInstruction)? as usize;
+                let proof = rest
+                    .get(84..84 + count * 32)
+                    .ok_or(InvalidInstruction)?
+                    .chunks_exact(32)
+                    .map(|node| node.try_into().unwrap())
+                    .collect();
+                Self::ClaimIntoLock {
+                    airdrop_seeds,
+                    seeds,
+                    schedule: Schedule { time_delta, amount },
+                    proof,
                 }
             }
             2 | 3 => {
@@ -146,30 +1219,231 @@ impl VestingInstruction {
                     _ => Self::InitializeUnlock { seeds },
                 }
             }
-            _ => {
-                msg!("Unsupported tag");
-                return Err(InvalidInstruction.into());
+            7 | 10 | 14 | 18 | 19 | 24 | 26 => {
+                let seeds: [u8; 32] = rest
+                    .get(..32)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(InvalidInstruction)?;
+                match tag {
+                    7 => Self::Revoke { seeds },
+                    10 => Self::EarlyUnlock { seeds },
+                    14 => Self::EmergencyUnlock { seeds },
+                    18 => Self::Close { seeds },
+                    19 => Self::MintPosition { seeds },
+                    24 => Self::AcceptPosition { seeds },
+                    _ => Self::OptOutRenewal { seeds },
+                }
             }
-        })
-    }
-
-    pub fn pack(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(size_of::<Self>());
-        match self {
-            &Self::Init { seeds } => {
-                buf.push(0);
-                buf.extend_from_slice(&seeds);
+            8 | 9 | 31 | 33 => {
+                let bps = rest
+                    .get(..2)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u16::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+                match tag {
+                    8 => Self::InitConfig {
+                        max_penalty_bps: bps,
+                        lock_mode: match rest.get(2) {
+                            None => LockMode::Timestamp,
+                            Some(&mode) => LockMode::from_u8(mode).ok_or(InvalidInstruction)?,
+                        },
+                    },
+                    9 => Self::UpdatePenalty {
+                        max_penalty_bps: bps,
+                    },
+                    31 => Self::SetDepositFee {
+                        deposit_fee_bps: bps,
+                    },
+                    _ => Self::SetReferralShare {
+                        referral_share_bps: bps,
+                    },
+                }
             }
-            Self::Create {
-                seeds,
-                schedule,
-            } => {
-                buf.push(1);
-                buf.extend_from_slice(seeds);
-                buf.extend_from_slice(&schedule.time_delta.to_le_bytes());
-                buf.extend_from_slice(&schedule.amount.to_le_bytes());
+            11 | 12 => {
+                let flag = match rest.first() {
+                    Some(0) => false,
+                    Some(1) => true,
+                    _ => return Err(InvalidInstruction.into()),
+                };
+                match tag {
+                    11 => Self::SetPaused { paused: flag },
+                    _ => Self::SetEmergencyMode { enabled: flag },
+                }
             }
-            &Self::Unlock { seeds } => {
+            13 => Self::SetGuardian,
+            27 => {
+                let is_enabled = match rest.first() {
+                    Some(0) => false,
+                    Some(1) => true,
+                    _ => return Err(InvalidInstruction.into()),
+                };
+                let root: [u8; 32] = rest
+                    .get(1..33)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(InvalidInstruction)?;
+                Self::SetAllowlist { is_enabled, root }
+            }
+            28 => Self::AddAllowlistEntry,
+            29 => Self::RemoveAllowlistEntry,
+            30 => {
+                let amounts: Vec<u64> = rest
+                    .get(..56)
+                    .ok_or(InvalidInstruction)?
+                    .chunks_exact(8)
+                    .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+                    .collect();
+                Self::SetLimits {
+                    max_total_locked: amounts[0],
+                    max_locked_per_wallet: amounts[1],
+                    min_lock_amounts: amounts[2..].try_into().unwrap(),
+                }
+            }
+            15 => Self::InitStats,
+            16 => Self::GetStats,
+            17 => Self::InitRegistry,
+            32 => Self::InitReferrer,
+            20 => Self::InitReceiptMint,
+            21 => {
+                let seeds: [u8; 32] = rest
+                    .get(..32)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(InvalidInstruction)?;
+                let new_seeds: [u8; 32] = rest
+                    .get(32..64)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(InvalidInstruction)?;
+                let amount = rest
+                    .get(64..72)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u64::from_le_bytes)
+                    .ok_or(InvalidInstruction)?;
+                Self::Split {
+                    seeds,
+                    new_seeds,
+                    amount,
+                }
+            }
+            22 => {
+                let seeds: [u8; 32] = rest
+                    .get(..32)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(InvalidInstruction)?;
+                let count = rest
+                    .get(32..36)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u32::from_le_bytes)
+                    .ok_or(InvalidInstruction)? as usize;
+                let source_seeds = rest
+                    .get(36..36 + count * 32)
+                    .ok_or(InvalidInstruction)?
+                    .chunks_exact(32)
+                    .map(|seeds| seeds.try_into().unwrap())
+                    .collect();
+                Self::Merge {
+                    seeds,
+                    source_seeds,
+                }
+            }
+            25 => {
+                let count = rest
+                    .get(..4)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(u32::from_le_bytes)
+                    .ok_or(InvalidInstruction)? as usize;
+                let seeds = rest
+                    .get(4..4 + count * 32)
+                    .ok_or(InvalidInstruction)?
+                    .chunks_exact(32)
+                    .map(|seeds| seeds.try_into().unwrap())
+                    .collect();
+                Self::UnlockMany { seeds }
+            }
+            23 => {
+                let seeds: [u8; 32] = rest
+                    .get(..32)
+                    .and_then(|slice| slice.try_into().ok())
+                    .ok_or(InvalidInstruction)?;
+                let require_accept = match rest.get(32) {
+                    Some(0) => false,
+                    Some(1) => true,
+                    _ => return Err(InvalidInstruction.into()),
+                };
+                Self::TransferPosition {
+                    seeds,
+                    require_accept,
+                }
+            }
+            _ => {
+                msg!("Unsupported tag");
+                return Err(InvalidInstruction.into());
+            }
+        })
+    }
+
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match self {
+            &Self::Init { seeds } => {
+                buf.push(0);
+                buf.extend_from_slice(&seeds);
+            }
+            Self::Create {
+                seeds,
+                schedule,
+                options:
+                    CreateOptions {
+                        revocation_authority,
+                        keeper_tip,
+                        auto_renew,
+                        hook,
+                        allowlist_proof,
+                        referrer,
+                        position,
+                        multisig_signers,
+                    },
+            } => {
+                buf.push(1);
+                buf.extend_from_slice(seeds);
+                buf.extend_from_slice(&schedule.time_delta.to_le_bytes());
+                buf.extend_from_slice(&schedule.amount.to_le_bytes());
+                match revocation_authority {
+                    Some(revocation_authority) => {
+                        buf.push(1);
+                        buf.extend_from_slice(revocation_authority.as_ref());
+                    }
+                    None => buf.push(0),
+                }
+                buf.extend_from_slice(&keeper_tip.to_le_bytes());
+                buf.push(*auto_renew as u8);
+                match hook {
+                    Some(hook) => {
+                        buf.push(1);
+                        buf.extend_from_slice(hook.program_id.as_ref());
+                        buf.push(hook.mode as u8);
+                    }
+                    None => buf.push(0),
+                }
+                match allowlist_proof {
+                    Some(proof) => {
+                        buf.push(1);
+                        buf.extend_from_slice(&(proof.len() as u32).to_le_bytes());
+                        for node in proof {
+                            buf.extend_from_slice(node);
+                        }
+                    }
+                    None => buf.push(0),
+                }
+                match referrer {
+                    Some(referrer) => {
+                        buf.push(1);
+                        buf.extend_from_slice(referrer.as_ref());
+                    }
+                    None => buf.push(0),
+                }
+                buf.push(*position as u8);
+                buf.push(*multisig_signers);
+            }
+            &Self::Unlock { seeds } => {
                 buf.push(2);
                 buf.extend_from_slice(&seeds);
             }
@@ -177,6 +1451,157 @@ impl VestingInstruction {
                 buf.push(3);
                 buf.extend_from_slice(&seeds);
             }
+            Self::CreateAirdrop { seeds, merkle_root } => {
+                buf.push(5);
+                buf.extend_from_slice(seeds);
+                buf.extend_from_slice(merkle_root);
+            }
+            Self::ClaimIntoLock {
+                airdrop_seeds,
+                seeds,
+                schedule,
+                proof,
+            } => {
+                buf.push(6);
+                buf.extend_from_slice(airdrop_seeds);
+                buf.extend_from_slice(seeds);
+                buf.extend_from_slice(&schedule.time_delta.to_le_bytes());
+                buf.extend_from_slice(&schedule.amount.to_le_bytes());
+                buf.extend_from_slice(&(proof.len() as u32).to_le_bytes());
+                for node in proof {
+                    buf.extend_from_slice(node);
+                }
+            }
+            &Self::Revoke { seeds } => {
+                buf.push(7);
+                buf.extend_from_slice(&seeds);
+            }
+            &Self::InitConfig {
+                max_penalty_bps,
+                lock_mode,
+            } => {
+                buf.push(8);
+                buf.extend_from_slice(&max_penalty_bps.to_le_bytes());
+                buf.push(lock_mode as u8);
+            }
+            &Self::UpdatePenalty { max_penalty_bps } => {
+                buf.push(9);
+                buf.extend_from_slice(&max_penalty_bps.to_le_bytes());
+            }
+            &Self::EarlyUnlock { seeds } => {
+                buf.push(10);
+                buf.extend_from_slice(&seeds);
+            }
+            &Self::SetPaused { paused } => {
+                buf.push(11);
+                buf.push(paused as u8);
+            }
+            &Self::SetEmergencyMode { enabled } => {
+                buf.push(12);
+                buf.push(enabled as u8);
+            }
+            Self::SetGuardian => buf.push(13),
+            &Self::EmergencyUnlock { seeds } => {
+                buf.push(14);
+                buf.extend_from_slice(&seeds);
+            }
+            Self::InitStats => buf.push(15),
+            Self::GetStats => buf.push(16),
+            Self::InitRegistry => buf.push(17),
+            &Self::Close { seeds } => {
+                buf.push(18);
+                buf.extend_from_slice(&seeds);
+            }
+            &Self::MintPosition { seeds } => {
+                buf.push(19);
+                buf.extend_from_slice(&seeds);
+            }
+            Self::InitReceiptMint => buf.push(20),
+            Self::Split {
+                seeds,
+                new_seeds,
+                amount,
+            } => {
+                buf.push(21);
+                buf.extend_from_slice(seeds);
+                buf.extend_from_slice(new_seeds);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            Self::Merge {
+                seeds,
+                source_seeds,
+            } => {
+                buf.push(22);
+                buf.extend_from_slice(seeds);
+                buf.extend_from_slice(&(source_seeds.len() as u32).to_le_bytes());
+                for source in source_seeds {
+                    buf.extend_from_slice(source);
+                }
+            }
+            &Self::TransferPosition {
+                seeds,
+                require_accept,
+            } => {
+                buf.push(23);
+                buf.extend_from_slice(&seeds);
+                buf.push(require_accept as u8);
+            }
+            &Self::AcceptPosition { seeds } => {
+                buf.push(24);
+                buf.extend_from_slice(&seeds);
+            }
+            &Self::OptOutRenewal { seeds } => {
+                buf.push(26);
+                buf.extend_from_slice(&seeds);
+            }
+            &Self::SetAllowlist { is_enabled, root } => {
+                buf.push(27);
+                buf.push(is_enabled as u8);
+                buf.extend_from_slice(&root);
+            }
+            Self::AddAllowlistEntry => buf.push(28),
+            Self::RemoveAllowlistEntry => buf.push(29),
+            Self::SetLimits {
+                max_total_locked,
+                max_locked_per_wallet,
+                min_lock_amounts,
+            } => {
+                buf.push(30);
+                buf.extend_from_slice(&max_total_locked.to_le_bytes());
+                buf.extend_from_slice(&max_locked_per_wallet.to_le_bytes());
+                for min_lock_amount in min_lock_amounts {
+                    buf.extend_from_slice(&min_lock_amount.to_le_bytes());
+                }
+            }
+            &Self::SetDepositFee { deposit_fee_bps } => {
+                buf.push(31);
+                buf.extend_from_slice(&deposit_fee_bps.to_le_bytes());
+            }
+            Self::InitReferrer => buf.push(32),
+            &Self::SetReferralShare { referral_share_bps } => {
+                buf.push(33);
+                buf.extend_from_slice(&referral_share_bps.to_le_bytes());
+            }
+            Self::UnlockMany { seeds } => {
+                buf.push(25);
+                buf.extend_from_slice(&(seeds.len() as u32).to_le_bytes());
+                for seeds in seeds {
+                    buf.extend_from_slice(seeds);
+                }
+            }
+            Self::CreateBatch {
+                entries,
+                multisig_signers,
+            } => {
+                buf.push(4);
+                buf.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+                for entry in entries {
+                    buf.extend_from_slice(&entry.seeds);
+                    buf.extend_from_slice(&entry.schedule.time_delta.to_le_bytes());
+                    buf.extend_from_slice(&entry.schedule.amount.to_le_bytes());
+                }
+                buf.push(*multisig_signers);
+            }
         };
         buf
     }
@@ -206,6 +1631,7 @@ pub fn init(
 }
 
 // Creates a `CreateSchedule` instruction
+#[allow(clippy::too_many_arguments)]
 pub fn create(
     vesting_program_id: &Pubkey,
     token_program_id: &Pubkey,
@@ -214,22 +1640,80 @@ pub fn create(
     vesting_token_account_key: &Pubkey,
     source_token_account_owner_key: &Pubkey,
     source_token_account_key: &Pubkey,
+    beneficiary_token_account_key: &Pubkey,
+    config_account_key: &Pubkey,
+    stats_account_key: &Pubkey,
+    registry_account_key: &Pubkey,
+    receipt_mint_key: &Pubkey,
+    receipt_token_account_key: &Pubkey,
+    signer_pubkeys: &[&Pubkey],
     schedule: Schedule,
     seeds: [u8; 32],
+    args: CreateArgs,
 ) -> Result<Instruction, ProgramError> {
+    let CreateArgs {
+        revocation_authority,
+        keeper_tip,
+        auto_renew,
+        hook,
+        allowlist,
+        deposit_fee_treasury_token_account_key,
+        referral,
+        position,
+    } = args;
+    let (allowlist_entry_key, allowlist_proof) = match allowlist {
+        Some(AllowlistAccess::Entry(allowlist_entry_key)) => (Some(allowlist_entry_key), None),
+        Some(AllowlistAccess::Proof(proof)) => (None, Some(proof)),
+        None => (None, None),
+    };
     let data = VestingInstruction::Create {
         seeds,
         schedule,
+        options: CreateOptions {
+            revocation_authority,
+            keeper_tip,
+            auto_renew,
+            hook,
+            allowlist_proof,
+            referrer: referral.as_ref().map(|referral| referral.referrer),
+            position: position.is_some(),
+            multisig_signers: multisig_signers(signer_pubkeys)?,
+        },
     }
     .pack();
-    let accounts = vec![
+    let mut accounts = vec![
         AccountMeta::new_readonly(*token_program_id, false),
         AccountMeta::new_readonly(*clock_sysvar_id, false),
         AccountMeta::new(*vesting_account_key, false),
         AccountMeta::new(*vesting_token_account_key, false),
-        AccountMeta::new_readonly(*source_token_account_owner_key, true),
+        AccountMeta::new_readonly(*source_token_account_owner_key, signer_pubkeys.is_empty()),
         AccountMeta::new(*source_token_account_key, false),
+        AccountMeta::new_readonly(*beneficiary_token_account_key, false),
+        AccountMeta::new_readonly(*config_account_key, false),
+        AccountMeta::new(*stats_account_key, false),
+        AccountMeta::new(*registry_account_key, false),
+        AccountMeta::new(*receipt_mint_key, false),
+        AccountMeta::new(*receipt_token_account_key, false),
     ];
+    if let Some(allowlist_entry_key) = allowlist_entry_key {
+        accounts.push(AccountMeta::new_readonly(allowlist_entry_key, false));
+    }
+    if let Some(deposit_fee_treasury_token_account_key) = deposit_fee_treasury_token_account_key {
+        accounts.push(AccountMeta::new(deposit_fee_treasury_token_account_key, false));
+    }
+    if let Some(referral) = referral {
+        accounts.push(AccountMeta::new(referral.referrer_account, false));
+        if let Some(referrer_token_account) = referral.referrer_token_account {
+            accounts.push(AccountMeta::new(referrer_token_account, false));
+        }
+    }
+    if let Some(position) = position {
+        accounts.push(AccountMeta::new(position.mint, false));
+        accounts.push(AccountMeta::new(position.token_account, false));
+    }
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
     Ok(Instruction {
         program_id: *vesting_program_id,
         accounts,
@@ -237,7 +1721,17 @@ pub fn create(
     })
 }
 
+/// The number of signer accounts of a multisig source token account owner, at most
+/// `spl_token::instruction::MAX_SIGNERS`
+fn multisig_signers(signer_pubkeys: &[&Pubkey]) -> Result<u8, ProgramError> {
+    if signer_pubkeys.len() > MAX_SIGNERS {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(signer_pubkeys.len() as u8)
+}
+
 // Creates an `Unlock` instruction
+#[allow(clippy::too_many_arguments)]
 pub fn unlock(
     vesting_program_id: &Pubkey,
     token_program_id: &Pubkey,
@@ -245,6 +1739,8 @@ pub fn unlock(
     vesting_account_key: &Pubkey,
     vesting_token_account_key: &Pubkey,
     destination_token_account_key: &Pubkey,
+    stats_account_key: &Pubkey,
+    registry_account_key: &Pubkey,
     seeds: [u8; 32],
 ) -> Result<Instruction, ProgramError> {
     let data = VestingInstruction::Unlock { seeds }.pack();
@@ -254,6 +1750,8 @@ pub fn unlock(
         AccountMeta::new(*vesting_account_key, false),
         AccountMeta::new(*vesting_token_account_key, false),
         AccountMeta::new(*destination_token_account_key, false),
+        AccountMeta::new(*stats_account_key, false),
+        AccountMeta::new(*registry_account_key, false),
     ];
     Ok(Instruction {
         program_id: *vesting_program_id,
@@ -263,6 +1761,7 @@ pub fn unlock(
 }
 
 // Creates an `Unlock` instruction
+#[allow(clippy::too_many_arguments)]
 pub fn initialize_unlock(
     vesting_program_id: &Pubkey,
     token_program_id: &Pubkey,
@@ -270,6 +1769,10 @@ pub fn initialize_unlock(
     vesting_account_key: &Pubkey,
     vesting_token_account_key: &Pubkey,
     destination_token_account_key: &Pubkey,
+    stats_account_key: &Pubkey,
+    receipt_mint_key: &Pubkey,
+    receipt_token_account_key: &Pubkey,
+    destination_token_account_owner_key: &Pubkey,
     seeds: [u8; 32],
 ) -> Result<Instruction, ProgramError> {
     let data = VestingInstruction::InitializeUnlock { seeds }.pack();
@@ -279,6 +1782,10 @@ pub fn initialize_unlock(
         AccountMeta::new(*vesting_account_key, false),
         AccountMeta::new(*vesting_token_account_key, false),
         AccountMeta::new(*destination_token_account_key, false),
+        AccountMeta::new(*stats_account_key, false),
+        AccountMeta::new(*receipt_mint_key, false),
+        AccountMeta::new(*receipt_token_account_key, false),
+        AccountMeta::new_readonly(*destination_token_account_owner_key, true),
     ];
     Ok(Instruction {
         program_id: *vesting_program_id,
@@ -287,34 +1794,1226 @@ pub fn initialize_unlock(
     })
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+/// The accounts of a lock created by a `CreateBatch` instruction
+pub struct BatchLock {
+    pub vesting_account_key: Pubkey,
+    pub vesting_token_account_key: Pubkey,
+    pub beneficiary_token_account_key: Pubkey,
+    pub receipt_token_account_key: Pubkey,
+    pub registry_account_key: Pubkey,
+}
 
-    #[test]
-    fn test_instruction_packing() {
+// Creates a `CreateBatch` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn create_batch(
+    vesting_program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    clock_sysvar_id: &Pubkey,
+    payer_key: &Pubkey,
+    source_token_account_owner_key: &Pubkey,
+    source_token_account_key: &Pubkey,
+    config_account_key: &Pubkey,
+    stats_account_key: &Pubkey,
+    receipt_mint_key: &Pubkey,
+    deposit_fee_treasury_token_account_key: Option<&Pubkey>,
+    signer_pubkeys: &[&Pubkey],
+    locks: &[BatchLock],
+    entries: Vec<BatchEntry>,
+) -> Result<Instruction, ProgramError> {
+    if locks.len() != entries.len() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let data = VestingInstruction::CreateBatch {
+        entries,
+        multisig_signers: multisig_signers(signer_pubkeys)?,
+    }
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(*clock_sysvar_id, false),
+        AccountMeta::new(*payer_key, true),
+        AccountMeta::new_readonly(*source_token_account_owner_key, signer_pubkeys.is_empty()),
+        AccountMeta::new(*source_token_account_key, false),
+        AccountMeta::new_readonly(*config_account_key, false),
+        AccountMeta::new(*stats_account_key, false),
+        AccountMeta::new(*receipt_mint_key, false),
+    ];
+    for lock in locks {
+        accounts.push(AccountMeta::new(lock.vesting_account_key, false));
+        accounts.push(AccountMeta::new(lock.vesting_token_account_key, false));
+        accounts.push(AccountMeta::new_readonly(
+            lock.beneficiary_token_account_key,
+            false,
+        ));
+        accounts.push(AccountMeta::new(lock.receipt_token_account_key, false));
+        accounts.push(AccountMeta::new(lock.registry_account_key, false));
+    }
+    if let Some(deposit_fee_treasury_token_account_key) = deposit_fee_treasury_token_account_key {
+        accounts.push(AccountMeta::new(*deposit_fee_treasury_token_account_key, false));
+    }
+    for signer_pubkey in signer_pubkeys.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
 
-        let original_create = VestingInstruction::Create {
-            seeds: [50u8; 32],
-            schedule: Schedule {
-                amount: 42,
-                time_delta: 250,
-            },
-        };
-        let packed_create = original_create.pack();
-        let unpacked_create = VestingInstruction::unpack(&packed_create).unwrap();
-        assert_eq!(original_create, unpacked_create);
+// Creates a `CreateAirdrop` instruction
+pub fn create_airdrop(
+    vesting_program_id: &Pubkey,
+    authority_key: &Pubkey,
+    airdrop_account_key: &Pubkey,
+    vault_token_account_key: &Pubkey,
+    config_account_key: &Pubkey,
+    seeds: [u8; 32],
+    merkle_root: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::CreateAirdrop { seeds, merkle_root }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new(*authority_key, true),
+        AccountMeta::new(*airdrop_account_key, false),
+        AccountMeta::new_readonly(*vault_token_account_key, false),
+        AccountMeta::new_readonly(*config_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
 
-        let original_unlock = VestingInstruction::Unlock { seeds: [50u8; 32] };
-        assert_eq!(
-            original_unlock,
-            VestingInstruction::unpack(&original_unlock.pack()).unwrap()
-        );
+// Creates a `ClaimIntoLock` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn claim_into_lock(
+    vesting_program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    clock_sysvar_id: &Pubkey,
+    claimant_key: &Pubkey,
+    airdrop_account_key: &Pubkey,
+    vault_token_account_key: &Pubkey,
+    vesting_account_key: &Pubkey,
+    vesting_token_account_key: &Pubkey,
+    destination_token_account_key: &Pubkey,
+    config_account_key: &Pubkey,
+    stats_account_key: &Pubkey,
+    receipt_mint_key: &Pubkey,
+    receipt_token_account_key: &Pubkey,
+    registry_account_key: &Pubkey,
+    deposit_fee_treasury_token_account_key: Option<&Pubkey>,
+    airdrop_seeds: [u8; 32],
+    seeds: [u8; 32],
+    schedule: Schedule,
+    proof: Vec<[u8; 32]>,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::ClaimIntoLock {
+        airdrop_seeds,
+        seeds,
+        schedule,
+        proof,
+    }
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(*clock_sysvar_id, false),
+        AccountMeta::new(*claimant_key, true),
+        AccountMeta::new_readonly(*airdrop_account_key, false),
+        AccountMeta::new(*vault_token_account_key, false),
+        AccountMeta::new(*vesting_account_key, false),
+        AccountMeta::new(*vesting_token_account_key, false),
+        AccountMeta::new_readonly(*destination_token_account_key, false),
+        AccountMeta::new_readonly(*config_account_key, false),
+        AccountMeta::new(*stats_account_key, false),
+        AccountMeta::new(*receipt_mint_key, false),
+        AccountMeta::new(*receipt_token_account_key, false),
+        AccountMeta::new(*registry_account_key, false),
+    ];
+    if let Some(deposit_fee_treasury_token_account_key) = deposit_fee_treasury_token_account_key {
+        accounts.push(AccountMeta::new(*deposit_fee_treasury_token_account_key, false));
+    }
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
 
-        let original_init = VestingInstruction::Init { seeds: [50u8; 32] };
-        assert_eq!(
-            original_init,
-            VestingInstruction::unpack(&original_init.pack()).unwrap()
+// Creates a `Revoke` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn revoke(
+    vesting_program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    clock_sysvar_id: &Pubkey,
+    vesting_account_key: &Pubkey,
+    vesting_token_account_key: &Pubkey,
+    destination_token_account_key: &Pubkey,
+    revocation_authority_key: &Pubkey,
+    treasury_token_account_key: &Pubkey,
+    stats_account_key: &Pubkey,
+    registry_account_key: &Pubkey,
+    seeds: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::Revoke { seeds }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(*clock_sysvar_id, false),
+        AccountMeta::new(*vesting_account_key, false),
+        AccountMeta::new(*vesting_token_account_key, false),
+        AccountMeta::new(*destination_token_account_key, false),
+        AccountMeta::new_readonly(*revocation_authority_key, true),
+        AccountMeta::new(*treasury_token_account_key, false),
+        AccountMeta::new(*stats_account_key, false),
+        AccountMeta::new(*registry_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates an `InitConfig` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn init_config(
+    vesting_program_id: &Pubkey,
+    admin_key: &Pubkey,
+    config_account_key: &Pubkey,
+    penalty_treasury_token_account_key: &Pubkey,
+    guardian_key: &Pubkey,
+    program_data_account_key: &Pubkey,
+    max_penalty_bps: u16,
+    lock_mode: LockMode,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::InitConfig {
+        max_penalty_bps,
+        lock_mode,
+    }
+    .pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new(*admin_key, true),
+        AccountMeta::new(*config_account_key, false),
+        AccountMeta::new_readonly(*penalty_treasury_token_account_key, false),
+        AccountMeta::new_readonly(*guardian_key, false),
+        AccountMeta::new_readonly(*program_data_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates an `UpdatePenalty` instruction
+pub fn update_penalty(
+    vesting_program_id: &Pubkey,
+    admin_key: &Pubkey,
+    config_account_key: &Pubkey,
+    penalty_treasury_token_account_key: &Pubkey,
+    max_penalty_bps: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::UpdatePenalty { max_penalty_bps }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_key, true),
+        AccountMeta::new(*config_account_key, false),
+        AccountMeta::new_readonly(*penalty_treasury_token_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates an `EarlyUnlock` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn early_unlock(
+    vesting_program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    clock_sysvar_id: &Pubkey,
+    vesting_account_key: &Pubkey,
+    vesting_token_account_key: &Pubkey,
+    destination_token_account_key: &Pubkey,
+    destination_token_account_owner_key: &Pubkey,
+    config_account_key: &Pubkey,
+    penalty_treasury_token_account_key: &Pubkey,
+    stats_account_key: &Pubkey,
+    registry_account_key: &Pubkey,
+    seeds: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::EarlyUnlock { seeds }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(*clock_sysvar_id, false),
+        AccountMeta::new(*vesting_account_key, false),
+        AccountMeta::new(*vesting_token_account_key, false),
+        AccountMeta::new(*destination_token_account_key, false),
+        AccountMeta::new_readonly(*destination_token_account_owner_key, true),
+        AccountMeta::new_readonly(*config_account_key, false),
+        AccountMeta::new(*penalty_treasury_token_account_key, false),
+        AccountMeta::new(*stats_account_key, false),
+        AccountMeta::new(*registry_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates a `SetPaused` instruction
+pub fn set_paused(
+    vesting_program_id: &Pubkey,
+    guardian_key: &Pubkey,
+    config_account_key: &Pubkey,
+    paused: bool,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::SetPaused { paused }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*guardian_key, true),
+        AccountMeta::new(*config_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates a `SetEmergencyMode` instruction
+pub fn set_emergency_mode(
+    vesting_program_id: &Pubkey,
+    admin_key: &Pubkey,
+    config_account_key: &Pubkey,
+    enabled: bool,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::SetEmergencyMode { enabled }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_key, true),
+        AccountMeta::new(*config_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates a `SetGuardian` instruction
+pub fn set_guardian(
+    vesting_program_id: &Pubkey,
+    admin_key: &Pubkey,
+    config_account_key: &Pubkey,
+    guardian_key: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::SetGuardian.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_key, true),
+        AccountMeta::new(*config_account_key, false),
+        AccountMeta::new_readonly(*guardian_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates a `SetAllowlist` instruction
+pub fn set_allowlist(
+    vesting_program_id: &Pubkey,
+    admin_key: &Pubkey,
+    config_account_key: &Pubkey,
+    is_enabled: bool,
+    root: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::SetAllowlist { is_enabled, root }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_key, true),
+        AccountMeta::new(*config_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates an `AddAllowlistEntry` instruction
+pub fn add_allowlist_entry(
+    vesting_program_id: &Pubkey,
+    payer_key: &Pubkey,
+    admin_key: &Pubkey,
+    config_account_key: &Pubkey,
+    wallet_key: &Pubkey,
+    allowlist_entry_key: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::AddAllowlistEntry.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new(*payer_key, true),
+        AccountMeta::new_readonly(*admin_key, true),
+        AccountMeta::new_readonly(*config_account_key, false),
+        AccountMeta::new_readonly(*wallet_key, false),
+        AccountMeta::new(*allowlist_entry_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates a `RemoveAllowlistEntry` instruction
+pub fn remove_allowlist_entry(
+    vesting_program_id: &Pubkey,
+    admin_key: &Pubkey,
+    config_account_key: &Pubkey,
+    allowlist_entry_key: &Pubkey,
+    recipient_key: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::RemoveAllowlistEntry.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_key, true),
+        AccountMeta::new_readonly(*config_account_key, false),
+        AccountMeta::new(*allowlist_entry_key, false),
+        AccountMeta::new(*recipient_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates a `SetLimits` instruction
+pub fn set_limits(
+    vesting_program_id: &Pubkey,
+    admin_key: &Pubkey,
+    config_account_key: &Pubkey,
+    max_total_locked: u64,
+    max_locked_per_wallet: u64,
+    min_lock_amounts: [u64; 5],
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::SetLimits {
+        max_total_locked,
+        max_locked_per_wallet,
+        min_lock_amounts,
+    }
+    .pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_key, true),
+        AccountMeta::new(*config_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates a `SetDepositFee` instruction
+pub fn set_deposit_fee(
+    vesting_program_id: &Pubkey,
+    admin_key: &Pubkey,
+    config_account_key: &Pubkey,
+    deposit_fee_treasury_token_account_key: &Pubkey,
+    deposit_fee_bps: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::SetDepositFee { deposit_fee_bps }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_key, true),
+        AccountMeta::new(*config_account_key, false),
+        AccountMeta::new_readonly(*deposit_fee_treasury_token_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates a `SetReferralShare` instruction
+pub fn set_referral_share(
+    vesting_program_id: &Pubkey,
+    admin_key: &Pubkey,
+    config_account_key: &Pubkey,
+    referral_share_bps: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::SetReferralShare { referral_share_bps }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin_key, true),
+        AccountMeta::new(*config_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates an `EmergencyUnlock` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn emergency_unlock(
+    vesting_program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    clock_sysvar_id: &Pubkey,
+    vesting_account_key: &Pubkey,
+    vesting_token_account_key: &Pubkey,
+    destination_token_account_key: &Pubkey,
+    config_account_key: &Pubkey,
+    stats_account_key: &Pubkey,
+    registry_account_key: &Pubkey,
+    seeds: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::EmergencyUnlock { seeds }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(*clock_sysvar_id, false),
+        AccountMeta::new(*vesting_account_key, false),
+        AccountMeta::new(*vesting_token_account_key, false),
+        AccountMeta::new(*destination_token_account_key, false),
+        AccountMeta::new_readonly(*config_account_key, false),
+        AccountMeta::new(*stats_account_key, false),
+        AccountMeta::new(*registry_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates an `InitStats` instruction
+pub fn init_stats(
+    vesting_program_id: &Pubkey,
+    payer_key: &Pubkey,
+    stats_account_key: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::InitStats.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new(*payer_key, true),
+        AccountMeta::new(*stats_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates a `GetStats` instruction
+pub fn get_stats(
+    vesting_program_id: &Pubkey,
+    stats_account_key: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::GetStats.pack();
+    let accounts = vec![AccountMeta::new_readonly(*stats_account_key, false)];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates an `InitRegistry` instruction
+pub fn init_registry(
+    vesting_program_id: &Pubkey,
+    payer_key: &Pubkey,
+    owner_key: &Pubkey,
+    registry_account_key: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::InitRegistry.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new(*payer_key, true),
+        AccountMeta::new_readonly(*owner_key, false),
+        AccountMeta::new(*registry_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates an `InitReferrer` instruction
+pub fn init_referrer(
+    vesting_program_id: &Pubkey,
+    payer_key: &Pubkey,
+    referrer_key: &Pubkey,
+    referrer_account_key: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::InitReferrer.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new(*payer_key, true),
+        AccountMeta::new_readonly(*referrer_key, false),
+        AccountMeta::new(*referrer_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates a `Close` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn close(
+    vesting_program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_account_key: &Pubkey,
+    vesting_token_account_key: &Pubkey,
+    destination_token_account_key: &Pubkey,
+    destination_token_account_owner: &Pubkey,
+    registry_account_key: &Pubkey,
+    seeds: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::Close { seeds }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(*vesting_account_key, false),
+        AccountMeta::new(*vesting_token_account_key, false),
+        AccountMeta::new_readonly(*destination_token_account_key, false),
+        AccountMeta::new(*destination_token_account_owner, true),
+        AccountMeta::new(*registry_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates a `MintPosition` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn mint_position(
+    vesting_program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_account_key: &Pubkey,
+    destination_token_account_key: &Pubkey,
+    destination_token_account_owner: &Pubkey,
+    position_mint_key: &Pubkey,
+    position_token_account_key: &Pubkey,
+    seeds: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::MintPosition { seeds }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(*vesting_account_key, false),
+        AccountMeta::new_readonly(*destination_token_account_key, false),
+        AccountMeta::new_readonly(*destination_token_account_owner, true),
+        AccountMeta::new(*position_mint_key, false),
+        AccountMeta::new(*position_token_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates an `InitReceiptMint` instruction
+pub fn init_receipt_mint(
+    vesting_program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    payer_key: &Pubkey,
+    receipt_mint_key: &Pubkey,
+    token_mint_key: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::InitReceiptMint.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(*payer_key, true),
+        AccountMeta::new(*receipt_mint_key, false),
+        AccountMeta::new_readonly(*token_mint_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates a `Split` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn split(
+    vesting_program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    payer_key: &Pubkey,
+    vesting_account_key: &Pubkey,
+    vesting_token_account_key: &Pubkey,
+    new_vesting_account_key: &Pubkey,
+    new_vesting_token_account_key: &Pubkey,
+    destination_token_account_key: &Pubkey,
+    owner_key: &Pubkey,
+    new_destination_token_account_key: &Pubkey,
+    stats_account_key: &Pubkey,
+    registry_account_key: &Pubkey,
+    new_registry_account_key: &Pubkey,
+    config_account_key: &Pubkey,
+    seeds: [u8; 32],
+    new_seeds: [u8; 32],
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::Split {
+        seeds,
+        new_seeds,
+        amount,
+    }
+    .pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(*payer_key, true),
+        AccountMeta::new(*vesting_account_key, false),
+        AccountMeta::new(*vesting_token_account_key, false),
+        AccountMeta::new(*new_vesting_account_key, false),
+        AccountMeta::new(*new_vesting_token_account_key, false),
+        AccountMeta::new_readonly(*destination_token_account_key, false),
+        AccountMeta::new_readonly(*owner_key, true),
+        AccountMeta::new_readonly(*new_destination_token_account_key, false),
+        AccountMeta::new(*stats_account_key, false),
+        AccountMeta::new(*registry_account_key, false),
+        AccountMeta::new(*new_registry_account_key, false),
+        AccountMeta::new_readonly(*config_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// The accounts of a lock merged by a `Merge` instruction
+pub struct MergedLock {
+    pub vesting_account_key: Pubkey,
+    pub vesting_token_account_key: Pubkey,
+    pub seeds: [u8; 32],
+}
+
+// Creates a `Merge` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn merge(
+    vesting_program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    vesting_account_key: &Pubkey,
+    vesting_token_account_key: &Pubkey,
+    destination_token_account_key: &Pubkey,
+    destination_token_account_owner_key: &Pubkey,
+    stats_account_key: &Pubkey,
+    registry_account_key: &Pubkey,
+    seeds: [u8; 32],
+    sources: &[MergedLock],
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::Merge {
+        seeds,
+        source_seeds: sources.iter().map(|source| source.seeds).collect(),
+    }
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(*vesting_account_key, false),
+        AccountMeta::new(*vesting_token_account_key, false),
+        AccountMeta::new_readonly(*destination_token_account_key, false),
+        AccountMeta::new(*destination_token_account_owner_key, true),
+        AccountMeta::new(*stats_account_key, false),
+        AccountMeta::new(*registry_account_key, false),
+    ];
+    for source in sources {
+        accounts.push(AccountMeta::new(source.vesting_account_key, false));
+        accounts.push(AccountMeta::new(source.vesting_token_account_key, false));
+    }
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates a `TransferPosition` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_position(
+    vesting_program_id: &Pubkey,
+    vesting_account_key: &Pubkey,
+    destination_token_account_key: &Pubkey,
+    owner_key: &Pubkey,
+    registry_account_key: &Pubkey,
+    new_destination_token_account_key: &Pubkey,
+    new_registry_account_key: &Pubkey,
+    config_account_key: &Pubkey,
+    seeds: [u8; 32],
+    require_accept: bool,
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::TransferPosition {
+        seeds,
+        require_accept,
+    }
+    .pack();
+    let accounts = vec![
+        AccountMeta::new(*vesting_account_key, false),
+        AccountMeta::new_readonly(*destination_token_account_key, false),
+        AccountMeta::new_readonly(*owner_key, true),
+        AccountMeta::new(*registry_account_key, false),
+        AccountMeta::new_readonly(*new_destination_token_account_key, false),
+        AccountMeta::new(*new_registry_account_key, false),
+        AccountMeta::new_readonly(*config_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates an `AcceptPosition` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn accept_position(
+    vesting_program_id: &Pubkey,
+    vesting_account_key: &Pubkey,
+    destination_token_account_key: &Pubkey,
+    registry_account_key: &Pubkey,
+    new_destination_token_account_key: &Pubkey,
+    new_destination_token_account_owner_key: &Pubkey,
+    new_registry_account_key: &Pubkey,
+    config_account_key: &Pubkey,
+    seeds: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::AcceptPosition { seeds }.pack();
+    let accounts = vec![
+        AccountMeta::new(*vesting_account_key, false),
+        AccountMeta::new_readonly(*destination_token_account_key, false),
+        AccountMeta::new(*registry_account_key, false),
+        AccountMeta::new_readonly(*new_destination_token_account_key, false),
+        AccountMeta::new_readonly(*new_destination_token_account_owner_key, true),
+        AccountMeta::new(*new_registry_account_key, false),
+        AccountMeta::new_readonly(*config_account_key, false),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+// Creates an `OptOutRenewal` instruction
+pub fn opt_out_renewal(
+    vesting_program_id: &Pubkey,
+    clock_sysvar_id: &Pubkey,
+    vesting_account_key: &Pubkey,
+    destination_token_account_key: &Pubkey,
+    destination_token_account_owner: &Pubkey,
+    seeds: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::OptOutRenewal { seeds }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*clock_sysvar_id, false),
+        AccountMeta::new(*vesting_account_key, false),
+        AccountMeta::new_readonly(*destination_token_account_key, false),
+        AccountMeta::new_readonly(*destination_token_account_owner, true),
+    ];
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// The accounts of a lock released by an `UnlockMany` instruction
+pub struct UnlockedLock {
+    pub vesting_account_key: Pubkey,
+    pub vesting_token_account_key: Pubkey,
+    pub destination_token_account_key: Pubkey,
+    pub registry_account_key: Pubkey,
+    pub seeds: [u8; 32],
+}
+
+// Creates an `UnlockMany` instruction
+pub fn unlock_many(
+    vesting_program_id: &Pubkey,
+    token_program_id: &Pubkey,
+    clock_sysvar_id: &Pubkey,
+    stats_account_key: &Pubkey,
+    keeper_account_key: &Pubkey,
+    locks: &[UnlockedLock],
+) -> Result<Instruction, ProgramError> {
+    let data = VestingInstruction::UnlockMany {
+        seeds: locks.iter().map(|lock| lock.seeds).collect(),
+    }
+    .pack();
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(*clock_sysvar_id, false),
+        AccountMeta::new(*stats_account_key, false),
+        AccountMeta::new(*keeper_account_key, false),
+    ];
+    for lock in locks {
+        accounts.push(AccountMeta::new(lock.vesting_account_key, false));
+        accounts.push(AccountMeta::new(lock.vesting_token_account_key, false));
+        accounts.push(AccountMeta::new(lock.destination_token_account_key, false));
+        accounts.push(AccountMeta::new(lock.registry_account_key, false));
+    }
+    Ok(Instruction {
+        program_id: *vesting_program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Appends the position token accounts to an `Unlock`, `InitializeUnlock`, `EarlyUnlock`,
+/// `EmergencyUnlock` or `OptOutRenewal` instruction, for a lock with a position token
+pub fn with_position_holder(
+    mut instruction: Instruction,
+    position_mint_key: &Pubkey,
+    position_token_account_key: &Pubkey,
+    holder_key: &Pubkey,
+) -> Instruction {
+    instruction.accounts.extend([
+        AccountMeta::new(*position_mint_key, false),
+        AccountMeta::new(*position_token_account_key, false),
+        AccountMeta::new_readonly(*holder_key, true),
+    ]);
+    instruction
+}
+
+/// Appends the keeper account to an `Unlock` instruction, after the position token accounts of a
+/// lock with a position token, to receive the keeper tip of the lock
+pub fn with_keeper(mut instruction: Instruction, keeper_account_key: &Pubkey) -> Instruction {
+    instruction
+        .accounts
+        .push(AccountMeta::new(*keeper_account_key, false));
+    instruction
+}
+
+/// Appends the hook program account of a lock, its hook authority and the accounts forwarded to
+/// its hook to a `Create`, `InitializeUnlock` or `Unlock` instruction, after all its other accounts
+pub fn with_hook(
+    mut instruction: Instruction,
+    hook_program_id: &Pubkey,
+    hook_authority_key: &Pubkey,
+    hook_accounts: &[AccountMeta],
+) -> Instruction {
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(*hook_program_id, false));
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(*hook_authority_key, false));
+    instruction.accounts.extend_from_slice(hook_accounts);
+    instruction
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_instruction_packing() {
+
+        let original_create = VestingInstruction::Create {
+            seeds: [50u8; 32],
+            schedule: Schedule {
+                amount: 42,
+                time_delta: 250,
+            },
+            options: CreateOptions::default(),
+        };
+        let packed_create = original_create.pack();
+        let unpacked_create = VestingInstruction::unpack(&packed_create).unwrap();
+        assert_eq!(original_create, unpacked_create);
+
+        // Locks created without the revocation authority, keeper tip, auto-renew, hook, allowlist
+        // proof, referrer, position and multisig signers bytes are not revocable, have no keeper
+        // tip, do not renew, have no hook, no allowlist proof, no referrer, no position token and
+        // a single owner
+        for omitted in [15, 14, 6, 5, 4, 3, 2, 1] {
+            assert_eq!(
+                original_create,
+                VestingInstruction::unpack(&packed_create[..packed_create.len() - omitted])
+                    .unwrap()
+            );
+        }
+
+        let original_revocable_create = VestingInstruction::Create {
+            seeds: [50u8; 32],
+            schedule: Schedule {
+                amount: 42,
+                time_delta: 250,
+            },
+            options: CreateOptions {
+                revocation_authority: Some(Pubkey::new_from_array([52u8; 32])),
+                keeper_tip: 1_000_000,
+                auto_renew: true,
+                hook: Some(Hook {
+                    program_id: Pubkey::new_from_array([53u8; 32]),
+                    mode: HookMode::Skippable,
+                }),
+                allowlist_proof: Some(vec![[54u8; 32], [55u8; 32]]),
+                referrer: Some(Pubkey::new_from_array([56u8; 32])),
+                position: true,
+                multisig_signers: 3,
+            },
+        };
+        let packed_revocable_create = original_revocable_create.pack();
+        assert_eq!(
+            packed_revocable_create.len(),
+            1 + 32 + SCHEDULE_SIZE + 33 + 8 + 1 + 34 + 69 + 33 + 1 + 1
+        );
+        assert_eq!(
+            original_revocable_create,
+            VestingInstruction::unpack(&packed_revocable_create).unwrap()
+        );
+        assert!(VestingInstruction::unpack(
+            &packed_revocable_create[..packed_revocable_create.len() - 148]
+        )
+        .is_err());
+        // A truncated keeper tip, hook, allowlist proof or referrer is rejected
+        for truncated in [141, 105, 68, 36, 3] {
+            assert!(VestingInstruction::unpack(
+                &packed_revocable_create[..packed_revocable_create.len() - truncated]
+            )
+            .is_err());
+        }
+
+        // Invalid auto-renew, hook, allowlist proof, referrer and position flags are rejected, and
+        // so are unknown hook modes
+        for flag_index in [6, 5, 4, 3, 2] {
+            let mut packed_invalid_flag = packed_create.clone();
+            let index = packed_invalid_flag.len() - flag_index;
+            packed_invalid_flag[index] = 2;
+            assert!(VestingInstruction::unpack(&packed_invalid_flag).is_err());
+        }
+        let mut packed_unknown_hook_mode = packed_revocable_create.clone();
+        let hook_mode_index = packed_unknown_hook_mode.len() - 105;
+        packed_unknown_hook_mode[hook_mode_index] = 2;
+        assert!(VestingInstruction::unpack(&packed_unknown_hook_mode).is_err());
+
+        let original_opt_out = VestingInstruction::OptOutRenewal { seeds: [50u8; 32] };
+        assert_eq!(
+            original_opt_out,
+            VestingInstruction::unpack(&original_opt_out.pack()).unwrap()
+        );
+
+        let original_revoke = VestingInstruction::Revoke { seeds: [50u8; 32] };
+        assert_eq!(
+            original_revoke,
+            VestingInstruction::unpack(&original_revoke.pack()).unwrap()
+        );
+
+        let original_init_config = VestingInstruction::InitConfig {
+            max_penalty_bps: 2_500,
+            lock_mode: LockMode::Slot,
+        };
+        let packed_init_config = original_init_config.pack();
+        assert_eq!(
+            original_init_config,
+            VestingInstruction::unpack(&packed_init_config).unwrap()
+        );
+        // The lock mode may be omitted
+        assert_eq!(
+            VestingInstruction::InitConfig {
+                max_penalty_bps: 2_500,
+                lock_mode: LockMode::Timestamp,
+            },
+            VestingInstruction::unpack(&packed_init_config[..3]).unwrap()
+        );
+
+        let original_update_penalty = VestingInstruction::UpdatePenalty {
+            max_penalty_bps: 1_000,
+        };
+        let packed_update_penalty = original_update_penalty.pack();
+        assert_eq!(
+            original_update_penalty,
+            VestingInstruction::unpack(&packed_update_penalty).unwrap()
+        );
+        assert!(VestingInstruction::unpack(&packed_update_penalty[..2]).is_err());
+
+        let original_early_unlock = VestingInstruction::EarlyUnlock { seeds: [50u8; 32] };
+        assert_eq!(
+            original_early_unlock,
+            VestingInstruction::unpack(&original_early_unlock.pack()).unwrap()
+        );
+
+        for original in [
+            VestingInstruction::SetPaused { paused: true },
+            VestingInstruction::SetEmergencyMode { enabled: false },
+            VestingInstruction::SetGuardian,
+            VestingInstruction::EmergencyUnlock { seeds: [50u8; 32] },
+            VestingInstruction::InitStats,
+            VestingInstruction::GetStats,
+            VestingInstruction::InitRegistry,
+            VestingInstruction::Close { seeds: [50u8; 32] },
+            VestingInstruction::MintPosition { seeds: [50u8; 32] },
+            VestingInstruction::InitReceiptMint,
+            VestingInstruction::TransferPosition {
+                seeds: [50u8; 32],
+                require_accept: true,
+            },
+            VestingInstruction::AcceptPosition { seeds: [50u8; 32] },
+            VestingInstruction::SetAllowlist {
+                is_enabled: true,
+                root: [51u8; 32],
+            },
+            VestingInstruction::AddAllowlistEntry,
+            VestingInstruction::RemoveAllowlistEntry,
+            VestingInstruction::SetLimits {
+                max_total_locked: 1_000_000,
+                max_locked_per_wallet: 10_000,
+                min_lock_amounts: [1, 2, 3, 4, 5],
+            },
+            VestingInstruction::SetDepositFee {
+                deposit_fee_bps: 50,
+            },
+            VestingInstruction::InitReferrer,
+            VestingInstruction::SetReferralShare {
+                referral_share_bps: 2_000,
+            },
+        ] {
+            assert_eq!(original, VestingInstruction::unpack(&original.pack()).unwrap());
+        }
+        assert!(VestingInstruction::unpack(&[11, 2]).is_err());
+        assert!(VestingInstruction::unpack(&[30; 56]).is_err());
+        assert!(VestingInstruction::unpack(&[31, 50]).is_err());
+        assert!(VestingInstruction::unpack(&[33, 50]).is_err());
+        let mut packed_transfer = VestingInstruction::TransferPosition {
+            seeds: [50u8; 32],
+            require_accept: false,
+        }
+        .pack();
+        packed_transfer[33] = 2;
+        assert!(VestingInstruction::unpack(&packed_transfer).is_err());
+
+        let original_split = VestingInstruction::Split {
+            seeds: [50u8; 32],
+            new_seeds: [51u8; 32],
+            amount: 42,
+        };
+        let packed_split = original_split.pack();
+        assert_eq!(packed_split.len(), 1 + 32 + 32 + 8);
+        assert_eq!(original_split, VestingInstruction::unpack(&packed_split).unwrap());
+        assert!(VestingInstruction::unpack(&packed_split[..packed_split.len() - 1]).is_err());
+
+        let original_merge = VestingInstruction::Merge {
+            seeds: [50u8; 32],
+            source_seeds: vec![[51u8; 32], [52u8; 32]],
+        };
+        let packed_merge = original_merge.pack();
+        assert_eq!(packed_merge.len(), 1 + 32 + 4 + 2 * 32);
+        assert_eq!(original_merge, VestingInstruction::unpack(&packed_merge).unwrap());
+        assert!(VestingInstruction::unpack(&packed_merge[..packed_merge.len() - 1]).is_err());
+        assert!(VestingInstruction::unpack(&[12]).is_err());
+
+        let original_unlock_many = VestingInstruction::UnlockMany {
+            seeds: vec![[51u8; 32], [52u8; 32], [53u8; 32]],
+        };
+        let packed_unlock_many = original_unlock_many.pack();
+        assert_eq!(packed_unlock_many.len(), 1 + 4 + 3 * 32);
+        assert_eq!(
+            original_unlock_many,
+            VestingInstruction::unpack(&packed_unlock_many).unwrap()
+        );
+        assert!(
+            VestingInstruction::unpack(&packed_unlock_many[..packed_unlock_many.len() - 1])
+                .is_err()
+        );
+
+        let original_unlock = VestingInstruction::Unlock { seeds: [50u8; 32] };
+        assert_eq!(
+            original_unlock,
+            VestingInstruction::unpack(&original_unlock.pack()).unwrap()
+        );
+
+        let original_init = VestingInstruction::Init { seeds: [50u8; 32] };
+        assert_eq!(
+            original_init,
+            VestingInstruction::unpack(&original_init.pack()).unwrap()
+        );
+
+        let original_create_batch = VestingInstruction::CreateBatch {
+            entries: vec![
+                BatchEntry {
+                    seeds: [50u8; 32],
+                    schedule: Schedule {
+                        amount: 42,
+                        time_delta: 0,
+                    },
+                },
+                BatchEntry {
+                    seeds: [51u8; 32],
+                    schedule: Schedule {
+                        amount: 43,
+                        time_delta: 7_776_000,
+                    },
+                },
+            ],
+            multisig_signers: 2,
+        };
+        let packed_create_batch = original_create_batch.pack();
+        assert_eq!(packed_create_batch.len(), 1 + 4 + 2 * BATCH_ENTRY_SIZE + 1);
+        assert_eq!(
+            original_create_batch,
+            VestingInstruction::unpack(&packed_create_batch).unwrap()
+        );
+        // Batches created without the multisig signers byte have a single owner
+        let mut single_owner_create_batch = original_create_batch.clone();
+        if let VestingInstruction::CreateBatch {
+            multisig_signers, ..
+        } = &mut single_owner_create_batch
+        {
+            *multisig_signers = 0;
+        }
+        assert_eq!(
+            single_owner_create_batch,
+            VestingInstruction::unpack(&packed_create_batch[..packed_create_batch.len() - 1])
+                .unwrap()
+        );
+        assert!(
+            VestingInstruction::unpack(&packed_create_batch[..packed_create_batch.len() - 2])
+                .is_err()
+        );
+
+        let original_create_airdrop = VestingInstruction::CreateAirdrop {
+            seeds: [50u8; 32],
+            merkle_root: [51u8; 32],
+        };
+        assert_eq!(
+            original_create_airdrop,
+            VestingInstruction::unpack(&original_create_airdrop.pack()).unwrap()
+        );
+
+        let original_claim = VestingInstruction::ClaimIntoLock {
+            airdrop_seeds: [50u8; 32],
+            seeds: [51u8; 32],
+            schedule: Schedule {
+                amount: 42,
+                time_delta: 15_552_000,
+            },
+            proof: vec![[1u8; 32], [2u8; 32], [3u8; 32]],
+        };
+        let packed_claim = original_claim.pack();
+        assert_eq!(packed_claim.len(), 1 + 64 + SCHEDULE_SIZE + 4 + 3 * 32);
+        assert_eq!(
+            original_claim,
+            VestingInstruction::unpack(&packed_claim).unwrap()
         );
+        assert!(VestingInstruction::unpack(&packed_claim[..packed_claim.len() - 1]).is_err());
     }
 }
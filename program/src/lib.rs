@@ -2,7 +2,11 @@
 pub mod entrypoint;
 
 pub mod error;
+pub mod event;
+pub mod hook;
 pub mod instruction;
+pub mod merkle;
+pub mod penalty;
 pub mod state;
 
 pub mod processor;
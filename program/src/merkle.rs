@@ -0,0 +1,107 @@
+//! Merkle proofs of the locked airdrops and of the allowlist.
+//!
+//! Airdrop leaves hash a `(wallet, amount, time_delta)` entry, allowlist leaves a wallet after
+//! their own prefix, so that neither can be proven against the other tree. Nodes hash their two children in sorted
+//! order, so that proofs are plain lists of sibling hashes. Leaves and nodes use different
+//! prefixes to prevent a node from being claimed as a leaf.
+use solana_program::{keccak::hashv, pubkey::Pubkey};
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+const ALLOWLIST_LEAF_PREFIX: &[u8] = &[2];
+
+/// Hash of an airdrop entry
+pub fn leaf_hash(wallet: &Pubkey, amount: u64, time_delta: u64) -> [u8; 32] {
+    hashv(&[
+        LEAF_PREFIX,
+        wallet.as_ref(),
+        &amount.to_le_bytes(),
+        &time_delta.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Hash of an allowlisted wallet
+pub fn allowlist_leaf_hash(wallet: &Pubkey) -> [u8; 32] {
+    hashv(&[ALLOWLIST_LEAF_PREFIX, wallet.as_ref()]).to_bytes()
+}
+
+/// Hash of the parent of two nodes
+pub fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if left <= right {
+        (left, right)
+    } else {
+        (right, left)
+    };
+    hashv(&[NODE_PREFIX, first, second]).to_bytes()
+}
+
+/// Checks that the leaf belongs to the tree with the given root
+pub fn verify(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed_root = proof
+        .iter()
+        .fold(leaf, |node, sibling| node_hash(&node, sibling));
+    computed_root == *root
+}
+
+/// Seed of the vesting account a wallet claims an airdrop into, before the bump seed. Claims must
+/// use the canonical bump seed, so that there is a single vesting account per wallet and airdrop,
+/// which prevents claiming twice.
+pub fn claim_seed(airdrop: &Pubkey, wallet: &Pubkey) -> [u8; 31] {
+    let hash = hashv(&[b"claim", airdrop.as_ref(), wallet.as_ref()]).to_bytes();
+    let mut seed = [0u8; 31];
+    seed.copy_from_slice(&hash[..31]);
+    seed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let wallets: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = wallets
+            .iter()
+            .map(|wallet| leaf_hash(wallet, 100, 7_776_000))
+            .collect();
+        // Three leaves: the last one is paired with itself
+        let left = node_hash(&leaves[0], &leaves[1]);
+        let right = node_hash(&leaves[2], &leaves[2]);
+        let root = node_hash(&left, &right);
+
+        assert!(verify(&[leaves[1], right], &root, leaves[0]));
+        assert!(verify(&[leaves[0], right], &root, leaves[1]));
+        assert!(verify(&[leaves[2], left], &root, leaves[2]));
+
+        // Wrong amount, tier or wallet
+        assert!(!verify(
+            &[leaves[1], right],
+            &root,
+            leaf_hash(&wallets[0], 101, 7_776_000)
+        ));
+        assert!(!verify(
+            &[leaves[1], right],
+            &root,
+            leaf_hash(&wallets[0], 100, 0)
+        ));
+        assert!(!verify(
+            &[leaves[1], right],
+            &root,
+            leaf_hash(&wallets[1], 100, 7_776_000)
+        ));
+        // Proof of another leaf
+        assert!(!verify(&[leaves[1], right], &root, leaves[2]));
+    }
+
+    #[test]
+    fn test_claim_seed() {
+        let airdrop = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        assert_eq!(claim_seed(&airdrop, &wallet), claim_seed(&airdrop, &wallet));
+        assert_ne!(
+            claim_seed(&airdrop, &wallet),
+            claim_seed(&Pubkey::new_unique(), &wallet)
+        );
+    }
+}
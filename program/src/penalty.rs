@@ -0,0 +1,94 @@
+//! Penalty charged by `EarlyUnlock` on the tokens of a lock released before its release time.
+//!
+//! The penalty is a share of the locked amount proportional to the remaining lock time: the full
+//! `max_penalty_bps` with a whole 12-month lock ahead, nothing once the release time is reached.
+
+/// Denominator of the penalty rates, in basis points
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Remaining lock time charged the maximum penalty, the longest lock period
+pub const MAX_LOCK_DURATION: u64 = 31_104_000;
+
+/// Penalty on `amount` tokens released `remaining_time` seconds before their release time, rounded
+/// down. It never exceeds `max_penalty_bps` of the amount, nor the amount itself.
+pub fn early_unlock_penalty(amount: u64, remaining_time: u64, max_penalty_bps: u16) -> u64 {
+    let remaining_time = remaining_time.min(MAX_LOCK_DURATION);
+    let max_penalty_bps = max_penalty_bps.min(BPS_DENOMINATOR);
+    let penalty = u128::from(amount) * u128::from(max_penalty_bps) * u128::from(remaining_time)
+        / (u128::from(BPS_DENOMINATOR) * u128::from(MAX_LOCK_DURATION));
+    // Bounded by the amount, so the conversion never fails
+    penalty as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_early_unlock_penalty() {
+        assert_eq!(early_unlock_penalty(1_000, MAX_LOCK_DURATION, 5_000), 500);
+        assert_eq!(
+            early_unlock_penalty(1_000, MAX_LOCK_DURATION / 2, 5_000),
+            250
+        );
+        assert_eq!(early_unlock_penalty(1_000, 0, 5_000), 0);
+        assert_eq!(
+            early_unlock_penalty(1_000, 2 * MAX_LOCK_DURATION, 5_000),
+            500
+        );
+        assert_eq!(
+            early_unlock_penalty(u64::MAX, MAX_LOCK_DURATION, 10_000),
+            u64::MAX
+        );
+        assert_eq!(early_unlock_penalty(1_000, MAX_LOCK_DURATION, 0), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn penalty_is_bounded(amount: u64, remaining_time: u64, max_penalty_bps: u16) {
+            let penalty = early_unlock_penalty(amount, remaining_time, max_penalty_bps);
+            prop_assert!(penalty <= amount);
+            let max_penalty_bps = max_penalty_bps.min(BPS_DENOMINATOR);
+            prop_assert!(
+                u128::from(penalty) * u128::from(BPS_DENOMINATOR)
+                    <= u128::from(amount) * u128::from(max_penalty_bps)
+            );
+        }
+
+        #[test]
+        fn penalty_grows_with_remaining_time(
+            amount: u64,
+            remaining_time in 0..2 * MAX_LOCK_DURATION,
+            extra_time in 0..MAX_LOCK_DURATION,
+            max_penalty_bps in 0..=BPS_DENOMINATOR,
+        ) {
+            prop_assert!(
+                early_unlock_penalty(amount, remaining_time, max_penalty_bps)
+                    <= early_unlock_penalty(amount, remaining_time + extra_time, max_penalty_bps)
+            );
+        }
+
+        #[test]
+        fn penalty_grows_with_amount_and_rate(
+            amount in 0..u64::MAX / 2,
+            extra_amount in 0..u64::MAX / 2,
+            remaining_time: u64,
+            max_penalty_bps in 0..BPS_DENOMINATOR,
+            extra_bps in 0..BPS_DENOMINATOR,
+        ) {
+            let penalty = early_unlock_penalty(amount, remaining_time, max_penalty_bps);
+            prop_assert!(
+                penalty <= early_unlock_penalty(amount + extra_amount, remaining_time, max_penalty_bps)
+            );
+            prop_assert!(
+                penalty <= early_unlock_penalty(amount, remaining_time, max_penalty_bps + extra_bps)
+            );
+        }
+
+        #[test]
+        fn penalty_is_zero_at_release_time(amount: u64, max_penalty_bps: u16) {
+            prop_assert_eq!(early_unlock_penalty(amount, 0, max_penalty_bps), 0);
+        }
+    }
+}
@@ -1,12 +1,16 @@
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     decode_error::DecodeError,
     entrypoint::ProgramResult,
+    instruction::AccountMeta,
     msg,
-    program::{invoke, invoke_signed},
+    program::{invoke, invoke_signed, set_return_data},
     program_error::PrintProgramError,
     program_error::ProgramError,
+    program_option::COption,
     program_pack::Pack,
+    program_utils::limited_deserialize,
     pubkey::Pubkey,
     rent::Rent,
     system_instruction::create_account,
@@ -15,19 +19,90 @@ use solana_program::{
 };
 
 use num_traits::FromPrimitive;
-use spl_token::{instruction::transfer, state::Account};
+use spl_token::{
+    instruction::{burn, close_account, mint_to, set_authority, transfer, AuthorityType},
+    state::{Account, Mint},
+};
 
 use crate::{
     error::VestingError,
-    instruction::{Schedule, VestingInstruction},
-    state::{pack_schedule_into_slice, unpack_schedule, VestingSchedule, VestingScheduleHeader},
+    event::VestingEvent,
+    hook::{Hook, HookEvent, HookMode, HookPayload},
+    instruction::{BatchEntry, CreateOptions, Schedule, VestingInstruction, BATCH_ENTRY_ACCOUNTS},
+    merkle, penalty,
+    state::{
+        pack_schedule_into_slice, unpack_schedule, Airdrop, AllowlistEntry, Config, LockMode,
+        PositionRegistry, Referrer, Stats, VestingSchedule, VestingScheduleHeader,
+        LEGACY_HEADER_LEN, LEGACY_VESTING_ACCOUNT_LEN, RENEWAL_GRACE_PERIOD, RENEWAL_NOTICE_PERIOD,
+        TIER_TIME_DELTAS,
+    },
 };
 
 pub const TOKEN_MINT: Pubkey =
     solana_program::pubkey!("FrnSwyMzw2u6DB2bQUTpia9mRHqeujdUF2bomY8Zt5BX");
 
+/// Prefix of the seeds of airdrop accounts
+pub const AIRDROP_SEED: &[u8] = b"airdrop";
+
+/// Seed of the config account
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Seed of the stats account
+pub const STATS_SEED: &[u8] = b"stats";
+
+/// Prefix of the seeds of position registry accounts, followed by the owner
+pub const REGISTRY_SEED: &[u8] = b"registry";
+
+/// Seed of the receipt mint, minted 1:1 with the amount staked in unlocked stakes
+pub const RECEIPT_SEED: &[u8] = b"receipt";
+
+/// Prefix of the seeds of allowlist entries, followed by the allowlisted wallet
+pub const ALLOWLIST_SEED: &[u8] = b"allowlist";
+
+/// Prefix of the seeds of referrer accounts, followed by the referrer
+pub const REFERRER_SEED: &[u8] = b"referrer";
+
+/// Prefix of the seeds of hook authorities, followed by the vesting account of the lock. The hook
+/// authority signs the invocations of the hook of a lock and has no authority over its tokens.
+pub const HOOK_AUTHORITY_SEED: &[u8] = b"hook";
+
 pub struct Processor {}
 
+/// Accounts of a single lock creation, shared by `Create`, `CreateBatch` and `ClaimIntoLock`
+struct CreateAccounts<'a, 'b> {
+    spl_token_account: &'a AccountInfo<'b>,
+    clock_sysvar_account: &'a AccountInfo<'b>,
+    vesting_account: &'a AccountInfo<'b>,
+    vesting_token_account: &'a AccountInfo<'b>,
+    source_token_account_owner: &'a AccountInfo<'b>,
+    source_token_account: &'a AccountInfo<'b>,
+    beneficiary_token_account: &'a AccountInfo<'b>,
+    config_account: &'a AccountInfo<'b>,
+    stats_account: &'a AccountInfo<'b>,
+    receipt_mint: &'a AccountInfo<'b>,
+    /// The account of the beneficiary receiving the receipts of an unlocked stake
+    receipt_token_account: &'a AccountInfo<'b>,
+    /// The position registry of the beneficiary, recording the lock
+    registry_account: &'a AccountInfo<'b>,
+    /// The wallet creating the lock, whose locks take the reserved positions of its own registry
+    creator: &'a Pubkey,
+    signer_accounts: &'a [AccountInfo<'b>],
+}
+
+/// Accounts of the holder of a position token, passed when releasing its lock
+struct PositionHolder<'a, 'b> {
+    position_mint: &'a AccountInfo<'b>,
+    position_token_account: &'a AccountInfo<'b>,
+    holder: &'a AccountInfo<'b>,
+}
+
+/// The hook program account of a lock and the accounts forwarded to its hook
+struct HookAccounts<'a, 'b> {
+    hook_program: &'a AccountInfo<'b>,
+    hook_authority: &'a AccountInfo<'b>,
+    forwarded_accounts: &'a [AccountInfo<'b>],
+}
+
 impl Processor {
     pub fn process_init(
         program_id: &Pubkey,
@@ -41,6 +116,25 @@ impl Processor {
         let payer = next_account_info(accounts_iter)?;
         let vesting_account = next_account_info(accounts_iter)?;
 
+        Self::init_vesting_account(
+            program_id,
+            system_program_account,
+            rent_sysvar_account,
+            payer,
+            vesting_account,
+            seeds,
+        )
+    }
+
+    /// Creates the vesting account derived from the seeds, owned by the program
+    fn init_vesting_account<'a>(
+        program_id: &Pubkey,
+        system_program_account: &AccountInfo<'a>,
+        rent_sysvar_account: &AccountInfo<'a>,
+        payer: &AccountInfo<'a>,
+        vesting_account: &AccountInfo<'a>,
+        seeds: [u8; 32],
+    ) -> ProgramResult {
         // Validate that the system program account is correct
         if *system_program_account.key != system_program::ID {
             msg!("Invalid system program account");
@@ -91,8 +185,17 @@ impl Processor {
         accounts: &[AccountInfo],
         seeds: [u8; 32],
         schedule: Schedule,
+        options: CreateOptions,
     ) -> ProgramResult {
-
+        let CreateOptions {
+            revocation_authority,
+            hook,
+            ref allowlist_proof,
+            referrer,
+            position,
+            multisig_signers,
+            ..
+        } = options;
         let accounts_iter = &mut accounts.iter();
 
         let spl_token_account = next_account_info(accounts_iter)?;
@@ -101,9 +204,302 @@ impl Processor {
         let vesting_token_account = next_account_info(accounts_iter)?;
         let source_token_account_owner = next_account_info(accounts_iter)?;
         let source_token_account = next_account_info(accounts_iter)?;
-            
+        let beneficiary_token_account = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let stats_account = next_account_info(accounts_iter)?;
+        let registry_account = next_account_info(accounts_iter)?;
+        let receipt_mint = next_account_info(accounts_iter)?;
+        let receipt_token_account = next_account_info(accounts_iter)?;
+
+        Self::check_not_paused(program_id, config_account)?;
+        Self::check_allowlisted(
+            program_id,
+            config_account,
+            source_token_account_owner.key,
+            allowlist_proof.as_deref(),
+            accounts_iter,
+        )?;
+        let deposit_fee_treasury =
+            Self::next_deposit_fee_treasury(program_id, config_account, accounts_iter)?;
+
+        // Validate that the source token account owner does not refer its own lock
+        if referrer == Some(*source_token_account_owner.key) {
+            msg!("A lock can not be referred by its source token account owner");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let referral = match referrer {
+            Some(referrer) => Some(Self::next_referral_accounts(
+                program_id,
+                config_account,
+                &referrer,
+                accounts_iter,
+            )?),
+            None => None,
+        };
+        let referral_share = referral.and_then(|(_, referral_share)| referral_share);
+        let position_accounts = if position {
+            Some((
+                next_account_info(accounts_iter)?,
+                next_account_info(accounts_iter)?,
+            ))
+        } else {
+            None
+        };
+        let (signer_accounts, remaining_accounts) =
+            Self::split_signer_accounts(accounts_iter.as_slice(), multisig_signers)?;
+        let (_, hook_accounts) = Self::split_hook_accounts(hook, remaining_accounts, true)?;
+
+        // Validate that a lock created with its position token is not revocable, and that the
+        // token is minted to the beneficiary
+        if let Some((_, position_token_account)) = position_accounts {
+            if revocation_authority.is_some() {
+                msg!("Revocable locks can not have a position token");
+                return Err(ProgramError::InvalidArgument);
+            }
+            if Self::unpack_token_account(position_token_account)?.owner
+                != Self::unpack_token_account(beneficiary_token_account)?.owner
+            {
+                msg!("The position token should be minted to the beneficiary token account owner");
+                return Err(ProgramError::InvalidArgument);
+            }
+        }
+
+        // Take the deposit fee from the schedule amount, the lock holds the rest. The referrer
+        // receives its share of the fee.
+        let deposit_fee = deposit_fee_treasury.map_or(0, |(_, deposit_fee_bps)| {
+            Self::deposit_fee(schedule.amount, deposit_fee_bps)
+        });
+        let referral_fee = referral_share.map_or(0, |(_, referral_share_bps)| {
+            (u128::from(deposit_fee) * u128::from(referral_share_bps)
+                / u128::from(penalty::BPS_DENOMINATOR)) as u64
+        });
+        let schedule = Schedule {
+            amount: schedule.amount - deposit_fee,
+            ..schedule
+        };
+
+        let create_accounts = CreateAccounts {
+            spl_token_account,
+            clock_sysvar_account,
+            vesting_account,
+            vesting_token_account,
+            source_token_account_owner,
+            source_token_account,
+            beneficiary_token_account,
+            config_account,
+            stats_account,
+            receipt_mint,
+            receipt_token_account,
+            registry_account,
+            creator: source_token_account_owner.key,
+            signer_accounts,
+        };
+        let amount = schedule.amount;
+        Self::create_schedule(program_id, &create_accounts, seeds, schedule, &options, &[])?;
+
+        // Mint the position token, the lock is then released to its holder
+        if let Some((position_mint_account, position_token_account)) = position_accounts {
+            Self::mint_position_token(
+                spl_token_account,
+                vesting_account,
+                position_mint_account,
+                position_token_account,
+                seeds,
+            )?;
+            let mut header_state = Self::load_header(vesting_account)?;
+            header_state.position_mint = *position_mint_account.key;
+            header_state.pack_into_slice(
+                &mut vesting_account.data.borrow_mut()[..VestingScheduleHeader::LEN],
+            );
+        }
+
+        // Credit the referrer with the lock
+        if let Some((referrer_account, _)) = referral {
+            let tier = Self::load_header(vesting_account)?.tier;
+            let mut referrer_state = Self::load_referrer(program_id, referrer_account)?;
+            referrer_state.record_lock(tier, amount);
+            referrer_state.pack_into_slice(&mut referrer_account.data.borrow_mut());
+        }
+
+        // Transfer the deposit fee to the treasury of the config, and the referral share of the
+        // fee to the referrer
+        if let Some((deposit_fee_treasury, _)) =
+            deposit_fee_treasury.filter(|_| deposit_fee > referral_fee)
+        {
+            Self::transfer_from_source(
+                &create_accounts,
+                deposit_fee_treasury,
+                deposit_fee - referral_fee,
+                &[],
+            )?;
+        }
+        if let Some((referrer_token_account, _)) = referral_share.filter(|_| referral_fee > 0) {
+            Self::transfer_from_source(
+                &create_accounts,
+                referrer_token_account,
+                referral_fee,
+                &[],
+            )?;
+        }
+
+        Self::invoke_hook(
+            program_id,
+            hook_accounts,
+            vesting_account,
+            HookEvent::Create,
+            amount,
+        )
+    }
+
+    pub fn process_create_batch(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        entries: Vec<BatchEntry>,
+        multisig_signers: u8,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let rent_sysvar_account = next_account_info(accounts_iter)?;
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let clock_sysvar_account = next_account_info(accounts_iter)?;
+        let payer = next_account_info(accounts_iter)?;
+        let source_token_account_owner = next_account_info(accounts_iter)?;
+        let source_token_account = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let stats_account = next_account_info(accounts_iter)?;
+        let receipt_mint = next_account_info(accounts_iter)?;
+
+        Self::check_not_paused(program_id, config_account)?;
+
+        // While the allowlist is enabled, only the admin creates batches
+        Self::check_allowlist_admin(program_id, config_account, source_token_account_owner.key)?;
+
+        // Validate that the batch is not empty
+        if entries.is_empty() {
+            msg!("The batch should contain at least one lock");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Split the remaining accounts into the accounts of each lock, the deposit fee treasury
+        // and the multisig signers
+        let remaining_accounts = accounts_iter.as_slice();
+        let lock_accounts_len = entries.len() * BATCH_ENTRY_ACCOUNTS;
+        if remaining_accounts.len() < lock_accounts_len {
+            msg!("Missing accounts for {} locks", entries.len());
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (lock_accounts, remaining_accounts) = remaining_accounts.split_at(lock_accounts_len);
+        let accounts_iter = &mut remaining_accounts.iter();
+        let deposit_fee_treasury =
+            Self::next_deposit_fee_treasury(program_id, config_account, accounts_iter)?;
+        let (signer_accounts, _) =
+            Self::split_signer_accounts(accounts_iter.as_slice(), multisig_signers)?;
+
+        for (entry, lock_accounts) in entries
+            .into_iter()
+            .zip(lock_accounts.chunks_exact(BATCH_ENTRY_ACCOUNTS))
+        {
+            let vesting_account = &lock_accounts[0];
+            Self::init_vesting_account(
+                program_id,
+                system_program_account,
+                rent_sysvar_account,
+                payer,
+                vesting_account,
+                entry.seeds,
+            )?;
+
+            let create_accounts = CreateAccounts {
+                spl_token_account,
+                clock_sysvar_account,
+                vesting_account,
+                vesting_token_account: &lock_accounts[1],
+                source_token_account_owner,
+                source_token_account,
+                beneficiary_token_account: &lock_accounts[2],
+                config_account,
+                stats_account,
+                receipt_mint,
+                receipt_token_account: &lock_accounts[3],
+                registry_account: &lock_accounts[4],
+                creator: source_token_account_owner.key,
+                signer_accounts,
+            };
+
+            // Take the deposit fee from the amount of each entry, as for a single lock
+            let deposit_fee = deposit_fee_treasury.map_or(0, |(_, deposit_fee_bps)| {
+                Self::deposit_fee(entry.schedule.amount, deposit_fee_bps)
+            });
+            Self::create_schedule(
+                program_id,
+                &create_accounts,
+                entry.seeds,
+                Schedule {
+                    amount: entry.schedule.amount - deposit_fee,
+                    ..entry.schedule
+                },
+                &CreateOptions::default(),
+                &[],
+            )?;
+            if let Some((deposit_fee_treasury, _)) =
+                deposit_fee_treasury.filter(|_| deposit_fee > 0)
+            {
+                Self::transfer_from_source(
+                    &create_accounts,
+                    deposit_fee_treasury,
+                    deposit_fee,
+                    &[],
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates the accounts of a new lock, writes its schedule and transfers the locked tokens.
+    /// The release time is measured in the unit of the lock mode.
+    ///
+    /// When the source token account is owned by a program address, `source_authority_seeds` are
+    /// the seeds signing the transfer for it.
+    ///
+    /// The keeper tip must already be held by the vesting account, on top of its rent. The hook
+    /// is only recorded, the caller invokes it. The allowlist proof, position and multisig
+    /// signers of `options` are handled by the caller.
+    fn create_schedule(
+        program_id: &Pubkey,
+        accounts: &CreateAccounts,
+        seeds: [u8; 32],
+        schedule: Schedule,
+        options: &CreateOptions,
+        source_authority_seeds: &[&[u8]],
+    ) -> ProgramResult {
+        let CreateOptions {
+            revocation_authority,
+            keeper_tip,
+            auto_renew,
+            hook,
+            referrer,
+            ..
+        } = *options;
+        let CreateAccounts {
+            spl_token_account,
+            clock_sysvar_account,
+            vesting_account,
+            vesting_token_account,
+            source_token_account_owner,
+            source_token_account,
+            beneficiary_token_account,
+            config_account,
+            stats_account,
+            receipt_mint,
+            receipt_token_account,
+            registry_account,
+            creator,
+            signer_accounts,
+        } = *accounts;
+
         // Deserialize the account data into the TokenAccount struct
-        let token_account = Account::unpack(&source_token_account.data.borrow())?;
+        let token_account = Self::unpack_token_account(source_token_account)?;
 
         // Validate the mint address matches the expected token address
         if token_account.mint != TOKEN_MINT {
@@ -111,6 +507,13 @@ impl Processor {
             return Err(ProgramError::InvalidArgument);
         }
 
+        // Validate that the beneficiary token account holds the same mint
+        let beneficiary_token_account_data = Self::unpack_token_account(beneficiary_token_account)?;
+        if beneficiary_token_account_data.mint != TOKEN_MINT {
+            msg!("Invalid beneficiary token mint address");
+            return Err(ProgramError::InvalidArgument);
+        }
+
         // Validate the SPL Token Program account
         if spl_token_account.key != &spl_token::id() {
             msg!("The provided spl token program account is invalid");
@@ -130,10 +533,19 @@ impl Processor {
             return Err(ProgramError::InvalidArgument);
         }
 
-        // Validate that the source token account owner is a signer
-        if !source_token_account_owner.is_signer {
-            msg!("Source token account owner should be a signer");
-            return Err(ProgramError::InvalidArgument);
+        // Validate that the source token account owner is a signer, or that all the provided
+        // multisig signers are, unless the program signs for it. The token program checks the
+        // multisig threshold on transfer.
+        if source_authority_seeds.is_empty() {
+            if signer_accounts.is_empty() {
+                if !source_token_account_owner.is_signer {
+                    msg!("Source token account owner should be a signer");
+                    return Err(ProgramError::InvalidArgument);
+                }
+            } else if signer_accounts.iter().any(|signer| !signer.is_signer) {
+                msg!("Source token account multisig signers should be signers");
+                return Err(ProgramError::MissingRequiredSignature);
+            }
         }
 
         // Validate that the vesting account is owned by the program
@@ -143,16 +555,17 @@ impl Processor {
         }
 
         // Validate that the vesting account is not already initialized
-        let is_initialized =
-            vesting_account.try_borrow_data()?[VestingScheduleHeader::LEN - 1] == 1;
-
-        if is_initialized {
-            msg!("Cannot overwrite an existing vesting contract");
-            return Err(ProgramError::InvalidArgument);
+        match Self::load_header(vesting_account) {
+            Err(ProgramError::UninitializedAccount) => (),
+            Ok(_) => {
+                msg!("Cannot overwrite an existing vesting contract");
+                return Err(ProgramError::InvalidArgument);
+            }
+            Err(error) => return Err(error),
         }
 
         // Unpack the vesting token account and validate it
-        let vesting_token_account_data = Account::unpack(&vesting_token_account.data.borrow())?;
+        let vesting_token_account_data = Self::unpack_token_account(vesting_token_account)?;
 
         if vesting_token_account_data.owner != vesting_account_key {
             msg!("The vesting token account should be owned by the vesting account");
@@ -171,10 +584,67 @@ impl Processor {
             return Err(ProgramError::InvalidAccountData);
         }
 
+        // Validate the schedule time delta, one of the supported tiers
+        let tier = match TIER_TIME_DELTAS
+            .iter()
+            .position(|time_delta| *time_delta == schedule.time_delta)
+        {
+            Some(tier) => tier as u8,
+            None => {
+                msg!("Unsupported time delta: {}", schedule.time_delta);
+                return Err(ProgramError::InvalidInstructionData);
+            }
+        };
+
+        // Validate that unlocked stakes are not revocable, their receipts could not be burnt back
+        if tier == 0 && revocation_authority.is_some() {
+            msg!("Unlocked stakes can not be revocable");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // The lock is measured with the clock of the pool
+        let lock_mode = Self::pool_lock_mode(program_id, config_account)?;
+
+        // Validate that unlocked stakes do not renew, they have no lock period
+        if tier == 0 && auto_renew {
+            msg!("Unlocked stakes can not renew");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // Validate the amount against the limits of the config, before the lock is recorded in
+        // the stats and in the registry of the beneficiary, if it has one
+        let stats = Self::load_stats(program_id, stats_account)?;
+        let registry = Self::load_wallet_registry(
+            program_id,
+            registry_account,
+            &beneficiary_token_account_data.owner,
+        )?;
+        Self::check_limits(
+            program_id,
+            config_account,
+            tier,
+            schedule.amount,
+            stats.as_ref().map(Stats::total_locked),
+            Some(registry.as_ref().map_or(0, PositionRegistry::locked_amount)),
+        )?;
+
         // Pack the vesting schedule header into the vesting account data
         let state_header = VestingScheduleHeader {
-            destination_address: *source_token_account.key,
+            destination_address: *beneficiary_token_account.key,
             is_initialized: true,
+            revocation_authority: revocation_authority.unwrap_or_default(),
+            is_revoked: false,
+            lock_mode,
+            tier,
+            position_mint: Pubkey::default(),
+            pending_destination_address: Pubkey::default(),
+            seeds,
+            keeper_tip,
+            auto_renew,
+            hook_program: hook.map(|hook| hook.program_id).unwrap_or_default(),
+            hook_mode: hook.map_or(HookMode::Required, |hook| hook.mode),
+            referrer: referrer.unwrap_or_default(),
+            owner: beneficiary_token_account_data.owner,
         };
 
         // Validate that the schedule data is not corrupted
@@ -182,31 +652,25 @@ impl Processor {
         if data.len() != VestingScheduleHeader::LEN + VestingSchedule::LEN {
             return Err(ProgramError::InvalidAccountData);
         }
+
+        // Validate that the vesting account holds the keeper tip on top of its rent
+        let rent_exempt_balance = Rent::get()?.minimum_balance(data.len());
+        if vesting_account.lamports() < rent_exempt_balance.saturating_add(keeper_tip) {
+            msg!(
+                "The vesting account should hold the keeper tip of {} lamports",
+                keeper_tip
+            );
+            return Err(ProgramError::InsufficientFunds);
+        }
         state_header.pack_into_slice(&mut data);
 
-        // Retrieve the clock sysvar and validate schedule time delta
+        // Retrieve the clock sysvar, unlocked stakes have no release time until their withdrawal
         let clock = clock::Clock::from_account_info(&clock_sysvar_account)?;
-
-        let release_time;
-        match schedule.time_delta {
-            /* Valid time_delta values:
-             * 0: unlocked (with 7 day withdrawal period)
-             * 3 months = 3 * 30 * 86400 = 7_776_000
-             * 6 months = 6 * 30 * 86400 = 15_552_000
-             * 9 months = 9 * 30 * 86400 = 23_328_000
-             * 12 months = 12 * 30 * 86400 = 31_104_000
-             */
-            0 => {
-                release_time = 0;
-            }
-            7_776_000 | 15_552_000 | 23_328_000 | 31_104_000 => {
-                release_time = clock.unix_timestamp as u64 + schedule.time_delta;
-            }
-            _ => {
-                msg!("Unsupported time delta: {}", schedule.time_delta);
-                return Err(ProgramError::InvalidInstructionData);
-            }
-        }
+        let release_time = if schedule.time_delta == 0 {
+            0
+        } else {
+            lock_mode.now(&clock) + lock_mode.from_seconds(schedule.time_delta)
+        };
 
         // Pack the schedule data
         let state_schedule = VestingSchedule {
@@ -215,32 +679,89 @@ impl Processor {
         };
         state_schedule.pack_into_slice(&mut data[VestingScheduleHeader::LEN..]);
 
+        if let Some(mut stats) = stats {
+            stats.record_lock(tier, schedule.amount);
+            stats.pack_into_slice(&mut stats_account.data.borrow_mut());
+        }
+
+        // Record the lock in the registry of the beneficiary, if it has one
+        if let Some(mut registry) = registry {
+            let by_owner = *creator == registry.owner;
+            Self::register_position(
+                &mut registry,
+                &vesting_account_key,
+                schedule.amount,
+                by_owner,
+            );
+            registry.pack_into_slice(&mut registry_account.data.borrow_mut());
+        }
+
+        // Mint the receipts of an unlocked stake to the beneficiary
+        if tier == 0 {
+            let bump = Self::check_receipt_mint(program_id, receipt_mint)?;
+            let receipt_token_account_data = Self::unpack_token_account(receipt_token_account)?;
+            if receipt_token_account_data.mint != *receipt_mint.key {
+                msg!("Invalid receipt token mint address");
+                return Err(ProgramError::InvalidArgument);
+            }
+            if receipt_token_account_data.owner != beneficiary_token_account_data.owner {
+                msg!("The receipt token account should belong to the beneficiary token account owner");
+                return Err(ProgramError::InvalidArgument);
+            }
+            let mint_receipts = mint_to(
+                spl_token_account.key,
+                receipt_mint.key,
+                receipt_token_account.key,
+                receipt_mint.key,
+                &[],
+                schedule.amount,
+            )?;
+            invoke_signed(
+                &mint_receipts,
+                &[
+                    receipt_mint.clone(),
+                    receipt_token_account.clone(),
+                    spl_token_account.clone(),
+                ],
+                &[&[RECEIPT_SEED, &[bump]]],
+            )?;
+        }
+
         // Validate that the source token account has sufficient funds
-        if Account::unpack(&source_token_account.data.borrow())?.amount < schedule.amount {
+        if Self::unpack_token_account(source_token_account)?.amount < schedule.amount {
             msg!("The source token account has insufficient funds.");
             return Err(ProgramError::InsufficientFunds);
         }
 
         // Create the transfer instruction
+        let signer_pubkeys: Vec<&Pubkey> =
+            signer_accounts.iter().map(|signer| signer.key).collect();
         let transfer_tokens_to_vesting_account = transfer(
             spl_token_account.key,
             source_token_account.key,
             vesting_token_account.key,
             source_token_account_owner.key,
-            &[],
+            &signer_pubkeys,
             schedule.amount,
         )?;
 
         // Invoke the transfer instruction
-        invoke(
-            &transfer_tokens_to_vesting_account,
-            &[
-                source_token_account.clone(),
-                vesting_token_account.clone(),
-                spl_token_account.clone(),
-                source_token_account_owner.clone(),
-            ],
-        )?;
+        let mut transfer_accounts = vec![
+            source_token_account.clone(),
+            vesting_token_account.clone(),
+            spl_token_account.clone(),
+            source_token_account_owner.clone(),
+        ];
+        transfer_accounts.extend_from_slice(signer_accounts);
+        if source_authority_seeds.is_empty() {
+            invoke(&transfer_tokens_to_vesting_account, &transfer_accounts)?;
+        } else {
+            invoke_signed(
+                &transfer_tokens_to_vesting_account,
+                &transfer_accounts,
+                &[source_authority_seeds],
+            )?;
+        }
         Ok(())
     }
 
@@ -257,6 +778,21 @@ impl Processor {
         let vesting_token_account = next_account_info(accounts_iter)?;
         let destination_token_account = next_account_info(accounts_iter)?;
 
+        // Vesting accounts of the first layout are released with the accounts of that layout
+        if vesting_account.data_len() == LEGACY_VESTING_ACCOUNT_LEN {
+            return Self::process_legacy_unlock(
+                program_id,
+                spl_token_account,
+                clock_sysvar_account,
+                vesting_account,
+                vesting_token_account,
+                destination_token_account,
+                seeds,
+            );
+        }
+        let stats_account = next_account_info(accounts_iter)?;
+        let registry_account = next_account_info(accounts_iter)?;
+
         // Validate the SPL Token Program account
         if spl_token_account.key != &spl_token::id() {
             msg!("The provided spl token program account is invalid");
@@ -282,18 +818,35 @@ impl Processor {
             return Err(ProgramError::InvalidArgument);
         }
 
-        // Validate that the destination token account is the correct one from the schedule header
+        // Validate that the destination token account is the correct one from the schedule header, or
+        // belongs to the holder of the position token of the lock
         let packed_state = &vesting_account.data;
-        let header_state =
-            VestingScheduleHeader::unpack(&packed_state.borrow()[..VestingScheduleHeader::LEN])?;
+        let header_state = Self::load_header(vesting_account)?;
 
-        if header_state.destination_address != *destination_token_account.key {
-            msg!("Contract destination account does not matched provided account");
+        let position_holder =
+            Self::check_destination(&header_state, destination_token_account, accounts_iter)?;
+
+        // Only the holder of the position token, or the owner of the lock signing as the keeper
+        // account, can skip a skippable hook
+        let owner_signed = accounts_iter
+            .as_slice()
+            .first()
+            .is_some_and(|account| account.is_signer && *account.key == header_state.owner);
+        let (keeper_accounts, hook_accounts) = Self::split_hook_accounts(
+            header_state.hook(),
+            accounts_iter.as_slice(),
+            position_holder.is_some() || owner_signed,
+        )?;
+        let keeper_account = keeper_accounts.first();
+
+        // Validate that the lock has not been revoked
+        if header_state.is_revoked {
+            msg!("Vesting contract has been revoked");
             return Err(ProgramError::InvalidArgument);
         }
 
         // Unpack the vesting token account and validate it is owned by the vesting account
-        let vesting_token_account_data = Account::unpack(&vesting_token_account.data.borrow())?;
+        let vesting_token_account_data = Self::unpack_token_account(vesting_token_account)?;
 
         if vesting_token_account_data.owner != vesting_account_key {
             msg!("The vesting token account should be owned by the vesting account.");
@@ -312,8 +865,11 @@ impl Processor {
             return Err(ProgramError::InvalidArgument);
         }
 
-        // Check if the release time has been reached and release the amount
-        if clock.unix_timestamp as u64 >= schedule.release_time {
+        // Check if the release time has been reached and release the amount. The release time of
+        // an auto-renewing lock rolls forward at each maturity, it is never reached. A lock opted
+        // out during the grace period after a maturity is released from that maturity.
+        let now = header_state.lock_mode.now(&clock);
+        if now >= header_state.release_time(&schedule, now) {
             amount_to_transfer = schedule.amount;
             schedule.amount = 0;
         }
@@ -347,102 +903,3571 @@ impl Processor {
         )?;
 
         // Reset the unlocked amounts in the schedule to 0 to avoid re-using
+        let release_time = schedule.release_time;
         pack_schedule_into_slice(
             schedule,
             &mut packed_state.borrow_mut()[VestingScheduleHeader::LEN..],
         );
+        Self::release_from_registry(
+            program_id,
+            registry_account,
+            destination_token_account,
+            position_holder.is_some(),
+            &vesting_account_key,
+        )?;
+        if let Some(position_holder) = position_holder {
+            Self::burn_position_token(
+                spl_token_account,
+                vesting_account,
+                destination_token_account,
+                position_holder,
+            )?;
+        }
+        if let Some(keeper_account) = keeper_account {
+            Self::pay_keeper_tip(vesting_account, keeper_account)?;
+        }
 
-        Ok(())
+        Self::update_stats(program_id, stats_account, |stats| {
+            stats.record_release(&header_state, release_time, amount_to_transfer)
+        })?;
+        Self::invoke_hook(
+            program_id,
+            hook_accounts,
+            vesting_account,
+            HookEvent::Unlock,
+            amount_to_transfer,
+        )
     }
 
-    pub fn process_initialize_unlock(
+    /// Releases a vesting account of the first layout once its release time is reached. These
+    /// locks are measured in unix timestamps, and have no stats, registry, keeper tip or hook.
+    fn process_legacy_unlock<'a>(
         program_id: &Pubkey,
-        _accounts: &[AccountInfo],
+        spl_token_account: &AccountInfo<'a>,
+        clock_sysvar_account: &AccountInfo<'a>,
+        vesting_account: &AccountInfo<'a>,
+        vesting_token_account: &AccountInfo<'a>,
+        destination_token_account: &AccountInfo<'a>,
         seeds: [u8; 32],
     ) -> ProgramResult {
-        let accounts_iter = &mut _accounts.iter();
+        let mut schedule = Self::load_legacy_schedule(
+            program_id,
+            spl_token_account,
+            clock_sysvar_account,
+            vesting_account,
+            vesting_token_account,
+            destination_token_account,
+            seeds,
+        )?;
 
-        let spl_token_account = next_account_info(accounts_iter)?;
-        let clock_sysvar_account = next_account_info(accounts_iter)?;
-        let vesting_account = next_account_info(accounts_iter)?;
-        let vesting_token_account = next_account_info(accounts_iter)?;
-        let destination_token_account = next_account_info(accounts_iter)?;
+        // Ensure the schedule has been initialized (release time should not be 0)
+        if schedule.release_time == 0 {
+            msg!("Should initialize withdrawal first");
+            return Err(ProgramError::InvalidArgument);
+        }
 
-        // Validate the SPL Token Program account
-        if spl_token_account.key != &spl_token::id() {
-            msg!("The provided SPL token program account is invalid");
+        // Validate that the release time has been reached and that there is an amount to transfer
+        let clock = clock::Clock::from_account_info(clock_sysvar_account)?;
+        if (clock.unix_timestamp as u64) < schedule.release_time || schedule.amount == 0 {
+            msg!("Vesting contract has not yet reached release time");
             return Err(ProgramError::InvalidArgument);
         }
 
-        // Validate the Clock Sysvar account
-        if *clock_sysvar_account.key != clock::ID {
-            msg!("Invalid clock sysvar account");
+        let transfer_tokens_from_vesting_account = transfer(
+            spl_token_account.key,
+            vesting_token_account.key,
+            destination_token_account.key,
+            vesting_account.key,
+            &[],
+            schedule.amount,
+        )?;
+        invoke_signed(
+            &transfer_tokens_from_vesting_account,
+            &[
+                spl_token_account.clone(),
+                vesting_token_account.clone(),
+                destination_token_account.clone(),
+                vesting_account.clone(),
+            ],
+            &[&[&seeds]],
+        )?;
+
+        // Reset the unlocked amount in the schedule to 0 to avoid re-using
+        schedule.amount = 0;
+        pack_schedule_into_slice(
+            schedule,
+            &mut vesting_account.data.borrow_mut()[LEGACY_HEADER_LEN..],
+        );
+        Ok(())
+    }
+
+    /// Starts the 7 day withdrawal period of an unlocked stake of the first layout. These stakes
+    /// have no receipts to burn.
+    fn process_legacy_initialize_unlock<'a>(
+        program_id: &Pubkey,
+        spl_token_account: &AccountInfo<'a>,
+        clock_sysvar_account: &AccountInfo<'a>,
+        vesting_account: &AccountInfo<'a>,
+        vesting_token_account: &AccountInfo<'a>,
+        destination_token_account: &AccountInfo<'a>,
+        seeds: [u8; 32],
+    ) -> ProgramResult {
+        let mut schedule = Self::load_legacy_schedule(
+            program_id,
+            spl_token_account,
+            clock_sysvar_account,
+            vesting_account,
+            vesting_token_account,
+            destination_token_account,
+            seeds,
+        )?;
+
+        // Check if the vesting contract has already been fully claimed
+        if schedule.amount == 0 {
+            msg!("Vesting contract already claimed");
             return Err(ProgramError::InvalidArgument);
         }
 
-        // Validate the vesting account key derived from seeds
-        let vesting_account_key = Pubkey::create_program_address(&[&seeds], program_id)?;
-        if vesting_account_key != *vesting_account.key {
-            msg!("Invalid vesting account key");
+        // Ensure the withdrawal is not already initialized
+        if schedule.release_time != 0 {
+            msg!("Shouldn't initialize withdrawal for already initialized schedule");
             return Err(ProgramError::InvalidArgument);
         }
 
+        let clock = clock::Clock::from_account_info(clock_sysvar_account)?;
+        // Withdrawal period is 7 days = 7 * 86400 = 604_800
+        schedule.release_time = clock.unix_timestamp as u64 + 604_800;
+        pack_schedule_into_slice(
+            schedule,
+            &mut vesting_account.data.borrow_mut()[LEGACY_HEADER_LEN..],
+        );
+        Ok(())
+    }
+
+    /// Validates the accounts of an instruction on a vesting account of the first layout, and
+    /// returns its schedule
+    fn load_legacy_schedule(
+        program_id: &Pubkey,
+        spl_token_account: &AccountInfo,
+        clock_sysvar_account: &AccountInfo,
+        vesting_account: &AccountInfo,
+        vesting_token_account: &AccountInfo,
+        destination_token_account: &AccountInfo,
+        seeds: [u8; 32],
+    ) -> Result<VestingSchedule, ProgramError> {
         // Validate the SPL Token Program account
         if spl_token_account.key != &spl_token::id() {
             msg!("The provided spl token program account is invalid");
             return Err(ProgramError::InvalidArgument);
         }
 
+        // Validate that the clock sysvar account is correct
+        if *clock_sysvar_account.key != clock::ID {
+            msg!("Invalid clock sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
         // Validate that the vesting account is owned by the program
         if *vesting_account.owner != *program_id {
             msg!("Vesting account is not owned by this program");
             return Err(ProgramError::InvalidArgument);
         }
 
-        // Unpack the vesting account's state
-        let packed_state = &vesting_account.data;
-        let header_state =
-            VestingScheduleHeader::unpack(&packed_state.borrow()[..VestingScheduleHeader::LEN])?;
+        // Validate that the vesting account public key is derived from the seeds
+        let vesting_account_key = Pubkey::create_program_address(&[&seeds], program_id)?;
+        if vesting_account_key != *vesting_account.key {
+            msg!("Invalid vesting account key");
+            return Err(ProgramError::InvalidArgument);
+        }
 
-        // Validate that the destination token account matches the contract's stored destination address
-        if header_state.destination_address != *destination_token_account.key {
+        // Validate that the lock is initialized and that the destination token account is the
+        // one of its header: the destination address followed by the initialized flag
+        let data = vesting_account.data.borrow();
+        if data[32] != 1 {
+            msg!("Vesting account is not initialized");
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if data[..32] != destination_token_account.key.to_bytes() {
             msg!("Contract destination account does not matched provided account");
             return Err(ProgramError::InvalidArgument);
         }
 
-        // Unpack the vesting token account and validate ownership by the vesting account
-        let vesting_token_account_data = Account::unpack(&vesting_token_account.data.borrow())?;
-
+        // Unpack the vesting token account and validate it is owned by the vesting account
+        let vesting_token_account_data = Self::unpack_token_account(vesting_token_account)?;
         if vesting_token_account_data.owner != vesting_account_key {
             msg!("The vesting token account should be owned by the vesting account.");
             return Err(ProgramError::InvalidArgument);
         }
 
-        // Unpack the schedule data
-        let clock = clock::Clock::from_account_info(&clock_sysvar_account)?;
-        let mut schedule = unpack_schedule(&packed_state.borrow()[VestingScheduleHeader::LEN..])?;
+        unpack_schedule(&data[LEGACY_HEADER_LEN..])
+    }
 
-        // Check if the vesting contract has already been fully claimed
-        if schedule.amount == 0 {
-            msg!("Vesting contract already claimed");
+    pub fn process_unlock_many(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seeds: Vec<[u8; 32]>,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let clock_sysvar_account = next_account_info(accounts_iter)?;
+        let stats_account = next_account_info(accounts_iter)?;
+        let keeper_account = next_account_info(accounts_iter)?;
+
+        // Validate the SPL Token Program account
+        if spl_token_account.key != &spl_token::id() {
+            msg!("The provided spl token program account is invalid");
             return Err(ProgramError::InvalidArgument);
         }
 
-        // Ensure the withdrawal is not already initialized
-        if schedule.release_time != 0 {
-            msg!("Shouldn't initialize withdrawal for already initialized schedule");
+        // Validate that the clock sysvar account is correct
+        if *clock_sysvar_account.key != clock::ID {
+            msg!("Invalid clock sysvar account");
             return Err(ProgramError::InvalidArgument);
         }
 
-        // Withdrawal period is 7 days = 7 * 86400 = 604_800
-        schedule.release_time = clock.unix_timestamp as u64 + 604_800;
+        // Validate that there are locks to release, and their accounts
+        if seeds.is_empty() {
+            msg!("The instruction should contain at least one lock");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let lock_accounts = accounts_iter.as_slice();
+        if lock_accounts.len() < seeds.len() * 4 {
+            msg!("Missing accounts for {} locks", seeds.len());
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let clock = clock::Clock::from_account_info(clock_sysvar_account)?;
+        let mut released = 0;
+        let mut keeper_tips: u64 = 0;
+        for (seeds, lock_accounts) in seeds.iter().zip(lock_accounts.chunks_exact(4)) {
+            let vesting_account = &lock_accounts[0];
+            let vesting_token_account = &lock_accounts[1];
+            let destination_token_account = &lock_accounts[2];
+            let registry_account = &lock_accounts[3];
+
+            // Validate that the vesting account is owned by the program
+            if *vesting_account.owner != *program_id {
+                msg!("Vesting account is not owned by this program");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            // Validate that the vesting account public key is derived from the seeds
+            let vesting_account_key = Pubkey::create_program_address(&[seeds], program_id)?;
+            if vesting_account_key != *vesting_account.key {
+                msg!("Invalid vesting account key");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            // Skip the vesting accounts of the first layout, which only `Unlock` releases
+            if vesting_account.data_len() == LEGACY_VESTING_ACCOUNT_LEN {
+                msg!("Skipping lock {}", vesting_account_key);
+                continue;
+            }
+
+            // Validate that the destination token account is the one of the lock
+            let header_state = Self::load_header(vesting_account)?;
+            if header_state.destination_address != *destination_token_account.key {
+                msg!("Contract destination account does not matched provided account");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            // Unpack the vesting token account and validate it is owned by the vesting account
+            let vesting_token_account_data = Self::unpack_token_account(vesting_token_account)?;
+            if vesting_token_account_data.owner != vesting_account_key {
+                msg!("The vesting token account should be owned by the vesting account.");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            // Skip the locks that can not be released yet, only by the holder of their position
+            // token, or only along with their hook, which no account signs for here to skip it
+            let mut schedule =
+                unpack_schedule(&vesting_account.data.borrow()[VestingScheduleHeader::LEN..])?;
+            let now = header_state.lock_mode.now(&clock);
+            if header_state.is_revoked
+                || header_state.position_mint().is_some()
+                || header_state.hook().is_some()
+                || schedule.amount == 0
+                || schedule.release_time == 0
+                || now < header_state.release_time(&schedule, now)
+            {
+                msg!("Skipping lock {}", vesting_account_key);
+                continue;
+            }
+
+            let amount_to_transfer = schedule.amount;
+            let transfer_tokens_from_vesting_account = transfer(
+                spl_token_account.key,
+                vesting_token_account.key,
+                destination_token_account.key,
+                &vesting_account_key,
+                &[],
+                amount_to_transfer,
+            )?;
+            invoke_signed(
+                &transfer_tokens_from_vesting_account,
+                &[
+                    spl_token_account.clone(),
+                    vesting_token_account.clone(),
+                    destination_token_account.clone(),
+                    vesting_account.clone(),
+                ],
+                &[&[seeds]],
+            )?;
+
+            // Reset the unlocked amount in the schedule to 0 to avoid re-using
+            let release_time = schedule.release_time;
+            schedule.amount = 0;
+            pack_schedule_into_slice(
+                schedule,
+                &mut vesting_account.data.borrow_mut()[VestingScheduleHeader::LEN..],
+            );
+            Self::release_from_registry(
+                program_id,
+                registry_account,
+                destination_token_account,
+                false,
+                &vesting_account_key,
+            )?;
+            Self::update_stats(program_id, stats_account, |stats| {
+                stats.record_release(&header_state, release_time, amount_to_transfer)
+            })?;
+            keeper_tips += Self::pay_keeper_tip(vesting_account, keeper_account)?;
+            released += 1;
+        }
+        msg!(
+            "Released {} of {} locks, paying {} lamports of keeper tips",
+            released,
+            seeds.len(),
+            keeper_tips
+        );
+        Ok(())
+    }
+
+    pub fn process_opt_out_renewal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seeds: [u8; 32],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let clock_sysvar_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let destination_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account_owner = next_account_info(accounts_iter)?;
+
+        // Validate the Clock Sysvar account
+        if *clock_sysvar_account.key != clock::ID {
+            msg!("Invalid clock sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the vesting account is owned by the program
+        if *vesting_account.owner != *program_id {
+            msg!("Vesting account is not owned by this program");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the vesting account key derived from seeds
+        let vesting_account_key = Pubkey::create_program_address(&[&seeds], program_id)?;
+        if vesting_account_key != *vesting_account.key {
+            msg!("Invalid vesting account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let mut header_state = Self::load_header(vesting_account)?;
+
+        // Validate that the destination token account matches the contract's stored destination
+        // address, or belongs to the holder of the position token of the lock, and that its
+        // owner signed
+        Self::check_destination(&header_state, destination_token_account, accounts_iter)?;
+        let destination_token_account_data = Self::unpack_token_account(destination_token_account)?;
+        if destination_token_account_data.owner != *destination_token_account_owner.key
+            || !destination_token_account_owner.is_signer
+        {
+            msg!("The destination token account owner should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut schedule =
+            unpack_schedule(&vesting_account.data.borrow()[VestingScheduleHeader::LEN..])?;
+        if !header_state.auto_renew || header_state.is_revoked || schedule.amount == 0 {
+            msg!("The lock does not renew");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Within the grace period following a maturity, the lock is released at that maturity.
+        // Otherwise, validate that its next maturity is within the renewal notice period.
+        let clock = clock::Clock::from_account_info(clock_sysvar_account)?;
+        let lock_mode = header_state.lock_mode;
+        let now = lock_mode.now(&clock);
+        let grace_period = lock_mode.from_seconds(RENEWAL_GRACE_PERIOD);
+        let release_time = match header_state
+            .last_maturity(&schedule, now)
+            .filter(|&last_maturity| now < last_maturity.saturating_add(grace_period))
+        {
+            Some(last_maturity) => last_maturity,
+            None => {
+                let release_time = header_state.release_time(&schedule, now);
+                let notice_period = lock_mode.from_seconds(RENEWAL_NOTICE_PERIOD);
+                if now < release_time.saturating_sub(notice_period) {
+                    msg!(
+                        "Renewals can only be stopped from {}, before the release time {}",
+                        release_time.saturating_sub(notice_period),
+                        release_time
+                    );
+                    return Err(ProgramError::InvalidArgument);
+                }
+                release_time
+            }
+        };
+
+        // The lock is released at that maturity
+        header_state.auto_renew = false;
+        schedule.release_time = release_time;
+        let mut data = vesting_account.data.borrow_mut();
+        header_state.pack_into_slice(&mut data[..VestingScheduleHeader::LEN]);
+        pack_schedule_into_slice(schedule, &mut data[VestingScheduleHeader::LEN..]);
+        msg!("The lock is released at {}", release_time);
+        Ok(())
+    }
+
+    pub fn process_initialize_unlock(
+        program_id: &Pubkey,
+        _accounts: &[AccountInfo],
+        seeds: [u8; 32],
+    ) -> ProgramResult {
+        let accounts_iter = &mut _accounts.iter();
+
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let clock_sysvar_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account = next_account_info(accounts_iter)?;
+
+        // Vesting accounts of the first layout start their withdrawal period with the accounts
+        // of that layout
+        if vesting_account.data_len() == LEGACY_VESTING_ACCOUNT_LEN {
+            return Self::process_legacy_initialize_unlock(
+                program_id,
+                spl_token_account,
+                clock_sysvar_account,
+                vesting_account,
+                vesting_token_account,
+                destination_token_account,
+                seeds,
+            );
+        }
+        let stats_account = next_account_info(accounts_iter)?;
+        let receipt_mint = next_account_info(accounts_iter)?;
+        let receipt_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account_owner = next_account_info(accounts_iter)?;
+
+        // Validate the SPL Token Program account
+        if spl_token_account.key != &spl_token::id() {
+            msg!("The provided SPL token program account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the Clock Sysvar account
+        if *clock_sysvar_account.key != clock::ID {
+            msg!("Invalid clock sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the vesting account key derived from seeds
+        let vesting_account_key = Pubkey::create_program_address(&[&seeds], program_id)?;
+        if vesting_account_key != *vesting_account.key {
+            msg!("Invalid vesting account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the SPL Token Program account
+        if spl_token_account.key != &spl_token::id() {
+            msg!("The provided spl token program account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the vesting account is owned by the program
+        if *vesting_account.owner != *program_id {
+            msg!("Vesting account is not owned by this program");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Unpack the vesting account's state
+        let packed_state = &vesting_account.data;
+        let header_state = Self::load_header(vesting_account)?;
+
+        // Validate that the destination token account matches the contract's stored destination
+        // address, or belongs to the holder of the position token of the lock
+        Self::check_destination(&header_state, destination_token_account, accounts_iter)?;
+        // The destination token account owner signs below, so it can skip a skippable hook
+        let (_, hook_accounts) =
+            Self::split_hook_accounts(header_state.hook(), accounts_iter.as_slice(), true)?;
+
+        // Validate that the lock has not been revoked
+        if header_state.is_revoked {
+            msg!("Vesting contract has been revoked");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Unpack the vesting token account and validate ownership by the vesting account
+        let vesting_token_account_data = Self::unpack_token_account(vesting_token_account)?;
+
+        if vesting_token_account_data.owner != vesting_account_key {
+            msg!("The vesting token account should be owned by the vesting account.");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Unpack the schedule data
+        let clock = clock::Clock::from_account_info(&clock_sysvar_account)?;
+        let mut schedule = unpack_schedule(&packed_state.borrow()[VestingScheduleHeader::LEN..])?;
+
+        // Check if the vesting contract has already been fully claimed
+        if schedule.amount == 0 {
+            msg!("Vesting contract already claimed");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Ensure the withdrawal is not already initialized
+        if schedule.release_time != 0 {
+            msg!("Shouldn't initialize withdrawal for already initialized schedule");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the owner of the destination token account signs the burn of the receipts
+        if !destination_token_account_owner.is_signer {
+            msg!("Destination token account owner should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let destination_token_account_data = Self::unpack_token_account(destination_token_account)?;
+        if destination_token_account_data.owner != *destination_token_account_owner.key {
+            msg!("Destination token account owner is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Withdrawal period is 7 days = 7 * 86400 = 604_800
+        let lock_mode = header_state.lock_mode;
+        schedule.release_time = lock_mode.now(&clock) + lock_mode.from_seconds(604_800);
+        let amount = schedule.amount;
+
+        // Burn the receipts of the stake
+        Self::check_receipt_mint(program_id, receipt_mint)?;
+        let burn_receipts = burn(
+            spl_token_account.key,
+            receipt_token_account.key,
+            receipt_mint.key,
+            destination_token_account_owner.key,
+            &[],
+            amount,
+        )?;
+        invoke(
+            &burn_receipts,
+            &[
+                receipt_token_account.clone(),
+                receipt_mint.clone(),
+                destination_token_account_owner.clone(),
+                spl_token_account.clone(),
+            ],
+        )?;
+
+        // Pack the updated schedule back into the account data
+        pack_schedule_into_slice(
+            schedule,
+            &mut packed_state.borrow_mut()[VestingScheduleHeader::LEN..],
+        );
+
+        Self::update_stats(program_id, stats_account, |stats| {
+            stats.record_cooldown(header_state.tier, amount)
+        })?;
+        Self::invoke_hook(
+            program_id,
+            hook_accounts,
+            vesting_account,
+            HookEvent::InitializeUnlock,
+            amount,
+        )
+    }
+
+    pub fn process_revoke(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seeds: [u8; 32],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let clock_sysvar_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account = next_account_info(accounts_iter)?;
+        let revocation_authority = next_account_info(accounts_iter)?;
+        let treasury_token_account = next_account_info(accounts_iter)?;
+        let stats_account = next_account_info(accounts_iter)?;
+        let registry_account = next_account_info(accounts_iter)?;
+
+        // Validate the SPL Token Program account
+        if spl_token_account.key != &spl_token::id() {
+            msg!("The provided spl token program account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the Clock Sysvar account
+        if *clock_sysvar_account.key != clock::ID {
+            msg!("Invalid clock sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the vesting account is owned by the program
+        if *vesting_account.owner != *program_id {
+            msg!("Vesting account is not owned by this program");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the vesting account key derived from seeds
+        let vesting_account_key = Pubkey::create_program_address(&[&seeds], program_id)?;
+        if vesting_account_key != *vesting_account.key {
+            msg!("Invalid vesting account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let packed_state = &vesting_account.data;
+        let mut header_state = Self::load_header(vesting_account)?;
+
+        // Validate that the lock is revocable by the signing authority
+        if header_state.revocation_authority() != Some(*revocation_authority.key) {
+            msg!("The lock is not revocable by the provided authority");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if !revocation_authority.is_signer {
+            msg!("Revocation authority should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Validate that the lock has not been revoked already
+        if header_state.is_revoked {
+            msg!("Vesting contract has been revoked");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the destination token account matches the contract's stored destination address
+        if header_state.destination_address != *destination_token_account.key {
+            msg!("Contract destination account does not matched provided account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Unpack the vesting token account and validate ownership by the vesting account
+        let vesting_token_account_data = Self::unpack_token_account(vesting_token_account)?;
+        if vesting_token_account_data.owner != vesting_account_key {
+            msg!("The vesting token account should be owned by the vesting account.");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let clock = clock::Clock::from_account_info(&clock_sysvar_account)?;
+        let mut schedule = unpack_schedule(&packed_state.borrow()[VestingScheduleHeader::LEN..])?;
+
+        // Check if the vesting contract has already been fully claimed
+        if schedule.amount == 0 {
+            msg!("Vesting contract already claimed");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // The tokens are vested once the release time has been reached, the rest is clawed back.
+        // Auto-renewing locks are only vested once their owner opted out of the renewal.
+        let now = header_state.lock_mode.now(&clock);
+        let vested_amount =
+            if schedule.release_time != 0 && now >= header_state.release_time(&schedule, now) {
+                schedule.amount
+            } else {
+                0
+            };
+        let unvested_amount = schedule.amount - vested_amount;
+
+        for (amount, recipient) in [
+            (vested_amount, destination_token_account),
+            (unvested_amount, treasury_token_account),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            let transfer_tokens_from_vesting_account = transfer(
+                spl_token_account.key,
+                vesting_token_account.key,
+                recipient.key,
+                &vesting_account_key,
+                &[],
+                amount,
+            )?;
+            invoke_signed(
+                &transfer_tokens_from_vesting_account,
+                &[
+                    spl_token_account.clone(),
+                    vesting_token_account.clone(),
+                    recipient.clone(),
+                    vesting_account.clone(),
+                ],
+                &[&[&seeds]],
+            )?;
+        }
+
+        Self::release_from_registry(
+            program_id,
+            registry_account,
+            destination_token_account,
+            false,
+            &vesting_account_key,
+        )?;
+        Self::update_stats(program_id, stats_account, |stats| {
+            stats.record_release(&header_state, schedule.release_time, schedule.amount)
+        })?;
+
+        // Mark the lock as revoked so that it can not be unlocked anymore
+        schedule.amount = 0;
+        header_state.is_revoked = true;
+        let mut data = packed_state.borrow_mut();
+        header_state.pack_into_slice(&mut data[..VestingScheduleHeader::LEN]);
+        pack_schedule_into_slice(schedule, &mut data[VestingScheduleHeader::LEN..]);
+
+        Ok(())
+    }
+
+    /// Validates and unpacks the config account
+    fn load_config(
+        program_id: &Pubkey,
+        config_account: &AccountInfo,
+    ) -> Result<Config, ProgramError> {
+        if *config_account.owner != *program_id {
+            msg!("Config account is not owned by this program");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let config = Config::unpack(&config_account.data.borrow())?;
+        let config_account_key =
+            Pubkey::create_program_address(&[CONFIG_SEED, &[config.bump]], program_id)?;
+        if config_account_key != *config_account.key {
+            msg!("Invalid config account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(config)
+    }
+
+    /// The lock mode of the locks of the pool, set in its config. Release times are unix
+    /// timestamps while the config account is not initialized.
+    fn pool_lock_mode(
+        program_id: &Pubkey,
+        config_account: &AccountInfo,
+    ) -> Result<LockMode, ProgramError> {
+        if *config_account.owner != *program_id {
+            return Ok(LockMode::Timestamp);
+        }
+        Ok(Self::load_config(program_id, config_account)?.lock_mode)
+    }
+
+    /// Validates that the creation of locks is not paused. The config account may not be
+    /// initialized yet, in which case nothing is paused.
+    fn check_not_paused(program_id: &Pubkey, config_account: &AccountInfo) -> ProgramResult {
+        if *config_account.owner != *program_id {
+            let (config_account_key, _) = Pubkey::find_program_address(&[CONFIG_SEED], program_id);
+            if config_account_key != *config_account.key {
+                msg!("Invalid config account key");
+                return Err(ProgramError::InvalidArgument);
+            }
+            return Ok(());
+        }
+        let config = Self::load_config(program_id, config_account)?;
+        if config.is_paused {
+            msg!("The creation of locks is paused");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    /// Validates the amount of a new lock against the minimum of its tier and the caps of the
+    /// config, given the amounts held by all the locks, counted by the stats account if it is
+    /// initialized, and by the positions of the beneficiary. Without a config, only locks of 0
+    /// tokens are rejected.
+    fn check_limits(
+        program_id: &Pubkey,
+        config_account: &AccountInfo,
+        tier: u8,
+        amount: u64,
+        total_locked: Option<u64>,
+        wallet_locked: Option<u64>,
+    ) -> ProgramResult {
+        if amount == 0 {
+            msg!("A lock should hold tokens");
+            return Err(VestingError::AmountBelowMinimum.into());
+        }
+        if *config_account.owner != *program_id {
+            return Ok(());
+        }
+        let config = Self::load_config(program_id, config_account)?;
+        if config.max_total_locked != 0 {
+            let total_locked = total_locked.ok_or_else(|| {
+                msg!("The stats account should be initialized to cap the locks");
+                ProgramError::UninitializedAccount
+            })?;
+            if total_locked.saturating_add(amount) > config.max_total_locked {
+                msg!(
+                    "The locks can hold at most {} tokens",
+                    config.max_total_locked
+                );
+                return Err(VestingError::PoolCapExceeded.into());
+            }
+        }
+        Self::check_position_limits(&config, tier, amount, wallet_locked)
+    }
+
+    /// Validates the amount of a lock split off another lock or moving to another wallet against
+    /// the minimum of its tier and the wallet cap of the config, given the amount held by the
+    /// positions of its new owner, or `None` if the lock stays with its owner. The tokens stay
+    /// locked, the pool cap does not apply.
+    fn check_moved_limits(
+        program_id: &Pubkey,
+        config_account: &AccountInfo,
+        tier: u8,
+        amount: u64,
+        wallet_locked: Option<u64>,
+    ) -> ProgramResult {
+        if *config_account.owner != *program_id {
+            let (config_account_key, _) = Pubkey::find_program_address(&[CONFIG_SEED], program_id);
+            if config_account_key != *config_account.key {
+                msg!("Invalid config account key");
+                return Err(ProgramError::InvalidArgument);
+            }
+            return Ok(());
+        }
+        let config = Self::load_config(program_id, config_account)?;
+        Self::check_position_limits(&config, tier, amount, wallet_locked)
+    }
+
+    /// Validates the amount of a lock against the minimum of its tier and the wallet cap of the
+    /// config
+    fn check_position_limits(
+        config: &Config,
+        tier: u8,
+        amount: u64,
+        wallet_locked: Option<u64>,
+    ) -> ProgramResult {
+        let min_lock_amount = config.min_lock_amounts[usize::from(tier)];
+        if amount < min_lock_amount {
+            msg!(
+                "Locks of tier {} should hold at least {} tokens",
+                tier,
+                min_lock_amount
+            );
+            return Err(VestingError::AmountBelowMinimum.into());
+        }
+        if let Some(wallet_locked) = wallet_locked {
+            if config.max_locked_per_wallet != 0
+                && wallet_locked.saturating_add(amount) > config.max_locked_per_wallet
+            {
+                msg!(
+                    "The positions of a wallet can hold at most {} tokens",
+                    config.max_locked_per_wallet
+                );
+                return Err(VestingError::WalletCapExceeded.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates that the creator of locks on behalf of other wallets, which are not allowlisted
+    /// themselves, is the admin of the config while its allowlist is enabled. The config account
+    /// may not be initialized yet, in which case there is no allowlist.
+    fn check_allowlist_admin(
+        program_id: &Pubkey,
+        config_account: &AccountInfo,
+        creator: &Pubkey,
+    ) -> ProgramResult {
+        if *config_account.owner != *program_id {
+            let (config_account_key, _) = Pubkey::find_program_address(&[CONFIG_SEED], program_id);
+            if config_account_key != *config_account.key {
+                msg!("Invalid config account key");
+                return Err(ProgramError::InvalidArgument);
+            }
+            return Ok(());
+        }
+        let config = Self::load_config(program_id, config_account)?;
+        if config.is_allowlist_enabled && config.admin != *creator {
+            msg!(
+                "Only the config admin can create locks for others while the allowlist is enabled"
+            );
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    /// Validates that a wallet can create locks: while the allowlist of the config is enabled,
+    /// it proves it against the allowlist root, or the next account is its allowlist entry
+    fn check_allowlisted(
+        program_id: &Pubkey,
+        config_account: &AccountInfo,
+        wallet: &Pubkey,
+        allowlist_proof: Option<&[[u8; 32]]>,
+        accounts_iter: &mut std::slice::Iter<AccountInfo>,
+    ) -> ProgramResult {
+        if *config_account.owner != *program_id {
+            return Ok(());
+        }
+        let config = Self::load_config(program_id, config_account)?;
+        if !config.is_allowlist_enabled {
+            return Ok(());
+        }
+        let is_allowlisted = match allowlist_proof {
+            Some(proof) => merkle::verify(
+                proof,
+                &config.allowlist_root,
+                merkle::allowlist_leaf_hash(wallet),
+            ),
+            None => {
+                let allowlist_entry = next_account_info(accounts_iter)?;
+                Self::load_allowlist_entry(program_id, allowlist_entry)?.wallet == *wallet
+            }
+        };
+        if !is_allowlisted {
+            msg!("The source token account owner is not allowlisted");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    /// The deposit fee treasury of the config and the deposit fee, in basis points, none while
+    /// the config charges no deposit fee
+    fn next_deposit_fee_treasury<'a, 'b>(
+        program_id: &Pubkey,
+        config_account: &AccountInfo,
+        accounts_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    ) -> Result<Option<(&'a AccountInfo<'b>, u16)>, ProgramError> {
+        if *config_account.owner != *program_id {
+            return Ok(None);
+        }
+        let config = Self::load_config(program_id, config_account)?;
+        if config.deposit_fee_bps == 0 {
+            return Ok(None);
+        }
+        let deposit_fee_treasury = next_account_info(accounts_iter)?;
+        if *deposit_fee_treasury.key != config.deposit_fee_treasury {
+            msg!("Invalid deposit fee treasury account");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(Some((deposit_fee_treasury, config.deposit_fee_bps)))
+    }
+
+    /// The referrer account of a lock with a referrer, followed by the spl-token account of the
+    /// referrer and its share of the deposit fee, in basis points of the fee, while the config
+    /// charges a deposit fee with a referral share
+    #[allow(clippy::type_complexity)]
+    fn next_referral_accounts<'a, 'b>(
+        program_id: &Pubkey,
+        config_account: &AccountInfo,
+        referrer: &Pubkey,
+        accounts_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    ) -> Result<(&'a AccountInfo<'b>, Option<(&'a AccountInfo<'b>, u16)>), ProgramError> {
+        let referrer_account = next_account_info(accounts_iter)?;
+        if Self::load_referrer(program_id, referrer_account)?.referrer != *referrer {
+            msg!("The referrer account should belong to the referrer");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if *config_account.owner != *program_id {
+            return Ok((referrer_account, None));
+        }
+        let config = Self::load_config(program_id, config_account)?;
+        if config.deposit_fee_bps == 0 || config.referral_share_bps == 0 {
+            return Ok((referrer_account, None));
+        }
+        let referrer_token_account = next_account_info(accounts_iter)?;
+        if Self::unpack_token_account(referrer_token_account)?.owner != *referrer {
+            msg!("The referrer token account should belong to the referrer");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok((
+            referrer_account,
+            Some((referrer_token_account, config.referral_share_bps)),
+        ))
+    }
+
+    /// Validates and unpacks a referrer account
+    fn load_referrer(
+        program_id: &Pubkey,
+        referrer_account: &AccountInfo,
+    ) -> Result<Referrer, ProgramError> {
+        if *referrer_account.owner != *program_id {
+            msg!("Referrer account is not owned by this program");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let referrer = Referrer::unpack(&referrer_account.data.borrow())?;
+        let referrer_account_key = Pubkey::create_program_address(
+            &[REFERRER_SEED, referrer.referrer.as_ref(), &[referrer.bump]],
+            program_id,
+        )?;
+        if referrer_account_key != *referrer_account.key {
+            msg!("Invalid referrer account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(referrer)
+    }
+
+    /// The deposit fee taken from the amount of a new lock
+    fn deposit_fee(amount: u64, deposit_fee_bps: u16) -> u64 {
+        (u128::from(amount) * u128::from(deposit_fee_bps) / u128::from(penalty::BPS_DENOMINATOR))
+            as u64
+    }
+
+    /// Transfers tokens from the source token account of a new lock, signed by its owner or by
+    /// the multisig signers, or by the program with `source_authority_seeds` for a source token
+    /// account owned by a program address
+    fn transfer_from_source<'a>(
+        accounts: &CreateAccounts<'_, 'a>,
+        destination_token_account: &AccountInfo<'a>,
+        amount: u64,
+        source_authority_seeds: &[&[u8]],
+    ) -> ProgramResult {
+        let signer_pubkeys: Vec<&Pubkey> = accounts
+            .signer_accounts
+            .iter()
+            .map(|signer| signer.key)
+            .collect();
+        let transfer_tokens = transfer(
+            accounts.spl_token_account.key,
+            accounts.source_token_account.key,
+            destination_token_account.key,
+            accounts.source_token_account_owner.key,
+            &signer_pubkeys,
+            amount,
+        )?;
+        let mut transfer_accounts = vec![
+            accounts.source_token_account.clone(),
+            destination_token_account.clone(),
+            accounts.spl_token_account.clone(),
+            accounts.source_token_account_owner.clone(),
+        ];
+        transfer_accounts.extend_from_slice(accounts.signer_accounts);
+        if source_authority_seeds.is_empty() {
+            invoke(&transfer_tokens, &transfer_accounts)
+        } else {
+            invoke_signed(
+                &transfer_tokens,
+                &transfer_accounts,
+                &[source_authority_seeds],
+            )
+        }
+    }
+
+    /// Validates and unpacks an allowlist entry
+    fn load_allowlist_entry(
+        program_id: &Pubkey,
+        allowlist_entry: &AccountInfo,
+    ) -> Result<AllowlistEntry, ProgramError> {
+        if *allowlist_entry.owner != *program_id {
+            msg!("Allowlist entry is not owned by this program");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let entry = AllowlistEntry::unpack(&allowlist_entry.data.borrow())?;
+        let allowlist_entry_key = Pubkey::create_program_address(
+            &[ALLOWLIST_SEED, entry.wallet.as_ref(), &[entry.bump]],
+            program_id,
+        )?;
+        if allowlist_entry_key != *allowlist_entry.key {
+            msg!("Invalid allowlist entry key");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(entry)
+    }
+
+    /// Validates and unpacks the stats account. The stats account may not be initialized yet,
+    /// in which case nothing is counted.
+    fn load_stats(
+        program_id: &Pubkey,
+        stats_account: &AccountInfo,
+    ) -> Result<Option<Stats>, ProgramError> {
+        if *stats_account.owner != *program_id {
+            let (stats_account_key, _) = Pubkey::find_program_address(&[STATS_SEED], program_id);
+            if stats_account_key != *stats_account.key {
+                msg!("Invalid stats account key");
+                return Err(ProgramError::InvalidArgument);
+            }
+            return Ok(None);
+        }
+        let stats = Stats::unpack(&stats_account.data.borrow())?;
+        let stats_account_key =
+            Pubkey::create_program_address(&[STATS_SEED, &[stats.bump]], program_id)?;
+        if stats_account_key != *stats_account.key {
+            msg!("Invalid stats account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(Some(stats))
+    }
+
+    /// Applies an update to the stats account, if it is initialized
+    fn update_stats(
+        program_id: &Pubkey,
+        stats_account: &AccountInfo,
+        update: impl FnOnce(&mut Stats),
+    ) -> ProgramResult {
+        if let Some(mut stats) = Self::load_stats(program_id, stats_account)? {
+            update(&mut stats);
+            stats.pack_into_slice(&mut stats_account.data.borrow_mut());
+        }
+        Ok(())
+    }
+
+    /// Unpacks a spl-token account, which must be owned by the spl-token program
+    fn unpack_token_account(token_account: &AccountInfo) -> Result<Account, ProgramError> {
+        if *token_account.owner != spl_token::id() {
+            msg!("Token account is not owned by the spl token program");
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Account::unpack(&token_account.data.borrow())
+    }
+
+    /// Validates the receipt mint account key and returns its bump seed
+    fn check_receipt_mint(
+        program_id: &Pubkey,
+        receipt_mint: &AccountInfo,
+    ) -> Result<u8, ProgramError> {
+        let (receipt_mint_key, bump) = Pubkey::find_program_address(&[RECEIPT_SEED], program_id);
+        if receipt_mint_key != *receipt_mint.key {
+            msg!("Invalid receipt mint key");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(bump)
+    }
+
+    /// Validates and unpacks a position registry account
+    fn load_registry(
+        program_id: &Pubkey,
+        registry_account: &AccountInfo,
+    ) -> Result<PositionRegistry, ProgramError> {
+        if *registry_account.owner != *program_id {
+            msg!("Registry account is not owned by this program");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let registry = PositionRegistry::unpack(&registry_account.data.borrow())?;
+        let registry_account_key = Pubkey::create_program_address(
+            &[REGISTRY_SEED, registry.owner.as_ref(), &[registry.bump]],
+            program_id,
+        )?;
+        if registry_account_key != *registry_account.key {
+            msg!("Invalid registry account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(registry)
+    }
+
+    /// Removes a lock from the position registry of a wallet, unless the wallet has none
+    fn remove_from_registry(
+        program_id: &Pubkey,
+        registry_account: &AccountInfo,
+        owner: &Pubkey,
+        vesting_account_key: &Pubkey,
+    ) -> ProgramResult {
+        Self::update_registry(program_id, registry_account, owner, |registry| {
+            registry.remove(vesting_account_key);
+            Ok(())
+        })
+    }
+
+    /// Records a lock in a position registry. Only the locks registered by the owner of the
+    /// registry take its reserved positions. Once the registry has no position left for it, the
+    /// lock is left unregistered: the registry does not cap the locks of a wallet.
+    fn register_position(
+        registry: &mut PositionRegistry,
+        vesting_account_key: &Pubkey,
+        amount: u64,
+        by_owner: bool,
+    ) {
+        if registry
+            .add(*vesting_account_key, amount, by_owner)
+            .is_none()
+        {
+            msg!("The position registry is full, the lock is not registered");
+        }
+    }
+
+    /// Records a lock moving to the position registry of its new owner, which should be
+    /// initialized, within the minimum of its tier and the wallet cap of the config. Only the
+    /// new owner, as the signer, reaches its reserved positions.
+    #[allow(clippy::too_many_arguments)]
+    fn register_moved_lock(
+        program_id: &Pubkey,
+        config_account: &AccountInfo,
+        registry_account: &AccountInfo,
+        owner: &Pubkey,
+        previous_owner: &Pubkey,
+        signer: &Pubkey,
+        vesting_account_key: &Pubkey,
+        tier: u8,
+        amount: u64,
+    ) -> ProgramResult {
+        let mut registry = Self::load_registry(program_id, registry_account)?;
+        if registry.owner != *owner {
+            msg!("The registry should belong to the new destination token account owner");
+            return Err(ProgramError::InvalidArgument);
+        }
+        // Within a wallet, the tokens already count toward its cap
+        let wallet_locked = (owner != previous_owner).then(|| registry.locked_amount());
+        Self::check_moved_limits(program_id, config_account, tier, amount, wallet_locked)?;
+        Self::register_position(&mut registry, vesting_account_key, amount, signer == owner);
+        registry.pack_into_slice(&mut registry_account.data.borrow_mut());
+        Ok(())
+    }
+
+    /// Zeroes the amount of a released lock in the position registry of the owner of its
+    /// destination, unless the wallet has none, so that it no longer counts toward the wallet
+    /// cap. A lock with a position token stays in the registry of the wallet which minted it,
+    /// updated if the given registry holds the lock.
+    fn release_from_registry(
+        program_id: &Pubkey,
+        registry_account: &AccountInfo,
+        destination_token_account: &AccountInfo,
+        has_position: bool,
+        vesting_account_key: &Pubkey,
+    ) -> ProgramResult {
+        if !has_position {
+            let owner = Self::unpack_token_account(destination_token_account)?.owner;
+            return Self::update_registry(program_id, registry_account, &owner, |registry| {
+                registry.set_amount(vesting_account_key, 0);
+                Ok(())
+            });
+        }
+        if *registry_account.owner == *program_id {
+            let mut registry = Self::load_registry(program_id, registry_account)?;
+            if registry.set_amount(vesting_account_key, 0) {
+                registry.pack_into_slice(&mut registry_account.data.borrow_mut());
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies an update to the position registry of a wallet, unless the wallet has none
+    fn update_registry(
+        program_id: &Pubkey,
+        registry_account: &AccountInfo,
+        owner: &Pubkey,
+        update: impl FnOnce(&mut PositionRegistry) -> ProgramResult,
+    ) -> ProgramResult {
+        if let Some(mut registry) = Self::load_wallet_registry(program_id, registry_account, owner)?
+        {
+            update(&mut registry)?;
+            registry.pack_into_slice(&mut registry_account.data.borrow_mut());
+        }
+        Ok(())
+    }
+
+    /// Validates and unpacks the position registry of a wallet, or returns `None` if the wallet
+    /// has none
+    fn load_wallet_registry(
+        program_id: &Pubkey,
+        registry_account: &AccountInfo,
+        owner: &Pubkey,
+    ) -> Result<Option<PositionRegistry>, ProgramError> {
+        if *registry_account.owner == *program_id {
+            let registry = Self::load_registry(program_id, registry_account)?;
+            if registry.owner != *owner {
+                msg!("The registry should belong to the destination token account owner");
+                return Err(ProgramError::InvalidArgument);
+            }
+            return Ok(Some(registry));
+        }
+        let (registry_account_key, _) =
+            Pubkey::find_program_address(&[REGISTRY_SEED, owner.as_ref()], program_id);
+        if registry_account_key != *registry_account.key {
+            msg!("Invalid registry account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(None)
+    }
+
+    /// Validates the destination token account of a released lock: the destination address of
+    /// the lock, or for a lock with a position token, an account of the token holder. The
+    /// accounts of the holder then follow the other accounts of the instruction.
+    fn check_destination<'a, 'b>(
+        header_state: &VestingScheduleHeader,
+        destination_token_account: &AccountInfo,
+        accounts_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    ) -> Result<Option<PositionHolder<'a, 'b>>, ProgramError> {
+        let position_mint = match header_state.position_mint() {
+            Some(position_mint) => position_mint,
+            None => {
+                if header_state.destination_address != *destination_token_account.key {
+                    msg!("Contract destination account does not matched provided account");
+                    return Err(ProgramError::InvalidArgument);
+                }
+                return Ok(None);
+            }
+        };
+        let position_holder = PositionHolder {
+            position_mint: next_account_info(accounts_iter)?,
+            position_token_account: next_account_info(accounts_iter)?,
+            holder: next_account_info(accounts_iter)?,
+        };
+
+        if *position_holder.position_mint.key != position_mint {
+            msg!("Invalid position mint");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the holder of the position token signed
+        let position_token_account_data =
+            Self::unpack_token_account(position_holder.position_token_account)?;
+        if position_token_account_data.mint != position_mint
+            || position_token_account_data.amount != 1
+        {
+            msg!("The position token account should hold the position token");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if position_token_account_data.owner != *position_holder.holder.key
+            || !position_holder.holder.is_signer
+        {
+            msg!("The position token holder should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let destination_token_account_data = Self::unpack_token_account(destination_token_account)?;
+        if destination_token_account_data.owner != *position_holder.holder.key {
+            msg!("The destination token account should belong to the position token holder");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(Some(position_holder))
+    }
+
+    /// Burns the position token of a released lock. The destination token account becomes the
+    /// destination of the lock, so that its owner can close it.
+    fn burn_position_token<'a>(
+        spl_token_account: &AccountInfo<'a>,
+        vesting_account: &AccountInfo<'a>,
+        destination_token_account: &AccountInfo<'a>,
+        position_holder: PositionHolder<'_, 'a>,
+    ) -> ProgramResult {
+        let burn_position_token = burn(
+            spl_token_account.key,
+            position_holder.position_token_account.key,
+            position_holder.position_mint.key,
+            position_holder.holder.key,
+            &[],
+            1,
+        )?;
+        invoke(
+            &burn_position_token,
+            &[
+                spl_token_account.clone(),
+                position_holder.position_token_account.clone(),
+                position_holder.position_mint.clone(),
+                position_holder.holder.clone(),
+            ],
+        )?;
+
+        let mut header_state = Self::load_header(vesting_account)?;
+        header_state.destination_address = *destination_token_account.key;
+        header_state
+            .pack_into_slice(&mut vesting_account.data.borrow_mut()[..VestingScheduleHeader::LEN]);
+        Ok(())
+    }
+
+    /// Pays the keeper tip of a released lock from the vesting account to the keeper account,
+    /// returning the amount paid
+    fn pay_keeper_tip(
+        vesting_account: &AccountInfo,
+        keeper_account: &AccountInfo,
+    ) -> Result<u64, ProgramError> {
+        let mut header_state = Self::load_header(vesting_account)?;
+        let keeper_tip = header_state.keeper_tip;
+        if keeper_tip == 0 {
+            return Ok(0);
+        }
+        header_state.keeper_tip = 0;
+        header_state
+            .pack_into_slice(&mut vesting_account.data.borrow_mut()[..VestingScheduleHeader::LEN]);
+
+        **vesting_account.lamports.borrow_mut() -= keeper_tip;
+        **keeper_account.lamports.borrow_mut() += keeper_tip;
+        Ok(keeper_tip)
+    }
+
+    /// Unpacks the header of a vesting account, rejecting the vesting accounts of the first layout
+    /// instead of reading past their end
+    fn load_header(vesting_account: &AccountInfo) -> Result<VestingScheduleHeader, ProgramError> {
+        if vesting_account.data_len() != VestingScheduleHeader::LEN + VestingSchedule::LEN {
+            msg!("The vesting account does not have the layout of this program version");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        VestingScheduleHeader::unpack(&vesting_account.data.borrow()[..VestingScheduleHeader::LEN])
+    }
+
+    /// Splits the signer accounts of a multisig source token account owner, as many as given in
+    /// the instruction data, from the trailing accounts of an instruction
+    fn split_signer_accounts<'a, 'b>(
+        accounts: &'a [AccountInfo<'b>],
+        multisig_signers: u8,
+    ) -> Result<(&'a [AccountInfo<'b>], &'a [AccountInfo<'b>]), ProgramError> {
+        let multisig_signers = usize::from(multisig_signers);
+        if accounts.len() < multisig_signers {
+            msg!("Missing the {} multisig signer accounts", multisig_signers);
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        Ok(accounts.split_at(multisig_signers))
+    }
+
+    /// Splits the trailing accounts of an instruction at the hook program account of a lock: the
+    /// accounts before it belong to the instruction, it is followed by the hook authority of the
+    /// lock and the accounts forwarded to the hook.
+    /// Only a lock with a skippable hook can leave out its hook program account, and only when
+    /// `can_skip`, for an instruction signed by the owner of the lock.
+    fn split_hook_accounts<'a, 'b>(
+        hook: Option<Hook>,
+        accounts: &'a [AccountInfo<'b>],
+        can_skip: bool,
+    ) -> Result<(&'a [AccountInfo<'b>], Option<HookAccounts<'a, 'b>>), ProgramError> {
+        let hook = match hook {
+            Some(hook) => hook,
+            None => return Ok((accounts, None)),
+        };
+        match accounts
+            .iter()
+            .position(|account| *account.key == hook.program_id)
+        {
+            Some(index) => {
+                let hook_authority = accounts.get(index + 1).ok_or_else(|| {
+                    msg!("The hook authority account of the lock is missing");
+                    ProgramError::NotEnoughAccountKeys
+                })?;
+                Ok((
+                    &accounts[..index],
+                    Some(HookAccounts {
+                        hook_program: &accounts[index],
+                        hook_authority,
+                        forwarded_accounts: &accounts[index + 2..],
+                    }),
+                ))
+            }
+            None if hook.mode == HookMode::Skippable && can_skip => {
+                msg!("Skipping the hook of the lock");
+                Ok((accounts, None))
+            }
+            None if hook.mode == HookMode::Skippable => {
+                msg!("The hook of the lock can only be skipped by its owner");
+                Err(ProgramError::MissingRequiredSignature)
+            }
+            None => {
+                msg!("The hook program account of the lock is missing");
+                Err(ProgramError::NotEnoughAccountKeys)
+            }
+        }
+    }
+
+    /// Invokes the hook of a lock after one of its lifecycle events, signed by the hook authority
+    /// of the lock rather than the vesting account, which owns the vesting token account. The
+    /// payload describes the lock as updated by the event.
+    fn invoke_hook<'b>(
+        program_id: &Pubkey,
+        hook_accounts: Option<HookAccounts<'_, 'b>>,
+        vesting_account: &AccountInfo<'b>,
+        event: HookEvent,
+        amount: u64,
+    ) -> ProgramResult {
+        let HookAccounts {
+            hook_program,
+            hook_authority,
+            forwarded_accounts,
+        } = match hook_accounts {
+            Some(hook_accounts) => hook_accounts,
+            None => return Ok(()),
+        };
+        let (hook_authority_key, bump) = Pubkey::find_program_address(
+            &[HOOK_AUTHORITY_SEED, vesting_account.key.as_ref()],
+            program_id,
+        );
+        if hook_authority_key != *hook_authority.key {
+            msg!("Invalid hook authority account");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let header_state = Self::load_header(vesting_account)?;
+        let schedule =
+            unpack_schedule(&vesting_account.data.borrow()[VestingScheduleHeader::LEN..])?;
+        let payload = HookPayload {
+            event,
+            vesting_account: *vesting_account.key,
+            destination_address: header_state.destination_address,
+            tier: header_state.tier,
+            amount,
+            release_time: schedule.release_time,
+        };
+        let forwarded_metas: Vec<AccountMeta> = forwarded_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: *account.key,
+                is_signer: account.is_signer,
+                is_writable: account.is_writable,
+            })
+            .collect();
+        let mut account_infos = vec![hook_authority.clone(), vesting_account.clone()];
+        account_infos.extend_from_slice(forwarded_accounts);
+        account_infos.push(hook_program.clone());
+        invoke_signed(
+            &payload.instruction(hook_program.key, hook_authority.key, &forwarded_metas),
+            &account_infos,
+            &[&[HOOK_AUTHORITY_SEED, vesting_account.key.as_ref(), &[bump]]],
+        )
+    }
+
+    /// Validates the early unlock penalty settings
+    fn validate_penalty(
+        penalty_treasury_token_account: &AccountInfo,
+        max_penalty_bps: u16,
+    ) -> ProgramResult {
+        if max_penalty_bps > penalty::BPS_DENOMINATOR {
+            msg!("The maximum penalty should not exceed 100%");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let penalty_treasury_data = Self::unpack_token_account(penalty_treasury_token_account)?;
+        if penalty_treasury_data.mint != TOKEN_MINT {
+            msg!("Invalid penalty treasury token mint address");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    pub fn process_init_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        max_penalty_bps: u16,
+        lock_mode: LockMode,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let rent_sysvar_account = next_account_info(accounts_iter)?;
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let penalty_treasury_token_account = next_account_info(accounts_iter)?;
+        let guardian = next_account_info(accounts_iter)?;
+        let program_data_account = next_account_info(accounts_iter)?;
+
+        // Validate that the system program account is correct
+        if *system_program_account.key != system_program::ID {
+            msg!("Invalid system program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the rent sysvar account is correct
+        if *rent_sysvar_account.key != rent::ID {
+            msg!("Invalid rent sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the admin is a signer
+        if !admin.is_signer {
+            msg!("Admin should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Validate that the admin is the upgrade authority of the program, so that nobody else
+        // creates the config once the program is deployed
+        let (program_data_account_key, _) =
+            Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+        if program_data_account_key != *program_data_account.key
+            || *program_data_account.owner != bpf_loader_upgradeable::id()
+        {
+            msg!("Invalid program data account");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let upgrade_authority = match limited_deserialize(
+            &program_data_account.data.borrow(),
+            UpgradeableLoaderState::size_of_programdata_metadata() as u64,
+        ) {
+            Ok(UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address,
+                ..
+            }) => upgrade_authority_address,
+            _ => {
+                msg!("Invalid program data account");
+                return Err(ProgramError::InvalidAccountData);
+            }
+        };
+        if upgrade_authority != Some(*admin.key) {
+            msg!("The admin should be the upgrade authority of the program");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the config account key, there is a single config per program
+        let (config_account_key, bump) = Pubkey::find_program_address(&[CONFIG_SEED], program_id);
+        if config_account_key != *config_account.key {
+            msg!("Provided config account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Self::validate_penalty(penalty_treasury_token_account, max_penalty_bps)?;
+
+        let rent = Rent::from_account_info(rent_sysvar_account)?;
+        let init_config_account = create_account(
+            admin.key,
+            &config_account_key,
+            rent.minimum_balance(Config::LEN),
+            Config::LEN as u64,
+            program_id,
+        );
+        invoke_signed(
+            &init_config_account,
+            &[
+                system_program_account.clone(),
+                admin.clone(),
+                config_account.clone(),
+            ],
+            &[&[CONFIG_SEED, &[bump]]],
+        )?;
+
+        let config = Config {
+            admin: *admin.key,
+            penalty_treasury: *penalty_treasury_token_account.key,
+            max_penalty_bps,
+            bump,
+            is_initialized: true,
+            guardian: *guardian.key,
+            is_paused: false,
+            is_emergency_mode: false,
+            is_allowlist_enabled: false,
+            allowlist_root: [0; 32],
+            max_total_locked: 0,
+            max_locked_per_wallet: 0,
+            min_lock_amounts: [0; 5],
+            deposit_fee_treasury: Pubkey::default(),
+            deposit_fee_bps: 0,
+            referral_share_bps: 0,
+            lock_mode,
+        };
+        config.pack_into_slice(&mut config_account.data.borrow_mut());
+        Ok(())
+    }
+
+    pub fn process_update_penalty(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        max_penalty_bps: u16,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let penalty_treasury_token_account = next_account_info(accounts_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+
+        // Validate that the admin signed
+        if config.admin != *admin.key || !admin.is_signer {
+            msg!("The config admin should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Self::validate_penalty(penalty_treasury_token_account, max_penalty_bps)?;
+
+        config.penalty_treasury = *penalty_treasury_token_account.key;
+        config.max_penalty_bps = max_penalty_bps;
+        config.pack_into_slice(&mut config_account.data.borrow_mut());
+        Ok(())
+    }
+
+    pub fn process_init_stats(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let rent_sysvar_account = next_account_info(accounts_iter)?;
+        let payer = next_account_info(accounts_iter)?;
+        let stats_account = next_account_info(accounts_iter)?;
+
+        // Validate that the system program account is correct
+        if *system_program_account.key != system_program::ID {
+            msg!("Invalid system program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the rent sysvar account is correct
+        if *rent_sysvar_account.key != rent::ID {
+            msg!("Invalid rent sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the stats account key, there is a single stats account per program
+        let (stats_account_key, bump) = Pubkey::find_program_address(&[STATS_SEED], program_id);
+        if stats_account_key != *stats_account.key {
+            msg!("Provided stats account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let rent = Rent::from_account_info(rent_sysvar_account)?;
+        let init_stats_account = create_account(
+            payer.key,
+            &stats_account_key,
+            rent.minimum_balance(Stats::LEN),
+            Stats::LEN as u64,
+            program_id,
+        );
+        invoke_signed(
+            &init_stats_account,
+            &[
+                system_program_account.clone(),
+                payer.clone(),
+                stats_account.clone(),
+            ],
+            &[&[STATS_SEED, &[bump]]],
+        )?;
+
+        let stats = Stats {
+            tiers: Default::default(),
+            bump,
+            is_initialized: true,
+        };
+        stats.pack_into_slice(&mut stats_account.data.borrow_mut());
+        Ok(())
+    }
+
+    pub fn process_init_receipt_mint(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let rent_sysvar_account = next_account_info(accounts_iter)?;
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let payer = next_account_info(accounts_iter)?;
+        let receipt_mint = next_account_info(accounts_iter)?;
+        let token_mint = next_account_info(accounts_iter)?;
+
+        // Validate that the system program account is correct
+        if *system_program_account.key != system_program::ID {
+            msg!("Invalid system program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the rent sysvar account is correct
+        if *rent_sysvar_account.key != rent::ID {
+            msg!("Invalid rent sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the SPL Token Program account
+        if spl_token_account.key != &spl_token::id() {
+            msg!("The provided spl token program account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the locked token mint, the receipts share its decimals
+        if *token_mint.key != TOKEN_MINT || *token_mint.owner != spl_token::id() {
+            msg!("Invalid token mint account");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let decimals = Mint::unpack(&token_mint.data.borrow())?.decimals;
+
+        // Validate the receipt mint account key, there is a single receipt mint per program
+        let bump = Self::check_receipt_mint(program_id, receipt_mint)?;
+
+        let rent = Rent::from_account_info(rent_sysvar_account)?;
+        let init_receipt_mint_account = create_account(
+            payer.key,
+            receipt_mint.key,
+            rent.minimum_balance(Mint::LEN),
+            Mint::LEN as u64,
+            &spl_token::id(),
+        );
+        invoke_signed(
+            &init_receipt_mint_account,
+            &[
+                system_program_account.clone(),
+                payer.clone(),
+                receipt_mint.clone(),
+            ],
+            &[&[RECEIPT_SEED, &[bump]]],
+        )?;
+
+        // The receipt mint is its own mint authority
+        let init_receipt_mint = spl_token::instruction::initialize_mint2(
+            spl_token_account.key,
+            receipt_mint.key,
+            receipt_mint.key,
+            None,
+            decimals,
+        )?;
+        invoke(
+            &init_receipt_mint,
+            &[receipt_mint.clone(), spl_token_account.clone()],
+        )
+    }
+
+    pub fn process_get_stats(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let stats_account = next_account_info(accounts_iter)?;
+
+        if Self::load_stats(program_id, stats_account)?.is_none() {
+            msg!("Stats account is not initialized");
+            return Err(ProgramError::UninitializedAccount);
+        }
+        set_return_data(&stats_account.data.borrow()[..Stats::LEN]);
+        Ok(())
+    }
+
+    pub fn process_init_registry(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let rent_sysvar_account = next_account_info(accounts_iter)?;
+        let payer = next_account_info(accounts_iter)?;
+        let owner = next_account_info(accounts_iter)?;
+        let registry_account = next_account_info(accounts_iter)?;
+
+        // Validate that the system program account is correct
+        if *system_program_account.key != system_program::ID {
+            msg!("Invalid system program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the rent sysvar account is correct
+        if *rent_sysvar_account.key != rent::ID {
+            msg!("Invalid rent sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the registry account key, derived from its owner
+        let (registry_account_key, bump) =
+            Pubkey::find_program_address(&[REGISTRY_SEED, owner.key.as_ref()], program_id);
+        if registry_account_key != *registry_account.key {
+            msg!("Provided registry account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // The registry may already exist, so that it can be created along with every lock
+        if *registry_account.owner == *program_id {
+            Self::load_registry(program_id, registry_account)?;
+            return Ok(());
+        }
+
+        let rent = Rent::from_account_info(rent_sysvar_account)?;
+        let init_registry_account = create_account(
+            payer.key,
+            &registry_account_key,
+            rent.minimum_balance(PositionRegistry::LEN),
+            PositionRegistry::LEN as u64,
+            program_id,
+        );
+        invoke_signed(
+            &init_registry_account,
+            &[
+                system_program_account.clone(),
+                payer.clone(),
+                registry_account.clone(),
+            ],
+            &[&[REGISTRY_SEED, owner.key.as_ref(), &[bump]]],
+        )?;
+
+        let mut registry = PositionRegistry::unpack_from_slice(&registry_account.data.borrow())?;
+        registry.owner = *owner.key;
+        registry.bump = bump;
+        registry.is_initialized = true;
+        registry.pack_into_slice(&mut registry_account.data.borrow_mut());
+        Ok(())
+    }
+
+    pub fn process_close(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seeds: [u8; 32],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account_owner = next_account_info(accounts_iter)?;
+        let registry_account = next_account_info(accounts_iter)?;
+
+        // Validate the SPL Token Program account
+        if spl_token_account.key != &spl_token::id() {
+            msg!("The provided spl token program account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the vesting account is owned by the program
+        if *vesting_account.owner != *program_id {
+            msg!("Vesting account is not owned by this program");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the vesting account public key is derived from the seeds
+        let vesting_account_key = Pubkey::create_program_address(&[&seeds], program_id)?;
+        if vesting_account_key != *vesting_account.key {
+            msg!("Invalid vesting account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let header_state = Self::load_header(vesting_account)?;
+        let schedule =
+            unpack_schedule(&vesting_account.data.borrow()[VestingScheduleHeader::LEN..])?;
+
+        // Validate that the destination token account is the one of the lock, and that its
+        // owner signed
+        if header_state.destination_address != *destination_token_account.key {
+            msg!("Contract destination account does not matched provided account");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let destination_token_account_data = Self::unpack_token_account(destination_token_account)?;
+        if destination_token_account_data.owner != *destination_token_account_owner.key
+            || !destination_token_account_owner.is_signer
+        {
+            msg!("The destination token account owner should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Validate that all the tokens of the lock have been released or revoked
+        if schedule.amount != 0 {
+            msg!("Vesting contract still holds tokens");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Remove the lock from the registry of the owner
+        Self::remove_from_registry(
+            program_id,
+            registry_account,
+            destination_token_account_owner.key,
+            &vesting_account_key,
+        )?;
+
+        // Close the vesting token account, emptied by the release of the lock
+        let vesting_token_account_data = Self::unpack_token_account(vesting_token_account)?;
+        if vesting_token_account_data.owner != vesting_account_key {
+            msg!("The vesting token account should be owned by the vesting account.");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Self::close_lock_accounts(
+            spl_token_account,
+            vesting_account,
+            vesting_token_account,
+            destination_token_account_owner,
+            seeds,
+        )
+    }
+
+    /// Closes the empty vesting spl-token account and the vesting account of a lock, returning
+    /// their rent to the recipient. The seeds can then be used for a new lock.
+    fn close_lock_accounts<'a>(
+        spl_token_account: &AccountInfo<'a>,
+        vesting_account: &AccountInfo<'a>,
+        vesting_token_account: &AccountInfo<'a>,
+        recipient: &AccountInfo<'a>,
+        seeds: [u8; 32],
+    ) -> ProgramResult {
+        let close_vesting_token_account = close_account(
+            spl_token_account.key,
+            vesting_token_account.key,
+            recipient.key,
+            vesting_account.key,
+            &[],
+        )?;
+        invoke_signed(
+            &close_vesting_token_account,
+            &[
+                spl_token_account.clone(),
+                vesting_token_account.clone(),
+                recipient.clone(),
+                vesting_account.clone(),
+            ],
+            &[&[&seeds]],
+        )?;
+
+        let lamports = vesting_account.lamports();
+        **vesting_account.lamports.borrow_mut() = 0;
+        **recipient.lamports.borrow_mut() += lamports;
+        vesting_account.data.borrow_mut().fill(0);
+        Ok(())
+    }
+
+    /// Validates a lock that is split or merged: its vesting account and vesting spl-token
+    /// account, and that it holds tokens, is not revoked and has no position token
+    fn load_movable_lock(
+        program_id: &Pubkey,
+        vesting_account: &AccountInfo,
+        vesting_token_account: &AccountInfo,
+        seeds: [u8; 32],
+    ) -> Result<(VestingScheduleHeader, VestingSchedule), ProgramError> {
+        // Validate that the vesting account is owned by the program
+        if *vesting_account.owner != *program_id {
+            msg!("Vesting account is not owned by this program");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the vesting account public key is derived from the seeds
+        let vesting_account_key = Pubkey::create_program_address(&[&seeds], program_id)?;
+        if vesting_account_key != *vesting_account.key {
+            msg!("Invalid vesting account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Unpack the vesting token account and validate ownership by the vesting account
+        let vesting_token_account_data = Self::unpack_token_account(vesting_token_account)?;
+        if vesting_token_account_data.owner != vesting_account_key {
+            msg!("The vesting token account should be owned by the vesting account.");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let header_state = Self::load_header(vesting_account)?;
+        let schedule =
+            unpack_schedule(&vesting_account.data.borrow()[VestingScheduleHeader::LEN..])?;
+        if !header_state.is_initialized {
+            msg!("Vesting contract is not initialized");
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if header_state.is_revoked {
+            msg!("Vesting contract has been revoked");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if schedule.amount == 0 {
+            msg!("Vesting contract already claimed");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if header_state.position_mint().is_some() {
+            msg!("Locks with a position token can not be split or merged");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok((header_state, schedule))
+    }
+
+    pub fn process_split(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seeds: [u8; 32],
+        new_seeds: [u8; 32],
+        amount: u64,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let rent_sysvar_account = next_account_info(accounts_iter)?;
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let payer = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_token_account = next_account_info(accounts_iter)?;
+        let new_vesting_account = next_account_info(accounts_iter)?;
+        let new_vesting_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account = next_account_info(accounts_iter)?;
+        let owner = next_account_info(accounts_iter)?;
+        let new_destination_token_account = next_account_info(accounts_iter)?;
+        let stats_account = next_account_info(accounts_iter)?;
+        let registry_account = next_account_info(accounts_iter)?;
+        let new_registry_account = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+
+        // Validate the SPL Token Program account
+        if spl_token_account.key != &spl_token::id() {
+            msg!("The provided spl token program account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (header_state, mut schedule) =
+            Self::load_movable_lock(program_id, vesting_account, vesting_token_account, seeds)?;
+
+        // Validate that the destination token account is the one of the lock, and that the owner
+        // of the lock signed
+        if header_state.destination_address != *destination_token_account.key {
+            msg!("Contract destination account does not matched provided account");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Self::check_owner(&header_state, owner)?;
+
+        // Validate that both locks keep some tokens
+        if amount == 0 || amount >= schedule.amount {
+            msg!("The split amount should be less than the locked amount");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        // Validate that the lock keeps the minimum of its tier, the new lock is validated along
+        // with its registration
+        Self::check_moved_limits(
+            program_id,
+            config_account,
+            header_state.tier,
+            schedule.amount - amount,
+            None,
+        )?;
+
+        // Validate that the new destination token account holds the locked mint
+        let new_destination_token_account_data =
+            Self::unpack_token_account(new_destination_token_account)?;
+        if new_destination_token_account_data.mint != TOKEN_MINT {
+            msg!("Invalid new destination token mint address");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if new_destination_token_account_data.owner != header_state.owner {
+            Self::check_receipts_released(&header_state, &schedule)?;
+        }
+
+        // Create the new vesting account, and validate its vesting token account
+        Self::init_vesting_account(
+            program_id,
+            system_program_account,
+            rent_sysvar_account,
+            payer,
+            new_vesting_account,
+            new_seeds,
+        )?;
+        let new_vesting_token_account_data = Self::unpack_token_account(new_vesting_token_account)?;
+        if new_vesting_token_account_data.owner != *new_vesting_account.key {
+            msg!("The new vesting token account should be owned by the new vesting account");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if new_vesting_token_account_data.delegate.is_some()
+            || new_vesting_token_account_data.close_authority.is_some()
+        {
+            msg!("The new vesting token account should not have a delegate or close authority");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Move the split amount to the new vesting token account
+        let transfer_tokens_to_new_vesting_account = transfer(
+            spl_token_account.key,
+            vesting_token_account.key,
+            new_vesting_token_account.key,
+            vesting_account.key,
+            &[],
+            amount,
+        )?;
+        invoke_signed(
+            &transfer_tokens_to_new_vesting_account,
+            &[
+                spl_token_account.clone(),
+                vesting_token_account.clone(),
+                new_vesting_token_account.clone(),
+                vesting_account.clone(),
+            ],
+            &[&[&seeds]],
+        )?;
+
+        let release_time = schedule.release_time;
+        schedule.amount -= amount;
+        let remaining_amount = schedule.amount;
+        pack_schedule_into_slice(
+            schedule,
+            &mut vesting_account.data.borrow_mut()[VestingScheduleHeader::LEN..],
+        );
+
+        // The new lock shares the terms of the lock, released to the new destination, without the
+        // pending transfer and the keeper tip of the lock
+        let new_header = VestingScheduleHeader {
+            destination_address: *new_destination_token_account.key,
+            pending_destination_address: Pubkey::default(),
+            seeds: new_seeds,
+            keeper_tip: 0,
+            owner: new_destination_token_account_data.owner,
+            ..header_state
+        };
+        let new_schedule = VestingSchedule {
+            release_time,
+            amount,
+        };
+        let mut new_data = new_vesting_account.data.borrow_mut();
+        new_header.pack_into_slice(&mut new_data);
+        new_schedule.pack_into_slice(&mut new_data[VestingScheduleHeader::LEN..]);
+
+        // The tokens move from the position of the lock to the one of the new lock
+        Self::update_registry(program_id, registry_account, owner.key, |registry| {
+            registry.set_amount(vesting_account.key, remaining_amount);
+            Ok(())
+        })?;
+        Self::register_moved_lock(
+            program_id,
+            config_account,
+            new_registry_account,
+            &new_destination_token_account_data.owner,
+            owner.key,
+            owner.key,
+            new_vesting_account.key,
+            header_state.tier,
+            amount,
+        )?;
+        Self::update_stats(program_id, stats_account, |stats| {
+            stats.record_split(header_state.tier)
+        })
+    }
+
+    pub fn process_merge(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seeds: [u8; 32],
+        source_seeds: Vec<[u8; 32]>,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account_owner = next_account_info(accounts_iter)?;
+        let stats_account = next_account_info(accounts_iter)?;
+        let registry_account = next_account_info(accounts_iter)?;
+
+        // Validate the SPL Token Program account
+        if spl_token_account.key != &spl_token::id() {
+            msg!("The provided spl token program account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (header_state, mut schedule) =
+            Self::load_movable_lock(program_id, vesting_account, vesting_token_account, seeds)?;
+
+        // Validate that the destination token account is the one of the lock, and that its
+        // owner signed
+        if header_state.destination_address != *destination_token_account.key {
+            msg!("Contract destination account does not matched provided account");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let destination_token_account_data = Self::unpack_token_account(destination_token_account)?;
+        if destination_token_account_data.owner != *destination_token_account_owner.key
+            || !destination_token_account_owner.is_signer
+        {
+            msg!("The destination token account owner should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Validate that there are locks to merge, and their accounts
+        if source_seeds.is_empty() {
+            msg!("The merge should contain at least one lock");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let source_accounts = accounts_iter.as_slice();
+        if source_accounts.len() < source_seeds.len() * 2 {
+            msg!("Missing accounts for {} locks", source_seeds.len());
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        for (source_seeds, source_accounts) in source_seeds
+            .into_iter()
+            .zip(source_accounts.chunks_exact(2))
+        {
+            let source_vesting_account = &source_accounts[0];
+            let source_vesting_token_account = &source_accounts[1];
+            if source_vesting_account.key == vesting_account.key {
+                msg!("A lock can not be merged into itself");
+                return Err(ProgramError::InvalidArgument);
+            }
+            let (source_header, source_schedule) = Self::load_movable_lock(
+                program_id,
+                source_vesting_account,
+                source_vesting_token_account,
+                source_seeds,
+            )?;
+
+            // Validate that both locks share the same terms
+            if source_header.destination_address != header_state.destination_address
+                || source_header.revocation_authority != header_state.revocation_authority
+                || source_header.lock_mode != header_state.lock_mode
+                || source_header.tier != header_state.tier
+                || source_header.auto_renew != header_state.auto_renew
+                || source_header.hook() != header_state.hook()
+                || source_schedule.release_time != schedule.release_time
+            {
+                msg!("Merged locks should have the same destination, tier and release time");
+                return Err(ProgramError::InvalidArgument);
+            }
+
+            // Move all the tokens of the merged lock, then close its accounts
+            let transfer_tokens_to_vesting_account = transfer(
+                spl_token_account.key,
+                source_vesting_token_account.key,
+                vesting_token_account.key,
+                source_vesting_account.key,
+                &[],
+                source_schedule.amount,
+            )?;
+            invoke_signed(
+                &transfer_tokens_to_vesting_account,
+                &[
+                    spl_token_account.clone(),
+                    source_vesting_token_account.clone(),
+                    vesting_token_account.clone(),
+                    source_vesting_account.clone(),
+                ],
+                &[&[&source_seeds]],
+            )?;
+            schedule.amount = schedule
+                .amount
+                .checked_add(source_schedule.amount)
+                .ok_or(ProgramError::InvalidArgument)?;
+
+            Self::remove_from_registry(
+                program_id,
+                registry_account,
+                destination_token_account_owner.key,
+                source_vesting_account.key,
+            )?;
+            Self::close_lock_accounts(
+                spl_token_account,
+                source_vesting_account,
+                source_vesting_token_account,
+                destination_token_account_owner,
+                source_seeds,
+            )?;
+            Self::update_stats(program_id, stats_account, |stats| {
+                stats.record_merge(header_state.tier)
+            })?;
+        }
+
+        Self::update_registry(
+            program_id,
+            registry_account,
+            destination_token_account_owner.key,
+            |registry| {
+                registry.set_amount(vesting_account.key, schedule.amount);
+                Ok(())
+            },
+        )?;
+        pack_schedule_into_slice(
+            schedule,
+            &mut vesting_account.data.borrow_mut()[VestingScheduleHeader::LEN..],
+        );
+        Ok(())
+    }
+
+    /// Validates a lock that can change hands: a vesting account derived from the seeds, holding
+    /// tokens, not revoked and without a position token, whose ownership follows the token
+    fn load_transferable_lock(
+        program_id: &Pubkey,
+        vesting_account: &AccountInfo,
+        seeds: [u8; 32],
+    ) -> Result<VestingScheduleHeader, ProgramError> {
+        // Validate that the vesting account is owned by the program
+        if *vesting_account.owner != *program_id {
+            msg!("Vesting account is not owned by this program");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the vesting account public key is derived from the seeds
+        let vesting_account_key = Pubkey::create_program_address(&[&seeds], program_id)?;
+        if vesting_account_key != *vesting_account.key {
+            msg!("Invalid vesting account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let header_state = Self::load_header(vesting_account)?;
+        let schedule =
+            unpack_schedule(&vesting_account.data.borrow()[VestingScheduleHeader::LEN..])?;
+        if !header_state.is_initialized {
+            msg!("Vesting contract is not initialized");
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if header_state.is_revoked {
+            msg!("Vesting contract has been revoked");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if schedule.amount == 0 {
+            msg!("Vesting contract already claimed");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if header_state.position_mint().is_some() {
+            msg!("Locks with a position token are transferred with their token");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Self::check_receipts_released(&header_state, &schedule)?;
+        Ok(header_state)
+    }
+
+    /// Validates that the owner of a lock signed
+    fn check_owner(header_state: &VestingScheduleHeader, owner: &AccountInfo) -> ProgramResult {
+        if header_state.owner != *owner.key || !owner.is_signer {
+            msg!("The owner of the lock should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(())
+    }
+
+    /// Validates that an unlocked stake has started its withdrawal before it leaves its owner.
+    /// Until then, the owner holds the receipts burned by `InitializeUnlock`.
+    fn check_receipts_released(
+        header_state: &VestingScheduleHeader,
+        schedule: &VestingSchedule,
+    ) -> ProgramResult {
+        if header_state.tier == 0 && schedule.release_time == 0 {
+            msg!("Unlocked stakes only change hands once their withdrawal is initialized");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    /// Releases a lock to a new destination spl-token account and hands it over to the new owner,
+    /// moving it from the position registry of the previous owner to the one of the new owner,
+    /// within the limits of the config
+    #[allow(clippy::too_many_arguments)]
+    fn reassign_lock(
+        program_id: &Pubkey,
+        vesting_account: &AccountInfo,
+        header_state: &mut VestingScheduleHeader,
+        registry_account: &AccountInfo,
+        new_destination_token_account: &AccountInfo,
+        new_owner: &Pubkey,
+        new_registry_account: &AccountInfo,
+        config_account: &AccountInfo,
+        signer: &Pubkey,
+    ) -> ProgramResult {
+        let schedule =
+            unpack_schedule(&vesting_account.data.borrow()[VestingScheduleHeader::LEN..])?;
+        let owner = header_state.owner;
+        Self::remove_from_registry(program_id, registry_account, &owner, vesting_account.key)?;
+        Self::register_moved_lock(
+            program_id,
+            config_account,
+            new_registry_account,
+            new_owner,
+            &owner,
+            signer,
+            vesting_account.key,
+            header_state.tier,
+            schedule.amount,
+        )?;
+        header_state.destination_address = *new_destination_token_account.key;
+        header_state.pending_destination_address = Pubkey::default();
+        header_state.owner = *new_owner;
+        Ok(())
+    }
+
+    pub fn process_transfer_position(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seeds: [u8; 32],
+        require_accept: bool,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let vesting_account = next_account_info(accounts_iter)?;
+        let destination_token_account = next_account_info(accounts_iter)?;
+        let owner = next_account_info(accounts_iter)?;
+        let registry_account = next_account_info(accounts_iter)?;
+        let new_destination_token_account = next_account_info(accounts_iter)?;
+        let new_registry_account = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+
+        let mut header_state = Self::load_transferable_lock(program_id, vesting_account, seeds)?;
+
+        // Validate that the destination token account is the one of the lock, and that the owner
+        // of the lock signed
+        if header_state.destination_address != *destination_token_account.key {
+            msg!("Contract destination account does not matched provided account");
+            return Err(ProgramError::InvalidArgument);
+        }
+        Self::check_owner(&header_state, owner)?;
+
+        // Validate that the new destination token account holds the locked mint
+        let new_destination_token_account_data =
+            Self::unpack_token_account(new_destination_token_account)?;
+        if new_destination_token_account_data.mint != TOKEN_MINT {
+            msg!("Invalid new destination token mint address");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        if new_destination_token_account.key == destination_token_account.key {
+            // Transferring a lock to its own destination cancels a pending transfer
+            header_state.pending_destination_address = Pubkey::default();
+        } else if require_accept {
+            // The transfer waits for the owner of the new destination to accept it
+            header_state.pending_destination_address = *new_destination_token_account.key;
+        } else {
+            Self::reassign_lock(
+                program_id,
+                vesting_account,
+                &mut header_state,
+                registry_account,
+                new_destination_token_account,
+                &new_destination_token_account_data.owner,
+                new_registry_account,
+                config_account,
+                owner.key,
+            )?;
+        }
+        header_state
+            .pack_into_slice(&mut vesting_account.data.borrow_mut()[..VestingScheduleHeader::LEN]);
+        Ok(())
+    }
+
+    pub fn process_accept_position(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seeds: [u8; 32],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let vesting_account = next_account_info(accounts_iter)?;
+        let destination_token_account = next_account_info(accounts_iter)?;
+        let registry_account = next_account_info(accounts_iter)?;
+        let new_destination_token_account = next_account_info(accounts_iter)?;
+        let new_destination_token_account_owner = next_account_info(accounts_iter)?;
+        let new_registry_account = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+
+        let mut header_state = Self::load_transferable_lock(program_id, vesting_account, seeds)?;
+
+        // Validate that the lock is being transferred to the new destination token account
+        if header_state.destination_address != *destination_token_account.key {
+            msg!("Contract destination account does not matched provided account");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if header_state.pending_destination() != Some(*new_destination_token_account.key) {
+            msg!("The lock is not being transferred to the provided account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the owner of the new destination token account signed
+        let new_destination_token_account_data =
+            Self::unpack_token_account(new_destination_token_account)?;
+        if new_destination_token_account_data.owner != *new_destination_token_account_owner.key
+            || !new_destination_token_account_owner.is_signer
+        {
+            msg!("The new destination token account owner should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        Self::reassign_lock(
+            program_id,
+            vesting_account,
+            &mut header_state,
+            registry_account,
+            new_destination_token_account,
+            new_destination_token_account_owner.key,
+            new_registry_account,
+            config_account,
+            new_destination_token_account_owner.key,
+        )?;
+        header_state
+            .pack_into_slice(&mut vesting_account.data.borrow_mut()[..VestingScheduleHeader::LEN]);
+        Ok(())
+    }
+
+    pub fn process_mint_position(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seeds: [u8; 32],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let destination_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account_owner = next_account_info(accounts_iter)?;
+        let position_mint_account = next_account_info(accounts_iter)?;
+        let position_token_account = next_account_info(accounts_iter)?;
+
+        // Validate the SPL Token Program account
+        if spl_token_account.key != &spl_token::id() {
+            msg!("The provided spl token program account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the vesting account is owned by the program
+        if *vesting_account.owner != *program_id {
+            msg!("Vesting account is not owned by this program");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the vesting account public key is derived from the seeds
+        let vesting_account_key = Pubkey::create_program_address(&[&seeds], program_id)?;
+        if vesting_account_key != *vesting_account.key {
+            msg!("Invalid vesting account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let mut header_state = Self::load_header(vesting_account)?;
+        let schedule =
+            unpack_schedule(&vesting_account.data.borrow()[VestingScheduleHeader::LEN..])?;
+
+        // Validate that the destination token account is the one of the lock, and that its
+        // owner signed
+        if header_state.destination_address != *destination_token_account.key {
+            msg!("Contract destination account does not matched provided account");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let destination_token_account_data = Self::unpack_token_account(destination_token_account)?;
+        if destination_token_account_data.owner != *destination_token_account_owner.key
+            || !destination_token_account_owner.is_signer
+        {
+            msg!("The destination token account owner should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Validate that the lock has no position token yet, and can not be revoked
+        if header_state.position_mint().is_some() {
+            msg!("The lock already has a position token");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if header_state.is_revoked || header_state.revocation_authority().is_some() {
+            msg!("Revocable locks can not have a position token");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if schedule.amount == 0 {
+            msg!("Vesting contract already claimed");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Self::mint_position_token(
+            spl_token_account,
+            vesting_account,
+            position_mint_account,
+            position_token_account,
+            seeds,
+        )?;
+
+        header_state.position_mint = *position_mint_account.key;
+        header_state.pending_destination_address = Pubkey::default();
+        header_state
+            .pack_into_slice(&mut vesting_account.data.borrow_mut()[..VestingScheduleHeader::LEN]);
+        Ok(())
+    }
+
+    /// Mints the single token of a position mint to a spl-token account, then removes the mint
+    /// authority of the vesting account
+    fn mint_position_token<'a>(
+        spl_token_account: &AccountInfo<'a>,
+        vesting_account: &AccountInfo<'a>,
+        position_mint_account: &AccountInfo<'a>,
+        position_token_account: &AccountInfo<'a>,
+        seeds: [u8; 32],
+    ) -> ProgramResult {
+        let vesting_account_key = *vesting_account.key;
+        // Validate that the vesting account can mint a single token, and nobody else
+        if *position_mint_account.owner != spl_token::id() {
+            msg!("The position mint should be owned by the spl token program");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let position_mint = Mint::unpack(&position_mint_account.data.borrow())?;
+        if position_mint.supply != 0
+            || position_mint.decimals != 0
+            || position_mint.mint_authority != COption::Some(vesting_account_key)
+            || position_mint.freeze_authority.is_some()
+        {
+            msg!("The position mint should be empty, without decimals, and only mintable by the vesting account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let mint_position_token = mint_to(
+            spl_token_account.key,
+            position_mint_account.key,
+            position_token_account.key,
+            &vesting_account_key,
+            &[],
+            1,
+        )?;
+        invoke_signed(
+            &mint_position_token,
+            &[
+                spl_token_account.clone(),
+                position_mint_account.clone(),
+                position_token_account.clone(),
+                vesting_account.clone(),
+            ],
+            &[&[&seeds]],
+        )?;
+
+        // Remove the mint authority, the position token is the only one of its mint
+        let remove_mint_authority = set_authority(
+            spl_token_account.key,
+            position_mint_account.key,
+            None,
+            AuthorityType::MintTokens,
+            &vesting_account_key,
+            &[],
+        )?;
+        invoke_signed(
+            &remove_mint_authority,
+            &[
+                spl_token_account.clone(),
+                position_mint_account.clone(),
+                vesting_account.clone(),
+            ],
+            &[&[&seeds]],
+        )?;
+        Ok(())
+    }
+
+    pub fn process_set_paused(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        paused: bool,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let guardian = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+
+        // Validate that the guardian signed
+        if config.guardian != *guardian.key || !guardian.is_signer {
+            msg!("The config guardian should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        config.is_paused = paused;
+        config.pack_into_slice(&mut config_account.data.borrow_mut());
+        VestingEvent::PauseSet { paused }.emit();
+        Ok(())
+    }
+
+    pub fn process_set_emergency_mode(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        enabled: bool,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+
+        // Validate that the admin signed
+        if config.admin != *admin.key || !admin.is_signer {
+            msg!("The config admin should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        config.is_emergency_mode = enabled;
+        config.pack_into_slice(&mut config_account.data.borrow_mut());
+        VestingEvent::EmergencyModeSet { enabled }.emit();
+        Ok(())
+    }
+
+    pub fn process_set_guardian(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let guardian = next_account_info(accounts_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+
+        // Validate that the admin signed
+        if config.admin != *admin.key || !admin.is_signer {
+            msg!("The config admin should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        config.guardian = *guardian.key;
+        config.pack_into_slice(&mut config_account.data.borrow_mut());
+        VestingEvent::GuardianSet {
+            guardian: *guardian.key,
+        }
+        .emit();
+        Ok(())
+    }
+
+    pub fn process_set_allowlist(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        is_enabled: bool,
+        root: [u8; 32],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+
+        // Validate that the admin signed
+        if config.admin != *admin.key || !admin.is_signer {
+            msg!("The config admin should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        config.is_allowlist_enabled = is_enabled;
+        config.allowlist_root = root;
+        config.pack_into_slice(&mut config_account.data.borrow_mut());
+        VestingEvent::AllowlistSet {
+            enabled: is_enabled,
+            root,
+        }
+        .emit();
+        Ok(())
+    }
+
+    pub fn process_set_limits(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        max_total_locked: u64,
+        max_locked_per_wallet: u64,
+        min_lock_amounts: [u64; 5],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+
+        // Validate that the admin signed
+        if config.admin != *admin.key || !admin.is_signer {
+            msg!("The config admin should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        config.max_total_locked = max_total_locked;
+        config.max_locked_per_wallet = max_locked_per_wallet;
+        config.min_lock_amounts = min_lock_amounts;
+        config.pack_into_slice(&mut config_account.data.borrow_mut());
+        VestingEvent::LimitsSet {
+            max_total_locked,
+            max_locked_per_wallet,
+            min_lock_amounts,
+        }
+        .emit();
+        Ok(())
+    }
+
+    pub fn process_set_deposit_fee(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        deposit_fee_bps: u16,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let deposit_fee_treasury_token_account = next_account_info(accounts_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+
+        // Validate that the admin signed
+        if config.admin != *admin.key || !admin.is_signer {
+            msg!("The config admin should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if deposit_fee_bps > penalty::BPS_DENOMINATOR {
+            msg!("The deposit fee should not exceed 100%");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let deposit_fee_treasury_data =
+            Self::unpack_token_account(deposit_fee_treasury_token_account)?;
+        if deposit_fee_treasury_data.mint != TOKEN_MINT {
+            msg!("Invalid deposit fee treasury token mint address");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        config.deposit_fee_treasury = *deposit_fee_treasury_token_account.key;
+        config.deposit_fee_bps = deposit_fee_bps;
+        config.pack_into_slice(&mut config_account.data.borrow_mut());
+        VestingEvent::DepositFeeSet {
+            treasury: config.deposit_fee_treasury,
+            deposit_fee_bps,
+        }
+        .emit();
+        Ok(())
+    }
+
+    pub fn process_set_referral_share(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        referral_share_bps: u16,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+
+        let mut config = Self::load_config(program_id, config_account)?;
+
+        // Validate that the admin signed
+        if config.admin != *admin.key || !admin.is_signer {
+            msg!("The config admin should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if referral_share_bps > penalty::BPS_DENOMINATOR {
+            msg!("The referral share should not exceed 100% of the deposit fee");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        config.referral_share_bps = referral_share_bps;
+        config.pack_into_slice(&mut config_account.data.borrow_mut());
+        VestingEvent::ReferralShareSet { referral_share_bps }.emit();
+        Ok(())
+    }
+
+    pub fn process_init_referrer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let rent_sysvar_account = next_account_info(accounts_iter)?;
+        let payer = next_account_info(accounts_iter)?;
+        let referrer = next_account_info(accounts_iter)?;
+        let referrer_account = next_account_info(accounts_iter)?;
+
+        // Validate that the system program account is correct
+        if *system_program_account.key != system_program::ID {
+            msg!("Invalid system program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the rent sysvar account is correct
+        if *rent_sysvar_account.key != rent::ID {
+            msg!("Invalid rent sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the referrer account key, derived from the referrer
+        let (referrer_account_key, bump) =
+            Pubkey::find_program_address(&[REFERRER_SEED, referrer.key.as_ref()], program_id);
+        if referrer_account_key != *referrer_account.key {
+            msg!("Provided referrer account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // The referrer account may already exist, so that it can be created along with every lock
+        if *referrer_account.owner == *program_id {
+            Self::load_referrer(program_id, referrer_account)?;
+            return Ok(());
+        }
+
+        let rent = Rent::from_account_info(rent_sysvar_account)?;
+        let init_referrer_account = create_account(
+            payer.key,
+            &referrer_account_key,
+            rent.minimum_balance(Referrer::LEN),
+            Referrer::LEN as u64,
+            program_id,
+        );
+        invoke_signed(
+            &init_referrer_account,
+            &[
+                system_program_account.clone(),
+                payer.clone(),
+                referrer_account.clone(),
+            ],
+            &[&[REFERRER_SEED, referrer.key.as_ref(), &[bump]]],
+        )?;
+
+        let referrer_state = Referrer {
+            referrer: *referrer.key,
+            bump,
+            is_initialized: true,
+            total_locked: 0,
+            weighted_volume: 0,
+        };
+        referrer_state.pack_into_slice(&mut referrer_account.data.borrow_mut());
+        Ok(())
+    }
+
+    pub fn process_add_allowlist_entry(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let rent_sysvar_account = next_account_info(accounts_iter)?;
+        let payer = next_account_info(accounts_iter)?;
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let wallet = next_account_info(accounts_iter)?;
+        let allowlist_entry = next_account_info(accounts_iter)?;
+
+        // Validate that the system program account is correct
+        if *system_program_account.key != system_program::ID {
+            msg!("Invalid system program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the rent sysvar account is correct
+        if *rent_sysvar_account.key != rent::ID {
+            msg!("Invalid rent sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the admin signed
+        let config = Self::load_config(program_id, config_account)?;
+        if config.admin != *admin.key || !admin.is_signer {
+            msg!("The config admin should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Validate the allowlist entry key, derived from the wallet
+        let (allowlist_entry_key, bump) =
+            Pubkey::find_program_address(&[ALLOWLIST_SEED, wallet.key.as_ref()], program_id);
+        if allowlist_entry_key != *allowlist_entry.key {
+            msg!("Provided allowlist entry is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let rent = Rent::from_account_info(rent_sysvar_account)?;
+        let init_allowlist_entry = create_account(
+            payer.key,
+            &allowlist_entry_key,
+            rent.minimum_balance(AllowlistEntry::LEN),
+            AllowlistEntry::LEN as u64,
+            program_id,
+        );
+        invoke_signed(
+            &init_allowlist_entry,
+            &[
+                system_program_account.clone(),
+                payer.clone(),
+                allowlist_entry.clone(),
+            ],
+            &[&[ALLOWLIST_SEED, wallet.key.as_ref(), &[bump]]],
+        )?;
+
+        AllowlistEntry {
+            wallet: *wallet.key,
+            bump,
+            is_initialized: true,
+        }
+        .pack_into_slice(&mut allowlist_entry.data.borrow_mut());
+        VestingEvent::AllowlistEntryAdded {
+            wallet: *wallet.key,
+        }
+        .emit();
+        Ok(())
+    }
+
+    pub fn process_remove_allowlist_entry(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let admin = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let allowlist_entry = next_account_info(accounts_iter)?;
+        let recipient = next_account_info(accounts_iter)?;
+
+        // Validate that the admin signed
+        let config = Self::load_config(program_id, config_account)?;
+        if config.admin != *admin.key || !admin.is_signer {
+            msg!("The config admin should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let entry = Self::load_allowlist_entry(program_id, allowlist_entry)?;
+        let lamports = allowlist_entry.lamports();
+        **allowlist_entry.lamports.borrow_mut() = 0;
+        **recipient.lamports.borrow_mut() += lamports;
+        allowlist_entry.data.borrow_mut().fill(0);
+        VestingEvent::AllowlistEntryRemoved {
+            wallet: entry.wallet,
+        }
+        .emit();
+        Ok(())
+    }
+
+    pub fn process_emergency_unlock(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seeds: [u8; 32],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let clock_sysvar_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let stats_account = next_account_info(accounts_iter)?;
+        let registry_account = next_account_info(accounts_iter)?;
+
+        // Validate the SPL Token Program account
+        if spl_token_account.key != &spl_token::id() {
+            msg!("The provided spl token program account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the Clock Sysvar account
+        if *clock_sysvar_account.key != clock::ID {
+            msg!("Invalid clock sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the program is in emergency mode
+        let config = Self::load_config(program_id, config_account)?;
+        if !config.is_emergency_mode {
+            msg!("The program is not in emergency mode");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the vesting account is owned by the program
+        if *vesting_account.owner != *program_id {
+            msg!("Vesting account is not owned by this program");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the vesting account key derived from seeds
+        let vesting_account_key = Pubkey::create_program_address(&[&seeds], program_id)?;
+        if vesting_account_key != *vesting_account.key {
+            msg!("Invalid vesting account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let packed_state = &vesting_account.data;
+        let header_state = Self::load_header(vesting_account)?;
+
+        // Validate that the destination token account matches the contract's stored destination
+        // address, or belongs to the holder of the position token of the lock
+        let position_holder =
+            Self::check_destination(&header_state, destination_token_account, accounts_iter)?;
+
+        // Validate that the lock has not been revoked
+        if header_state.is_revoked {
+            msg!("Vesting contract has been revoked");
+            return Err(ProgramError::InvalidArgument);
+        }
 
-        // Pack the updated schedule back into the account data
+        // Unpack the vesting token account and validate ownership by the vesting account
+        let vesting_token_account_data = Self::unpack_token_account(vesting_token_account)?;
+        if vesting_token_account_data.owner != vesting_account_key {
+            msg!("The vesting token account should be owned by the vesting account.");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Release the whole amount, whatever the release time
+        let mut schedule = unpack_schedule(&packed_state.borrow()[VestingScheduleHeader::LEN..])?;
+        let amount = schedule.amount;
+        if amount == 0 {
+            msg!("Vesting contract already claimed");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Unlocked stakes burn their receipts when initializing their withdrawal
+        if header_state.tier == 0 && schedule.release_time == 0 {
+            msg!("Should initialize withdrawal first");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let transfer_tokens_from_vesting_account = transfer(
+            spl_token_account.key,
+            vesting_token_account.key,
+            destination_token_account.key,
+            &vesting_account_key,
+            &[],
+            amount,
+        )?;
+        invoke_signed(
+            &transfer_tokens_from_vesting_account,
+            &[
+                spl_token_account.clone(),
+                vesting_token_account.clone(),
+                destination_token_account.clone(),
+                vesting_account.clone(),
+            ],
+            &[&[&seeds]],
+        )?;
+
+        // Reset the amount in the schedule to 0 to avoid re-using
+        let release_time = schedule.release_time;
+        schedule.amount = 0;
+        pack_schedule_into_slice(
+            schedule,
+            &mut packed_state.borrow_mut()[VestingScheduleHeader::LEN..],
+        );
+        Self::release_from_registry(
+            program_id,
+            registry_account,
+            destination_token_account,
+            position_holder.is_some(),
+            &vesting_account_key,
+        )?;
+        if let Some(position_holder) = position_holder {
+            Self::burn_position_token(
+                spl_token_account,
+                vesting_account,
+                destination_token_account,
+                position_holder,
+            )?;
+        }
+        Self::update_stats(program_id, stats_account, |stats| {
+            stats.record_release(&header_state, release_time, amount)
+        })?;
+        VestingEvent::EmergencyUnlock {
+            vesting_account: vesting_account_key,
+            amount,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    pub fn process_early_unlock(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seeds: [u8; 32],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let clock_sysvar_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account_owner = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let penalty_treasury_token_account = next_account_info(accounts_iter)?;
+        let stats_account = next_account_info(accounts_iter)?;
+        let registry_account = next_account_info(accounts_iter)?;
+
+        // Validate the SPL Token Program account
+        if spl_token_account.key != &spl_token::id() {
+            msg!("The provided spl token program account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the Clock Sysvar account
+        if *clock_sysvar_account.key != clock::ID {
+            msg!("Invalid clock sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the vesting account is owned by the program
+        if *vesting_account.owner != *program_id {
+            msg!("Vesting account is not owned by this program");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the vesting account key derived from seeds
+        let vesting_account_key = Pubkey::create_program_address(&[&seeds], program_id)?;
+        if vesting_account_key != *vesting_account.key {
+            msg!("Invalid vesting account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate the penalty treasury against the config
+        let config = Self::load_config(program_id, config_account)?;
+        if config.penalty_treasury != *penalty_treasury_token_account.key {
+            msg!("Penalty treasury does not match the config");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let packed_state = &vesting_account.data;
+        let header_state = Self::load_header(vesting_account)?;
+
+        // Validate that the destination token account matches the contract's stored destination
+        // address, or belongs to the holder of the position token of the lock
+        let position_holder =
+            Self::check_destination(&header_state, destination_token_account, accounts_iter)?;
+
+        // Validate that the owner of the destination token account agrees to pay the penalty
+        let destination_token_account_data = Self::unpack_token_account(destination_token_account)?;
+        if destination_token_account_data.owner != *destination_token_account_owner.key
+            || !destination_token_account_owner.is_signer
+        {
+            msg!("The destination token account owner should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Validate that the lock has not been revoked, and can not be revoked
+        if header_state.is_revoked {
+            msg!("Vesting contract has been revoked");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if header_state.revocation_authority().is_some() {
+            msg!("Revocable locks can not be unlocked early");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Unpack the vesting token account and validate ownership by the vesting account
+        let vesting_token_account_data = Self::unpack_token_account(vesting_token_account)?;
+        if vesting_token_account_data.owner != vesting_account_key {
+            msg!("The vesting token account should be owned by the vesting account.");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let clock = clock::Clock::from_account_info(&clock_sysvar_account)?;
+        let mut schedule = unpack_schedule(&packed_state.borrow()[VestingScheduleHeader::LEN..])?;
+
+        // Check if the vesting contract has already been fully claimed
+        if schedule.amount == 0 {
+            msg!("Vesting contract already claimed");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Unlocked stakes are released through their withdrawal period
+        if schedule.release_time == 0 {
+            msg!("Should initialize withdrawal first");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // The penalty scales with the remaining time in seconds, whatever the lock mode, until the
+        // rolled forward release time of an auto-renewing lock
+        let lock_mode = header_state.lock_mode;
+        let now = lock_mode.now(&clock);
+        let remaining_time = lock_mode.to_seconds(
+            header_state
+                .release_time(&schedule, now)
+                .saturating_sub(now),
+        );
+        let penalty_amount =
+            penalty::early_unlock_penalty(schedule.amount, remaining_time, config.max_penalty_bps);
+        msg!("Early unlock penalty: {}", penalty_amount);
+
+        for (amount, recipient) in [
+            (schedule.amount - penalty_amount, destination_token_account),
+            (penalty_amount, penalty_treasury_token_account),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            let transfer_tokens_from_vesting_account = transfer(
+                spl_token_account.key,
+                vesting_token_account.key,
+                recipient.key,
+                &vesting_account_key,
+                &[],
+                amount,
+            )?;
+            invoke_signed(
+                &transfer_tokens_from_vesting_account,
+                &[
+                    spl_token_account.clone(),
+                    vesting_token_account.clone(),
+                    recipient.clone(),
+                    vesting_account.clone(),
+                ],
+                &[&[&seeds]],
+            )?;
+        }
+
+        Self::update_stats(program_id, stats_account, |stats| {
+            stats.record_release(&header_state, schedule.release_time, schedule.amount)
+        })?;
+
+        // Reset the amount in the schedule to 0 to avoid re-using
+        schedule.amount = 0;
         pack_schedule_into_slice(
             schedule,
             &mut packed_state.borrow_mut()[VestingScheduleHeader::LEN..],
         );
+        Self::release_from_registry(
+            program_id,
+            registry_account,
+            destination_token_account,
+            position_holder.is_some(),
+            &vesting_account_key,
+        )?;
+        if let Some(position_holder) = position_holder {
+            Self::burn_position_token(
+                spl_token_account,
+                vesting_account,
+                destination_token_account,
+                position_holder,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn process_create_airdrop(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        seeds: [u8; 32],
+        merkle_root: [u8; 32],
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let rent_sysvar_account = next_account_info(accounts_iter)?;
+        let authority = next_account_info(accounts_iter)?;
+        let airdrop_account = next_account_info(accounts_iter)?;
+        let vault_token_account = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+
+        // Validate that the system program account is correct
+        if *system_program_account.key != system_program::ID {
+            msg!("Invalid system program account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the rent sysvar account is correct
+        if *rent_sysvar_account.key != rent::ID {
+            msg!("Invalid rent sysvar account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the authority is a signer
+        if !authority.is_signer {
+            msg!("Airdrop authority should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // While the allowlist is enabled, only the admin creates airdrops
+        Self::check_allowlist_admin(program_id, config_account, authority.key)?;
+
+        // Create and validate the airdrop account key with the provided seed
+        let airdrop_account_key =
+            Pubkey::create_program_address(&[AIRDROP_SEED, &seeds], program_id)?;
+        if airdrop_account_key != *airdrop_account.key {
+            msg!("Provided airdrop account is invalid");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the vault holds the locked mint and is controlled by the airdrop only
+        let vault_token_account_data = Self::unpack_token_account(vault_token_account)?;
+        if vault_token_account_data.mint != TOKEN_MINT {
+            msg!("Invalid vault token mint address");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if vault_token_account_data.owner != airdrop_account_key {
+            msg!("The vault token account should be owned by the airdrop account");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if vault_token_account_data.delegate.is_some()
+            || vault_token_account_data.close_authority.is_some()
+        {
+            msg!("The vault token account should not have a delegate or close authority");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let rent = Rent::from_account_info(rent_sysvar_account)?;
+        let init_airdrop_account = create_account(
+            authority.key,
+            &airdrop_account_key,
+            rent.minimum_balance(Airdrop::LEN),
+            Airdrop::LEN as u64,
+            program_id,
+        );
+        invoke_signed(
+            &init_airdrop_account,
+            &[
+                system_program_account.clone(),
+                authority.clone(),
+                airdrop_account.clone(),
+            ],
+            &[&[AIRDROP_SEED, &seeds]],
+        )?;
+
+        let airdrop = Airdrop {
+            authority: *authority.key,
+            merkle_root,
+            vault: *vault_token_account.key,
+            is_initialized: true,
+        };
+        airdrop.pack_into_slice(&mut airdrop_account.data.borrow_mut());
+        Ok(())
+    }
+
+    pub fn process_claim_into_lock(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        airdrop_seeds: [u8; 32],
+        seeds: [u8; 32],
+        schedule: Schedule,
+        proof: Vec<[u8; 32]>,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+
+        let system_program_account = next_account_info(accounts_iter)?;
+        let rent_sysvar_account = next_account_info(accounts_iter)?;
+        let spl_token_account = next_account_info(accounts_iter)?;
+        let clock_sysvar_account = next_account_info(accounts_iter)?;
+        let claimant = next_account_info(accounts_iter)?;
+        let airdrop_account = next_account_info(accounts_iter)?;
+        let vault_token_account = next_account_info(accounts_iter)?;
+        let vesting_account = next_account_info(accounts_iter)?;
+        let vesting_token_account = next_account_info(accounts_iter)?;
+        let destination_token_account = next_account_info(accounts_iter)?;
+        let config_account = next_account_info(accounts_iter)?;
+        let stats_account = next_account_info(accounts_iter)?;
+        let receipt_mint = next_account_info(accounts_iter)?;
+        let receipt_token_account = next_account_info(accounts_iter)?;
+        let registry_account = next_account_info(accounts_iter)?;
+
+        Self::check_not_paused(program_id, config_account)?;
+        let deposit_fee_treasury =
+            Self::next_deposit_fee_treasury(program_id, config_account, accounts_iter)?;
 
+        // Validate that the claimant is a signer
+        if !claimant.is_signer {
+            msg!("Claimant should be a signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // Validate the airdrop account and its vault
+        if *airdrop_account.owner != *program_id {
+            msg!("Airdrop account is not owned by this program");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let airdrop_account_key =
+            Pubkey::create_program_address(&[AIRDROP_SEED, &airdrop_seeds], program_id)?;
+        if airdrop_account_key != *airdrop_account.key {
+            msg!("Invalid airdrop account key");
+            return Err(ProgramError::InvalidArgument);
+        }
+        let airdrop = Airdrop::unpack(&airdrop_account.data.borrow())?;
+        if airdrop.vault != *vault_token_account.key {
+            msg!("Airdrop vault does not match provided account");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // While the allowlist is enabled, only the airdrops of the admin are claimed, including
+        // those created before it was enabled
+        Self::check_allowlist_admin(program_id, config_account, &airdrop.authority)?;
+
+        // Validate the claimed entry against the Merkle root
+        let leaf = merkle::leaf_hash(claimant.key, schedule.amount, schedule.time_delta);
+        if !merkle::verify(&proof, &airdrop.merkle_root, leaf) {
+            msg!("Invalid Merkle proof");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // Validate that the lock is the single one of the claimant for this airdrop, derived with
+        // the canonical bump seed so that another bump can not claim the same entry again
+        let claim_seed = merkle::claim_seed(&airdrop_account_key, claimant.key);
+        let (_, claim_bump) = Pubkey::find_program_address(&[&claim_seed], program_id);
+        if seeds[..31] != claim_seed || seeds[31] != claim_bump {
+            msg!("Invalid claim seeds");
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        Self::init_vesting_account(
+            program_id,
+            system_program_account,
+            rent_sysvar_account,
+            claimant,
+            vesting_account,
+            seeds,
+        )?;
+
+        let create_accounts = CreateAccounts {
+            spl_token_account,
+            clock_sysvar_account,
+            vesting_account,
+            vesting_token_account,
+            source_token_account_owner: airdrop_account,
+            source_token_account: vault_token_account,
+            beneficiary_token_account: destination_token_account,
+            config_account,
+            stats_account,
+            receipt_mint,
+            receipt_token_account,
+            registry_account,
+            creator: claimant.key,
+            signer_accounts: &[],
+        };
+
+        // Take the deposit fee from the claimed amount, the lock holds the rest
+        let deposit_fee = deposit_fee_treasury.map_or(0, |(_, deposit_fee_bps)| {
+            Self::deposit_fee(schedule.amount, deposit_fee_bps)
+        });
+        Self::create_schedule(
+            program_id,
+            &create_accounts,
+            seeds,
+            Schedule {
+                amount: schedule.amount - deposit_fee,
+                ..schedule
+            },
+            &CreateOptions::default(),
+            &[AIRDROP_SEED, &airdrop_seeds],
+        )?;
+        if let Some((deposit_fee_treasury, _)) = deposit_fee_treasury.filter(|_| deposit_fee > 0) {
+            Self::transfer_from_source(
+                &create_accounts,
+                deposit_fee_treasury,
+                deposit_fee,
+                &[AIRDROP_SEED, &airdrop_seeds],
+            )?;
+        }
         Ok(())
     }
 
@@ -470,9 +4495,168 @@ impl Processor {
             VestingInstruction::Create {
                 seeds,
                 schedule,
+                options,
             } => {
                 msg!("Instruction: Create Schedule");
-                Self::process_create(program_id, accounts, seeds, schedule)
+                Self::process_create(program_id, accounts, seeds, schedule, options)
+            }
+            VestingInstruction::CreateBatch {
+                entries,
+                multisig_signers,
+            } => {
+                msg!("Instruction: Create Batch");
+                Self::process_create_batch(program_id, accounts, entries, multisig_signers)
+            }
+            VestingInstruction::CreateAirdrop { seeds, merkle_root } => {
+                msg!("Instruction: Create Airdrop");
+                Self::process_create_airdrop(program_id, accounts, seeds, merkle_root)
+            }
+            VestingInstruction::ClaimIntoLock {
+                airdrop_seeds,
+                seeds,
+                schedule,
+                proof,
+            } => {
+                msg!("Instruction: Claim Into Lock");
+                Self::process_claim_into_lock(
+                    program_id,
+                    accounts,
+                    airdrop_seeds,
+                    seeds,
+                    schedule,
+                    proof,
+                )
+            }
+            VestingInstruction::Revoke { seeds } => {
+                msg!("Instruction: Revoke");
+                Self::process_revoke(program_id, accounts, seeds)
+            }
+            VestingInstruction::InitConfig {
+                max_penalty_bps,
+                lock_mode,
+            } => {
+                msg!("Instruction: Init Config");
+                Self::process_init_config(program_id, accounts, max_penalty_bps, lock_mode)
+            }
+            VestingInstruction::UpdatePenalty { max_penalty_bps } => {
+                msg!("Instruction: Update Penalty");
+                Self::process_update_penalty(program_id, accounts, max_penalty_bps)
+            }
+            VestingInstruction::EarlyUnlock { seeds } => {
+                msg!("Instruction: Early Unlock");
+                Self::process_early_unlock(program_id, accounts, seeds)
+            }
+            VestingInstruction::SetPaused { paused } => {
+                msg!("Instruction: Set Paused");
+                Self::process_set_paused(program_id, accounts, paused)
+            }
+            VestingInstruction::SetEmergencyMode { enabled } => {
+                msg!("Instruction: Set Emergency Mode");
+                Self::process_set_emergency_mode(program_id, accounts, enabled)
+            }
+            VestingInstruction::SetGuardian => {
+                msg!("Instruction: Set Guardian");
+                Self::process_set_guardian(program_id, accounts)
+            }
+            VestingInstruction::EmergencyUnlock { seeds } => {
+                msg!("Instruction: Emergency Unlock");
+                Self::process_emergency_unlock(program_id, accounts, seeds)
+            }
+            VestingInstruction::InitStats => {
+                msg!("Instruction: Init Stats");
+                Self::process_init_stats(program_id, accounts)
+            }
+            VestingInstruction::InitReceiptMint => {
+                msg!("Instruction: Init Receipt Mint");
+                Self::process_init_receipt_mint(program_id, accounts)
+            }
+            VestingInstruction::Split {
+                seeds,
+                new_seeds,
+                amount,
+            } => {
+                msg!("Instruction: Split");
+                Self::process_split(program_id, accounts, seeds, new_seeds, amount)
+            }
+            VestingInstruction::Merge {
+                seeds,
+                source_seeds,
+            } => {
+                msg!("Instruction: Merge");
+                Self::process_merge(program_id, accounts, seeds, source_seeds)
+            }
+            VestingInstruction::UnlockMany { seeds } => {
+                msg!("Instruction: Unlock Many");
+                Self::process_unlock_many(program_id, accounts, seeds)
+            }
+            VestingInstruction::TransferPosition {
+                seeds,
+                require_accept,
+            } => {
+                msg!("Instruction: Transfer Position");
+                Self::process_transfer_position(program_id, accounts, seeds, require_accept)
+            }
+            VestingInstruction::OptOutRenewal { seeds } => {
+                msg!("Instruction: Opt Out Renewal");
+                Self::process_opt_out_renewal(program_id, accounts, seeds)
+            }
+            VestingInstruction::AcceptPosition { seeds } => {
+                msg!("Instruction: Accept Position");
+                Self::process_accept_position(program_id, accounts, seeds)
+            }
+            VestingInstruction::GetStats => {
+                msg!("Instruction: Get Stats");
+                Self::process_get_stats(program_id, accounts)
+            }
+            VestingInstruction::InitRegistry => {
+                msg!("Instruction: Init Registry");
+                Self::process_init_registry(program_id, accounts)
+            }
+            VestingInstruction::Close { seeds } => {
+                msg!("Instruction: Close");
+                Self::process_close(program_id, accounts, seeds)
+            }
+            VestingInstruction::MintPosition { seeds } => {
+                msg!("Instruction: Mint Position");
+                Self::process_mint_position(program_id, accounts, seeds)
+            }
+            VestingInstruction::SetAllowlist { is_enabled, root } => {
+                msg!("Instruction: Set Allowlist");
+                Self::process_set_allowlist(program_id, accounts, is_enabled, root)
+            }
+            VestingInstruction::AddAllowlistEntry => {
+                msg!("Instruction: Add Allowlist Entry");
+                Self::process_add_allowlist_entry(program_id, accounts)
+            }
+            VestingInstruction::RemoveAllowlistEntry => {
+                msg!("Instruction: Remove Allowlist Entry");
+                Self::process_remove_allowlist_entry(program_id, accounts)
+            }
+            VestingInstruction::SetLimits {
+                max_total_locked,
+                max_locked_per_wallet,
+                min_lock_amounts,
+            } => {
+                msg!("Instruction: Set Limits");
+                Self::process_set_limits(
+                    program_id,
+                    accounts,
+                    max_total_locked,
+                    max_locked_per_wallet,
+                    min_lock_amounts,
+                )
+            }
+            VestingInstruction::SetDepositFee { deposit_fee_bps } => {
+                msg!("Instruction: Set Deposit Fee");
+                Self::process_set_deposit_fee(program_id, accounts, deposit_fee_bps)
+            }
+            VestingInstruction::InitReferrer => {
+                msg!("Instruction: Init Referrer");
+                Self::process_init_referrer(program_id, accounts)
+            }
+            VestingInstruction::SetReferralShare { referral_share_bps } => {
+                msg!("Instruction: Set Referral Share");
+                Self::process_set_referral_share(program_id, accounts, referral_share_bps)
             }
         }
     }
@@ -485,6 +4669,9 @@ impl PrintProgramError for VestingError {
     {
         match self {
             VestingError::InvalidInstruction => msg!("Error: Invalid instruction!"),
+            VestingError::AmountBelowMinimum => msg!("Error: Amount below minimum!"),
+            VestingError::PoolCapExceeded => msg!("Error: Pool cap exceeded!"),
+            VestingError::WalletCapExceeded => msg!("Error: Wallet cap exceeded!"),
         }
     }
 }
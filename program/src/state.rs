@@ -1,26 +1,223 @@
 use solana_program::{
+    clock::{Clock, DEFAULT_MS_PER_SLOT, DEFAULT_SLOTS_PER_EPOCH},
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::Pubkey,
 };
 
+use shank::ShankAccount;
 use std::convert::{TryFrom, TryInto};
-#[derive(Debug, PartialEq)]
+
+use crate::hook::{Hook, HookMode};
+#[derive(Debug, PartialEq, ShankAccount)]
 pub struct VestingSchedule {
     pub release_time: u64,
     pub amount: u64,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, ShankAccount)]
 pub struct VestingScheduleHeader {
     pub destination_address: Pubkey,
     pub is_initialized: bool,
+    /// The authority allowed to revoke the lock, the default pubkey if the lock is not revocable
+    pub revocation_authority: Pubkey,
+    pub is_revoked: bool,
+    /// The unit of the release time of the schedule
+    pub lock_mode: LockMode,
+    /// Index of the lock period in `TIER_TIME_DELTAS`
+    pub tier: u8,
+    /// The mint of the position token of the lock, the default pubkey if the lock has none
+    pub position_mint: Pubkey,
+    /// The spl-token account the lock is being transferred to, whose owner has to accept the
+    /// transfer, the default pubkey if there is no pending transfer
+    pub pending_destination_address: Pubkey,
+    /// The seeds of the vesting account, followed by its bump, so that keepers scanning the
+    /// vesting accounts can release them
+    pub seeds: [u8; 32],
+    /// Lamports held by the vesting account on top of its rent, paid to the account releasing the
+    /// lock once it matures
+    pub keeper_tip: u64,
+    /// Whether the lock is renewed for another period of its tier at each maturity, unless its
+    /// owner opts out during the renewal notice period or the grace period after the maturity
+    pub auto_renew: bool,
+    /// The program invoked after the lifecycle events of the lock, the default pubkey if the lock
+    /// has no hook
+    pub hook_program: Pubkey,
+    /// Whether the instructions of the lock must invoke its hook
+    pub hook_mode: HookMode,
+    /// The wallet credited with the creation of the lock, the default pubkey if the lock has no
+    /// referrer
+    pub referrer: Pubkey,
+    /// The wallet owning the lock, who signs its transfers to other wallets. It starts as the
+    /// owner of the beneficiary token account and follows the lock when it changes hands.
+    pub owner: Pubkey,
+}
+
+/// Size of the vesting accounts created with the first layout, whose header only holds the
+/// destination address and the initialized flag. Having no version byte, they are told apart by
+/// their size.
+pub const LEGACY_VESTING_ACCOUNT_LEN: usize = 49;
+
+/// Size of the header of the vesting accounts created with the first layout
+pub const LEGACY_HEADER_LEN: usize = 33;
+
+/// Lock periods supported by the program, in seconds. A month is 30 days.
+///
+/// The 0 time delta is "unlocked", with a 7 day withdrawal period.
+pub const TIER_TIME_DELTAS: [u64; 5] = [0, 7_776_000, 15_552_000, 23_328_000, 31_104_000];
+
+/// Weights of the tiers in the volume of a referrer, growing with the lock period
+pub const TIER_WEIGHTS: [u64; 5] = [1, 2, 3, 4, 5];
+
+/// Window before each maturity of an auto-renewing lock during which its owner can opt out of
+/// the renewal, in seconds
+pub const RENEWAL_NOTICE_PERIOD: u64 = 604_800;
+
+/// Window after each maturity of an auto-renewing lock during which its owner can still opt out
+/// of the renewal and release the lock at that maturity, in seconds
+pub const RENEWAL_GRACE_PERIOD: u64 = 604_800;
+
+/// Duration of an epoch of the default length, in seconds
+pub const SECONDS_PER_EPOCH: u64 = DEFAULT_SLOTS_PER_EPOCH * DEFAULT_MS_PER_SLOT / 1000;
+
+/// The clock measuring the release time of a lock. Slots and epochs do not depend on the
+/// timestamp voted by the validators.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LockMode {
+    /// Release times are unix timestamps
+    #[default]
+    Timestamp,
+    /// Release times are slots
+    Slot,
+    /// Release times are epochs
+    Epoch,
+}
+
+impl LockMode {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Timestamp),
+            1 => Some(Self::Slot),
+            2 => Some(Self::Epoch),
+            _ => None,
+        }
+    }
+
+    /// The current time of the clock, in the unit of the mode
+    pub fn now(self, clock: &Clock) -> u64 {
+        match self {
+            Self::Timestamp => clock.unix_timestamp as u64,
+            Self::Slot => clock.slot,
+            Self::Epoch => clock.epoch,
+        }
+    }
+
+    /// Converts a duration in seconds to the unit of the mode, with the default slot duration
+    /// and epoch length, rounded up so that a lock never matures earlier than its duration
+    pub fn from_seconds(self, seconds: u64) -> u64 {
+        match self {
+            Self::Timestamp => seconds,
+            Self::Slot => seconds.saturating_mul(1000).div_ceil(DEFAULT_MS_PER_SLOT),
+            Self::Epoch => seconds.div_ceil(SECONDS_PER_EPOCH),
+        }
+    }
+
+    /// Converts a duration in the unit of the mode to seconds
+    pub fn to_seconds(self, duration: u64) -> u64 {
+        match self {
+            Self::Timestamp => duration,
+            Self::Slot => duration.saturating_mul(DEFAULT_MS_PER_SLOT) / 1000,
+            Self::Epoch => duration.saturating_mul(SECONDS_PER_EPOCH),
+        }
+    }
+}
+
+impl VestingScheduleHeader {
+    pub fn revocation_authority(&self) -> Option<Pubkey> {
+        if self.revocation_authority == Pubkey::default() {
+            None
+        } else {
+            Some(self.revocation_authority)
+        }
+    }
+
+    /// The mint of the position token, whose holder releases the lock instead of its destination
+    pub fn position_mint(&self) -> Option<Pubkey> {
+        if self.position_mint == Pubkey::default() {
+            None
+        } else {
+            Some(self.position_mint)
+        }
+    }
+
+    /// The destination of a transfer of the lock awaiting the acceptance of its owner
+    pub fn pending_destination(&self) -> Option<Pubkey> {
+        if self.pending_destination_address == Pubkey::default() {
+            None
+        } else {
+            Some(self.pending_destination_address)
+        }
+    }
+
+    /// The wallet credited with the creation of the lock
+    pub fn referrer(&self) -> Option<Pubkey> {
+        if self.referrer == Pubkey::default() {
+            None
+        } else {
+            Some(self.referrer)
+        }
+    }
+
+    /// The hook invoked after the lifecycle events of the lock
+    pub fn hook(&self) -> Option<Hook> {
+        if self.hook_program == Pubkey::default() {
+            None
+        } else {
+            Some(Hook {
+                program_id: self.hook_program,
+                mode: self.hook_mode,
+            })
+        }
+    }
+
+    /// The release time of `schedule` at `now`, both in the unit of the lock mode. The release
+    /// time of an auto-renewing lock rolls forward by the period of its tier at each maturity.
+    pub fn release_time(&self, schedule: &VestingSchedule, now: u64) -> u64 {
+        let period = self.renewal_period();
+        if !self.auto_renew || period == 0 || schedule.release_time == 0 {
+            return schedule.release_time;
+        }
+        if now < schedule.release_time {
+            return schedule.release_time;
+        }
+        let renewals = (now - schedule.release_time) / period + 1;
+        schedule
+            .release_time
+            .saturating_add(renewals.saturating_mul(period))
+    }
+
+    /// The last maturity of an auto-renewing lock at `now`, after which it renewed, in the unit
+    /// of the lock mode. `None` until its first maturity, and for locks that do not renew.
+    pub fn last_maturity(&self, schedule: &VestingSchedule, now: u64) -> Option<u64> {
+        let release_time = self.release_time(schedule, now);
+        if release_time == schedule.release_time {
+            return None;
+        }
+        Some(release_time - self.renewal_period())
+    }
+
+    /// The period of the tier of the lock, in the unit of the lock mode
+    pub fn renewal_period(&self) -> u64 {
+        self.lock_mode
+            .from_seconds(TIER_TIME_DELTAS[usize::from(self.tier)])
+    }
 }
 
 impl Sealed for VestingScheduleHeader {}
 
 impl Pack for VestingScheduleHeader {
-    const LEN: usize = 33;
+    const LEN: usize = 270;
 
     fn pack_into_slice(&self, target: &mut [u8]) {
         let destination_address_bytes = self.destination_address.to_bytes();
@@ -29,6 +226,19 @@ impl Pack for VestingScheduleHeader {
         }
 
         target[32] = self.is_initialized as u8;
+        target[33..65].copy_from_slice(self.revocation_authority.as_ref());
+        target[65] = self.is_revoked as u8;
+        target[66] = self.lock_mode as u8;
+        target[67] = self.tier;
+        target[68..100].copy_from_slice(self.position_mint.as_ref());
+        target[100..132].copy_from_slice(self.pending_destination_address.as_ref());
+        target[132..164].copy_from_slice(&self.seeds);
+        target[164..172].copy_from_slice(&self.keeper_tip.to_le_bytes());
+        target[172] = self.auto_renew as u8;
+        target[173..205].copy_from_slice(self.hook_program.as_ref());
+        target[205] = self.hook_mode as u8;
+        target[206..238].copy_from_slice(self.referrer.as_ref());
+        target[238..270].copy_from_slice(self.owner.as_ref());
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
@@ -38,9 +248,43 @@ impl Pack for VestingScheduleHeader {
         let destination_address =
             Pubkey::try_from(&src[..32]).map_err(|_| ProgramError::InvalidArgument)?;
         let is_initialized = src[32] == 1;
+        let revocation_authority =
+            Pubkey::try_from(&src[33..65]).map_err(|_| ProgramError::InvalidArgument)?;
+        let is_revoked = src[65] == 1;
+        let lock_mode = LockMode::from_u8(src[66]).ok_or(ProgramError::InvalidAccountData)?;
+        let tier = src[67];
+        if usize::from(tier) >= TIER_TIME_DELTAS.len() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let position_mint =
+            Pubkey::try_from(&src[68..100]).map_err(|_| ProgramError::InvalidArgument)?;
+        let pending_destination_address =
+            Pubkey::try_from(&src[100..132]).map_err(|_| ProgramError::InvalidArgument)?;
+        let seeds = src[132..164].try_into().unwrap();
+        let keeper_tip = u64::from_le_bytes(src[164..172].try_into().unwrap());
+        let auto_renew = src[172] == 1;
+        let hook_program =
+            Pubkey::try_from(&src[173..205]).map_err(|_| ProgramError::InvalidArgument)?;
+        let hook_mode = HookMode::from_u8(src[205]).ok_or(ProgramError::InvalidAccountData)?;
+        let referrer =
+            Pubkey::try_from(&src[206..238]).map_err(|_| ProgramError::InvalidArgument)?;
+        let owner = Pubkey::try_from(&src[238..270]).map_err(|_| ProgramError::InvalidArgument)?;
         Ok(Self {
             destination_address,
             is_initialized,
+            revocation_authority,
+            is_revoked,
+            lock_mode,
+            tier,
+            position_mint,
+            pending_destination_address,
+            seeds,
+            keeper_tip,
+            auto_renew,
+            hook_program,
+            hook_mode,
+            referrer,
+            owner,
         })
     }
 }
@@ -81,6 +325,559 @@ impl IsInitialized for VestingScheduleHeader {
     }
 }
 
+/// A locked airdrop: recipients claim their entry of the Merkle tree into a new lock, funded from
+/// the vault token account owned by the airdrop account
+#[derive(Debug, PartialEq, ShankAccount)]
+pub struct Airdrop {
+    pub authority: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub vault: Pubkey,
+    pub is_initialized: bool,
+}
+
+impl Sealed for Airdrop {}
+
+impl Pack for Airdrop {
+    const LEN: usize = 97;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[..32].copy_from_slice(self.authority.as_ref());
+        dst[32..64].copy_from_slice(&self.merkle_root);
+        dst[64..96].copy_from_slice(self.vault.as_ref());
+        dst[96] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let authority = Pubkey::try_from(&src[..32]).map_err(|_| ProgramError::InvalidArgument)?;
+        let merkle_root = src[32..64].try_into().unwrap();
+        let vault = Pubkey::try_from(&src[64..96]).map_err(|_| ProgramError::InvalidArgument)?;
+        let is_initialized = src[96] == 1;
+        Ok(Self {
+            authority,
+            merkle_root,
+            vault,
+            is_initialized,
+        })
+    }
+}
+
+impl IsInitialized for Airdrop {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+/// Program-wide settings, set by the admin
+#[derive(Debug, PartialEq, ShankAccount)]
+pub struct Config {
+    pub admin: Pubkey,
+    /// Token account receiving the early unlock penalties
+    pub penalty_treasury: Pubkey,
+    /// Penalty charged on a lock released a whole 12 months early, in basis points
+    pub max_penalty_bps: u16,
+    pub bump: u8,
+    pub is_initialized: bool,
+    /// Key allowed to pause the creation of locks
+    pub guardian: Pubkey,
+    /// Whether the creation of locks is paused
+    pub is_paused: bool,
+    /// Whether every lock can be unlocked, whatever its release time
+    pub is_emergency_mode: bool,
+    /// Whether `Create` is restricted to the allowlisted source token account owners
+    pub is_allowlist_enabled: bool,
+    /// Merkle root of the allowlisted wallets, besides those with an allowlist entry. All zeros
+    /// when only the entries are allowlisted.
+    pub allowlist_root: [u8; 32],
+    /// Maximum amount held by all the locks, 0 for no cap
+    pub max_total_locked: u64,
+    /// Maximum amount of the positions of a wallet, counted over its position registry. Only
+    /// `Create` registers the locks it creates, 0 for no cap.
+    pub max_locked_per_wallet: u64,
+    /// Minimum amount of a new lock, by tier. Locks of 0 tokens are always rejected.
+    pub min_lock_amounts: [u64; 5],
+    /// Token account receiving the deposit fees
+    pub deposit_fee_treasury: Pubkey,
+    /// Fee taken from the amount of a new lock, in basis points
+    pub deposit_fee_bps: u16,
+    /// Share of the deposit fee of a new lock paid to its referrer, in basis points of the fee
+    pub referral_share_bps: u16,
+    /// The clock measuring the release time of the locks of the pool
+    pub lock_mode: LockMode,
+}
+
+impl Sealed for Config {}
+
+impl Pack for Config {
+    const LEN: usize = 228;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[..32].copy_from_slice(self.admin.as_ref());
+        dst[32..64].copy_from_slice(self.penalty_treasury.as_ref());
+        dst[64..66].copy_from_slice(&self.max_penalty_bps.to_le_bytes());
+        dst[66] = self.bump;
+        dst[67] = self.is_initialized as u8;
+        dst[68..100].copy_from_slice(self.guardian.as_ref());
+        dst[100] = self.is_paused as u8;
+        dst[101] = self.is_emergency_mode as u8;
+        dst[102] = self.is_allowlist_enabled as u8;
+        dst[103..135].copy_from_slice(&self.allowlist_root);
+        dst[135..143].copy_from_slice(&self.max_total_locked.to_le_bytes());
+        dst[143..151].copy_from_slice(&self.max_locked_per_wallet.to_le_bytes());
+        for (min_lock_amount, dst) in self
+            .min_lock_amounts
+            .iter()
+            .zip(dst[151..191].chunks_exact_mut(8))
+        {
+            dst.copy_from_slice(&min_lock_amount.to_le_bytes());
+        }
+        dst[191..223].copy_from_slice(self.deposit_fee_treasury.as_ref());
+        dst[223..225].copy_from_slice(&self.deposit_fee_bps.to_le_bytes());
+        dst[225..227].copy_from_slice(&self.referral_share_bps.to_le_bytes());
+        dst[227] = self.lock_mode as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let admin = Pubkey::try_from(&src[..32]).map_err(|_| ProgramError::InvalidArgument)?;
+        let penalty_treasury =
+            Pubkey::try_from(&src[32..64]).map_err(|_| ProgramError::InvalidArgument)?;
+        let max_penalty_bps = u16::from_le_bytes(src[64..66].try_into().unwrap());
+        let bump = src[66];
+        let is_initialized = src[67] == 1;
+        let guardian =
+            Pubkey::try_from(&src[68..100]).map_err(|_| ProgramError::InvalidArgument)?;
+        let is_paused = src[100] == 1;
+        let is_emergency_mode = src[101] == 1;
+        let is_allowlist_enabled = src[102] == 1;
+        let allowlist_root = src[103..135].try_into().unwrap();
+        let max_total_locked = u64::from_le_bytes(src[135..143].try_into().unwrap());
+        let max_locked_per_wallet = u64::from_le_bytes(src[143..151].try_into().unwrap());
+        let mut min_lock_amounts = [0; 5];
+        for (min_lock_amount, src) in min_lock_amounts
+            .iter_mut()
+            .zip(src[151..191].chunks_exact(8))
+        {
+            *min_lock_amount = u64::from_le_bytes(src.try_into().unwrap());
+        }
+        let deposit_fee_treasury =
+            Pubkey::try_from(&src[191..223]).map_err(|_| ProgramError::InvalidArgument)?;
+        let deposit_fee_bps = u16::from_le_bytes(src[223..225].try_into().unwrap());
+        let referral_share_bps = u16::from_le_bytes(src[225..227].try_into().unwrap());
+        let lock_mode = LockMode::from_u8(src[227]).ok_or(ProgramError::InvalidAccountData)?;
+        Ok(Self {
+            admin,
+            penalty_treasury,
+            max_penalty_bps,
+            bump,
+            is_initialized,
+            guardian,
+            is_paused,
+            is_emergency_mode,
+            is_allowlist_enabled,
+            allowlist_root,
+            max_total_locked,
+            max_locked_per_wallet,
+            min_lock_amounts,
+            deposit_fee_treasury,
+            deposit_fee_bps,
+            referral_share_bps,
+            lock_mode,
+        })
+    }
+}
+
+impl IsInitialized for Config {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+/// An allowlisted wallet, allowed to create locks while the allowlist of the config is enabled
+#[derive(Debug, PartialEq, ShankAccount)]
+pub struct AllowlistEntry {
+    pub wallet: Pubkey,
+    pub bump: u8,
+    pub is_initialized: bool,
+}
+
+impl Sealed for AllowlistEntry {}
+
+impl Pack for AllowlistEntry {
+    const LEN: usize = 34;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[..32].copy_from_slice(self.wallet.as_ref());
+        dst[32] = self.bump;
+        dst[33] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let wallet = Pubkey::try_from(&src[..32]).map_err(|_| ProgramError::InvalidArgument)?;
+        Ok(Self {
+            wallet,
+            bump: src[32],
+            is_initialized: src[33] == 1,
+        })
+    }
+}
+
+impl IsInitialized for AllowlistEntry {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+/// Totals of the locks created with a referrer. They are never decreased when the locks are
+/// released.
+#[derive(Debug, PartialEq, ShankAccount)]
+pub struct Referrer {
+    pub referrer: Pubkey,
+    pub bump: u8,
+    pub is_initialized: bool,
+    /// Amount locked by the referred locks, net of the deposit fee
+    pub total_locked: u64,
+    /// Amounts locked by the referred locks, weighted by the `TIER_WEIGHTS` of their tiers
+    pub weighted_volume: u64,
+}
+
+impl Referrer {
+    /// Credits the referrer with a lock of `amount` tokens in `tier`
+    pub fn record_lock(&mut self, tier: u8, amount: u64) {
+        self.total_locked = self.total_locked.saturating_add(amount);
+        self.weighted_volume = self
+            .weighted_volume
+            .saturating_add(amount.saturating_mul(TIER_WEIGHTS[usize::from(tier)]));
+    }
+}
+
+impl Sealed for Referrer {}
+
+impl Pack for Referrer {
+    const LEN: usize = 50;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[..32].copy_from_slice(self.referrer.as_ref());
+        dst[32] = self.bump;
+        dst[33] = self.is_initialized as u8;
+        dst[34..42].copy_from_slice(&self.total_locked.to_le_bytes());
+        dst[42..50].copy_from_slice(&self.weighted_volume.to_le_bytes());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let referrer = Pubkey::try_from(&src[..32]).map_err(|_| ProgramError::InvalidArgument)?;
+        Ok(Self {
+            referrer,
+            bump: src[32],
+            is_initialized: src[33] == 1,
+            total_locked: u64::from_le_bytes(src[34..42].try_into().unwrap()),
+            weighted_volume: u64::from_le_bytes(src[42..50].try_into().unwrap()),
+        })
+    }
+}
+
+impl IsInitialized for Referrer {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+/// Totals of the active locks of a tier
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TierStats {
+    /// Tokens held by the locks, including the ones in their withdrawal period
+    pub total_locked: u64,
+    /// Locks that still hold tokens
+    pub active_locks: u64,
+    /// Tokens of the unlocked stakes whose withdrawal period has been initialized
+    pub pending_cooldown: u64,
+}
+
+impl TierStats {
+    const LEN: usize = 24;
+}
+
+/// Program-wide statistics of the locks, by tier, kept up to date by every instruction creating
+/// or releasing a lock
+#[derive(Debug, PartialEq, ShankAccount)]
+pub struct Stats {
+    pub tiers: [TierStats; 5],
+    pub bump: u8,
+    pub is_initialized: bool,
+}
+
+impl Stats {
+    /// Tokens held by the locks of all the tiers
+    pub fn total_locked(&self) -> u64 {
+        self.tiers
+            .iter()
+            .fold(0, |total, tier| total.saturating_add(tier.total_locked))
+    }
+
+    /// Records a new lock of the tier
+    pub fn record_lock(&mut self, tier: u8, amount: u64) {
+        let tier = &mut self.tiers[usize::from(tier)];
+        tier.total_locked = tier.total_locked.saturating_add(amount);
+        tier.active_locks = tier.active_locks.saturating_add(1);
+    }
+
+    /// Records the start of the withdrawal period of a lock
+    pub fn record_cooldown(&mut self, tier: u8, amount: u64) {
+        let tier = &mut self.tiers[usize::from(tier)];
+        tier.pending_cooldown = tier.pending_cooldown.saturating_add(amount);
+    }
+
+    /// Records a lock split into two locks of the same tier, the locked amount is unchanged
+    pub fn record_split(&mut self, tier: u8) {
+        let tier = &mut self.tiers[usize::from(tier)];
+        tier.active_locks = tier.active_locks.saturating_add(1);
+    }
+
+    /// Records a lock merged into another lock of the same tier, the locked amount is unchanged
+    pub fn record_merge(&mut self, tier: u8) {
+        let tier = &mut self.tiers[usize::from(tier)];
+        tier.active_locks = tier.active_locks.saturating_sub(1);
+    }
+
+    /// Records the release of all the tokens of a lock, given its release time. Saturating, so
+    /// that the stats never prevent a lock from being released.
+    pub fn record_release(
+        &mut self,
+        header: &VestingScheduleHeader,
+        release_time: u64,
+        amount: u64,
+    ) {
+        // Unlocked stakes only have a release time once their withdrawal period started
+        let in_cooldown = header.tier == 0 && release_time != 0;
+        let tier = &mut self.tiers[usize::from(header.tier)];
+        tier.total_locked = tier.total_locked.saturating_sub(amount);
+        tier.active_locks = tier.active_locks.saturating_sub(1);
+        if in_cooldown {
+            tier.pending_cooldown = tier.pending_cooldown.saturating_sub(amount);
+        }
+    }
+}
+
+impl Sealed for Stats {}
+
+impl Pack for Stats {
+    const LEN: usize = 122;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        for (tier, dst) in self.tiers.iter().zip(dst.chunks_exact_mut(TierStats::LEN)) {
+            dst[..8].copy_from_slice(&tier.total_locked.to_le_bytes());
+            dst[8..16].copy_from_slice(&tier.active_locks.to_le_bytes());
+            dst[16..24].copy_from_slice(&tier.pending_cooldown.to_le_bytes());
+        }
+        dst[120] = self.bump;
+        dst[121] = self.is_initialized as u8;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut tiers = [TierStats::default(); 5];
+        for (tier, src) in tiers.iter_mut().zip(src.chunks_exact(TierStats::LEN)) {
+            tier.total_locked = u64::from_le_bytes(src[..8].try_into().unwrap());
+            tier.active_locks = u64::from_le_bytes(src[8..16].try_into().unwrap());
+            tier.pending_cooldown = u64::from_le_bytes(src[16..24].try_into().unwrap());
+        }
+        let bump = src[120];
+        let is_initialized = src[121] == 1;
+        Ok(Self {
+            tiers,
+            bump,
+            is_initialized,
+        })
+    }
+}
+
+impl IsInitialized for Stats {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+/// Maximum number of open positions of a registry
+pub const MAX_POSITIONS: usize = 64;
+
+/// Number of positions of a registry only taken by the locks registered by its owner, so that
+/// the locks other wallets create for it can not fill the registry
+pub const RESERVED_POSITIONS: usize = 16;
+
+/// A lock recorded in a position registry
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Position {
+    pub vesting_account: Pubkey,
+    /// Index of the position among all the locks registered to the owner, never reused
+    pub index: u64,
+    /// Amount of the lock when it was registered, updated when tokens move between the
+    /// positions of the registry. Released locks keep it until they are closed.
+    pub amount: u64,
+}
+
+impl Position {
+    const LEN: usize = 48;
+}
+
+/// The open locks of a wallet, the owner of their destination token accounts. Every instruction
+/// creating a lock for a wallet with a registry registers it, and `Close` removes it once
+/// released. A lock is left unregistered, without failing, once the registry has no position left
+/// for it.
+#[derive(Debug, PartialEq, ShankAccount)]
+pub struct PositionRegistry {
+    pub owner: Pubkey,
+    pub bump: u8,
+    pub is_initialized: bool,
+    /// Index of the next registered position
+    pub next_index: u64,
+    /// Number of open positions, at the start of `positions`
+    pub len: u8,
+    pub positions: [Position; 64],
+}
+
+impl PositionRegistry {
+    /// The open positions, in the order they were registered
+    pub fn open_positions(&self) -> &[Position] {
+        &self.positions[..usize::from(self.len)]
+    }
+
+    /// Amount of the open positions, checked against the per-wallet cap of the config
+    pub fn locked_amount(&self) -> u64 {
+        self.open_positions()
+            .iter()
+            .fold(0, |total, position| total.saturating_add(position.amount))
+    }
+
+    /// Registers a lock and returns its position index, or `None` if the registry is full. Only
+    /// the locks registered by the owner take the last `RESERVED_POSITIONS` positions.
+    pub fn add(&mut self, vesting_account: Pubkey, amount: u64, by_owner: bool) -> Option<u64> {
+        let len = usize::from(self.len);
+        let max_len = if by_owner {
+            MAX_POSITIONS
+        } else {
+            MAX_POSITIONS - RESERVED_POSITIONS
+        };
+        if len >= max_len {
+            return None;
+        }
+        let index = self.next_index;
+        self.positions[len] = Position {
+            vesting_account,
+            index,
+            amount,
+        };
+        self.len += 1;
+        self.next_index += 1;
+        Some(index)
+    }
+
+    /// Updates the amount of a lock. Returns whether it was registered.
+    pub fn set_amount(&mut self, vesting_account: &Pubkey, amount: u64) -> bool {
+        let len = usize::from(self.len);
+        match self.positions[..len]
+            .iter_mut()
+            .find(|position| position.vesting_account == *vesting_account)
+        {
+            Some(position) => {
+                position.amount = amount;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes a lock, keeping the other positions in order. Returns whether it was registered.
+    pub fn remove(&mut self, vesting_account: &Pubkey) -> bool {
+        let len = usize::from(self.len);
+        match self
+            .open_positions()
+            .iter()
+            .position(|position| position.vesting_account == *vesting_account)
+        {
+            Some(i) => {
+                self.positions.copy_within(i + 1..len, i);
+                self.positions[len - 1] = Position::default();
+                self.len -= 1;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Sealed for PositionRegistry {}
+
+impl Pack for PositionRegistry {
+    const LEN: usize = 43 + MAX_POSITIONS * Position::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[..32].copy_from_slice(self.owner.as_ref());
+        dst[32] = self.bump;
+        dst[33] = self.is_initialized as u8;
+        dst[34..42].copy_from_slice(&self.next_index.to_le_bytes());
+        dst[42] = self.len;
+        for (position, dst) in self
+            .positions
+            .iter()
+            .zip(dst[43..].chunks_exact_mut(Position::LEN))
+        {
+            dst[..32].copy_from_slice(position.vesting_account.as_ref());
+            dst[32..40].copy_from_slice(&position.index.to_le_bytes());
+            dst[40..48].copy_from_slice(&position.amount.to_le_bytes());
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let owner = Pubkey::try_from(&src[..32]).map_err(|_| ProgramError::InvalidArgument)?;
+        let bump = src[32];
+        let is_initialized = src[33] == 1;
+        let next_index = u64::from_le_bytes(src[34..42].try_into().unwrap());
+        let len = src[42];
+        if usize::from(len) > MAX_POSITIONS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut positions = [Position::default(); MAX_POSITIONS];
+        for (position, src) in positions
+            .iter_mut()
+            .zip(src[43..Self::LEN].chunks_exact(Position::LEN))
+        {
+            position.vesting_account =
+                Pubkey::try_from(&src[..32]).map_err(|_| ProgramError::InvalidArgument)?;
+            position.index = u64::from_le_bytes(src[32..40].try_into().unwrap());
+            position.amount = u64::from_le_bytes(src[40..48].try_into().unwrap());
+        }
+        Ok(Self {
+            owner,
+            bump,
+            is_initialized,
+            next_index,
+            len,
+            positions,
+        })
+    }
+}
+
+impl IsInitialized for PositionRegistry {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
 pub fn unpack_schedule(input: &[u8]) -> Result<VestingSchedule, ProgramError> {
     let output: VestingSchedule =
         VestingSchedule::unpack_from_slice(&input[..VestingSchedule::LEN])?;
@@ -93,21 +890,39 @@ pub fn pack_schedule_into_slice(schedule: VestingSchedule, target: &mut [u8]) {
 
 #[cfg(test)]
 mod tests {
-    use super::{unpack_schedule, VestingSchedule, VestingScheduleHeader};
-    use solana_program::{program_pack::Pack, pubkey::Pubkey};
+    use super::{
+        unpack_schedule, Airdrop, AllowlistEntry, Config, LockMode, PositionRegistry, Referrer,
+        Stats, TierStats, VestingSchedule, VestingScheduleHeader, MAX_POSITIONS,
+        RESERVED_POSITIONS,
+    };
+    use crate::hook::{Hook, HookMode};
+    use solana_program::{clock::Clock, program_pack::Pack, pubkey::Pubkey};
 
     #[test]
     fn test_state_packing() {
         let header_state = VestingScheduleHeader {
             destination_address: Pubkey::new_unique(),
             is_initialized: true,
+            revocation_authority: Pubkey::new_unique(),
+            is_revoked: false,
+            lock_mode: LockMode::Slot,
+            tier: 2,
+            position_mint: Pubkey::new_unique(),
+            pending_destination_address: Pubkey::new_unique(),
+            seeds: [7; 32],
+            keeper_tip: 5_000_000,
+            auto_renew: true,
+            hook_program: Pubkey::new_unique(),
+            hook_mode: HookMode::Skippable,
+            referrer: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
         };
         let schedule_state = VestingSchedule {
             release_time: 30767976,
             amount: 969,
         };
         let state_size = VestingScheduleHeader::LEN + VestingSchedule::LEN;
-        let mut state_array = [0u8; 49];
+        let mut state_array = [0u8; 286];
         header_state.pack_into_slice(&mut state_array[..VestingScheduleHeader::LEN]);
         schedule_state.pack_into_slice(
             &mut state_array
@@ -117,6 +932,19 @@ mod tests {
         let mut expected = Vec::with_capacity(state_size);
         expected.extend_from_slice(&header_state.destination_address.to_bytes());
         expected.extend_from_slice(&[header_state.is_initialized as u8]);
+        expected.extend_from_slice(&header_state.revocation_authority.to_bytes());
+        expected.extend_from_slice(&[header_state.is_revoked as u8]);
+        expected.extend_from_slice(&[header_state.lock_mode as u8]);
+        expected.extend_from_slice(&[header_state.tier]);
+        expected.extend_from_slice(&header_state.position_mint.to_bytes());
+        expected.extend_from_slice(&header_state.pending_destination_address.to_bytes());
+        expected.extend_from_slice(&header_state.seeds);
+        expected.extend_from_slice(&header_state.keeper_tip.to_le_bytes());
+        expected.extend_from_slice(&[header_state.auto_renew as u8]);
+        expected.extend_from_slice(&header_state.hook_program.to_bytes());
+        expected.extend_from_slice(&[header_state.hook_mode as u8]);
+        expected.extend_from_slice(&header_state.referrer.to_bytes());
+        expected.extend_from_slice(&header_state.owner.to_bytes());
         expected.extend_from_slice(&schedule_state.release_time.to_le_bytes());
         expected.extend_from_slice(&schedule_state.amount.to_le_bytes());
 
@@ -127,5 +955,278 @@ mod tests {
         assert_eq!(unpacked_header, header_state);
         let unpacked_schedules = unpack_schedule(&packed[VestingScheduleHeader::LEN..]).unwrap();
         assert_eq!(unpacked_schedules, schedule_state);
+        assert_eq!(
+            unpacked_header.revocation_authority(),
+            Some(header_state.revocation_authority)
+        );
+
+        // A zeroed revocation authority means the lock is not revocable
+        let unrevocable_header =
+            VestingScheduleHeader::unpack_from_slice(&[0; VestingScheduleHeader::LEN]).unwrap();
+        assert_eq!(unrevocable_header.revocation_authority(), None);
+        assert_eq!(unrevocable_header.position_mint(), None);
+        assert_eq!(unrevocable_header.pending_destination(), None);
+        assert_eq!(unrevocable_header.hook(), None);
+        assert_eq!(
+            unpacked_header.hook(),
+            Some(Hook {
+                program_id: header_state.hook_program,
+                mode: HookMode::Skippable,
+            })
+        );
+        assert_eq!(
+            unpacked_header.position_mint(),
+            Some(header_state.position_mint)
+        );
+
+        // Unknown tiers are rejected
+        let mut unknown_tier = [0u8; VestingScheduleHeader::LEN];
+        unknown_tier[67] = 5;
+        assert!(VestingScheduleHeader::unpack_from_slice(&unknown_tier).is_err());
+    }
+
+    #[test]
+    fn test_airdrop_packing() {
+        let airdrop = Airdrop {
+            authority: Pubkey::new_unique(),
+            merkle_root: [7u8; 32],
+            vault: Pubkey::new_unique(),
+            is_initialized: true,
+        };
+        let mut packed = [0u8; Airdrop::LEN];
+        Airdrop::pack(airdrop, &mut packed).unwrap();
+        let unpacked = Airdrop::unpack(&packed).unwrap();
+        assert_eq!(unpacked.merkle_root, [7u8; 32]);
+        assert_eq!(&packed[..32], unpacked.authority.as_ref());
+        assert_eq!(&packed[64..96], unpacked.vault.as_ref());
+        assert!(Airdrop::unpack(&[0u8; Airdrop::LEN]).is_err());
+    }
+
+    #[test]
+    fn test_config_packing() {
+        let config = Config {
+            admin: Pubkey::new_unique(),
+            penalty_treasury: Pubkey::new_unique(),
+            max_penalty_bps: 2_500,
+            bump: 254,
+            is_initialized: true,
+            guardian: Pubkey::new_unique(),
+            is_paused: true,
+            is_emergency_mode: false,
+            is_allowlist_enabled: true,
+            allowlist_root: [7; 32],
+            max_total_locked: 1_000_000,
+            max_locked_per_wallet: 10_000,
+            min_lock_amounts: [1, 2, 3, 4, 5],
+            deposit_fee_treasury: Pubkey::new_unique(),
+            deposit_fee_bps: 50,
+            referral_share_bps: 2_000,
+            lock_mode: LockMode::Epoch,
+        };
+        let mut packed = [0u8; Config::LEN];
+        Config::pack(config, &mut packed).unwrap();
+        let unpacked = Config::unpack(&packed).unwrap();
+        assert_eq!(unpacked.max_penalty_bps, 2_500);
+        assert_eq!(unpacked.bump, 254);
+        assert_eq!(&packed[..32], unpacked.admin.as_ref());
+        assert_eq!(&packed[32..64], unpacked.penalty_treasury.as_ref());
+        assert_eq!(&packed[68..100], unpacked.guardian.as_ref());
+        assert!(unpacked.is_paused);
+        assert!(!unpacked.is_emergency_mode);
+        assert!(unpacked.is_allowlist_enabled);
+        assert_eq!(unpacked.allowlist_root, [7; 32]);
+        assert_eq!(&packed[135..143], &1_000_000u64.to_le_bytes());
+        assert_eq!(unpacked.max_locked_per_wallet, 10_000);
+        assert_eq!(&packed[183..191], &5u64.to_le_bytes());
+        assert_eq!(unpacked.min_lock_amounts, [1, 2, 3, 4, 5]);
+        assert_eq!(&packed[191..223], unpacked.deposit_fee_treasury.as_ref());
+        assert_eq!(unpacked.deposit_fee_bps, 50);
+        assert_eq!(unpacked.referral_share_bps, 2_000);
+        assert_eq!(unpacked.lock_mode, LockMode::Epoch);
+        assert!(Config::unpack(&[0u8; Config::LEN]).is_err());
+
+        let entry = AllowlistEntry {
+            wallet: Pubkey::new_unique(),
+            bump: 253,
+            is_initialized: true,
+        };
+        let mut packed = [0u8; AllowlistEntry::LEN];
+        AllowlistEntry::pack(entry, &mut packed).unwrap();
+        let unpacked = AllowlistEntry::unpack(&packed).unwrap();
+        assert_eq!(&packed[..32], unpacked.wallet.as_ref());
+        assert_eq!(unpacked.bump, 253);
+    }
+
+    #[test]
+    fn test_referrer() {
+        let mut referrer = Referrer::unpack_from_slice(&[0u8; Referrer::LEN]).unwrap();
+        referrer.referrer = Pubkey::new_unique();
+        referrer.bump = 252;
+        referrer.is_initialized = true;
+        referrer.record_lock(0, 100);
+        referrer.record_lock(4, 30);
+        assert_eq!(referrer.total_locked, 130);
+        assert_eq!(referrer.weighted_volume, 100 + 30 * 5);
+
+        let mut packed = [0u8; Referrer::LEN];
+        Referrer::pack(referrer, &mut packed).unwrap();
+        let unpacked = Referrer::unpack(&packed).unwrap();
+        assert_eq!(&packed[..32], unpacked.referrer.as_ref());
+        assert_eq!(unpacked.bump, 252);
+        assert_eq!(&packed[42..50], &250u64.to_le_bytes());
+        assert!(Referrer::unpack(&[0u8; Referrer::LEN]).is_err());
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut stats = Stats::unpack_from_slice(&[0u8; Stats::LEN]).unwrap();
+        stats.bump = 253;
+        stats.is_initialized = true;
+        stats.record_lock(0, 100);
+        stats.record_lock(0, 50);
+        stats.record_lock(3, 70);
+        stats.record_cooldown(0, 100);
+        stats.record_split(3);
+        assert_eq!(stats.tiers[3].active_locks, 2);
+        stats.record_merge(3);
+        assert_eq!(stats.tiers[3].total_locked, 70);
+        assert_eq!(stats.total_locked(), 220);
+
+        let mut header =
+            VestingScheduleHeader::unpack_from_slice(&[0; VestingScheduleHeader::LEN]).unwrap();
+        stats.record_release(&header, 1_700_000_000, 100);
+        header.tier = 3;
+        stats.record_release(&header, 1_700_000_000, 70);
+        assert_eq!(
+            stats.tiers[0],
+            TierStats {
+                total_locked: 50,
+                active_locks: 1,
+                pending_cooldown: 0,
+            }
+        );
+        assert_eq!(stats.tiers[3], TierStats::default());
+
+        let mut packed = [0u8; Stats::LEN];
+        Stats::pack(stats, &mut packed).unwrap();
+        assert_eq!(&packed[..8], &50u64.to_le_bytes());
+        assert_eq!(&packed[8..16], &1u64.to_le_bytes());
+        assert_eq!(packed[120], 253);
+        let unpacked = Stats::unpack(&packed).unwrap();
+        assert_eq!(unpacked.tiers[0].total_locked, 50);
+        assert!(Stats::unpack(&[0u8; Stats::LEN]).is_err());
+    }
+
+    #[test]
+    fn test_position_registry() {
+        let mut registry =
+            PositionRegistry::unpack_from_slice(&[0u8; PositionRegistry::LEN]).unwrap();
+        registry.owner = Pubkey::new_unique();
+        registry.bump = 252;
+        registry.is_initialized = true;
+        let locks: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        for (i, lock) in locks.iter().enumerate() {
+            assert_eq!(registry.add(*lock, 100, true), Some(i as u64));
+        }
+        assert_eq!(registry.locked_amount(), 300);
+
+        // Removing a position keeps the others in order, and indexes are never reused
+        assert!(registry.remove(&locks[1]));
+        assert!(!registry.remove(&locks[1]));
+        let lock = Pubkey::new_unique();
+        assert_eq!(registry.add(lock, 50, false), Some(3));
+        assert!(registry.set_amount(&locks[2], 20));
+        assert!(!registry.set_amount(&locks[1], 20));
+        assert_eq!(registry.locked_amount(), 170);
+        let open: Vec<(Pubkey, u64)> = registry
+            .open_positions()
+            .iter()
+            .map(|position| (position.vesting_account, position.index))
+            .collect();
+        assert_eq!(open, vec![(locks[0], 0), (locks[2], 2), (lock, 3)]);
+
+        let mut packed = vec![0u8; PositionRegistry::LEN];
+        registry.pack_into_slice(&mut packed);
+        assert_eq!(&packed[..32], registry.owner.as_ref());
+        assert_eq!(&packed[34..42], &4u64.to_le_bytes());
+        assert_eq!(packed[42], 3);
+        assert_eq!(PositionRegistry::unpack(&packed).unwrap(), registry);
+        assert!(PositionRegistry::unpack(&[0u8; PositionRegistry::LEN]).is_err());
+
+        // Other wallets do not take the reserved positions, and a full registry does not accept
+        // more positions
+        while usize::from(registry.len) < MAX_POSITIONS - RESERVED_POSITIONS {
+            registry.add(Pubkey::new_unique(), 0, false).unwrap();
+        }
+        assert_eq!(registry.add(Pubkey::new_unique(), 0, false), None);
+        while usize::from(registry.len) < MAX_POSITIONS {
+            registry.add(Pubkey::new_unique(), 0, true).unwrap();
+        }
+        assert_eq!(registry.add(Pubkey::new_unique(), 0, true), None);
+        packed[42] = MAX_POSITIONS as u8 + 1;
+        assert!(PositionRegistry::unpack_from_slice(&packed).is_err());
+    }
+
+    #[test]
+    fn test_lock_mode() {
+        let clock = Clock {
+            slot: 1_000,
+            epoch: 2,
+            unix_timestamp: 1_700_000_000,
+            ..Clock::default()
+        };
+        assert_eq!(LockMode::Timestamp.now(&clock), 1_700_000_000);
+        assert_eq!(LockMode::Slot.now(&clock), 1_000);
+        assert_eq!(LockMode::Epoch.now(&clock), 2);
+
+        // The 3 months tier
+        assert_eq!(LockMode::Timestamp.from_seconds(7_776_000), 7_776_000);
+        assert_eq!(LockMode::Slot.from_seconds(7_776_000), 19_440_000);
+        assert_eq!(LockMode::Epoch.from_seconds(7_776_000), 45);
+        // Partial slots and epochs are rounded up
+        assert_eq!(LockMode::Slot.from_seconds(1), 3);
+        assert_eq!(LockMode::Epoch.from_seconds(604_800), 4);
+        assert_eq!(LockMode::Epoch.from_seconds(1), 1);
+        for mode in [LockMode::Timestamp, LockMode::Slot, LockMode::Epoch] {
+            assert_eq!(mode.to_seconds(mode.from_seconds(7_776_000)), 7_776_000);
+            assert_eq!(LockMode::from_u8(mode as u8), Some(mode));
+        }
+        assert_eq!(LockMode::from_u8(3), None);
+    }
+
+    #[test]
+    fn test_auto_renew() {
+        let mut header =
+            VestingScheduleHeader::unpack_from_slice(&[0; VestingScheduleHeader::LEN]).unwrap();
+        header.tier = 1;
+        let schedule = VestingSchedule {
+            release_time: 10_000_000,
+            amount: 969,
+        };
+        assert_eq!(header.release_time(&schedule, 20_000_000), 10_000_000);
+
+        // Auto-renewing locks roll forward by the period of their tier at each maturity
+        header.auto_renew = true;
+        assert_eq!(header.release_time(&schedule, 9_999_999), 10_000_000);
+        assert_eq!(header.release_time(&schedule, 10_000_000), 17_776_000);
+        assert_eq!(header.release_time(&schedule, 17_776_000), 25_552_000);
+        assert_eq!(header.last_maturity(&schedule, 9_999_999), None);
+        assert_eq!(
+            header.last_maturity(&schedule, 10_000_000),
+            Some(10_000_000)
+        );
+        assert_eq!(
+            header.last_maturity(&schedule, 20_000_000),
+            Some(17_776_000)
+        );
+        // The 3 months period is exactly 45 epochs, rounding it up does not lengthen it
+        header.lock_mode = LockMode::Epoch;
+        assert_eq!(header.renewal_period(), 45);
+        assert_eq!(header.release_time(&schedule, 10_000_100), 10_000_135);
+
+        // Unlocked stakes are never renewed
+        header.tier = 0;
+        assert_eq!(header.release_time(&schedule, 20_000_000), 10_000_000);
+        assert_eq!(header.last_maturity(&schedule, 20_000_000), None);
     }
 }
@@ -0,0 +1,1674 @@
+//! Test environment shared by the behaviour tests: the program with its mint, a funded source
+//! token account, and the lifecycle of the locks built from the instructions of the program.
+#![allow(dead_code)]
+
+use std::collections::BTreeMap;
+
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    message::Message,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::{
+    get_associated_token_address,
+    instruction::{create_associated_token_account, create_associated_token_account_idempotent},
+};
+use token_vesting::{
+    error::VestingError,
+    hook::Hook,
+    instruction::{
+        self, AllowlistAccess, BatchEntry, BatchLock, CreateArgs, MergedLock, Position, Referral,
+        Schedule, UnlockedLock,
+    },
+    merkle,
+    processor::{
+        Processor, AIRDROP_SEED, ALLOWLIST_SEED, CONFIG_SEED, HOOK_AUTHORITY_SEED, RECEIPT_SEED,
+        REFERRER_SEED, REGISTRY_SEED, STATS_SEED, TOKEN_MINT,
+    },
+    state::{
+        unpack_schedule, Config, LockMode, PositionRegistry, Referrer, Stats, VestingSchedule,
+        VestingScheduleHeader,
+    },
+};
+
+/// Locks created in a single `CreateBatch` transaction
+const BATCH_SIZE: usize = 3;
+
+/// Compute units requested for every lock of a batch
+const COMPUTE_UNITS_PER_LOCK: u32 = 90_000;
+
+pub fn config_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[CONFIG_SEED], program_id).0
+}
+
+pub fn stats_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[STATS_SEED], program_id).0
+}
+
+pub fn registry_address(program_id: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[REGISTRY_SEED, owner.as_ref()], program_id).0
+}
+
+pub fn receipt_mint_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[RECEIPT_SEED], program_id).0
+}
+
+pub fn allowlist_entry_address(program_id: &Pubkey, wallet: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ALLOWLIST_SEED, wallet.as_ref()], program_id).0
+}
+
+pub fn referrer_address(program_id: &Pubkey, referrer: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[REFERRER_SEED, referrer.as_ref()], program_id).0
+}
+
+pub fn hook_authority_address(program_id: &Pubkey, vesting_account: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[HOOK_AUTHORITY_SEED, vesting_account.as_ref()], program_id).0
+}
+
+/// Address of the program data account of the program, holding its upgrade authority
+pub fn program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+/// The addresses of a lock, all derived from its seeds
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockAddresses {
+    pub seeds: [u8; 32],
+    pub vesting_account: Pubkey,
+    pub vesting_token_account: Pubkey,
+}
+
+impl LockAddresses {
+    /// Derives the addresses of a lock from its seed, with the canonical bump seed
+    pub fn find(program_id: &Pubkey, seed: &[u8; 31]) -> Self {
+        let (vesting_account, bump) = Pubkey::find_program_address(&[seed], program_id);
+        let mut seeds = [0u8; 32];
+        seeds[..31].copy_from_slice(seed);
+        seeds[31] = bump;
+        Self::with_vesting_account(seeds, vesting_account)
+    }
+
+    /// Derives the addresses of a lock from its full seeds, if they are off the curve
+    pub fn from_seeds(program_id: &Pubkey, seeds: [u8; 32]) -> Option<Self> {
+        let vesting_account = Pubkey::create_program_address(&[&seeds], program_id).ok()?;
+        Some(Self::with_vesting_account(seeds, vesting_account))
+    }
+
+    fn with_vesting_account(seeds: [u8; 32], vesting_account: Pubkey) -> Self {
+        Self {
+            seeds,
+            vesting_account,
+            vesting_token_account: get_associated_token_address(&vesting_account, &TOKEN_MINT),
+        }
+    }
+}
+
+/// The decoded state of a vesting account
+#[derive(Debug, PartialEq)]
+pub struct LockInfo {
+    pub header: VestingScheduleHeader,
+    pub schedule: VestingSchedule,
+}
+
+/// The optional terms of a lock, none of them by default
+#[derive(Clone, Debug, Default)]
+pub struct LockOptions {
+    /// The token account the tokens are locked from, the associated token account of the source
+    /// owner by default
+    pub source_token_account: Option<Pubkey>,
+    /// The token account the tokens are released to, the source token account by default
+    pub beneficiary_token_account: Option<Pubkey>,
+    pub revocation_authority: Option<Pubkey>,
+    pub keeper_tip: u64,
+    pub auto_renew: bool,
+    pub hook: Option<Hook>,
+    /// Proof of the source owner against the allowlist root, used instead of its allowlist entry
+    /// while the allowlist is enabled
+    pub allowlist_proof: Option<Vec<[u8; 32]>>,
+    pub referrer: Option<Pubkey>,
+    /// Whether the position token of the lock is minted to the beneficiary owner
+    pub position: bool,
+}
+
+/// An entry of an airdrop: the wallet claims `amount` tokens into a lock of `time_delta` seconds
+#[derive(Clone, Debug, PartialEq)]
+pub struct AirdropEntry {
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub time_delta: u64,
+}
+
+impl AirdropEntry {
+    pub fn leaf(&self) -> [u8; 32] {
+        merkle::leaf_hash(&self.wallet, self.amount, self.time_delta)
+    }
+}
+
+/// The addresses of an airdrop, all derived from its seed
+#[derive(Clone, Debug, PartialEq)]
+pub struct AirdropAddresses {
+    pub seeds: [u8; 32],
+    pub airdrop_account: Pubkey,
+    pub vault_token_account: Pubkey,
+}
+
+impl AirdropAddresses {
+    pub fn find(program_id: &Pubkey, seed: &[u8; 31]) -> Self {
+        let (airdrop_account, bump) =
+            Pubkey::find_program_address(&[AIRDROP_SEED, seed], program_id);
+        let mut seeds = [0u8; 32];
+        seeds[..31].copy_from_slice(seed);
+        seeds[31] = bump;
+        Self {
+            seeds,
+            airdrop_account,
+            vault_token_account: get_associated_token_address(&airdrop_account, &TOKEN_MINT),
+        }
+    }
+
+    /// Addresses of the lock a wallet claims its entry into
+    pub fn claim_lock(&self, program_id: &Pubkey, wallet: &Pubkey) -> LockAddresses {
+        LockAddresses::find(
+            program_id,
+            &merkle::claim_seed(&self.airdrop_account, wallet),
+        )
+    }
+}
+
+/// Merkle tree hashed the way the program verifies the proofs, a node without sibling being
+/// paired with itself
+pub struct MerkleTree {
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    pub fn new(leaves: Vec<[u8; 32]>) -> Self {
+        let mut levels = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let parents = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| merkle::node_hash(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+                .collect();
+            levels.push(parents);
+        }
+        Self { levels }
+    }
+
+    pub fn airdrop(entries: &[AirdropEntry]) -> Self {
+        Self::new(entries.iter().map(AirdropEntry::leaf).collect())
+    }
+
+    pub fn allowlist(wallets: &[Pubkey]) -> Self {
+        Self::new(wallets.iter().map(merkle::allowlist_leaf_hash).collect())
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.levels.last().unwrap()[0]
+    }
+
+    pub fn proof(&self, index: usize) -> Vec<[u8; 32]> {
+        let mut index = index;
+        let mut proof = vec![];
+        for level in &self.levels[..self.levels.len() - 1] {
+            proof.push(*level.get(index ^ 1).unwrap_or(&level[index]));
+            index /= 2;
+        }
+        proof
+    }
+}
+
+pub struct TestEnv {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+    /// Owner of the source token account, when it is not a multisig
+    pub owner: Keypair,
+    pub source_token_account: Pubkey,
+    /// Accounts forwarded to the hook of a lock, by hook program
+    pub hook_accounts: BTreeMap<Pubkey, Vec<AccountMeta>>,
+}
+
+pub async fn setup(amount: u64) -> TestEnv {
+    let owner = Keypair::new();
+    let owner_pubkey = owner.pubkey();
+    start(ProgramTest::default(), owner, &owner_pubkey, amount).await
+}
+
+/// Sets up a source token account owned by an `m` of `n` SPL multisig, returning the multisig
+/// and its signers
+pub async fn setup_multisig(amount: u64, m: u8, n: u8) -> (TestEnv, Pubkey, Vec<Keypair>) {
+    let multisig = Pubkey::new_unique();
+    let signers: Vec<Keypair> = (0..n).map(|_| Keypair::new()).collect();
+    let mut signer_pubkeys = [Pubkey::default(); spl_token::instruction::MAX_SIGNERS];
+    for (signer_pubkey, signer) in signer_pubkeys.iter_mut().zip(&signers) {
+        *signer_pubkey = signer.pubkey();
+    }
+    let mut program_test = ProgramTest::default();
+    program_test.add_account(
+        multisig,
+        packed_account(spl_token::state::Multisig {
+            m,
+            n,
+            is_initialized: true,
+            signers: signer_pubkeys,
+        }),
+    );
+    let env = start(program_test, Keypair::new(), &multisig, amount).await;
+    (env, multisig, signers)
+}
+
+/// Sets up an environment whose config measures the locks with `lock_mode`
+pub async fn setup_pool(lock_mode: LockMode) -> TestEnv {
+    let mut env = setup(100).await;
+    let payer = env.payer();
+    let treasury = env.create_associated_token_account(&payer.pubkey()).await;
+    env.init_config(&payer, &treasury, &payer.pubkey(), 0, lock_mode)
+        .await
+        .unwrap();
+    env
+}
+
+/// Adds the program, the mint and a funded source token account, then starts the test validator
+/// and creates the stats account
+pub async fn start(
+    program_test: ProgramTest,
+    owner: Keypair,
+    source_owner: &Pubkey,
+    amount: u64,
+) -> TestEnv {
+    let mut env = start_without_stats(program_test, owner, source_owner, amount).await;
+    env.init_stats().await.unwrap();
+    env
+}
+
+/// Adds the program, the mint and a funded source token account, then starts the test validator
+/// and creates the receipt mint
+pub async fn start_without_stats(
+    mut program_test: ProgramTest,
+    owner: Keypair,
+    source_owner: &Pubkey,
+    amount: u64,
+) -> TestEnv {
+    let program_id = Pubkey::new_unique();
+    let source_token_account = get_associated_token_address(source_owner, &TOKEN_MINT);
+    program_test.add_program(
+        "token_vesting",
+        program_id,
+        processor!(Processor::process_instruction),
+    );
+    program_test.add_account(
+        TOKEN_MINT,
+        packed_account(spl_token::state::Mint {
+            mint_authority: COption::Some(Pubkey::new_unique()),
+            supply: amount,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        }),
+    );
+    program_test.add_account(
+        source_token_account,
+        packed_account(spl_token::state::Account {
+            mint: TOKEN_MINT,
+            owner: *source_owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..spl_token::state::Account::default()
+        }),
+    );
+
+    let context = program_test.start_with_context().await;
+    let mut env = TestEnv {
+        context,
+        program_id,
+        owner,
+        source_token_account,
+        hook_accounts: BTreeMap::new(),
+    };
+    env.set_upgrade_authority().await;
+    env.init_receipt_mint().await.unwrap();
+    env
+}
+
+/// An account holding a packed spl-token state
+pub fn packed_account<T: Pack>(state: T) -> Account {
+    let mut data = vec![0; T::LEN];
+    state.pack_into_slice(&mut data);
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: spl_token::id(),
+        ..Account::default()
+    }
+}
+
+/// Adds a token account holding the given mint to the test validator
+pub fn add_token_account(program_test: &mut ProgramTest, mint: Pubkey) -> Pubkey {
+    let token_account = Pubkey::new_unique();
+    program_test.add_account(
+        token_account,
+        packed_account(spl_token::state::Account {
+            mint,
+            owner: Pubkey::new_unique(),
+            state: spl_token::state::AccountState::Initialized,
+            ..spl_token::state::Account::default()
+        }),
+    );
+    token_account
+}
+
+/// Checks that a transaction failed with the given program error
+pub fn assert_vesting_error<T: std::fmt::Debug>(
+    result: Result<T, BanksClientError>,
+    error: VestingError,
+) {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, error as u32)
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+}
+
+impl TestEnv {
+    pub fn payer(&self) -> Keypair {
+        self.context.payer.insecure_clone()
+    }
+
+    pub fn lock_addresses(&self, seed: &[u8; 31]) -> LockAddresses {
+        LockAddresses::find(&self.program_id, seed)
+    }
+
+    pub fn airdrop_addresses(&self, seed: &[u8; 31]) -> AirdropAddresses {
+        AirdropAddresses::find(&self.program_id, seed)
+    }
+
+    /// Sends instructions paid by the payer with a new blockhash, signed by the signers among
+    /// `signers` that the instructions require
+    pub async fn send(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let payer = self.payer();
+        let message = Message::new(instructions, Some(&payer.pubkey()));
+        let required = &message.account_keys[..usize::from(message.header.num_required_signatures)];
+        let mut all_signers = vec![&payer];
+        for signer in signers {
+            if required.contains(&signer.pubkey())
+                && !all_signers.iter().any(|s| s.pubkey() == signer.pubkey())
+            {
+                all_signers.push(*signer);
+            }
+        }
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new(&all_signers, message, blockhash);
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    /// Sets an account in the bank. Its lamports come from the payer, keeping the capitalization
+    /// of the bank.
+    pub async fn set_funded_account(&mut self, address: &Pubkey, account: Account) {
+        let payer = self.context.payer.pubkey();
+        let mut payer_account = self.get_account(&payer).await.unwrap();
+        payer_account.lamports -= account.lamports;
+        self.context.set_account(&payer, &payer_account.into());
+        self.context.set_account(address, &account.into());
+    }
+
+    /// Makes the payer the upgrade authority of the program, as if it deployed it
+    async fn set_upgrade_authority(&mut self) {
+        let program_data = Account::new_data(
+            1_000_000_000,
+            &UpgradeableLoaderState::ProgramData {
+                slot: 0,
+                upgrade_authority_address: Some(self.context.payer.pubkey()),
+            },
+            &bpf_loader_upgradeable::id(),
+        )
+        .unwrap();
+        let program_data_address = program_data_address(&self.program_id);
+        self.set_funded_account(&program_data_address, program_data)
+            .await;
+    }
+
+    /// Sends lamports from the payer, for wallets paying for the accounts they create
+    pub async fn fund(&mut self, wallet: &Pubkey) {
+        let payer = self.context.payer.pubkey();
+        self.send(
+            &[system_instruction::transfer(&payer, wallet, 1_000_000_000)],
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+
+    /// Creates the associated token account of a wallet for the locked mint
+    pub async fn create_associated_token_account(&mut self, wallet: &Pubkey) -> Pubkey {
+        let payer = self.context.payer.pubkey();
+        self.send(
+            &[create_associated_token_account(
+                &payer,
+                wallet,
+                &TOKEN_MINT,
+                &spl_token::id(),
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+        get_associated_token_address(wallet, &TOKEN_MINT)
+    }
+
+    /// Moves the clock forward, warping to a later slot
+    pub async fn warp_forward(&mut self, seconds: i64) {
+        let clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        self.context.warp_to_slot(clock.slot + 100).unwrap();
+        let mut warped_clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+        warped_clock.unix_timestamp = clock.unix_timestamp + seconds;
+        self.context.set_sysvar(&warped_clock);
+    }
+
+    pub async fn get_clock(&mut self) -> Clock {
+        self.context.banks_client.get_sysvar().await.unwrap()
+    }
+
+    pub async fn get_account(&mut self, address: &Pubkey) -> Option<Account> {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+    }
+
+    pub async fn lamports(&mut self, address: &Pubkey) -> u64 {
+        self.context
+            .banks_client
+            .get_balance(*address)
+            .await
+            .unwrap()
+    }
+
+    pub async fn token_balance(&mut self, token_account: &Pubkey) -> u64 {
+        let account = self.get_account(token_account).await.unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    pub async fn token_account_owner(&mut self, token_account: &Pubkey) -> Pubkey {
+        let account = self.get_account(token_account).await.unwrap();
+        spl_token::state::Account::unpack_unchecked(&account.data)
+            .unwrap()
+            .owner
+    }
+
+    pub async fn get_lock(&mut self, addresses: &LockAddresses) -> LockInfo {
+        let account = self.get_account(&addresses.vesting_account).await.unwrap();
+        let (header, schedule) = account.data.split_at(VestingScheduleHeader::LEN);
+        LockInfo {
+            header: VestingScheduleHeader::unpack_from_slice(header).unwrap(),
+            schedule: unpack_schedule(schedule).unwrap(),
+        }
+    }
+
+    /// The position registry of a wallet, if it was created
+    pub async fn get_registry(&mut self, owner: &Pubkey) -> Option<PositionRegistry> {
+        let registry_address = registry_address(&self.program_id, owner);
+        let account = self.get_account(&registry_address).await?;
+        Some(PositionRegistry::unpack(&account.data).unwrap())
+    }
+
+    pub async fn get_stats(&mut self) -> Stats {
+        let stats_address = stats_address(&self.program_id);
+        let account = self.get_account(&stats_address).await.unwrap();
+        Stats::unpack(&account.data).unwrap()
+    }
+
+    /// The config of the program, if it was created
+    pub async fn get_config(&mut self) -> Option<Config> {
+        let config_address = config_address(&self.program_id);
+        let account = self.get_account(&config_address).await?;
+        Some(Config::unpack(&account.data).unwrap())
+    }
+
+    pub async fn get_referrer(&mut self, referrer: &Pubkey) -> Referrer {
+        let referrer_address = referrer_address(&self.program_id, referrer);
+        let account = self.get_account(&referrer_address).await.unwrap();
+        Referrer::unpack(&account.data).unwrap()
+    }
+
+    /// The deposit fee treasury of the config, while it charges a deposit fee
+    async fn deposit_fee_treasury(&mut self) -> Option<Pubkey> {
+        self.get_config()
+            .await
+            .filter(|config| config.deposit_fee_bps > 0)
+            .map(|config| config.deposit_fee_treasury)
+    }
+
+    pub async fn init_stats(&mut self) -> Result<(), BanksClientError> {
+        let instruction = instruction::init_stats(
+            &self.program_id,
+            &self.context.payer.pubkey(),
+            &stats_address(&self.program_id),
+        )
+        .unwrap();
+        self.send(&[instruction], &[]).await
+    }
+
+    pub async fn init_receipt_mint(&mut self) -> Result<(), BanksClientError> {
+        let instruction = instruction::init_receipt_mint(
+            &self.program_id,
+            &spl_token::id(),
+            &self.context.payer.pubkey(),
+            &receipt_mint_address(&self.program_id),
+            &TOKEN_MINT,
+        )
+        .unwrap();
+        self.send(&[instruction], &[]).await
+    }
+
+    /// Locks tokens of the owner of the environment, released to the beneficiary token account
+    /// or back to the source token account
+    pub async fn lock(
+        &mut self,
+        seed: u8,
+        schedule: Schedule,
+        options: LockOptions,
+    ) -> Result<LockAddresses, BanksClientError> {
+        let owner = self.owner.insecure_clone();
+        self.lock_with_signers(&owner.pubkey(), &[&owner], &[seed; 31], schedule, options)
+            .await
+    }
+
+    /// Locks tokens of the owner of the environment into an unlocked stake
+    pub async fn lock_stake(
+        &mut self,
+        seed: u8,
+        amount: u64,
+        beneficiary_token_account: Option<Pubkey>,
+    ) -> Result<LockAddresses, BanksClientError> {
+        let schedule = Schedule {
+            time_delta: 0,
+            amount,
+        };
+        let options = LockOptions {
+            beneficiary_token_account,
+            ..LockOptions::default()
+        };
+        self.lock(seed, schedule, options).await
+    }
+
+    /// Locks tokens of a source owner, signed by the owner itself or by the signers of a
+    /// multisig owner
+    pub async fn lock_with_signers(
+        &mut self,
+        source_owner: &Pubkey,
+        signers: &[&Keypair],
+        seed: &[u8; 31],
+        schedule: Schedule,
+        options: LockOptions,
+    ) -> Result<LockAddresses, BanksClientError> {
+        let position_mint = Keypair::new();
+        let signer_pubkeys: Vec<Pubkey> = signers
+            .iter()
+            .map(|signer| signer.pubkey())
+            .filter(|pubkey| pubkey != source_owner)
+            .collect();
+        let (addresses, instructions) = self
+            .lock_instructions(
+                source_owner,
+                &signer_pubkeys,
+                seed,
+                schedule,
+                options,
+                &position_mint.pubkey(),
+            )
+            .await;
+        let mut all_signers = signers.to_vec();
+        all_signers.push(&position_mint);
+        self.send(&instructions, &all_signers).await?;
+        Ok(addresses)
+    }
+
+    /// Instructions locking tokens of a source owner: they create the vesting account, its token
+    /// account, the position registry of the beneficiary owner and the accounts the options
+    /// need, then the lock. The position mint is only created if the options ask for one.
+    pub async fn lock_instructions(
+        &mut self,
+        source_owner: &Pubkey,
+        signer_pubkeys: &[Pubkey],
+        seed: &[u8; 31],
+        schedule: Schedule,
+        options: LockOptions,
+        position_mint: &Pubkey,
+    ) -> (LockAddresses, Vec<Instruction>) {
+        let program_id = self.program_id;
+        let payer = self.context.payer.pubkey();
+        let addresses = self.lock_addresses(seed);
+        let source_token_account = options
+            .source_token_account
+            .unwrap_or_else(|| get_associated_token_address(source_owner, &TOKEN_MINT));
+        let (beneficiary_token_account, beneficiary_owner) = match options.beneficiary_token_account
+        {
+            Some(beneficiary_token_account) => (
+                beneficiary_token_account,
+                self.token_account_owner(&beneficiary_token_account).await,
+            ),
+            None => (source_token_account, *source_owner),
+        };
+        let registry_account = registry_address(&program_id, &beneficiary_owner);
+        let receipt_mint = receipt_mint_address(&program_id);
+        let config = self.get_config().await;
+        let is_allowlist_enabled = config
+            .as_ref()
+            .is_some_and(|config| config.is_allowlist_enabled);
+        let pays_referral_share = config
+            .as_ref()
+            .is_some_and(|config| config.deposit_fee_bps > 0 && config.referral_share_bps > 0);
+
+        let mut instructions = vec![
+            instruction::init(
+                &system_program::id(),
+                &sysvar::rent::id(),
+                &program_id,
+                &payer,
+                &addresses.vesting_account,
+                addresses.seeds,
+            )
+            .unwrap(),
+            create_associated_token_account(
+                &payer,
+                &addresses.vesting_account,
+                &TOKEN_MINT,
+                &spl_token::id(),
+            ),
+            instruction::init_registry(&program_id, &payer, &beneficiary_owner, &registry_account)
+                .unwrap(),
+        ];
+        if schedule.time_delta == 0 {
+            instructions.push(create_associated_token_account_idempotent(
+                &payer,
+                &beneficiary_owner,
+                &receipt_mint,
+                &spl_token::id(),
+            ));
+        }
+        let referral = options.referrer.map(|referrer| Referral {
+            referrer,
+            referrer_account: referrer_address(&program_id, &referrer),
+            referrer_token_account: pays_referral_share
+                .then(|| get_associated_token_address(&referrer, &TOKEN_MINT)),
+        });
+        if let Some(referral) = &referral {
+            instructions.push(
+                instruction::init_referrer(
+                    &program_id,
+                    &payer,
+                    &referral.referrer,
+                    &referral.referrer_account,
+                )
+                .unwrap(),
+            );
+        }
+        let position = options.position.then(|| Position {
+            mint: *position_mint,
+            token_account: get_associated_token_address(&beneficiary_owner, position_mint),
+        });
+        if position.is_some() {
+            instructions.extend(create_position_mint(
+                &payer,
+                &beneficiary_owner,
+                &addresses,
+                position_mint,
+            ));
+        }
+        if options.keeper_tip > 0 {
+            instructions.push(system_instruction::transfer(
+                &payer,
+                &addresses.vesting_account,
+                options.keeper_tip,
+            ));
+        }
+        let allowlist_proof = options.allowlist_proof;
+        let allowlist = is_allowlist_enabled.then(|| match allowlist_proof {
+            Some(proof) => AllowlistAccess::Proof(proof),
+            None => AllowlistAccess::Entry(allowlist_entry_address(&program_id, source_owner)),
+        });
+        let signer_pubkeys: Vec<&Pubkey> = signer_pubkeys.iter().collect();
+        let create = instruction::create(
+            &program_id,
+            &spl_token::id(),
+            &sysvar::clock::id(),
+            &addresses.vesting_account,
+            &addresses.vesting_token_account,
+            source_owner,
+            &source_token_account,
+            &beneficiary_token_account,
+            &config_address(&program_id),
+            &stats_address(&program_id),
+            &registry_account,
+            &receipt_mint,
+            &get_associated_token_address(&beneficiary_owner, &receipt_mint),
+            &signer_pubkeys,
+            schedule,
+            addresses.seeds,
+            CreateArgs {
+                revocation_authority: options.revocation_authority,
+                keeper_tip: options.keeper_tip,
+                auto_renew: options.auto_renew,
+                hook: options.hook,
+                allowlist,
+                deposit_fee_treasury_token_account_key: config
+                    .filter(|config| config.deposit_fee_bps > 0)
+                    .map(|config| config.deposit_fee_treasury),
+                referral,
+                position,
+            },
+        )
+        .unwrap();
+        instructions.push(match options.hook {
+            Some(hook) => self.with_hook(&addresses, &hook, create),
+            None => create,
+        });
+        (addresses, instructions)
+    }
+
+    /// Creates the locks of `schedules` for their beneficiary wallets in `CreateBatch`
+    /// instructions, from the source token account of the owner of the environment. The locks
+    /// are released to the associated token accounts of the beneficiaries.
+    pub async fn lock_batch(
+        &mut self,
+        batch_id: u8,
+        schedules: &[(Pubkey, Schedule)],
+    ) -> Result<Vec<LockAddresses>, BanksClientError> {
+        let program_id = self.program_id;
+        let payer = self.context.payer.pubkey();
+        let owner = self.owner.insecure_clone();
+        let receipt_mint = receipt_mint_address(&program_id);
+        let deposit_fee_treasury = self.deposit_fee_treasury().await;
+        let mut locks = vec![];
+        for (chunk_index, chunk) in schedules.chunks(BATCH_SIZE).enumerate() {
+            let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+                COMPUTE_UNITS_PER_LOCK * chunk.len() as u32,
+            )];
+            let mut batch_locks = vec![];
+            let mut entries = vec![];
+            for (index, (beneficiary, schedule)) in chunk.iter().enumerate() {
+                let mut seed = [0u8; 31];
+                seed[0] = batch_id;
+                seed[1] = (chunk_index * BATCH_SIZE + index + 1) as u8;
+                let addresses = self.lock_addresses(&seed);
+                let registry_account = registry_address(&program_id, beneficiary);
+                let mut token_accounts = vec![
+                    (&addresses.vesting_account, &TOKEN_MINT),
+                    (beneficiary, &TOKEN_MINT),
+                ];
+                if schedule.time_delta == 0 {
+                    token_accounts.push((beneficiary, &receipt_mint));
+                }
+                for (wallet, mint) in token_accounts {
+                    instructions.push(create_associated_token_account_idempotent(
+                        &payer,
+                        wallet,
+                        mint,
+                        &spl_token::id(),
+                    ));
+                }
+                instructions.push(
+                    instruction::init_registry(&program_id, &payer, beneficiary, &registry_account)
+                        .unwrap(),
+                );
+                batch_locks.push(BatchLock {
+                    vesting_account_key: addresses.vesting_account,
+                    vesting_token_account_key: addresses.vesting_token_account,
+                    beneficiary_token_account_key: get_associated_token_address(
+                        beneficiary,
+                        &TOKEN_MINT,
+                    ),
+                    receipt_token_account_key: get_associated_token_address(
+                        beneficiary,
+                        &receipt_mint,
+                    ),
+                    registry_account_key: registry_account,
+                });
+                entries.push(BatchEntry {
+                    seeds: addresses.seeds,
+                    schedule: schedule.clone(),
+                });
+                locks.push(addresses);
+            }
+            instructions.push(
+                instruction::create_batch(
+                    &program_id,
+                    &spl_token::id(),
+                    &sysvar::clock::id(),
+                    &payer,
+                    &owner.pubkey(),
+                    &self.source_token_account,
+                    &config_address(&program_id),
+                    &stats_address(&program_id),
+                    &receipt_mint,
+                    deposit_fee_treasury.as_ref(),
+                    &[],
+                    &batch_locks,
+                    entries,
+                )
+                .unwrap(),
+            );
+            self.send(&instructions, &[&owner]).await?;
+        }
+        Ok(locks)
+    }
+
+    /// Creates an airdrop of the entries, funded with their total amount from the source token
+    /// account of the owner of the environment
+    pub async fn create_airdrop(
+        &mut self,
+        seed: u8,
+        entries: &[AirdropEntry],
+    ) -> Result<AirdropAddresses, BanksClientError> {
+        let owner = self.owner.insecure_clone();
+        let addresses = self.airdrop_addresses(&[seed; 31]);
+        let amount = entries.iter().map(|entry| entry.amount).sum();
+        let instructions = [
+            create_associated_token_account(
+                &self.context.payer.pubkey(),
+                &addresses.airdrop_account,
+                &TOKEN_MINT,
+                &spl_token::id(),
+            ),
+            instruction::create_airdrop(
+                &self.program_id,
+                &owner.pubkey(),
+                &addresses.airdrop_account,
+                &addresses.vault_token_account,
+                &config_address(&self.program_id),
+                addresses.seeds,
+                MerkleTree::airdrop(entries).root(),
+            )
+            .unwrap(),
+            spl_token::instruction::transfer(
+                &spl_token::id(),
+                &self.source_token_account,
+                &addresses.vault_token_account,
+                &owner.pubkey(),
+                &[],
+                amount,
+            )
+            .unwrap(),
+        ];
+        self.send(&instructions, &[&owner]).await?;
+        Ok(addresses)
+    }
+
+    /// Claims the entry of an airdrop at `index` into the lock of the claimant, released to its
+    /// associated token account
+    pub async fn claim_into_lock(
+        &mut self,
+        claimant: &Keypair,
+        airdrop: &AirdropAddresses,
+        entries: &[AirdropEntry],
+        index: usize,
+    ) -> Result<LockAddresses, BanksClientError> {
+        let lock = airdrop.claim_lock(&self.program_id, &claimant.pubkey());
+        let proof = MerkleTree::airdrop(entries).proof(index);
+        self.claim_into(claimant, airdrop, &lock, &entries[index], proof)
+            .await?;
+        Ok(lock)
+    }
+
+    /// Claims an airdrop entry into the given lock, with the given proof
+    pub async fn claim_into(
+        &mut self,
+        claimant: &Keypair,
+        airdrop: &AirdropAddresses,
+        lock: &LockAddresses,
+        entry: &AirdropEntry,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<(), BanksClientError> {
+        let program_id = self.program_id;
+        let payer = self.context.payer.pubkey();
+        let wallet = claimant.pubkey();
+        let receipt_mint = receipt_mint_address(&program_id);
+        let registry_account = registry_address(&program_id, &wallet);
+        let deposit_fee_treasury = self.deposit_fee_treasury().await;
+        let mut instructions = vec![];
+        for (owner, mint) in [
+            (&lock.vesting_account, &TOKEN_MINT),
+            (&wallet, &TOKEN_MINT),
+            (&wallet, &receipt_mint),
+        ] {
+            instructions.push(create_associated_token_account_idempotent(
+                &payer,
+                owner,
+                mint,
+                &spl_token::id(),
+            ));
+        }
+        instructions.push(
+            instruction::init_registry(&program_id, &payer, &wallet, &registry_account).unwrap(),
+        );
+        instructions.push(
+            instruction::claim_into_lock(
+                &program_id,
+                &spl_token::id(),
+                &sysvar::clock::id(),
+                &wallet,
+                &airdrop.airdrop_account,
+                &airdrop.vault_token_account,
+                &lock.vesting_account,
+                &lock.vesting_token_account,
+                &get_associated_token_address(&wallet, &TOKEN_MINT),
+                &config_address(&program_id),
+                &stats_address(&program_id),
+                &receipt_mint,
+                &get_associated_token_address(&wallet, &receipt_mint),
+                &registry_account,
+                deposit_fee_treasury.as_ref(),
+                airdrop.seeds,
+                lock.seeds,
+                Schedule {
+                    time_delta: entry.time_delta,
+                    amount: entry.amount,
+                },
+                proof,
+            )
+            .unwrap(),
+        );
+        self.send(&instructions, &[claimant]).await
+    }
+
+    /// Adds the hook program account of a lock, its hook authority and the accounts of
+    /// `hook_accounts` to an instruction
+    fn with_hook(
+        &self,
+        addresses: &LockAddresses,
+        hook: &Hook,
+        instruction: Instruction,
+    ) -> Instruction {
+        instruction::with_hook(
+            instruction,
+            &hook.program_id,
+            &hook_authority_address(&self.program_id, &addresses.vesting_account),
+            self.hook_accounts
+                .get(&hook.program_id)
+                .map_or(&[], |accounts| accounts.as_slice()),
+        )
+    }
+
+    /// Builds an instruction releasing a lock to its destination, or for a lock with a position
+    /// token, to the associated token account of the holder
+    fn release_instruction(
+        info: &LockInfo,
+        holder: &Pubkey,
+        build: impl FnOnce(&Pubkey) -> Result<Instruction, ProgramError>,
+    ) -> Instruction {
+        match info.header.position_mint() {
+            Some(position_mint) => instruction::with_position_holder(
+                build(&get_associated_token_address(holder, &TOKEN_MINT)).unwrap(),
+                &position_mint,
+                &get_associated_token_address(holder, &position_mint),
+                holder,
+            ),
+            None => build(&info.header.destination_address).unwrap(),
+        }
+    }
+
+    /// The owner of the destination token account of a lock, whose registry records it
+    async fn registry_owner(&mut self, info: &LockInfo) -> Pubkey {
+        self.token_account_owner(&info.header.destination_address)
+            .await
+    }
+
+    /// Releases a matured lock, paying its keeper tip to the keeper. A lock with a position token
+    /// is released to the keeper, which must hold the token.
+    pub async fn unlock(
+        &mut self,
+        keeper: &Keypair,
+        addresses: &LockAddresses,
+    ) -> Result<(), BanksClientError> {
+        let info = self.get_lock(addresses).await;
+        let registry_owner = self.registry_owner(&info).await;
+        let instruction = Self::release_instruction(&info, &keeper.pubkey(), |destination| {
+            instruction::unlock(
+                &self.program_id,
+                &spl_token::id(),
+                &sysvar::clock::id(),
+                &addresses.vesting_account,
+                &addresses.vesting_token_account,
+                destination,
+                &stats_address(&self.program_id),
+                &registry_address(&self.program_id, &registry_owner),
+                addresses.seeds,
+            )
+        });
+        let instruction = instruction::with_keeper(instruction, &keeper.pubkey());
+        let instruction = match info.header.hook() {
+            Some(hook) => self.with_hook(addresses, &hook, instruction),
+            None => instruction,
+        };
+        self.send(&[instruction], &[keeper]).await
+    }
+
+    /// Starts the withdrawal period of an unlocked stake, burning the receipts of the owner of
+    /// its destination
+    pub async fn initialize_unlock(
+        &mut self,
+        owner: &Keypair,
+        addresses: &LockAddresses,
+    ) -> Result<(), BanksClientError> {
+        let info = self.get_lock(addresses).await;
+        let receipt_mint = receipt_mint_address(&self.program_id);
+        let instruction = Self::release_instruction(&info, &owner.pubkey(), |destination| {
+            instruction::initialize_unlock(
+                &self.program_id,
+                &spl_token::id(),
+                &sysvar::clock::id(),
+                &addresses.vesting_account,
+                &addresses.vesting_token_account,
+                destination,
+                &stats_address(&self.program_id),
+                &receipt_mint,
+                &get_associated_token_address(&owner.pubkey(), &receipt_mint),
+                &owner.pubkey(),
+                addresses.seeds,
+            )
+        });
+        let instruction = match info.header.hook() {
+            Some(hook) => self.with_hook(addresses, &hook, instruction),
+            None => instruction,
+        };
+        self.send(&[instruction], &[owner]).await
+    }
+
+    /// Releases the matured locks of a list, skipping the others
+    pub async fn unlock_many(
+        &mut self,
+        keeper: &Pubkey,
+        locks: &[LockAddresses],
+    ) -> Result<(), BanksClientError> {
+        let mut unlocked_locks = vec![];
+        for addresses in locks {
+            let info = self.get_lock(addresses).await;
+            let registry_owner = self.registry_owner(&info).await;
+            unlocked_locks.push(UnlockedLock {
+                vesting_account_key: addresses.vesting_account,
+                vesting_token_account_key: addresses.vesting_token_account,
+                destination_token_account_key: info.header.destination_address,
+                registry_account_key: registry_address(&self.program_id, &registry_owner),
+                seeds: addresses.seeds,
+            });
+        }
+        let instruction = instruction::unlock_many(
+            &self.program_id,
+            &spl_token::id(),
+            &sysvar::clock::id(),
+            &stats_address(&self.program_id),
+            keeper,
+            &unlocked_locks,
+        )
+        .unwrap();
+        self.send(&[instruction], &[]).await
+    }
+
+    /// Revokes a lock, clawing its unvested tokens back to the treasury token account
+    pub async fn revoke(
+        &mut self,
+        revocation_authority: &Keypair,
+        addresses: &LockAddresses,
+        treasury_token_account: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let info = self.get_lock(addresses).await;
+        let registry_owner = self.registry_owner(&info).await;
+        let instruction = instruction::revoke(
+            &self.program_id,
+            &spl_token::id(),
+            &sysvar::clock::id(),
+            &addresses.vesting_account,
+            &addresses.vesting_token_account,
+            &info.header.destination_address,
+            &revocation_authority.pubkey(),
+            treasury_token_account,
+            &stats_address(&self.program_id),
+            &registry_address(&self.program_id, &registry_owner),
+            addresses.seeds,
+        )
+        .unwrap();
+        self.send(&[instruction], &[revocation_authority]).await
+    }
+
+    /// Releases a lock before its release time, minus the early unlock penalty of the config
+    pub async fn early_unlock(
+        &mut self,
+        owner: &Keypair,
+        addresses: &LockAddresses,
+    ) -> Result<(), BanksClientError> {
+        let info = self.get_lock(addresses).await;
+        let registry_owner = self.registry_owner(&info).await;
+        let penalty_treasury = self
+            .get_config()
+            .await
+            .map_or_else(Pubkey::new_unique, |config| config.penalty_treasury);
+        let instruction = Self::release_instruction(&info, &owner.pubkey(), |destination| {
+            instruction::early_unlock(
+                &self.program_id,
+                &spl_token::id(),
+                &sysvar::clock::id(),
+                &addresses.vesting_account,
+                &addresses.vesting_token_account,
+                destination,
+                &owner.pubkey(),
+                &config_address(&self.program_id),
+                &penalty_treasury,
+                &stats_address(&self.program_id),
+                &registry_address(&self.program_id, &registry_owner),
+                addresses.seeds,
+            )
+        });
+        self.send(&[instruction], &[owner]).await
+    }
+
+    /// Releases all the tokens of a lock while the program is in emergency mode
+    pub async fn emergency_unlock(
+        &mut self,
+        holder: &Keypair,
+        addresses: &LockAddresses,
+    ) -> Result<(), BanksClientError> {
+        let info = self.get_lock(addresses).await;
+        let registry_owner = self.registry_owner(&info).await;
+        let instruction = Self::release_instruction(&info, &holder.pubkey(), |destination| {
+            instruction::emergency_unlock(
+                &self.program_id,
+                &spl_token::id(),
+                &sysvar::clock::id(),
+                &addresses.vesting_account,
+                &addresses.vesting_token_account,
+                destination,
+                &config_address(&self.program_id),
+                &stats_address(&self.program_id),
+                &registry_address(&self.program_id, &registry_owner),
+                addresses.seeds,
+            )
+        });
+        self.send(&[instruction], &[holder]).await
+    }
+
+    /// Stops the renewal of an auto-renewing lock
+    pub async fn opt_out_renewal(
+        &mut self,
+        owner: &Keypair,
+        addresses: &LockAddresses,
+    ) -> Result<(), BanksClientError> {
+        let info = self.get_lock(addresses).await;
+        let instruction = Self::release_instruction(&info, &owner.pubkey(), |destination| {
+            instruction::opt_out_renewal(
+                &self.program_id,
+                &sysvar::clock::id(),
+                &addresses.vesting_account,
+                destination,
+                &owner.pubkey(),
+                addresses.seeds,
+            )
+        });
+        self.send(&[instruction], &[owner]).await
+    }
+
+    /// Closes a released or revoked lock, signed by the owner of its destination
+    pub async fn close(
+        &mut self,
+        owner: &Keypair,
+        addresses: &LockAddresses,
+    ) -> Result<(), BanksClientError> {
+        let info = self.get_lock(addresses).await;
+        let instruction = instruction::close(
+            &self.program_id,
+            &spl_token::id(),
+            &addresses.vesting_account,
+            &addresses.vesting_token_account,
+            &info.header.destination_address,
+            &owner.pubkey(),
+            &registry_address(&self.program_id, &owner.pubkey()),
+            addresses.seeds,
+        )
+        .unwrap();
+        self.send(&[instruction], &[owner]).await
+    }
+
+    /// Transfers a lock to a token account of another wallet, whose position registry is created
+    /// unless the transfer has to be accepted
+    pub async fn transfer_position(
+        &mut self,
+        owner: &Keypair,
+        addresses: &LockAddresses,
+        new_destination_token_account: &Pubkey,
+        require_accept: bool,
+    ) -> Result<(), BanksClientError> {
+        let info = self.get_lock(addresses).await;
+        let new_owner = self
+            .token_account_owner(new_destination_token_account)
+            .await;
+        let new_registry_account = registry_address(&self.program_id, &new_owner);
+        let mut instructions = vec![];
+        if !require_accept {
+            instructions.push(
+                instruction::init_registry(
+                    &self.program_id,
+                    &self.context.payer.pubkey(),
+                    &new_owner,
+                    &new_registry_account,
+                )
+                .unwrap(),
+            );
+        }
+        instructions.push(
+            instruction::transfer_position(
+                &self.program_id,
+                &addresses.vesting_account,
+                &info.header.destination_address,
+                &owner.pubkey(),
+                &registry_address(&self.program_id, &owner.pubkey()),
+                new_destination_token_account,
+                &new_registry_account,
+                &config_address(&self.program_id),
+                addresses.seeds,
+                require_accept,
+            )
+            .unwrap(),
+        );
+        self.send(&instructions, &[owner]).await
+    }
+
+    /// Accepts the pending transfer of a lock, signed by the owner of its pending destination.
+    /// Without a pending transfer, the instruction names the destination of the lock.
+    pub async fn accept_position(
+        &mut self,
+        new_owner: &Keypair,
+        addresses: &LockAddresses,
+    ) -> Result<(), BanksClientError> {
+        let info = self.get_lock(addresses).await;
+        let new_destination_token_account = &info
+            .header
+            .pending_destination()
+            .unwrap_or(info.header.destination_address);
+        let new_registry_account = registry_address(&self.program_id, &new_owner.pubkey());
+        let instructions = [
+            instruction::init_registry(
+                &self.program_id,
+                &self.context.payer.pubkey(),
+                &new_owner.pubkey(),
+                &new_registry_account,
+            )
+            .unwrap(),
+            instruction::accept_position(
+                &self.program_id,
+                &addresses.vesting_account,
+                &info.header.destination_address,
+                &registry_address(&self.program_id, &info.header.owner),
+                new_destination_token_account,
+                &new_owner.pubkey(),
+                &new_registry_account,
+                &config_address(&self.program_id),
+                addresses.seeds,
+            )
+            .unwrap(),
+        ];
+        self.send(&instructions, &[new_owner]).await
+    }
+
+    /// Splits an amount off a lock into the lock of `new_seed`, released to the new destination
+    /// token account or by default to the same destination
+    pub async fn split(
+        &mut self,
+        owner: &Keypair,
+        addresses: &LockAddresses,
+        new_seed: u8,
+        amount: u64,
+        new_destination_token_account: Option<Pubkey>,
+    ) -> Result<LockAddresses, BanksClientError> {
+        let program_id = self.program_id;
+        let payer = self.context.payer.pubkey();
+        let new_addresses = self.lock_addresses(&[new_seed; 31]);
+        let info = self.get_lock(addresses).await;
+        let destination_token_account = info.header.destination_address;
+        let (new_destination_token_account, new_owner) = match new_destination_token_account {
+            Some(new_destination_token_account) => (
+                new_destination_token_account,
+                self.token_account_owner(&new_destination_token_account)
+                    .await,
+            ),
+            None => (destination_token_account, owner.pubkey()),
+        };
+        let instructions = [
+            create_associated_token_account_idempotent(
+                &payer,
+                &new_addresses.vesting_account,
+                &TOKEN_MINT,
+                &spl_token::id(),
+            ),
+            instruction::init_registry(
+                &program_id,
+                &payer,
+                &new_owner,
+                &registry_address(&program_id, &new_owner),
+            )
+            .unwrap(),
+            instruction::split(
+                &program_id,
+                &spl_token::id(),
+                &payer,
+                &addresses.vesting_account,
+                &addresses.vesting_token_account,
+                &new_addresses.vesting_account,
+                &new_addresses.vesting_token_account,
+                &destination_token_account,
+                &owner.pubkey(),
+                &new_destination_token_account,
+                &stats_address(&program_id),
+                &registry_address(&program_id, &owner.pubkey()),
+                &registry_address(&program_id, &new_owner),
+                &config_address(&program_id),
+                addresses.seeds,
+                new_addresses.seeds,
+                amount,
+            )
+            .unwrap(),
+        ];
+        self.send(&instructions, &[owner]).await?;
+        Ok(new_addresses)
+    }
+
+    /// Merges locks into the lock at `addresses`, signed by the owner of their destination
+    pub async fn merge(
+        &mut self,
+        owner: &Keypair,
+        addresses: &LockAddresses,
+        sources: &[LockAddresses],
+    ) -> Result<(), BanksClientError> {
+        let info = self.get_lock(addresses).await;
+        let sources: Vec<MergedLock> = sources
+            .iter()
+            .map(|source| MergedLock {
+                vesting_account_key: source.vesting_account,
+                vesting_token_account_key: source.vesting_token_account,
+                seeds: source.seeds,
+            })
+            .collect();
+        let instruction = instruction::merge(
+            &self.program_id,
+            &spl_token::id(),
+            &addresses.vesting_account,
+            &addresses.vesting_token_account,
+            &info.header.destination_address,
+            &owner.pubkey(),
+            &stats_address(&self.program_id),
+            &registry_address(&self.program_id, &owner.pubkey()),
+            addresses.seeds,
+            &sources,
+        )
+        .unwrap();
+        self.send(&[instruction], &[owner]).await
+    }
+
+    /// Turns a lock into a position token minted to the associated token account of the owner of
+    /// its destination, returning that token account
+    pub async fn mint_position(
+        &mut self,
+        owner: &Keypair,
+        addresses: &LockAddresses,
+        position_mint: &Keypair,
+    ) -> Result<Pubkey, BanksClientError> {
+        let info = self.get_lock(addresses).await;
+        let payer = self.context.payer.pubkey();
+        let position_token_account =
+            get_associated_token_address(&owner.pubkey(), &position_mint.pubkey());
+        let mut instructions =
+            create_position_mint(&payer, &owner.pubkey(), addresses, &position_mint.pubkey());
+        instructions.push(
+            instruction::mint_position(
+                &self.program_id,
+                &spl_token::id(),
+                &addresses.vesting_account,
+                &info.header.destination_address,
+                &owner.pubkey(),
+                &position_mint.pubkey(),
+                &position_token_account,
+                addresses.seeds,
+            )
+            .unwrap(),
+        );
+        self.send(&instructions, &[owner, position_mint]).await?;
+        Ok(position_token_account)
+    }
+
+    pub async fn init_config(
+        &mut self,
+        admin: &Keypair,
+        penalty_treasury_token_account: &Pubkey,
+        guardian: &Pubkey,
+        max_penalty_bps: u16,
+        lock_mode: LockMode,
+    ) -> Result<(), BanksClientError> {
+        let instruction = instruction::init_config(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            penalty_treasury_token_account,
+            guardian,
+            &program_data_address(&self.program_id),
+            max_penalty_bps,
+            lock_mode,
+        )
+        .unwrap();
+        self.send(&[instruction], &[admin]).await
+    }
+
+    pub async fn update_penalty(
+        &mut self,
+        admin: &Keypair,
+        penalty_treasury_token_account: &Pubkey,
+        max_penalty_bps: u16,
+    ) -> Result<(), BanksClientError> {
+        let instruction = instruction::update_penalty(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            penalty_treasury_token_account,
+            max_penalty_bps,
+        )
+        .unwrap();
+        self.send(&[instruction], &[admin]).await
+    }
+
+    pub async fn set_paused(
+        &mut self,
+        guardian: &Keypair,
+        paused: bool,
+    ) -> Result<(), BanksClientError> {
+        let instruction = instruction::set_paused(
+            &self.program_id,
+            &guardian.pubkey(),
+            &config_address(&self.program_id),
+            paused,
+        )
+        .unwrap();
+        self.send(&[instruction], &[guardian]).await
+    }
+
+    pub async fn set_emergency_mode(
+        &mut self,
+        admin: &Keypair,
+        enabled: bool,
+    ) -> Result<(), BanksClientError> {
+        let instruction = instruction::set_emergency_mode(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            enabled,
+        )
+        .unwrap();
+        self.send(&[instruction], &[admin]).await
+    }
+
+    pub async fn set_guardian(
+        &mut self,
+        admin: &Keypair,
+        guardian: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let instruction = instruction::set_guardian(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            guardian,
+        )
+        .unwrap();
+        self.send(&[instruction], &[admin]).await
+    }
+
+    pub async fn set_allowlist(
+        &mut self,
+        admin: &Keypair,
+        enabled: bool,
+        root: [u8; 32],
+    ) -> Result<(), BanksClientError> {
+        let instruction = instruction::set_allowlist(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            enabled,
+            root,
+        )
+        .unwrap();
+        self.send(&[instruction], &[admin]).await
+    }
+
+    pub async fn add_allowlist_entry(
+        &mut self,
+        admin: &Keypair,
+        wallet: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let instruction = instruction::add_allowlist_entry(
+            &self.program_id,
+            &self.context.payer.pubkey(),
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            wallet,
+            &allowlist_entry_address(&self.program_id, wallet),
+        )
+        .unwrap();
+        self.send(&[instruction], &[admin]).await
+    }
+
+    pub async fn remove_allowlist_entry(
+        &mut self,
+        admin: &Keypair,
+        wallet: &Pubkey,
+        recipient: &Pubkey,
+    ) -> Result<(), BanksClientError> {
+        let instruction = instruction::remove_allowlist_entry(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            &allowlist_entry_address(&self.program_id, wallet),
+            recipient,
+        )
+        .unwrap();
+        self.send(&[instruction], &[admin]).await
+    }
+
+    pub async fn set_limits(
+        &mut self,
+        admin: &Keypair,
+        max_total_locked: u64,
+        max_locked_per_wallet: u64,
+        min_lock_amounts: [u64; 5],
+    ) -> Result<(), BanksClientError> {
+        let instruction = instruction::set_limits(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            max_total_locked,
+            max_locked_per_wallet,
+            min_lock_amounts,
+        )
+        .unwrap();
+        self.send(&[instruction], &[admin]).await
+    }
+
+    pub async fn set_deposit_fee(
+        &mut self,
+        admin: &Keypair,
+        deposit_fee_treasury_token_account: &Pubkey,
+        deposit_fee_bps: u16,
+    ) -> Result<(), BanksClientError> {
+        let instruction = instruction::set_deposit_fee(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            deposit_fee_treasury_token_account,
+            deposit_fee_bps,
+        )
+        .unwrap();
+        self.send(&[instruction], &[admin]).await
+    }
+
+    pub async fn set_referral_share(
+        &mut self,
+        admin: &Keypair,
+        referral_share_bps: u16,
+    ) -> Result<(), BanksClientError> {
+        let instruction = instruction::set_referral_share(
+            &self.program_id,
+            &admin.pubkey(),
+            &config_address(&self.program_id),
+            referral_share_bps,
+        )
+        .unwrap();
+        self.send(&[instruction], &[admin]).await
+    }
+}
+
+/// Instructions creating the one-of-one position mint of a lock, mintable by its vesting account,
+/// and the associated token account of the holder receiving the position token
+fn create_position_mint(
+    payer: &Pubkey,
+    holder: &Pubkey,
+    addresses: &LockAddresses,
+    position_mint: &Pubkey,
+) -> Vec<Instruction> {
+    let mint_len = spl_token::state::Mint::LEN;
+    vec![
+        system_instruction::create_account(
+            payer,
+            position_mint,
+            Rent::default().minimum_balance(mint_len),
+            mint_len as u64,
+            &spl_token::id(),
+        ),
+        spl_token::instruction::initialize_mint2(
+            &spl_token::id(),
+            position_mint,
+            &addresses.vesting_account,
+            None,
+            0,
+        )
+        .unwrap(),
+        create_associated_token_account(payer, holder, position_mint, &spl_token::id()),
+    ]
+}
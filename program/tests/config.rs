@@ -0,0 +1,423 @@
+#![cfg(feature = "test-bpf")]
+mod common;
+
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use token_vesting::{error::VestingError, instruction::Schedule, state::LockMode};
+
+use common::{assert_vesting_error, setup, AirdropEntry, LockOptions, MerkleTree, TestEnv};
+
+/// Sets up an environment with a config administered by the payer
+async fn setup_config(amount: u64) -> TestEnv {
+    let mut env = setup(amount).await;
+    let payer = env.payer();
+    let treasury = env.create_associated_token_account(&payer.pubkey()).await;
+    env.init_config(&payer, &treasury, &payer.pubkey(), 0, LockMode::Timestamp)
+        .await
+        .unwrap();
+    env
+}
+
+#[tokio::test]
+async fn test_pause_and_emergency_mode() {
+    let mut env = setup(1_000).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+    let guardian = Keypair::new();
+    let treasury = env.create_associated_token_account(&payer.pubkey()).await;
+    let source_token_account = env.source_token_account;
+
+    // Locks can be created before the config exists
+    let mut locks = vec![];
+    for (seed, time_delta) in [(1, 31_104_000), (2, 0)] {
+        let schedule = Schedule {
+            time_delta,
+            amount: 100,
+        };
+        locks.push(
+            env.lock(seed, schedule, LockOptions::default())
+                .await
+                .unwrap(),
+        );
+    }
+
+    // Only the upgrade authority of the program creates the config
+    assert!(env
+        .init_config(
+            &guardian,
+            &treasury,
+            &guardian.pubkey(),
+            0,
+            LockMode::Timestamp
+        )
+        .await
+        .is_err());
+    env.init_config(
+        &payer,
+        &treasury,
+        &guardian.pubkey(),
+        0,
+        LockMode::Timestamp,
+    )
+    .await
+    .unwrap();
+
+    // Only the guardian pauses the creation of locks
+    assert!(env.set_paused(&payer, true).await.is_err());
+    env.set_paused(&guardian, true).await.unwrap();
+    assert!(env.get_config().await.unwrap().is_paused);
+    assert!(env.lock_stake(3, 100, None).await.is_err());
+    env.set_paused(&guardian, false).await.unwrap();
+    env.lock_stake(3, 100, None).await.unwrap();
+
+    // Only the admin enters the emergency mode, in which locks are released whatever their
+    // release time
+    assert!(env.emergency_unlock(&payer, &locks[0]).await.is_err());
+    assert!(env.set_emergency_mode(&guardian, true).await.is_err());
+    env.set_emergency_mode(&payer, true).await.unwrap();
+
+    // Unlocked stakes burn their receipts by initializing their withdrawal first
+    assert!(env.emergency_unlock(&payer, &locks[1]).await.is_err());
+    env.initialize_unlock(&owner, &locks[1]).await.unwrap();
+    for lock in &locks {
+        env.emergency_unlock(&payer, lock).await.unwrap();
+    }
+    assert_eq!(env.token_balance(&source_token_account).await, 900);
+    assert!(env.emergency_unlock(&payer, &locks[0]).await.is_err());
+
+    // The admin replaces the guardian
+    env.set_guardian(&payer, &payer.pubkey()).await.unwrap();
+    assert!(env.set_paused(&guardian, true).await.is_err());
+    env.set_paused(&payer, true).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_allowlist() {
+    let mut env = setup_config(1_000).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+
+    // An airdrop of an unlisted wallet, created before the allowlist is enabled
+    env.fund(&owner.pubkey()).await;
+    let claimant = Keypair::new();
+    env.fund(&claimant.pubkey()).await;
+    let entries = [AirdropEntry {
+        wallet: claimant.pubkey(),
+        amount: 100,
+        time_delta: 0,
+    }];
+    let airdrop = env.create_airdrop(5, &entries).await.unwrap();
+
+    // Only the admin enables the allowlist, after which unlisted wallets can't lock
+    assert!(env.set_allowlist(&owner, true, [0; 32]).await.is_err());
+    env.set_allowlist(&payer, true, [0; 32]).await.unwrap();
+    assert!(env.get_config().await.unwrap().is_allowlist_enabled);
+    assert!(env.lock_stake(1, 100, None).await.is_err());
+
+    // An allowlist entry lets the wallet lock, until the admin removes it
+    env.add_allowlist_entry(&payer, &owner.pubkey())
+        .await
+        .unwrap();
+    env.lock_stake(1, 100, None).await.unwrap();
+    env.remove_allowlist_entry(&payer, &owner.pubkey(), &payer.pubkey())
+        .await
+        .unwrap();
+    assert!(env.lock_stake(2, 100, None).await.is_err());
+
+    // Batches are restricted to the admin while the allowlist is enabled
+    let schedule = Schedule {
+        time_delta: 0,
+        amount: 10,
+    };
+    assert!(env
+        .lock_batch(1, &[(Pubkey::new_unique(), schedule)])
+        .await
+        .is_err());
+
+    // And so are airdrops, including the claims of those created before
+    assert!(env.create_airdrop(6, &entries).await.is_err());
+    assert!(env
+        .claim_into_lock(&claimant, &airdrop, &entries, 0)
+        .await
+        .is_err());
+
+    // Wallets of the allowlist root lock with their proof
+    let tree = MerkleTree::allowlist(&[Pubkey::new_unique(), owner.pubkey()]);
+    env.set_allowlist(&payer, true, tree.root()).await.unwrap();
+    let schedule = Schedule {
+        time_delta: 0,
+        amount: 100,
+    };
+    for (index, is_valid) in [(0, false), (1, true)] {
+        let options = LockOptions {
+            allowlist_proof: Some(tree.proof(index)),
+            ..LockOptions::default()
+        };
+        let result = env.lock(2, schedule.clone(), options).await;
+        assert_eq!(result.is_ok(), is_valid);
+    }
+
+    // Disabling the allowlist opens the creation of locks again
+    env.set_allowlist(&payer, false, [0; 32]).await.unwrap();
+    env.lock_stake(3, 100, None).await.unwrap();
+    env.claim_into_lock(&claimant, &airdrop, &entries, 0)
+        .await
+        .unwrap();
+    let source_token_account = env.source_token_account;
+    assert_eq!(env.token_balance(&source_token_account).await, 600);
+}
+
+#[tokio::test]
+async fn test_limits() {
+    let mut env = setup(1_000).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+    let treasury = env.create_associated_token_account(&payer.pubkey()).await;
+    let wallets: Vec<Pubkey> = (0..2).map(|_| Pubkey::new_unique()).collect();
+    let mut beneficiaries = vec![];
+    for wallet in &wallets {
+        beneficiaries.push(env.create_associated_token_account(wallet).await);
+    }
+
+    // Empty locks are rejected, even without a config
+    assert_vesting_error(
+        env.lock_stake(1, 0, None).await,
+        VestingError::AmountBelowMinimum,
+    );
+    env.init_config(&payer, &treasury, &payer.pubkey(), 0, LockMode::Timestamp)
+        .await
+        .unwrap();
+
+    // Only the admin sets the limits
+    assert!(env.set_limits(&owner, 300, 200, [10; 5]).await.is_err());
+    env.set_limits(&payer, 300, 200, [10; 5]).await.unwrap();
+    let config = env.get_config().await.unwrap();
+    assert_eq!(
+        (config.max_total_locked, config.max_locked_per_wallet),
+        (300, 200)
+    );
+    assert_vesting_error(
+        env.lock_stake(1, 9, None).await,
+        VestingError::AmountBelowMinimum,
+    );
+
+    // The positions of a wallet are capped, and so are all the locks
+    env.lock_stake(1, 150, Some(beneficiaries[0]))
+        .await
+        .unwrap();
+    assert_vesting_error(
+        env.lock_stake(2, 100, Some(beneficiaries[0])).await,
+        VestingError::WalletCapExceeded,
+    );
+    env.lock_stake(2, 100, Some(beneficiaries[1]))
+        .await
+        .unwrap();
+    assert_vesting_error(
+        env.lock_stake(3, 100, None).await,
+        VestingError::PoolCapExceeded,
+    );
+    let registry = env.get_registry(&wallets[0]).await.unwrap();
+    assert_eq!(registry.locked_amount(), 150);
+
+    // Lifting the caps lets the locks grow again
+    env.set_limits(&payer, 0, 0, [0; 5]).await.unwrap();
+    env.lock_stake(3, 100, Some(beneficiaries[0]))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_limits_on_moved_locks() {
+    let mut env = setup_config(1_000).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+    let wallet = Keypair::new();
+    let gift = env.create_associated_token_account(&wallet.pubkey()).await;
+    env.set_limits(&payer, 0, 200, [10; 5]).await.unwrap();
+    let mut locks = vec![];
+    for (seed, amount, beneficiary_token_account) in [(1, 150, None), (2, 100, Some(gift))] {
+        let schedule = Schedule {
+            time_delta: 7_776_000,
+            amount,
+        };
+        let options = LockOptions {
+            beneficiary_token_account,
+            ..LockOptions::default()
+        };
+        locks.push(env.lock(seed, schedule, options).await.unwrap());
+    }
+    let (lock, gifted) = (&locks[0], &locks[1]);
+
+    // Both halves of a split keep the minimum of the tier
+    for amount in [5, 145] {
+        assert_vesting_error(
+            env.split(&owner, lock, 3, amount, None).await,
+            VestingError::AmountBelowMinimum,
+        );
+    }
+
+    // Moving a lock to another wallet counts against the cap of that wallet
+    env.split(&owner, lock, 3, 60, Some(gift)).await.unwrap();
+    let registry = env.get_registry(&owner.pubkey()).await.unwrap();
+    assert_eq!(registry.locked_amount(), 90);
+    let registry = env.get_registry(&wallet.pubkey()).await.unwrap();
+    assert_eq!(registry.locked_amount(), 160);
+    assert_vesting_error(
+        env.split(&owner, lock, 4, 50, Some(gift)).await,
+        VestingError::WalletCapExceeded,
+    );
+    assert_vesting_error(
+        env.transfer_position(&owner, lock, &gift, false).await,
+        VestingError::WalletCapExceeded,
+    );
+
+    // Released locks no longer count against the cap
+    env.warp_forward(7_776_000).await;
+    env.unlock(&payer, gifted).await.unwrap();
+    let registry = env.get_registry(&wallet.pubkey()).await.unwrap();
+    assert_eq!(registry.locked_amount(), 60);
+    env.transfer_position(&owner, lock, &gift, false)
+        .await
+        .unwrap();
+    let registry = env.get_registry(&wallet.pubkey()).await.unwrap();
+    assert_eq!(registry.locked_amount(), 150);
+    let registry = env.get_registry(&owner.pubkey()).await.unwrap();
+    assert_eq!(registry.locked_amount(), 0);
+}
+
+#[tokio::test]
+async fn test_deposit_fee() {
+    let mut env = setup_config(1_000).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+    env.fund(&owner.pubkey()).await;
+    let claimant = Keypair::new();
+    env.fund(&claimant.pubkey()).await;
+    let fee_treasury = env
+        .create_associated_token_account(&Pubkey::new_unique())
+        .await;
+
+    // Only the admin sets the deposit fee, up to 100%
+    assert!(env
+        .set_deposit_fee(&owner, &fee_treasury, 250)
+        .await
+        .is_err());
+    assert!(env
+        .set_deposit_fee(&payer, &fee_treasury, 10_001)
+        .await
+        .is_err());
+    env.set_deposit_fee(&payer, &fee_treasury, 250)
+        .await
+        .unwrap();
+
+    // The fee goes to the treasury, the lock holds the rest
+    let addresses = env.lock_stake(1, 400, None).await.unwrap();
+    assert_eq!(env.token_balance(&fee_treasury).await, 10);
+    assert_eq!(
+        env.token_balance(&addresses.vesting_token_account).await,
+        390
+    );
+    let source_token_account = env.source_token_account;
+    assert_eq!(env.token_balance(&source_token_account).await, 600);
+    assert_eq!(env.get_stats().await.tiers[0].total_locked, 390);
+
+    // So do the locks of batches and airdrop claims
+    let schedule = Schedule {
+        time_delta: 15_552_000,
+        amount: 200,
+    };
+    let locks = env
+        .lock_batch(1, &[(Pubkey::new_unique(), schedule)])
+        .await
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&locks[0].vesting_token_account).await,
+        195
+    );
+    assert_eq!(env.token_balance(&fee_treasury).await, 15);
+    let entries = [AirdropEntry {
+        wallet: claimant.pubkey(),
+        amount: 80,
+        time_delta: 0,
+    }];
+    let airdrop = env.create_airdrop(3, &entries).await.unwrap();
+    let claimed = env
+        .claim_into_lock(&claimant, &airdrop, &entries, 0)
+        .await
+        .unwrap();
+    assert_eq!(env.token_balance(&claimed.vesting_token_account).await, 78);
+    assert_eq!(env.token_balance(&fee_treasury).await, 17);
+    assert_eq!(env.token_balance(&source_token_account).await, 320);
+
+    // Without a fee, the lock holds the whole amount
+    env.set_deposit_fee(&payer, &fee_treasury, 0).await.unwrap();
+    let addresses = env.lock_stake(2, 100, None).await.unwrap();
+    assert_eq!(
+        env.token_balance(&addresses.vesting_token_account).await,
+        100
+    );
+    assert_eq!(env.token_balance(&fee_treasury).await, 17);
+}
+
+#[tokio::test]
+async fn test_referral() {
+    let mut env = setup_config(1_000).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+    let fee_treasury = env
+        .create_associated_token_account(&Pubkey::new_unique())
+        .await;
+    let referrer = Pubkey::new_unique();
+    let referrer_token_account = env.create_associated_token_account(&referrer).await;
+    env.set_deposit_fee(&payer, &fee_treasury, 250)
+        .await
+        .unwrap();
+
+    // Only the admin sets the referral share, up to 100% of the fee
+    assert!(env.set_referral_share(&owner, 2_000).await.is_err());
+    assert!(env.set_referral_share(&payer, 10_001).await.is_err());
+    env.set_referral_share(&payer, 2_000).await.unwrap();
+
+    // The referrer gets its share of the fee and is credited with the lock
+    let schedule = Schedule {
+        time_delta: 0,
+        amount: 400,
+    };
+    let referred = LockOptions {
+        referrer: Some(referrer),
+        ..LockOptions::default()
+    };
+    let addresses = env.lock(1, schedule, referred.clone()).await.unwrap();
+    assert_eq!(env.token_balance(&fee_treasury).await, 8);
+    assert_eq!(env.token_balance(&referrer_token_account).await, 2);
+    assert_eq!(
+        env.token_balance(&addresses.vesting_token_account).await,
+        390
+    );
+    let info = env.get_lock(&addresses).await;
+    assert_eq!(info.header.referrer(), Some(referrer));
+    let referrer_state = env.get_referrer(&referrer).await;
+    assert_eq!(referrer_state.total_locked, 390);
+    assert_eq!(referrer_state.weighted_volume, 390);
+
+    // Totals add up across locks
+    let schedule = Schedule {
+        time_delta: 0,
+        amount: 200,
+    };
+    env.lock(2, schedule, referred).await.unwrap();
+    assert_eq!(env.get_referrer(&referrer).await.total_locked, 585);
+
+    // The source token account owner can not refer its own lock
+    let schedule = Schedule {
+        time_delta: 0,
+        amount: 100,
+    };
+    let self_referred = LockOptions {
+        referrer: Some(owner.pubkey()),
+        ..LockOptions::default()
+    };
+    assert!(env.lock(3, schedule, self_referred).await.is_err());
+}
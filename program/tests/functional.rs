@@ -1,26 +1,33 @@
 #![cfg(feature = "test-bpf")]
 use std::str::FromStr;
 
-use solana_program::{hash::Hash, pubkey::Pubkey, rent::Rent, system_program, sysvar};
-use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_program::{
+    clock::Clock, hash::Hash, program_option::COption, program_pack::Pack, pubkey::Pubkey,
+    rent::Rent, system_program, sysvar,
+};
+use solana_program_test::{processor, BanksClient, ProgramTest, ProgramTestContext};
 use solana_sdk::{
     account::Account, signature::Keypair, signature::Signer, system_instruction,
     transaction::Transaction,
 };
-use solana_test_framework::ProgramTestContextExtension;
 use spl_token::{
     self,
-    instruction::{initialize_account, initialize_mint, mint_to},
+    instruction::{initialize_account, mint_to},
+    state::Mint,
+};
+use token_vesting::instruction::{
+    create, init, init_receipt_mint, init_registry, init_stats, initialize_unlock, unlock,
+};
+use token_vesting::{
+    instruction::{CreateArgs, Schedule},
+    processor::{Processor, CONFIG_SEED, RECEIPT_SEED, REGISTRY_SEED, STATS_SEED, TOKEN_MINT},
 };
-use token_vesting::instruction::{create, init, initialize_unlock, unlock};
-use token_vesting::{entrypoint::process_instruction, instruction::Schedule};
 
 #[tokio::test]
 async fn test_token_vesting() {
     // Create program and test environment
     let program_id = Pubkey::from_str("VestingbGKPFXCWuBvfkegQfZyiNwAJb9Ss623VQ5DA").unwrap();
     let mint_authority = Keypair::new();
-    let mint = Keypair::new();
 
     let source_account = Keypair::new();
     let source_token_account = Keypair::new();
@@ -29,9 +36,20 @@ async fn test_token_vesting() {
     let (vesting_account_key, bump) = Pubkey::find_program_address(&[&seeds[..31]], &program_id);
     seeds[31] = bump;
     let vesting_token_account = Keypair::new();
+    let (config_account_key, _) = Pubkey::find_program_address(&[CONFIG_SEED], &program_id);
+    let (stats_account_key, _) = Pubkey::find_program_address(&[STATS_SEED], &program_id);
+    let (receipt_mint_key, _) = Pubkey::find_program_address(&[RECEIPT_SEED], &program_id);
+    let receipt_token_account = Keypair::new();
+    let (registry_account_key, _) = Pubkey::find_program_address(
+        &[REGISTRY_SEED, source_account.pubkey().as_ref()],
+        &program_id,
+    );
 
-    let mut program_test =
-        ProgramTest::new("token_vesting", program_id, processor!(process_instruction));
+    let mut program_test = ProgramTest::new(
+        "token_vesting",
+        program_id,
+        processor!(Processor::process_instruction),
+    );
 
     // Add accounts
     program_test.add_account(
@@ -41,43 +59,55 @@ async fn test_token_vesting() {
             ..Account::default()
         },
     );
+    add_mint(&mut program_test, &mint_authority.pubkey());
 
     // Start and process transactions on the test network
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
-
-    // Initialize the vesting program account
-    let init_instruction = [init(
-        &system_program::id(),
-        &sysvar::rent::id(),
-        &program_id,
-        &payer.pubkey(),
-        &vesting_account_key,
-        seeds,
-    )
-    .unwrap()];
+    let mut context = program_test.start_with_context().await;
+    let banks_client = &mut context.banks_client;
+    let payer = &context.payer;
+    let recent_blockhash = context.last_blockhash;
+
+    // Initialize the stats, receipt mint, registry and vesting program accounts
+    let init_instruction = [
+        init_stats(&program_id, &payer.pubkey(), &stats_account_key).unwrap(),
+        init_receipt_mint(
+            &program_id,
+            &spl_token::id(),
+            &payer.pubkey(),
+            &receipt_mint_key,
+            &TOKEN_MINT,
+        )
+        .unwrap(),
+        init_registry(
+            &program_id,
+            &payer.pubkey(),
+            &source_account.pubkey(),
+            &registry_account_key,
+        )
+        .unwrap(),
+        init(
+            &system_program::id(),
+            &sysvar::rent::id(),
+            &program_id,
+            &payer.pubkey(),
+            &vesting_account_key,
+            seeds,
+        )
+        .unwrap(),
+    ];
     let mut init_transaction =
         Transaction::new_with_payer(&init_instruction, Some(&payer.pubkey()));
-    init_transaction.partial_sign(&[&payer], recent_blockhash);
+    init_transaction.partial_sign(&[payer], recent_blockhash);
     banks_client
         .process_transaction(init_transaction)
         .await
         .unwrap();
 
     // Initialize the token accounts
-    banks_client
-        .process_transaction(mint_init_transaction(
-            &payer,
-            &mint,
-            &mint_authority,
-            recent_blockhash,
-        ))
-        .await
-        .unwrap();
-
     banks_client
         .process_transaction(create_token_account(
-            &payer,
-            &mint,
+            payer,
+            &TOKEN_MINT,
             recent_blockhash,
             &source_token_account,
             &source_account.pubkey(),
@@ -86,8 +116,8 @@ async fn test_token_vesting() {
         .unwrap();
     banks_client
         .process_transaction(create_token_account(
-            &payer,
-            &mint,
+            payer,
+            &TOKEN_MINT,
             recent_blockhash,
             &vesting_token_account,
             &vesting_account_key,
@@ -98,7 +128,7 @@ async fn test_token_vesting() {
     // Create and process the vesting transactions
     let setup_instructions = [mint_to(
         &spl_token::id(),
-        &mint.pubkey(),
+        &TOKEN_MINT,
         &source_token_account.pubkey(),
         &mint_authority.pubkey(),
         &[],
@@ -111,35 +141,31 @@ async fn test_token_vesting() {
         time_delta: 7_776_000,
     };
 
-    let test_instructions = [
-        create(
-            &program_id,
-            &spl_token::id(),
-            &sysvar::clock::id(),
-            &vesting_account_key,
-            &vesting_token_account.pubkey(),
-            &source_account.pubkey(),
-            &source_token_account.pubkey(),
-            schedule,
-            seeds.clone(),
-        )
-        .unwrap(),
-        unlock(
-            &program_id,
-            &spl_token::id(),
-            &sysvar::clock::id(),
-            &vesting_account_key,
-            &vesting_token_account.pubkey(),
-            &source_token_account.pubkey(),
-            seeds.clone(),
-        )
-        .unwrap(),
-    ];
+    let test_instructions = [create(
+        &program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &vesting_account_key,
+        &vesting_token_account.pubkey(),
+        &source_account.pubkey(),
+        &source_token_account.pubkey(),
+        &source_token_account.pubkey(),
+        &config_account_key,
+        &stats_account_key,
+        &registry_account_key,
+        &receipt_mint_key,
+        &receipt_token_account.pubkey(),
+        &[],
+        schedule,
+        seeds.clone(),
+        CreateArgs::default(),
+    )
+    .unwrap()];
 
     // Process transaction on test network
     let mut setup_transaction =
         Transaction::new_with_payer(&setup_instructions, Some(&payer.pubkey()));
-    setup_transaction.partial_sign(&[&payer, &mint_authority], recent_blockhash);
+    setup_transaction.partial_sign(&[payer, &mint_authority], recent_blockhash);
 
     banks_client
         .process_transaction(setup_transaction)
@@ -149,14 +175,32 @@ async fn test_token_vesting() {
     // Process transaction on test network
     let mut test_transaction =
         Transaction::new_with_payer(&test_instructions, Some(&payer.pubkey()));
-    test_transaction.partial_sign(&[&payer, &source_account], recent_blockhash);
-
-    // NOTE: we're NOT doing `now() + time_delta` in the program (but we should), that's why this test passes
-    // TODO: add warp_to_timestamp to correctly test the behaviour
+    test_transaction.partial_sign(&[payer, &source_account], recent_blockhash);
     banks_client
         .process_transaction(test_transaction)
         .await
         .unwrap();
+
+    // The lock is released once its release time is reached
+    let unlock_instructions = [unlock(
+        &program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &vesting_account_key,
+        &vesting_token_account.pubkey(),
+        &source_token_account.pubkey(),
+        &stats_account_key,
+        &registry_account_key,
+        seeds,
+    )
+    .unwrap()];
+    assert!(process(&mut context, &unlock_instructions).await.is_err());
+    warp_forward(&mut context, 7_776_000).await;
+    process(&mut context, &unlock_instructions).await.unwrap();
+    assert_eq!(
+        token_balance(&mut context.banks_client, &source_token_account.pubkey()).await,
+        100
+    );
 }
 
 #[tokio::test]
@@ -164,7 +208,6 @@ async fn test_token_unlocking() {
     // Create program and test environment
     let program_id = Pubkey::from_str("VestingbGKPFXCWuBvfkegQfZyiNwAJb9Ss623VQ5DA").unwrap();
     let mint_authority = Keypair::new();
-    let mint = Keypair::new();
 
     let source_account = Keypair::new();
     let source_token_account = Keypair::new();
@@ -173,9 +216,20 @@ async fn test_token_unlocking() {
     let (vesting_account_key, bump) = Pubkey::find_program_address(&[&seeds[..31]], &program_id);
     seeds[31] = bump;
     let vesting_token_account = Keypair::new();
+    let (config_account_key, _) = Pubkey::find_program_address(&[CONFIG_SEED], &program_id);
+    let (stats_account_key, _) = Pubkey::find_program_address(&[STATS_SEED], &program_id);
+    let (receipt_mint_key, _) = Pubkey::find_program_address(&[RECEIPT_SEED], &program_id);
+    let receipt_token_account = Keypair::new();
+    let (registry_account_key, _) = Pubkey::find_program_address(
+        &[REGISTRY_SEED, source_account.pubkey().as_ref()],
+        &program_id,
+    );
 
-    let mut program_test =
-        ProgramTest::new("token_vesting", program_id, processor!(process_instruction));
+    let mut program_test = ProgramTest::new(
+        "token_vesting",
+        program_id,
+        processor!(Processor::process_instruction),
+    );
 
     // Add accounts
     program_test.add_account(
@@ -185,26 +239,45 @@ async fn test_token_unlocking() {
             ..Account::default()
         },
     );
+    add_mint(&mut program_test, &mint_authority.pubkey());
 
     // Start and process transactions on the test network
     let mut context: ProgramTestContext = program_test.start_with_context().await;
 
-    // NOTE: using scopes to allow partial borrows when context.warp_to_timestamp is called
+    // NOTE: using scopes to allow partial borrows when the clock is warped
     {
         let banks_client = &mut context.banks_client;
         let payer = &mut context.payer;
         let recent_blockhash = context.last_blockhash;
 
-        // Initialize the vesting program account
-        let init_instruction = [init(
-            &system_program::id(),
-            &sysvar::rent::id(),
-            &program_id,
-            &payer.pubkey(),
-            &vesting_account_key,
-            seeds,
-        )
-        .unwrap()];
+        // Initialize the stats, receipt mint, registry and vesting program accounts
+        let init_instruction = [
+            init_stats(&program_id, &payer.pubkey(), &stats_account_key).unwrap(),
+            init_receipt_mint(
+                &program_id,
+                &spl_token::id(),
+                &payer.pubkey(),
+                &receipt_mint_key,
+                &TOKEN_MINT,
+            )
+            .unwrap(),
+            init_registry(
+                &program_id,
+                &payer.pubkey(),
+                &source_account.pubkey(),
+                &registry_account_key,
+            )
+            .unwrap(),
+            init(
+                &system_program::id(),
+                &sysvar::rent::id(),
+                &program_id,
+                &payer.pubkey(),
+                &vesting_account_key,
+                seeds,
+            )
+            .unwrap(),
+        ];
         let mut init_transaction =
             Transaction::new_with_payer(&init_instruction, Some(&payer.pubkey()));
         init_transaction.partial_sign(&[&payer], recent_blockhash);
@@ -213,34 +286,35 @@ async fn test_token_unlocking() {
             .await
             .unwrap();
 
-        // Initialize the token accounts
+        // Initialize the token accounts, and the receipt token account of the source account
+        // receiving the receipts of the stake
         banks_client
-            .process_transaction(mint_init_transaction(
+            .process_transaction(create_token_account(
                 &payer,
-                &mint,
-                &mint_authority,
+                &TOKEN_MINT,
                 recent_blockhash,
+                &source_token_account,
+                &source_account.pubkey(),
             ))
             .await
             .unwrap();
-
         banks_client
             .process_transaction(create_token_account(
                 &payer,
-                &mint,
+                &TOKEN_MINT,
                 recent_blockhash,
-                &source_token_account,
-                &source_account.pubkey(),
+                &vesting_token_account,
+                &vesting_account_key,
             ))
             .await
             .unwrap();
         banks_client
             .process_transaction(create_token_account(
                 &payer,
-                &mint,
+                &receipt_mint_key,
                 recent_blockhash,
-                &vesting_token_account,
-                &vesting_account_key,
+                &receipt_token_account,
+                &source_account.pubkey(),
             ))
             .await
             .unwrap();
@@ -248,7 +322,7 @@ async fn test_token_unlocking() {
         // Create and process the vesting transactions
         let setup_instructions = [mint_to(
             &spl_token::id(),
-            &mint.pubkey(),
+            &TOKEN_MINT,
             &source_token_account.pubkey(),
             &mint_authority.pubkey(),
             &[],
@@ -270,8 +344,16 @@ async fn test_token_unlocking() {
                 &vesting_token_account.pubkey(),
                 &source_account.pubkey(),
                 &source_token_account.pubkey(),
+                &source_token_account.pubkey(),
+                &config_account_key,
+                &stats_account_key,
+                &registry_account_key,
+                &receipt_mint_key,
+                &receipt_token_account.pubkey(),
+                &[],
                 schedule,
                 seeds.clone(),
+                CreateArgs::default(),
             )
             .unwrap(),
             initialize_unlock(
@@ -281,17 +363,10 @@ async fn test_token_unlocking() {
                 &vesting_account_key,
                 &vesting_token_account.pubkey(),
                 &source_token_account.pubkey(),
-                seeds.clone(),
-            )
-            .unwrap(),
-            // TODO: move unlock after warp_to_timestamp
-            unlock(
-                &program_id,
-                &spl_token::id(),
-                &sysvar::clock::id(),
-                &vesting_account_key,
-                &vesting_token_account.pubkey(),
-                &source_token_account.pubkey(),
+                &stats_account_key,
+                &receipt_mint_key,
+                &receipt_token_account.pubkey(),
+                &source_account.pubkey(),
                 seeds.clone(),
             )
             .unwrap(),
@@ -318,69 +393,86 @@ async fn test_token_unlocking() {
             .unwrap();
     }
 
-    // Warp to a future slot
-    // TODO: this should increase the timestamp, and it does not.
-    let _ = context.warp_to_timestamp(99999999).await;
-
-    // {
-    //     let banks_client = &mut context.banks_client;
-    //     let payer = &mut context.payer;
-    //     let recent_blockhash = context.last_blockhash;
-
-    //     let unlock_instructions = [unlock(
-    //         &program_id,
-    //         &spl_token::id(),
-    //         &sysvar::clock::id(),
-    //         &vesting_account_key,
-    //         &vesting_token_account.pubkey(),
-    //         &source_token_account.pubkey(),
-    //         seeds.clone(),
-    //     )
-    //     .unwrap()];
-
-    //     let mut unlock_transaction =
-    //         Transaction::new_with_payer(&unlock_instructions, Some(&payer.pubkey()));
-
-    //     unlock_transaction.partial_sign(&[&payer], recent_blockhash);
-
-    //     banks_client
-    //         .process_transaction(unlock_transaction)
-    //         .await
-    //         .unwrap();
-    // }
+    // The stake is released once its 7 day withdrawal period is over
+    let unlock_instructions = [unlock(
+        &program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &vesting_account_key,
+        &vesting_token_account.pubkey(),
+        &source_token_account.pubkey(),
+        &stats_account_key,
+        &registry_account_key,
+        seeds,
+    )
+    .unwrap()];
+    assert!(process(&mut context, &unlock_instructions).await.is_err());
+    warp_forward(&mut context, 604_800).await;
+    process(&mut context, &unlock_instructions).await.unwrap();
+    assert_eq!(
+        token_balance(&mut context.banks_client, &source_token_account.pubkey()).await,
+        100
+    );
+    assert_eq!(
+        token_balance(&mut context.banks_client, &receipt_token_account.pubkey()).await,
+        0
+    );
 }
 
-fn mint_init_transaction(
-    payer: &Keypair,
-    mint: &Keypair,
-    mint_authority: &Keypair,
-    recent_blockhash: Hash,
-) -> Transaction {
-    let instructions = [
-        system_instruction::create_account(
-            &payer.pubkey(),
-            &mint.pubkey(),
-            Rent::default().minimum_balance(82),
-            82,
-            &spl_token::id(),
-        ),
-        initialize_mint(
-            &spl_token::id(),
-            &mint.pubkey(),
-            &mint_authority.pubkey(),
-            None,
-            0,
-        )
-        .unwrap(),
-    ];
-    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
-    transaction.partial_sign(&[payer, mint], recent_blockhash);
-    transaction
+/// Adds the token mint accepted by the program, with the given mint authority
+fn add_mint(program_test: &mut ProgramTest, mint_authority: &Pubkey) {
+    let mut data = vec![0; Mint::LEN];
+    Mint {
+        mint_authority: COption::Some(*mint_authority),
+        is_initialized: true,
+        ..Mint::default()
+    }
+    .pack_into_slice(&mut data);
+    program_test.add_account(
+        TOKEN_MINT,
+        Account {
+            lamports: Rent::default().minimum_balance(Mint::LEN),
+            data,
+            owner: spl_token::id(),
+            ..Account::default()
+        },
+    );
+}
+
+/// Processes instructions signed by the payer, with a new blockhash
+async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[solana_program::instruction::Instruction],
+) -> Result<(), solana_program_test::BanksClientError> {
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&context.payer.pubkey()));
+    transaction.partial_sign(&[&context.payer], recent_blockhash);
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// Moves the clock forward, warping to a later slot
+async fn warp_forward(context: &mut ProgramTestContext, seconds: i64) {
+    let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    context.warp_to_slot(clock.slot + 100).unwrap();
+    let mut warped_clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+    warped_clock.unix_timestamp = clock.unix_timestamp + seconds;
+    context.set_sysvar(&warped_clock);
+}
+
+async fn token_balance(banks_client: &mut BanksClient, token_account: &Pubkey) -> u64 {
+    let account = banks_client
+        .get_account(*token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
 }
 
 fn create_token_account(
     payer: &Keypair,
-    mint: &Keypair,
+    mint: &Pubkey,
     recent_blockhash: Hash,
     token_account: &Keypair,
     token_account_owner: &Pubkey,
@@ -396,7 +488,7 @@ fn create_token_account(
         initialize_account(
             &spl_token::id(),
             &token_account.pubkey(),
-            &mint.pubkey(),
+            mint,
             token_account_owner,
         )
         .unwrap(),
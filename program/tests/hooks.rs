@@ -0,0 +1,274 @@
+#![cfg(feature = "test-bpf")]
+mod common;
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke,
+    program_error::ProgramError,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    instruction::AccountMeta,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+};
+use std::convert::TryInto;
+use token_vesting::{
+    hook::{Hook, HookEvent, HookMode, HookPayload},
+    instruction::{self, Schedule},
+    processor::HOOK_AUTHORITY_SEED,
+};
+
+use common::{registry_address, start, stats_address, LockAddresses, LockOptions, TestEnv};
+
+/// Length of the record of the recording hook: its counts of creates, initial unlocks and
+/// unlocks, and the amount still locked, as little endian u64s
+const RECORD_LEN: usize = 32;
+
+/// A hook recording the events of the locks of the vesting program owning their vesting
+/// account, into the record account following the vesting account
+fn process_recording_hook(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let hook_authority = next_account_info(accounts_iter)?;
+    let vesting_account = next_account_info(accounts_iter)?;
+    let record_account = next_account_info(accounts_iter)?;
+    let payload = HookPayload::unpack(instruction_data)?;
+
+    let (hook_authority_key, _) = Pubkey::find_program_address(
+        &[HOOK_AUTHORITY_SEED, vesting_account.key.as_ref()],
+        vesting_account.owner,
+    );
+    if !hook_authority.is_signer
+        || *hook_authority.key != hook_authority_key
+        || *vesting_account.key != payload.vesting_account
+    {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let mut data = record_account.data.borrow_mut();
+    let mut record = read_record(&data);
+    match payload.event {
+        HookEvent::Create => {
+            record[0] += 1;
+            record[3] += payload.amount;
+        }
+        HookEvent::InitializeUnlock => record[1] += 1,
+        HookEvent::Unlock => {
+            record[2] += 1;
+            record[3] -= payload.amount;
+        }
+    }
+    for (chunk, value) in data.chunks_exact_mut(8).zip(record.iter()) {
+        chunk.copy_from_slice(&value.to_le_bytes());
+    }
+    Ok(())
+}
+
+fn read_record(data: &[u8]) -> [u64; 4] {
+    let mut record = [0; 4];
+    for (value, chunk) in record.iter_mut().zip(data.chunks_exact(8)) {
+        *value = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    record
+}
+
+/// A hostile hook, trying to transfer the tokens of the vesting token account it is forwarded
+/// with the authority of the vesting account
+fn process_hostile_hook(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let vesting_account = &accounts[1];
+    let vesting_token_account = &accounts[2];
+    let destination_token_account = &accounts[3];
+    let token_program = &accounts[4];
+    invoke(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            vesting_token_account.key,
+            destination_token_account.key,
+            vesting_account.key,
+            &[],
+            1,
+        )?,
+        &[
+            vesting_token_account.clone(),
+            destination_token_account.clone(),
+            vesting_account.clone(),
+            token_program.clone(),
+        ],
+    )
+}
+
+async fn get_record(env: &mut TestEnv, record_account: &Pubkey) -> [u64; 4] {
+    read_record(&env.get_account(record_account).await.unwrap().data)
+}
+
+/// Releases a lock of the owner of the test environment without its hook program account, signed
+/// by the owner as the keeper account if `by_owner`
+async fn unlock_without_hook(
+    env: &mut TestEnv,
+    addresses: &LockAddresses,
+    by_owner: bool,
+) -> Result<(), BanksClientError> {
+    let owner = env.owner.insecure_clone();
+    let mut instruction = instruction::unlock(
+        &env.program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &addresses.vesting_account,
+        &addresses.vesting_token_account,
+        &env.source_token_account,
+        &stats_address(&env.program_id),
+        &registry_address(&env.program_id, &owner.pubkey()),
+        addresses.seeds,
+    )
+    .unwrap();
+    if by_owner {
+        instruction
+            .accounts
+            .push(AccountMeta::new(owner.pubkey(), true));
+    }
+    env.send(&[instruction], &[&owner]).await
+}
+
+#[tokio::test]
+async fn test_hooks() {
+    let hook_program = Pubkey::new_unique();
+    let record_account = Pubkey::new_unique();
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "recording_hook",
+        hook_program,
+        processor!(process_recording_hook),
+    );
+    program_test.add_account(
+        record_account,
+        Account {
+            lamports: 1_000_000_000,
+            data: vec![0; RECORD_LEN],
+            owner: hook_program,
+            ..Account::default()
+        },
+    );
+    let owner = Keypair::new();
+    let owner_pubkey = owner.pubkey();
+    let mut env = start(program_test, owner, &owner_pubkey, 100).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+    env.fund(&owner_pubkey).await;
+    env.hook_accounts
+        .insert(hook_program, vec![AccountMeta::new(record_account, false)]);
+
+    // The hook is invoked after the creation, initial unlock and unlock of a lock
+    let staked = env
+        .lock(
+            1,
+            Schedule {
+                time_delta: 0,
+                amount: 40,
+            },
+            LockOptions {
+                hook: Some(Hook {
+                    program_id: hook_program,
+                    mode: HookMode::Required,
+                }),
+                ..LockOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(get_record(&mut env, &record_account).await, [1, 0, 0, 40]);
+    env.initialize_unlock(&owner, &staked).await.unwrap();
+    assert_eq!(get_record(&mut env, &record_account).await, [1, 1, 0, 40]);
+
+    // A required hook can not be left out, even by the owner
+    env.warp_forward(604_800).await;
+    assert!(unlock_without_hook(&mut env, &staked, true).await.is_err());
+    env.unlock(&payer, &staked).await.unwrap();
+    assert_eq!(get_record(&mut env, &record_account).await, [1, 1, 1, 0]);
+
+    // A failing skippable hook blocks the lock until its owner leaves out its hook program
+    // account
+    let timed = env
+        .lock(
+            2,
+            Schedule {
+                time_delta: 7_776_000,
+                amount: 60,
+            },
+            LockOptions {
+                hook: Some(Hook {
+                    program_id: hook_program,
+                    mode: HookMode::Skippable,
+                }),
+                ..LockOptions::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(get_record(&mut env, &record_account).await, [2, 1, 1, 60]);
+    env.warp_forward(7_776_000).await;
+    env.hook_accounts.clear();
+    assert!(env.unlock(&payer, &timed).await.is_err());
+    assert!(unlock_without_hook(&mut env, &timed, false).await.is_err());
+    unlock_without_hook(&mut env, &timed, true).await.unwrap();
+    assert_eq!(get_record(&mut env, &record_account).await, [2, 1, 1, 60]);
+    let source_token_account = env.source_token_account;
+    assert_eq!(env.token_balance(&source_token_account).await, 100);
+}
+
+#[tokio::test]
+async fn test_hostile_hook() {
+    let hook_program = Pubkey::new_unique();
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "hostile_hook",
+        hook_program,
+        processor!(process_hostile_hook),
+    );
+    let owner = Keypair::new();
+    let owner_pubkey = owner.pubkey();
+    let mut env = start(program_test, owner, &owner_pubkey, 100).await;
+    env.fund(&owner_pubkey).await;
+    let attacker_token_account = env.create_associated_token_account(&hook_program).await;
+    let addresses = env.lock_addresses(&[1; 31]);
+    env.hook_accounts.insert(
+        hook_program,
+        vec![
+            AccountMeta::new(addresses.vesting_token_account, false),
+            AccountMeta::new(attacker_token_account, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    );
+
+    // The hook is not signed by the vesting account, so it can not move the locked tokens
+    let result = env
+        .lock(
+            1,
+            Schedule {
+                time_delta: 0,
+                amount: 40,
+            },
+            LockOptions {
+                hook: Some(Hook {
+                    program_id: hook_program,
+                    mode: HookMode::Required,
+                }),
+                ..LockOptions::default()
+            },
+        )
+        .await;
+    assert!(result.is_err());
+    assert_eq!(env.token_balance(&attacker_token_account).await, 0);
+    let source_token_account = env.source_token_account;
+    assert_eq!(env.token_balance(&source_token_account).await, 100);
+}
@@ -0,0 +1,813 @@
+//! Checks that the committed IDL in `idl/token_vesting.json` still matches the Rust types.
+//!
+//! The IDL is generated with `shank idl` from the annotations on `VestingInstruction` and the
+//! state structs. When one of those changes, regenerate the IDL and extend `sample_instruction`
+//! below, otherwise this test fails. The `isOptionalSigner` flag of the accounts that sign unless
+//! they are a multisig can not be expressed with the annotations, and is kept by hand.
+use num_traits::FromPrimitive;
+use serde_json::Value;
+use solana_program::{instruction::Instruction, program_pack::Pack, pubkey::Pubkey};
+use token_vesting::{
+    error::VestingError,
+    instruction::{
+        self, BatchEntry, CreateArgs, CreateOptions, Schedule, VestingInstruction,
+        BATCH_ENTRY_SIZE, SCHEDULE_SIZE,
+    },
+    state::{
+        Airdrop, AllowlistEntry, Config, LockMode, Position, PositionRegistry, Referrer, Stats,
+        TierStats, VestingSchedule, VestingScheduleHeader,
+    },
+};
+
+const IDL: &str = include_str!("../idl/token_vesting.json");
+
+fn idl() -> Value {
+    serde_json::from_str(IDL).expect("The IDL is not valid JSON")
+}
+
+fn camel_to_snake(name: &str) -> String {
+    let mut snake = String::new();
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            snake.push('_');
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+fn find_type<'a>(idl: &'a Value, name: &str) -> &'a Value {
+    idl["types"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .chain(idl["accounts"].as_array().unwrap().iter())
+        .find(|t| t["name"] == name)
+        .unwrap_or_else(|| panic!("Type {} is not defined in the IDL", name))
+}
+
+/// Serialized size of an IDL type, assuming empty vectors, `None` options and the first
+/// variant of enums, which is how the samples below are built.
+fn type_size(idl: &Value, ty: &Value) -> usize {
+    match ty {
+        Value::String(primitive) => match primitive.as_str() {
+            "u8" | "i8" | "bool" => 1,
+            "u16" | "i16" => 2,
+            "u32" | "i32" => 4,
+            "u64" | "i64" => 8,
+            "u128" | "i128" => 16,
+            "publicKey" => 32,
+            other => panic!("Unsupported IDL type {}", other),
+        },
+        Value::Object(composite) => {
+            if let Some(array) = composite.get("array") {
+                type_size(idl, &array[0]) * array[1].as_u64().unwrap() as usize
+            } else if composite.contains_key("option") {
+                1
+            } else if composite.contains_key("vec") {
+                4
+            } else if let Some(defined) = composite.get("defined") {
+                let definition = &find_type(idl, defined.as_str().unwrap())["type"];
+                match definition["kind"].as_str().unwrap() {
+                    "struct" => fields_size(idl, &definition["fields"]),
+                    "enum" => 1,
+                    kind => panic!("Unsupported IDL type kind {}", kind),
+                }
+            } else {
+                panic!("Unsupported IDL type {:?}", composite)
+            }
+        }
+        _ => panic!("Unsupported IDL type {:?}", ty),
+    }
+}
+
+fn fields_size(idl: &Value, fields: &Value) -> usize {
+    fields
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| type_size(idl, &field["type"]))
+        .sum()
+}
+
+/// Asserts that the fields appear in the given order in the `Debug` output of a Rust value
+fn assert_field_order(debug: &str, fields: &Value, context: &str) {
+    let mut position = 0;
+    for field in fields.as_array().unwrap() {
+        let name = format!("{}: ", camel_to_snake(field["name"].as_str().unwrap()));
+        match debug[position..].find(&name) {
+            Some(offset) => position += offset + name.len(),
+            None => panic!("{}: field {} is missing or out of order", context, name),
+        }
+    }
+}
+
+/// Builds a sample of every instruction with its matching client-side builder
+fn sample_instruction(name: &str) -> (VestingInstruction, Instruction) {
+    let key = Pubkey::new_unique;
+    let seeds = [7u8; 32];
+    match name {
+        "Init" => (
+            VestingInstruction::Init { seeds },
+            instruction::init(&key(), &key(), &key(), &key(), &key(), seeds).unwrap(),
+        ),
+        "Create" => {
+            let schedule = Schedule {
+                time_delta: 0,
+                amount: 0,
+            };
+            (
+                VestingInstruction::Create {
+                    seeds,
+                    schedule: schedule.clone(),
+                    options: CreateOptions::default(),
+                },
+                instruction::create(
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &[],
+                    schedule,
+                    seeds,
+                    CreateArgs::default(),
+                )
+                .unwrap(),
+            )
+        }
+        "Unlock" => (
+            VestingInstruction::Unlock { seeds },
+            instruction::unlock(
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                seeds,
+            )
+            .unwrap(),
+        ),
+        "InitializeUnlock" => (
+            VestingInstruction::InitializeUnlock { seeds },
+            instruction::initialize_unlock(
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                seeds,
+            )
+            .unwrap(),
+        ),
+        "CreateBatch" => (
+            VestingInstruction::CreateBatch {
+                entries: vec![],
+                multisig_signers: 0,
+            },
+            instruction::create_batch(
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                None,
+                &[],
+                &[],
+                vec![],
+            )
+            .unwrap(),
+        ),
+        "CreateAirdrop" => (
+            VestingInstruction::CreateAirdrop {
+                seeds,
+                merkle_root: seeds,
+            },
+            instruction::create_airdrop(&key(), &key(), &key(), &key(), &key(), seeds, seeds)
+                .unwrap(),
+        ),
+        "ClaimIntoLock" => {
+            let schedule = Schedule {
+                time_delta: 0,
+                amount: 0,
+            };
+            (
+                VestingInstruction::ClaimIntoLock {
+                    airdrop_seeds: seeds,
+                    seeds,
+                    schedule: schedule.clone(),
+                    proof: vec![],
+                },
+                instruction::claim_into_lock(
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    &key(),
+                    None,
+                    seeds,
+                    seeds,
+                    schedule,
+                    vec![],
+                )
+                .unwrap(),
+            )
+        }
+        "Revoke" => (
+            VestingInstruction::Revoke { seeds },
+            instruction::revoke(
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                seeds,
+            )
+            .unwrap(),
+        ),
+        "InitConfig" => (
+            VestingInstruction::InitConfig {
+                max_penalty_bps: 0,
+                lock_mode: LockMode::Timestamp,
+            },
+            instruction::init_config(
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                0,
+                LockMode::Timestamp,
+            )
+            .unwrap(),
+        ),
+        "UpdatePenalty" => (
+            VestingInstruction::UpdatePenalty { max_penalty_bps: 0 },
+            instruction::update_penalty(&key(), &key(), &key(), &key(), 0).unwrap(),
+        ),
+        "EarlyUnlock" => (
+            VestingInstruction::EarlyUnlock { seeds },
+            instruction::early_unlock(
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                seeds,
+            )
+            .unwrap(),
+        ),
+        "SetPaused" => (
+            VestingInstruction::SetPaused { paused: false },
+            instruction::set_paused(&key(), &key(), &key(), false).unwrap(),
+        ),
+        "SetEmergencyMode" => (
+            VestingInstruction::SetEmergencyMode { enabled: false },
+            instruction::set_emergency_mode(&key(), &key(), &key(), false).unwrap(),
+        ),
+        "SetGuardian" => (
+            VestingInstruction::SetGuardian,
+            instruction::set_guardian(&key(), &key(), &key(), &key()).unwrap(),
+        ),
+        "SetAllowlist" => (
+            VestingInstruction::SetAllowlist {
+                is_enabled: false,
+                root: [0; 32],
+            },
+            instruction::set_allowlist(&key(), &key(), &key(), false, [0; 32]).unwrap(),
+        ),
+        "AddAllowlistEntry" => (
+            VestingInstruction::AddAllowlistEntry,
+            instruction::add_allowlist_entry(&key(), &key(), &key(), &key(), &key(), &key())
+                .unwrap(),
+        ),
+        "RemoveAllowlistEntry" => (
+            VestingInstruction::RemoveAllowlistEntry,
+            instruction::remove_allowlist_entry(&key(), &key(), &key(), &key(), &key()).unwrap(),
+        ),
+        "SetLimits" => (
+            VestingInstruction::SetLimits {
+                max_total_locked: 0,
+                max_locked_per_wallet: 0,
+                min_lock_amounts: [0; 5],
+            },
+            instruction::set_limits(&key(), &key(), &key(), 0, 0, [0; 5]).unwrap(),
+        ),
+        "SetDepositFee" => (
+            VestingInstruction::SetDepositFee { deposit_fee_bps: 0 },
+            instruction::set_deposit_fee(&key(), &key(), &key(), &key(), 0).unwrap(),
+        ),
+        "InitReferrer" => (
+            VestingInstruction::InitReferrer,
+            instruction::init_referrer(&key(), &key(), &key(), &key()).unwrap(),
+        ),
+        "SetReferralShare" => (
+            VestingInstruction::SetReferralShare {
+                referral_share_bps: 0,
+            },
+            instruction::set_referral_share(&key(), &key(), &key(), 0).unwrap(),
+        ),
+        "EmergencyUnlock" => (
+            VestingInstruction::EmergencyUnlock { seeds },
+            instruction::emergency_unlock(
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                seeds,
+            )
+            .unwrap(),
+        ),
+        "InitStats" => (
+            VestingInstruction::InitStats,
+            instruction::init_stats(&key(), &key(), &key()).unwrap(),
+        ),
+        "GetStats" => (
+            VestingInstruction::GetStats,
+            instruction::get_stats(&key(), &key()).unwrap(),
+        ),
+        "InitRegistry" => (
+            VestingInstruction::InitRegistry,
+            instruction::init_registry(&key(), &key(), &key(), &key()).unwrap(),
+        ),
+        "Close" => (
+            VestingInstruction::Close { seeds },
+            instruction::close(
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                seeds,
+            )
+            .unwrap(),
+        ),
+        "Split" => (
+            VestingInstruction::Split {
+                seeds,
+                new_seeds: seeds,
+                amount: 0,
+            },
+            instruction::split(
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                seeds,
+                seeds,
+                0,
+            )
+            .unwrap(),
+        ),
+        "Merge" => (
+            VestingInstruction::Merge {
+                seeds,
+                source_seeds: vec![],
+            },
+            instruction::merge(
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                seeds,
+                &[],
+            )
+            .unwrap(),
+        ),
+        "UnlockMany" => (
+            VestingInstruction::UnlockMany { seeds: vec![] },
+            instruction::unlock_many(&key(), &key(), &key(), &key(), &key(), &[]).unwrap(),
+        ),
+        "OptOutRenewal" => (
+            VestingInstruction::OptOutRenewal { seeds },
+            instruction::opt_out_renewal(&key(), &key(), &key(), &key(), &key(), seeds).unwrap(),
+        ),
+        "TransferPosition" => (
+            VestingInstruction::TransferPosition {
+                seeds,
+                require_accept: false,
+            },
+            instruction::transfer_position(
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                seeds,
+                false,
+            )
+            .unwrap(),
+        ),
+        "AcceptPosition" => (
+            VestingInstruction::AcceptPosition { seeds },
+            instruction::accept_position(
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                seeds,
+            )
+            .unwrap(),
+        ),
+        "InitReceiptMint" => (
+            VestingInstruction::InitReceiptMint,
+            instruction::init_receipt_mint(&key(), &key(), &key(), &key(), &key()).unwrap(),
+        ),
+        "MintPosition" => (
+            VestingInstruction::MintPosition { seeds },
+            instruction::mint_position(
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                seeds,
+            )
+            .unwrap(),
+        ),
+        _ => panic!(
+            "No sample for instruction {}, please extend tests/idl.rs",
+            name
+        ),
+    }
+}
+
+#[test]
+fn test_idl_instructions() {
+    let idl = idl();
+    let instructions = idl["instructions"].as_array().unwrap();
+
+    // Every tag accepted by the program is described in the IDL, and nothing else
+    let mut idl_tags: Vec<u64> = instructions
+        .iter()
+        .map(|ix| ix["discriminant"]["value"].as_u64().unwrap())
+        .collect();
+    idl_tags.sort_unstable();
+    let zeroes = [0u8; 4096];
+    let program_tags: Vec<u64> = (0..=u8::MAX)
+        .filter(|&tag| {
+            let mut data = zeroes.to_vec();
+            data[0] = tag;
+            VestingInstruction::unpack(&data).is_ok()
+        })
+        .map(u64::from)
+        .collect();
+    assert_eq!(idl_tags, program_tags);
+
+    for ix in instructions {
+        let name = ix["name"].as_str().unwrap();
+        let (sample, built) = sample_instruction(name);
+
+        // Arguments
+        let packed = sample.pack();
+        assert_eq!(
+            u64::from(packed[0]),
+            ix["discriminant"]["value"].as_u64().unwrap(),
+            "{}: discriminant mismatch",
+            name
+        );
+        assert_eq!(
+            packed.len(),
+            1 + fields_size(&idl, &ix["args"]),
+            "{}: argument layout mismatch",
+            name
+        );
+        assert_eq!(built.data, packed, "{}: builder data mismatch", name);
+        assert_field_order(&format!("{:?}", sample), &ix["args"], name);
+
+        // Accounts
+        let accounts = ix["accounts"].as_array().unwrap();
+        assert_eq!(
+            built.accounts.len(),
+            accounts.len(),
+            "{}: account count mismatch",
+            name
+        );
+        for (meta, account) in built.accounts.iter().zip(accounts) {
+            let account_name = account["name"].as_str().unwrap();
+            assert_eq!(
+                meta.is_writable,
+                account["isMut"].as_bool().unwrap(),
+                "{}: {} writable flag mismatch",
+                name,
+                account_name
+            );
+            // Optional signers sign in the samples, built for a single owner
+            assert_eq!(
+                meta.is_signer,
+                account["isSigner"].as_bool().unwrap()
+                    || account["isOptionalSigner"].as_bool().unwrap_or(false),
+                "{}: {} signer flag mismatch",
+                name,
+                account_name
+            );
+        }
+    }
+}
+
+#[test]
+fn test_idl_multisig_owner() {
+    let idl = idl();
+    let instructions = idl["instructions"].as_array().unwrap();
+    let key = Pubkey::new_unique;
+    let signer = key();
+
+    // The multisig owner does not sign, its signers follow the other accounts and are counted in
+    // the last argument
+    let create = instruction::create(
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &[&signer],
+        Schedule {
+            time_delta: 0,
+            amount: 0,
+        },
+        [7u8; 32],
+        CreateArgs::default(),
+    )
+    .unwrap();
+    let create_batch = instruction::create_batch(
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        &key(),
+        None,
+        &[&signer],
+        &[],
+        vec![],
+    )
+    .unwrap();
+    for (name, built) in [("Create", create), ("CreateBatch", create_batch)] {
+        let ix = instructions.iter().find(|ix| ix["name"] == name).unwrap();
+        let accounts = ix["accounts"].as_array().unwrap();
+        let owner = accounts
+            .iter()
+            .position(|account| account["name"] == "sourceTokenAccountOwner")
+            .unwrap();
+        assert!(accounts[owner]["isOptionalSigner"].as_bool().unwrap());
+        assert!(!accounts[owner]["isSigner"].as_bool().unwrap());
+        assert!(!built.accounts[owner].is_signer, "{}: owner signs", name);
+        let last = built.accounts.last().unwrap();
+        assert!(
+            last.pubkey == signer && last.is_signer,
+            "{}: signer missing",
+            name
+        );
+        let args = match ix["args"].as_array().unwrap().last().unwrap() {
+            options if options["name"] == "options" => {
+                find_type(&idl, "CreateOptions")["type"]["fields"].clone()
+            }
+            _ => ix["args"].clone(),
+        };
+        assert_eq!(
+            args.as_array().unwrap().last().unwrap()["name"],
+            "multisigSigners"
+        );
+        assert_eq!(
+            *built.data.last().unwrap(),
+            1,
+            "{}: signer count mismatch",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_idl_types() {
+    let idl = idl();
+
+    let schedule = find_type(&idl, "Schedule");
+    assert_eq!(
+        fields_size(&idl, &schedule["type"]["fields"]),
+        SCHEDULE_SIZE
+    );
+    assert_field_order(
+        &format!(
+            "{:?}",
+            Schedule {
+                time_delta: 0,
+                amount: 0
+            }
+        ),
+        &schedule["type"]["fields"],
+        "Schedule",
+    );
+
+    let batch_entry = find_type(&idl, "BatchEntry");
+    assert_eq!(
+        fields_size(&idl, &batch_entry["type"]["fields"]),
+        BATCH_ENTRY_SIZE
+    );
+    assert_field_order(
+        &format!(
+            "{:?}",
+            BatchEntry {
+                seeds: [0; 32],
+                schedule: Schedule {
+                    time_delta: 0,
+                    amount: 0
+                }
+            }
+        ),
+        &batch_entry["type"]["fields"],
+        "BatchEntry",
+    );
+
+    // The options of `Create`, which are all omitted in the sample above, take 15 bytes when set
+    // to their defaults
+    let create_options = find_type(&idl, "CreateOptions");
+    assert_eq!(fields_size(&idl, &create_options["type"]["fields"]), 15);
+    assert_field_order(
+        &format!("{:?}", CreateOptions::default()),
+        &create_options["type"]["fields"],
+        "CreateOptions",
+    );
+
+    let tier_stats = find_type(&idl, "TierStats");
+    assert_eq!(fields_size(&idl, &tier_stats["type"]["fields"]), 24);
+    assert_field_order(
+        &format!("{:?}", TierStats::default()),
+        &tier_stats["type"]["fields"],
+        "TierStats",
+    );
+
+    let position = find_type(&idl, "Position");
+    assert_eq!(fields_size(&idl, &position["type"]["fields"]), 48);
+    assert_field_order(
+        &format!("{:?}", Position::default()),
+        &position["type"]["fields"],
+        "Position",
+    );
+
+    let accounts = idl["accounts"].as_array().unwrap();
+    assert_eq!(accounts.len(), 8);
+    for account in accounts {
+        let name = account["name"].as_str().unwrap();
+        let fields = &account["type"]["fields"];
+        let (len, debug) = match name {
+            "VestingScheduleHeader" => (
+                VestingScheduleHeader::LEN,
+                format!(
+                    "{:?}",
+                    VestingScheduleHeader::unpack_from_slice(&[0; VestingScheduleHeader::LEN])
+                        .unwrap()
+                ),
+            ),
+            "VestingSchedule" => (
+                VestingSchedule::LEN,
+                format!(
+                    "{:?}",
+                    VestingSchedule::unpack_from_slice(&[0; VestingSchedule::LEN]).unwrap()
+                ),
+            ),
+            "Airdrop" => (
+                Airdrop::LEN,
+                format!(
+                    "{:?}",
+                    Airdrop::unpack_from_slice(&[0; Airdrop::LEN]).unwrap()
+                ),
+            ),
+            "AllowlistEntry" => (
+                AllowlistEntry::LEN,
+                format!(
+                    "{:?}",
+                    AllowlistEntry::unpack_from_slice(&[0; AllowlistEntry::LEN]).unwrap()
+                ),
+            ),
+            "Config" => (
+                Config::LEN,
+                format!(
+                    "{:?}",
+                    Config::unpack_from_slice(&[0; Config::LEN]).unwrap()
+                ),
+            ),
+            "Stats" => (
+                Stats::LEN,
+                format!("{:?}", Stats::unpack_from_slice(&[0; Stats::LEN]).unwrap()),
+            ),
+            "PositionRegistry" => (
+                PositionRegistry::LEN,
+                format!(
+                    "{:?}",
+                    PositionRegistry::unpack_from_slice(&[0; PositionRegistry::LEN]).unwrap()
+                ),
+            ),
+            "Referrer" => (
+                Referrer::LEN,
+                format!(
+                    "{:?}",
+                    Referrer::unpack_from_slice(&[0; Referrer::LEN]).unwrap()
+                ),
+            ),
+            _ => panic!("No layout for account {}, please extend tests/idl.rs", name),
+        };
+        assert_eq!(fields_size(&idl, fields), len, "{}: size mismatch", name);
+        assert_field_order(&debug, fields, name);
+    }
+}
+
+#[test]
+fn test_idl_errors() {
+    let idl = idl();
+    let errors = idl["errors"].as_array().unwrap();
+    for error in errors {
+        let code = error["code"].as_u64().unwrap() as u32;
+        let program_error = VestingError::from_u32(code)
+            .unwrap_or_else(|| panic!("Error code {} is not a VestingError", code));
+        assert_eq!(
+            format!("{:?}", program_error),
+            error["name"].as_str().unwrap()
+        );
+        assert_eq!(program_error.to_string(), error["msg"].as_str().unwrap());
+    }
+    assert!(VestingError::from_u32(errors.len() as u32).is_none());
+}
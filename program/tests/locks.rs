@@ -0,0 +1,845 @@
+#![cfg(feature = "test-bpf")]
+mod common;
+
+use solana_program_test::{BanksClientError, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    epoch_schedule::EpochSchedule,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::get_associated_token_address;
+use token_vesting::{
+    instruction::{self, get_stats, Schedule, UnlockedLock},
+    processor::TOKEN_MINT,
+    state::{LockMode, Stats, TierStats, LEGACY_VESTING_ACCOUNT_LEN},
+};
+
+use common::{
+    add_token_account, packed_account, receipt_mint_address, registry_address, setup,
+    setup_multisig, setup_pool, start, start_without_stats, stats_address, AirdropEntry,
+    LockAddresses, LockOptions, MerkleTree, TestEnv,
+};
+
+#[tokio::test]
+async fn test_lock_and_unlock_timed() {
+    let mut env = setup(100).await;
+    let payer = env.payer();
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 60,
+    };
+
+    let addresses = env
+        .lock(3, schedule.clone(), LockOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&addresses.vesting_token_account).await,
+        60
+    );
+    let info = env.get_lock(&addresses).await;
+    assert_eq!(info.header.destination_address, env.source_token_account);
+    assert_eq!(info.schedule.amount, 60);
+    assert_ne!(info.schedule.release_time, 0);
+
+    // The same seed can not be used twice
+    assert!(env.lock(3, schedule, LockOptions::default()).await.is_err());
+
+    // The lock has not matured yet
+    assert!(env.unlock(&payer, &addresses).await.is_err());
+
+    env.warp_forward(7_776_000).await;
+    env.unlock(&payer, &addresses).await.unwrap();
+    let source_token_account = env.source_token_account;
+    assert_eq!(env.token_balance(&source_token_account).await, 100);
+    assert_eq!(env.token_balance(&addresses.vesting_token_account).await, 0);
+}
+
+#[tokio::test]
+async fn test_lock_and_unlock_slot_and_epoch_modes() {
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 50,
+    };
+
+    // Locks of a slot pool are released at their release slot
+    let mut env = setup_pool(LockMode::Slot).await;
+    let payer = env.payer();
+    let clock = env.get_clock().await;
+    let slot_lock = env
+        .lock(8, schedule.clone(), LockOptions::default())
+        .await
+        .unwrap();
+    let slot_info = env.get_lock(&slot_lock).await;
+    assert_eq!(slot_info.header.lock_mode, LockMode::Slot);
+    let release_slot = slot_info.schedule.release_time;
+    assert_eq!(release_slot, clock.slot + 19_440_000);
+
+    // The timestamp does not matter to slot locks
+    env.warp_forward(7_776_000).await;
+    assert!(env.unlock(&payer, &slot_lock).await.is_err());
+    env.context.warp_to_slot(release_slot).unwrap();
+    env.unlock(&payer, &slot_lock).await.unwrap();
+    let source_token_account = env.source_token_account;
+    assert_eq!(env.token_balance(&source_token_account).await, 100);
+
+    // Locks of an epoch pool are released at their release epoch
+    let mut env = setup_pool(LockMode::Epoch).await;
+    let payer = env.payer();
+    let clock = env.get_clock().await;
+    let epoch_lock = env.lock(9, schedule, LockOptions::default()).await.unwrap();
+    let epoch_info = env.get_lock(&epoch_lock).await;
+    assert_eq!(epoch_info.header.lock_mode, LockMode::Epoch);
+    let release_epoch = epoch_info.schedule.release_time;
+    assert_eq!(release_epoch, clock.epoch + 45);
+
+    env.warp_forward(7_776_000).await;
+    assert!(env.unlock(&payer, &epoch_lock).await.is_err());
+    let epoch_schedule: EpochSchedule = env.context.banks_client.get_sysvar().await.unwrap();
+    env.context
+        .warp_to_slot(epoch_schedule.get_first_slot_in_epoch(release_epoch))
+        .unwrap();
+    env.unlock(&payer, &epoch_lock).await.unwrap();
+    let source_token_account = env.source_token_account;
+    assert_eq!(env.token_balance(&source_token_account).await, 100);
+}
+
+#[tokio::test]
+async fn test_pool_lock_mode() {
+    let mut env = setup_pool(LockMode::Slot).await;
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 40,
+    };
+
+    // Locks are measured with the clock of the pool
+    let clock = env.get_clock().await;
+    let lock = env.lock(8, schedule, LockOptions::default()).await.unwrap();
+    let info = env.get_lock(&lock).await;
+    assert_eq!(info.header.lock_mode, LockMode::Slot);
+    assert_eq!(info.schedule.release_time, clock.slot + 19_440_000);
+
+    // Including the locks of a batch
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 60,
+    };
+    let locks = env
+        .lock_batch(1, &[(Pubkey::new_unique(), schedule)])
+        .await
+        .unwrap();
+    let info = env.get_lock(&locks[0]).await;
+    assert_eq!(info.header.lock_mode, LockMode::Slot);
+}
+
+#[tokio::test]
+async fn test_lock_and_unlock_unlocked_tier() {
+    let mut env = setup(100).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+
+    let addresses = env.lock_stake(4, 100, None).await.unwrap();
+
+    // Unlocked stakes need to initialize their withdrawal period first
+    assert!(env.unlock(&payer, &addresses).await.is_err());
+    env.initialize_unlock(&owner, &addresses).await.unwrap();
+    let info = env.get_lock(&addresses).await;
+    assert_ne!(info.schedule.release_time, 0);
+
+    env.warp_forward(604_800).await;
+    env.unlock(&payer, &addresses).await.unwrap();
+    let source_token_account = env.source_token_account;
+    assert_eq!(env.token_balance(&source_token_account).await, 100);
+}
+
+#[tokio::test]
+async fn test_stats() {
+    let mut env = setup(100).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+
+    // The stats account can only be created once
+    assert!(env.init_stats().await.is_err());
+
+    let staked = env.lock_stake(10, 40, None).await.unwrap();
+    let locked = env
+        .lock(
+            11,
+            Schedule {
+                time_delta: 15_552_000,
+                amount: 60,
+            },
+            LockOptions::default(),
+        )
+        .await
+        .unwrap();
+    env.initialize_unlock(&owner, &staked).await.unwrap();
+
+    let stats = env.get_stats().await;
+    assert_eq!(
+        stats.tiers[0],
+        TierStats {
+            total_locked: 40,
+            active_locks: 1,
+            pending_cooldown: 40,
+        }
+    );
+    assert_eq!(
+        stats.tiers[2],
+        TierStats {
+            total_locked: 60,
+            active_locks: 1,
+            pending_cooldown: 0,
+        }
+    );
+
+    // The read-only instruction returns the same stats
+    let blockhash = env.context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[get_stats(&env.program_id, &stats_address(&env.program_id)).unwrap()],
+        Some(&payer.pubkey()),
+        &[&payer],
+        blockhash,
+    );
+    let simulation = env
+        .context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+    assert_eq!(Stats::unpack(&return_data.data).unwrap(), stats);
+
+    env.warp_forward(15_552_000).await;
+    env.unlock(&payer, &staked).await.unwrap();
+    env.unlock(&payer, &locked).await.unwrap();
+    let stats = env.get_stats().await;
+    assert_eq!(stats.tiers, [TierStats::default(); 5]);
+}
+
+#[tokio::test]
+async fn test_lock_without_stats() {
+    let owner = Keypair::new();
+    let owner_pubkey = owner.pubkey();
+    let mut env = start_without_stats(ProgramTest::default(), owner, &owner_pubkey, 100).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+
+    // Locks are created and released before the stats account exists
+    let addresses = env.lock_stake(10, 40, None).await.unwrap();
+    env.initialize_unlock(&owner, &addresses).await.unwrap();
+    env.warp_forward(604_800).await;
+    env.unlock(&payer, &addresses).await.unwrap();
+    let source_token_account = env.source_token_account;
+    assert_eq!(env.token_balance(&source_token_account).await, 100);
+
+    // The pool can not be capped without counting its locks
+    let treasury = env.create_associated_token_account(&payer.pubkey()).await;
+    env.init_config(&payer, &treasury, &payer.pubkey(), 0, LockMode::Timestamp)
+        .await
+        .unwrap();
+    env.set_limits(&payer, 1_000, 0, [0; 5]).await.unwrap();
+    assert!(env.lock_stake(11, 40, None).await.is_err());
+
+    // The locks are counted once the stats account is created
+    env.init_stats().await.unwrap();
+    env.lock_stake(11, 40, None).await.unwrap();
+    assert_eq!(env.get_stats().await.total_locked(), 40);
+}
+
+#[tokio::test]
+async fn test_lock_without_registry() {
+    let mut env = setup(100).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+
+    // A lock is created for a wallet without a position registry, and the registry is left
+    // uninitialized
+    let init_registry = instruction::init_registry(
+        &env.program_id,
+        &payer.pubkey(),
+        &owner.pubkey(),
+        &registry_address(&env.program_id, &owner.pubkey()),
+    )
+    .unwrap();
+    let (addresses, mut instructions) = env
+        .lock_instructions(
+            &owner.pubkey(),
+            &[],
+            &[6u8; 31],
+            Schedule {
+                time_delta: 7_776_000,
+                amount: 40,
+            },
+            LockOptions::default(),
+            &Pubkey::new_unique(),
+        )
+        .await;
+    instructions.retain(|instruction| *instruction != init_registry);
+    env.send(&instructions, &[&owner]).await.unwrap();
+    assert!(env.get_registry(&owner.pubkey()).await.is_none());
+    let source_token_account = env.source_token_account;
+    assert_eq!(env.token_balance(&source_token_account).await, 60);
+
+    // It is released like any other lock
+    env.warp_forward(7_776_000).await;
+    env.unlock(&payer, &addresses).await.unwrap();
+    assert_eq!(env.token_balance(&source_token_account).await, 100);
+}
+
+#[tokio::test]
+async fn test_lock_multisig_owner() {
+    let (mut env, multisig, signers) = setup_multisig(100, 2, 3).await;
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 40,
+    };
+
+    // One signer is below the multisig threshold
+    assert!(env
+        .lock_with_signers(
+            &multisig,
+            &[&signers[0]],
+            &[6u8; 31],
+            schedule.clone(),
+            LockOptions::default()
+        )
+        .await
+        .is_err());
+
+    let addresses = env
+        .lock_with_signers(
+            &multisig,
+            &[&signers[0], &signers[2]],
+            &[6u8; 31],
+            schedule,
+            LockOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        env.token_balance(&addresses.vesting_token_account).await,
+        40
+    );
+    let source_token_account = env.source_token_account;
+    assert_eq!(env.token_balance(&source_token_account).await, 60);
+    let info = env.get_lock(&addresses).await;
+    assert_eq!(info.header.destination_address, source_token_account);
+}
+
+#[tokio::test]
+async fn test_lock_for_beneficiary() {
+    let mut program_test = ProgramTest::default();
+    let other_mint_account = add_token_account(&mut program_test, Pubkey::new_unique());
+    let beneficiary = add_token_account(&mut program_test, TOKEN_MINT);
+    // A token account layout held by another program than spl-token
+    let spoofed_beneficiary = Pubkey::new_unique();
+    program_test.add_account(
+        spoofed_beneficiary,
+        Account {
+            owner: Pubkey::new_unique(),
+            ..packed_account(spl_token::state::Account {
+                mint: TOKEN_MINT,
+                owner: Pubkey::new_unique(),
+                state: spl_token::state::AccountState::Initialized,
+                ..spl_token::state::Account::default()
+            })
+        },
+    );
+    let owner = Keypair::new();
+    let owner_pubkey = owner.pubkey();
+    let mut env = start(program_test, owner, &owner_pubkey, 100).await;
+    let payer = env.payer();
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 70,
+    };
+
+    // The beneficiary token account must be owned by the spl-token program, and hold the locked
+    // mint
+    for invalid_beneficiary in [spoofed_beneficiary, other_mint_account] {
+        let options = LockOptions {
+            beneficiary_token_account: Some(invalid_beneficiary),
+            ..LockOptions::default()
+        };
+        assert!(env.lock(7, schedule.clone(), options).await.is_err());
+    }
+
+    let options = LockOptions {
+        beneficiary_token_account: Some(beneficiary),
+        ..LockOptions::default()
+    };
+    let addresses = env.lock(7, schedule, options).await.unwrap();
+    let info = env.get_lock(&addresses).await;
+    assert_eq!(info.header.destination_address, beneficiary);
+
+    env.warp_forward(7_776_000).await;
+    env.unlock(&payer, &addresses).await.unwrap();
+    assert_eq!(env.token_balance(&beneficiary).await, 70);
+    let source_token_account = env.source_token_account;
+    assert_eq!(env.token_balance(&source_token_account).await, 30);
+}
+
+#[tokio::test]
+async fn test_lock_batch() {
+    let mut env = setup(1_000).await;
+    let schedules: Vec<(Pubkey, Schedule)> = (1..=7)
+        .map(|i| {
+            let schedule = Schedule {
+                time_delta: if i % 2 == 0 { 0 } else { 15_552_000 },
+                amount: i * 10,
+            };
+            (Pubkey::new_unique(), schedule)
+        })
+        .collect();
+
+    let locks = env.lock_batch(1, &schedules).await.unwrap();
+    for ((beneficiary, schedule), addresses) in schedules.iter().zip(&locks) {
+        let info = env.get_lock(addresses).await;
+        assert_eq!(
+            info.header.destination_address,
+            get_associated_token_address(beneficiary, &TOKEN_MINT)
+        );
+        assert_eq!(info.schedule.amount, schedule.amount);
+        assert_eq!(info.schedule.release_time == 0, schedule.time_delta == 0);
+        assert_eq!(
+            env.token_balance(&addresses.vesting_token_account).await,
+            schedule.amount
+        );
+    }
+    let source_token_account = env.source_token_account;
+    assert_eq!(env.token_balance(&source_token_account).await, 1_000 - 280);
+    // Every lock is registered for its recipient
+    let registry = env.get_registry(&schedules[0].0).await.unwrap();
+    assert_eq!(
+        registry.open_positions()[0].vesting_account,
+        locks[0].vesting_account
+    );
+
+    // A lock of the batch can not be created twice
+    assert!(env.lock_batch(1, &schedules[..1]).await.is_err());
+    assert_eq!(env.token_balance(&source_token_account).await, 1_000 - 280);
+}
+
+#[tokio::test]
+async fn test_airdrop_claim_into_lock() {
+    let mut env = setup(1_000).await;
+    let owner_pubkey = env.owner.pubkey();
+    env.fund(&owner_pubkey).await;
+    let claimants: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    for claimant in &claimants {
+        env.fund(&claimant.pubkey()).await;
+    }
+    let entries: Vec<AirdropEntry> = claimants
+        .iter()
+        .zip([100, 200, 300])
+        .map(|(claimant, amount)| AirdropEntry {
+            wallet: claimant.pubkey(),
+            amount,
+            time_delta: 7_776_000,
+        })
+        .collect();
+
+    let airdrop = env.create_airdrop(9, &entries).await.unwrap();
+    assert_eq!(env.token_balance(&airdrop.vault_token_account).await, 600);
+
+    let addresses = env
+        .claim_into_lock(&claimants[1], &airdrop, &entries, 1)
+        .await
+        .unwrap();
+    let destination = get_associated_token_address(&claimants[1].pubkey(), &TOKEN_MINT);
+    let info = env.get_lock(&addresses).await;
+    assert_eq!(info.header.destination_address, destination);
+    assert_eq!(info.schedule.amount, 200);
+    assert_eq!(env.token_balance(&airdrop.vault_token_account).await, 400);
+    let registry = env.get_registry(&claimants[1].pubkey()).await.unwrap();
+    assert_eq!(
+        registry.open_positions()[0].vesting_account,
+        addresses.vesting_account
+    );
+
+    // An entry can only be claimed once
+    assert!(env
+        .claim_into_lock(&claimants[1], &airdrop, &entries, 1)
+        .await
+        .is_err());
+
+    // Nor claimed again into the vesting account of another bump seed
+    let mut seeds = addresses.seeds;
+    let other_bump_lock = (0..addresses.seeds[31])
+        .rev()
+        .find_map(|bump| {
+            seeds[31] = bump;
+            LockAddresses::from_seeds(&env.program_id, seeds)
+        })
+        .unwrap();
+    let proof = MerkleTree::airdrop(&entries).proof(1);
+    assert!(env
+        .claim_into(
+            &claimants[1],
+            &airdrop,
+            &other_bump_lock,
+            &entries[1],
+            proof
+        )
+        .await
+        .is_err());
+    assert_eq!(env.token_balance(&airdrop.vault_token_account).await, 400);
+
+    // The claimed amount must match the entry
+    let mut inflated = entries.clone();
+    inflated[0].amount = 500;
+    assert!(env
+        .claim_into_lock(&claimants[0], &airdrop, &inflated, 0)
+        .await
+        .is_err());
+
+    // Only the wallet of the entry can claim it
+    assert!(env
+        .claim_into_lock(&claimants[0], &airdrop, &entries, 2)
+        .await
+        .is_err());
+
+    // The claimed lock is released like any other lock
+    env.warp_forward(7_776_000).await;
+    env.unlock(&claimants[1], &addresses).await.unwrap();
+    assert_eq!(env.token_balance(&destination).await, 200);
+}
+
+#[tokio::test]
+async fn test_revoke() {
+    let mut env = setup(100).await;
+    let payer = env.payer();
+    let issuer = Keypair::new();
+    let beneficiary = env
+        .create_associated_token_account(&Pubkey::new_unique())
+        .await;
+    let source_token_account = env.source_token_account;
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 30,
+    };
+
+    let mut locks = vec![];
+    for (seed, revocation_authority) in [
+        (1, Some(issuer.pubkey())),
+        (2, None),
+        (3, Some(issuer.pubkey())),
+    ] {
+        let options = LockOptions {
+            beneficiary_token_account: Some(beneficiary),
+            revocation_authority,
+            ..LockOptions::default()
+        };
+        locks.push(env.lock(seed, schedule.clone(), options).await.unwrap());
+    }
+    let (unvested, not_revocable, vested) = (&locks[0], &locks[1], &locks[2]);
+    assert_eq!(env.token_balance(&source_token_account).await, 10);
+
+    // Only the revocation authority can revoke, and only revocable locks
+    assert!(env
+        .revoke(&payer, unvested, &source_token_account)
+        .await
+        .is_err());
+    assert!(env
+        .revoke(&issuer, not_revocable, &source_token_account)
+        .await
+        .is_err());
+
+    // Before the release time, all the tokens are clawed back
+    env.revoke(&issuer, unvested, &source_token_account)
+        .await
+        .unwrap();
+    assert_eq!(env.token_balance(&source_token_account).await, 40);
+    assert_eq!(env.token_balance(&beneficiary).await, 0);
+    let info = env.get_lock(unvested).await;
+    assert!(info.header.is_revoked);
+    assert_eq!(info.schedule.amount, 0);
+
+    // After the release time, the vested tokens go to the beneficiary
+    env.warp_forward(7_776_000).await;
+    env.revoke(&issuer, vested, &source_token_account)
+        .await
+        .unwrap();
+    assert_eq!(env.token_balance(&beneficiary).await, 30);
+    assert_eq!(env.token_balance(&source_token_account).await, 40);
+
+    // Revoked locks can not be unlocked nor revoked again
+    assert!(env.unlock(&payer, unvested).await.is_err());
+    assert!(env
+        .revoke(&issuer, vested, &source_token_account)
+        .await
+        .is_err());
+    env.unlock(&payer, not_revocable).await.unwrap();
+    assert_eq!(env.token_balance(&beneficiary).await, 60);
+}
+
+/// Checks that the receipt supply equals the amount staked in unlocked stakes, out of their
+/// cooldown
+async fn assert_receipt_invariant(env: &mut TestEnv) -> u64 {
+    let receipt_mint = receipt_mint_address(&env.program_id);
+    let account = env.get_account(&receipt_mint).await.unwrap();
+    let supply = spl_token::state::Mint::unpack(&account.data)
+        .unwrap()
+        .supply;
+    let stats = env.get_stats().await;
+    assert_eq!(
+        supply,
+        stats.tiers[0].total_locked - stats.tiers[0].pending_cooldown
+    );
+    supply
+}
+
+#[tokio::test]
+async fn test_receipt_supply_invariant() {
+    let mut env = setup(1_000).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+    let receipt_mint = receipt_mint_address(&env.program_id);
+    let receipt_token_account = get_associated_token_address(&owner.pubkey(), &receipt_mint);
+
+    // The receipt mint can only be created once
+    assert!(env.init_receipt_mint().await.is_err());
+    assert_eq!(assert_receipt_invariant(&mut env).await, 0);
+
+    // Unlocked stakes mint their receipts to the beneficiary owner, other tiers mint none
+    let staked = env.lock_stake(1, 40, None).await.unwrap();
+    assert_eq!(assert_receipt_invariant(&mut env).await, 40);
+    assert_eq!(env.token_balance(&receipt_token_account).await, 40);
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 60,
+    };
+    env.lock(2, schedule, LockOptions::default()).await.unwrap();
+    assert_eq!(assert_receipt_invariant(&mut env).await, 40);
+
+    // Unlocked stakes can not be revocable
+    let schedule = Schedule {
+        time_delta: 0,
+        amount: 10,
+    };
+    let options = LockOptions {
+        revocation_authority: Some(payer.pubkey()),
+        ..LockOptions::default()
+    };
+    assert!(env.lock(3, schedule, options).await.is_err());
+
+    // Batches and airdrop claims mint the receipts of their unlocked stakes too
+    let beneficiary = Pubkey::new_unique();
+    let schedules = [
+        (
+            beneficiary,
+            Schedule {
+                time_delta: 0,
+                amount: 50,
+            },
+        ),
+        (
+            Pubkey::new_unique(),
+            Schedule {
+                time_delta: 15_552_000,
+                amount: 50,
+            },
+        ),
+    ];
+    env.lock_batch(1, &schedules).await.unwrap();
+    assert_eq!(assert_receipt_invariant(&mut env).await, 90);
+    assert_eq!(
+        env.token_balance(&get_associated_token_address(&beneficiary, &receipt_mint))
+            .await,
+        50
+    );
+
+    env.fund(&owner.pubkey()).await;
+    let claimant = Keypair::new();
+    env.fund(&claimant.pubkey()).await;
+    let entries = [AirdropEntry {
+        wallet: claimant.pubkey(),
+        amount: 30,
+        time_delta: 0,
+    }];
+    let airdrop = env.create_airdrop(4, &entries).await.unwrap();
+    env.claim_into_lock(&claimant, &airdrop, &entries, 0)
+        .await
+        .unwrap();
+    assert_eq!(assert_receipt_invariant(&mut env).await, 120);
+
+    // Only the destination owner burns the receipts when initializing the withdrawal
+    assert!(env.initialize_unlock(&payer, &staked).await.is_err());
+    env.initialize_unlock(&owner, &staked).await.unwrap();
+    assert_eq!(assert_receipt_invariant(&mut env).await, 80);
+    assert_eq!(env.token_balance(&receipt_token_account).await, 0);
+
+    env.warp_forward(604_800).await;
+    env.unlock(&payer, &staked).await.unwrap();
+    assert_eq!(assert_receipt_invariant(&mut env).await, 80);
+}
+
+/// Adds a vesting account with the first layout of the program, holding a single schedule
+/// released to the source token account, and its vesting token account
+async fn add_legacy_lock(
+    env: &mut TestEnv,
+    seed: &[u8; 31],
+    release_time: u64,
+    amount: u64,
+) -> LockAddresses {
+    let addresses = env.lock_addresses(seed);
+    let mut data = vec![0; LEGACY_VESTING_ACCOUNT_LEN];
+    data[..32].copy_from_slice(&env.source_token_account.to_bytes());
+    data[32] = 1;
+    data[33..41].copy_from_slice(&release_time.to_le_bytes());
+    data[41..49].copy_from_slice(&amount.to_le_bytes());
+    let vesting_account = Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: env.program_id,
+        ..Account::default()
+    };
+    env.set_funded_account(&addresses.vesting_account, vesting_account)
+        .await;
+    let vesting_token_account = packed_account(spl_token::state::Account {
+        mint: TOKEN_MINT,
+        owner: addresses.vesting_account,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..spl_token::state::Account::default()
+    });
+    env.set_funded_account(&addresses.vesting_token_account, vesting_token_account)
+        .await;
+    addresses
+}
+
+/// The `Unlock` instruction of a lock, with the accounts of the current layout
+fn unlock_instruction(
+    env: &TestEnv,
+    addresses: &LockAddresses,
+    destination: &Pubkey,
+) -> Instruction {
+    instruction::unlock(
+        &env.program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &addresses.vesting_account,
+        &addresses.vesting_token_account,
+        destination,
+        &stats_address(&env.program_id),
+        &registry_address(&env.program_id, &env.owner.pubkey()),
+        addresses.seeds,
+    )
+    .unwrap()
+}
+
+/// Sends an instruction with only the accounts of the first layout of the program
+async fn send_legacy(
+    env: &mut TestEnv,
+    mut instruction: Instruction,
+) -> Result<(), BanksClientError> {
+    instruction.accounts.truncate(5);
+    env.send(&[instruction], &[]).await
+}
+
+#[tokio::test]
+async fn test_legacy_locks() {
+    let mut env = setup(100).await;
+    let program_id = env.program_id;
+    let owner = env.owner.insecure_clone();
+    let destination = env.source_token_account;
+
+    // A matured lock of the first layout is released with its original accounts
+    let lock = add_legacy_lock(&mut env, &[1u8; 31], 1, 40).await;
+    let unlock = unlock_instruction(&env, &lock, &destination);
+    send_legacy(&mut env, unlock.clone()).await.unwrap();
+    assert_eq!(env.token_balance(&destination).await, 140);
+    assert_eq!(env.token_balance(&lock.vesting_token_account).await, 0);
+    assert!(send_legacy(&mut env, unlock).await.is_err());
+
+    // A stake of the first layout starts its 7 day withdrawal period before being released
+    let stake = add_legacy_lock(&mut env, &[2u8; 31], 0, 25).await;
+    let unlock = unlock_instruction(&env, &stake, &destination);
+    assert!(send_legacy(&mut env, unlock.clone()).await.is_err());
+    let receipt_mint = receipt_mint_address(&program_id);
+    let initialize_unlock = instruction::initialize_unlock(
+        &program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &stake.vesting_account,
+        &stake.vesting_token_account,
+        &destination,
+        &stats_address(&program_id),
+        &receipt_mint,
+        &get_associated_token_address(&owner.pubkey(), &receipt_mint),
+        &owner.pubkey(),
+        stake.seeds,
+    )
+    .unwrap();
+    send_legacy(&mut env, initialize_unlock.clone())
+        .await
+        .unwrap();
+    assert!(send_legacy(&mut env, initialize_unlock).await.is_err());
+    assert!(send_legacy(&mut env, unlock.clone()).await.is_err());
+    env.warp_forward(604_800).await;
+    send_legacy(&mut env, unlock).await.unwrap();
+    assert_eq!(env.token_balance(&destination).await, 165);
+
+    // The destination of the header is enforced
+    let lock = add_legacy_lock(&mut env, &[3u8; 31], 1, 10).await;
+    let unlock = unlock_instruction(&env, &lock, &Pubkey::new_unique());
+    assert!(send_legacy(&mut env, unlock).await.is_err());
+
+    // UnlockMany skips the locks of the first layout, releasing the others
+    let payer = env.payer();
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 10,
+    };
+    let addresses = env.lock(4, schedule, LockOptions::default()).await.unwrap();
+    assert_eq!(env.token_balance(&destination).await, 155);
+    env.warp_forward(7_776_000).await;
+    let unlocked_locks: Vec<UnlockedLock> = [&lock, &addresses]
+        .iter()
+        .map(|addresses| UnlockedLock {
+            vesting_account_key: addresses.vesting_account,
+            vesting_token_account_key: addresses.vesting_token_account,
+            destination_token_account_key: destination,
+            registry_account_key: registry_address(&program_id, &owner.pubkey()),
+            seeds: addresses.seeds,
+        })
+        .collect();
+    let unlock_many = instruction::unlock_many(
+        &program_id,
+        &spl_token::id(),
+        &sysvar::clock::id(),
+        &stats_address(&program_id),
+        &payer.pubkey(),
+        &unlocked_locks,
+    )
+    .unwrap();
+    env.send(&[unlock_many], &[]).await.unwrap();
+    assert_eq!(env.token_balance(&destination).await, 165);
+    assert_eq!(env.token_balance(&lock.vesting_token_account).await, 10);
+
+    // The other instructions reject them instead of reading past their end
+    let close = instruction::close(
+        &program_id,
+        &spl_token::id(),
+        &lock.vesting_account,
+        &lock.vesting_token_account,
+        &destination,
+        &owner.pubkey(),
+        &registry_address(&program_id, &owner.pubkey()),
+        lock.seeds,
+    )
+    .unwrap();
+    assert_eq!(
+        env.send(&[close], &[&owner]).await.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidAccountData)
+    );
+    let unlock = unlock_instruction(&env, &lock, &destination);
+    send_legacy(&mut env, unlock).await.unwrap();
+    assert_eq!(env.token_balance(&destination).await, 175);
+}
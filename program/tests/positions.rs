@@ -0,0 +1,718 @@
+#![cfg(feature = "test-bpf")]
+mod common;
+
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+use token_vesting::{
+    instruction::Schedule,
+    penalty::early_unlock_penalty,
+    state::{LockMode, PositionRegistry, MAX_POSITIONS, RESERVED_POSITIONS},
+};
+
+use common::{setup, LockAddresses, LockOptions, TestEnv};
+
+/// The open positions of a registry, with their index
+fn positions(registry: &PositionRegistry) -> Vec<(Pubkey, u64)> {
+    registry
+        .open_positions()
+        .iter()
+        .map(|position| (position.vesting_account, position.index))
+        .collect()
+}
+
+/// The vesting accounts of the open positions of a registry
+fn registered(registry: &PositionRegistry) -> Vec<Pubkey> {
+    registry
+        .open_positions()
+        .iter()
+        .map(|position| position.vesting_account)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_position_registry() {
+    let mut env = setup(100).await;
+    let payer = env.payer();
+    let wallet = Keypair::new();
+    let beneficiary = env.create_associated_token_account(&wallet.pubkey()).await;
+    let source_token_account = env.source_token_account;
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 30,
+    };
+
+    let mut locks = vec![];
+    for (seed, revocation_authority) in [(1, Some(payer.pubkey())), (2, None)] {
+        let options = LockOptions {
+            beneficiary_token_account: Some(beneficiary),
+            revocation_authority,
+            ..LockOptions::default()
+        };
+        locks.push(env.lock(seed, schedule.clone(), options).await.unwrap());
+    }
+    let registry = env.get_registry(&wallet.pubkey()).await.unwrap();
+    assert_eq!(registry.owner, wallet.pubkey());
+    assert_eq!(
+        positions(&registry),
+        vec![(locks[0].vesting_account, 0), (locks[1].vesting_account, 1)]
+    );
+    // The locks of the source owner are not in its registry
+    let owner_pubkey = env.owner.pubkey();
+    assert!(env.get_registry(&owner_pubkey).await.is_none());
+
+    // Only locks without tokens left can be closed, by the owner of their destination
+    assert!(env.close(&wallet, &locks[0]).await.is_err());
+    env.revoke(&payer, &locks[0], &source_token_account)
+        .await
+        .unwrap();
+    assert!(env.close(&payer, &locks[0]).await.is_err());
+    let lamports = env.lamports(&wallet.pubkey()).await;
+    env.close(&wallet, &locks[0]).await.unwrap();
+    assert!(env.get_account(&locks[0].vesting_account).await.is_none());
+    assert!(env
+        .get_account(&locks[0].vesting_token_account)
+        .await
+        .is_none());
+    assert!(env.lamports(&wallet.pubkey()).await > lamports);
+
+    // Position indexes are never reused
+    let options = LockOptions {
+        beneficiary_token_account: Some(beneficiary),
+        ..LockOptions::default()
+    };
+    let addresses = env.lock(3, schedule, options).await.unwrap();
+    let registry = env.get_registry(&wallet.pubkey()).await.unwrap();
+    assert_eq!(
+        positions(&registry),
+        vec![
+            (locks[1].vesting_account, 1),
+            (addresses.vesting_account, 2)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_registry_reserved_positions() {
+    let mut env = setup(1_000).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+    let wallet = Keypair::new();
+    let wallet_token_account = env.create_associated_token_account(&wallet.pubkey()).await;
+    let transfer = spl_token::instruction::transfer(
+        &spl_token::id(),
+        &env.source_token_account,
+        &wallet_token_account,
+        &owner.pubkey(),
+        &[],
+        10,
+    )
+    .unwrap();
+    env.send(&[transfer], &[&owner]).await.unwrap();
+
+    // Another wallet fills every position of the registry it can reach, the extra lock is
+    // created without being registered, even under a wallet cap
+    let treasury = env.create_associated_token_account(&payer.pubkey()).await;
+    env.init_config(&payer, &treasury, &payer.pubkey(), 0, LockMode::Timestamp)
+        .await
+        .unwrap();
+    env.set_limits(&payer, 0, 1_000, [0; 5]).await.unwrap();
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 1,
+    };
+    let schedules = vec![(wallet.pubkey(), schedule); MAX_POSITIONS - RESERVED_POSITIONS + 1];
+    let locks = env.lock_batch(1, &schedules).await.unwrap();
+    let registry = env.get_registry(&wallet.pubkey()).await.unwrap();
+    assert_eq!(
+        registry.open_positions().len(),
+        MAX_POSITIONS - RESERVED_POSITIONS
+    );
+    assert_eq!(
+        registry.locked_amount(),
+        (MAX_POSITIONS - RESERVED_POSITIONS) as u64
+    );
+    let unregistered = &locks[MAX_POSITIONS - RESERVED_POSITIONS];
+    assert!(env
+        .get_account(&unregistered.vesting_account)
+        .await
+        .is_some());
+
+    // The owner still registers its own locks in the reserved positions
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 10,
+    };
+    let options = LockOptions {
+        source_token_account: Some(wallet_token_account),
+        ..LockOptions::default()
+    };
+    let addresses = env
+        .lock_with_signers(&wallet.pubkey(), &[&wallet], &[7u8; 31], schedule, options)
+        .await
+        .unwrap();
+    let registry = env.get_registry(&wallet.pubkey()).await.unwrap();
+    assert_eq!(
+        registry.open_positions().last().unwrap().vesting_account,
+        addresses.vesting_account
+    );
+}
+
+#[tokio::test]
+async fn test_position_token() {
+    let mut env = setup(100).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+    let buyer = Keypair::new();
+    let buyer_token_account = env.create_associated_token_account(&buyer.pubkey()).await;
+
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 60,
+    };
+    let addresses = env.lock(4, schedule, LockOptions::default()).await.unwrap();
+    let position_mint = Keypair::new();
+    let owner_position_account = env
+        .mint_position(&owner, &addresses, &position_mint)
+        .await
+        .unwrap();
+    assert_eq!(env.token_balance(&owner_position_account).await, 1);
+    let info = env.get_lock(&addresses).await;
+    assert_eq!(info.header.position_mint(), Some(position_mint.pubkey()));
+    // The lock follows its position token, and stays in the registry of the owner
+    let registry = env.get_registry(&owner.pubkey()).await.unwrap();
+    assert_eq!(registry.open_positions().len(), 1);
+
+    // No other position token can be minted
+    assert!(env
+        .mint_position(&owner, &addresses, &Keypair::new())
+        .await
+        .is_err());
+
+    // Sell the position token
+    let buyer_position_account =
+        get_associated_token_address(&buyer.pubkey(), &position_mint.pubkey());
+    let instructions = [
+        create_associated_token_account(
+            &payer.pubkey(),
+            &buyer.pubkey(),
+            &position_mint.pubkey(),
+            &spl_token::id(),
+        ),
+        spl_token::instruction::transfer(
+            &spl_token::id(),
+            &owner_position_account,
+            &buyer_position_account,
+            &owner.pubkey(),
+            &[],
+            1,
+        )
+        .unwrap(),
+    ];
+    env.send(&instructions, &[&owner]).await.unwrap();
+
+    env.warp_forward(7_776_000).await;
+    // Only the holder of the position token can release the lock
+    assert!(env.unlock(&payer, &addresses).await.is_err());
+    assert!(env.unlock(&owner, &addresses).await.is_err());
+    env.unlock(&buyer, &addresses).await.unwrap();
+    assert_eq!(env.token_balance(&buyer_token_account).await, 60);
+    assert_eq!(env.token_balance(&buyer_position_account).await, 0);
+
+    // The buyer then owns the destination of the lock, and closes it
+    let info = env.get_lock(&addresses).await;
+    assert_eq!(info.header.destination_address, buyer_token_account);
+    env.close(&buyer, &addresses).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_position_on_create() {
+    let mut env = setup(100).await;
+    let payer = env.payer();
+    let beneficiary = Keypair::new();
+    let keeper = Keypair::new();
+    let beneficiary_token_account = env
+        .create_associated_token_account(&beneficiary.pubkey())
+        .await;
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 40,
+    };
+
+    // Revocable locks can not have a position token
+    let options = LockOptions {
+        beneficiary_token_account: Some(beneficiary_token_account),
+        revocation_authority: Some(payer.pubkey()),
+        position: true,
+        ..LockOptions::default()
+    };
+    assert!(env.lock(1, schedule.clone(), options).await.is_err());
+
+    // The position token is minted to the beneficiary along with the lock
+    let options = LockOptions {
+        beneficiary_token_account: Some(beneficiary_token_account),
+        keeper_tip: 1_000_000,
+        position: true,
+        ..LockOptions::default()
+    };
+    let addresses = env.lock(2, schedule, options).await.unwrap();
+    let info = env.get_lock(&addresses).await;
+    let position_mint = info.header.position_mint().unwrap();
+    let position_account = get_associated_token_address(&beneficiary.pubkey(), &position_mint);
+    assert_eq!(env.token_balance(&position_account).await, 1);
+    let registry = env.get_registry(&beneficiary.pubkey()).await.unwrap();
+    assert_eq!(registry.open_positions().len(), 1);
+
+    // Keepers skip the lock, which only the holder of the position token releases
+    env.warp_forward(7_776_000).await;
+    env.unlock_many(&keeper.pubkey(), std::slice::from_ref(&addresses))
+        .await
+        .unwrap();
+    assert!(env.unlock(&keeper, &addresses).await.is_err());
+    let info = env.get_lock(&addresses).await;
+    assert_eq!(info.schedule.amount, 40);
+    assert_eq!(info.header.keeper_tip, 1_000_000);
+
+    // The holder earns the keeper tip of the lock it releases
+    let beneficiary_balance = env.lamports(&beneficiary.pubkey()).await;
+    env.unlock(&beneficiary, &addresses).await.unwrap();
+    assert_eq!(env.token_balance(&beneficiary_token_account).await, 40);
+    assert_eq!(env.token_balance(&position_account).await, 0);
+    assert_eq!(
+        env.lamports(&beneficiary.pubkey()).await,
+        beneficiary_balance + 1_000_000
+    );
+}
+
+#[tokio::test]
+async fn test_early_unlock() {
+    let mut env = setup(1_000).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+    let treasury = env.create_associated_token_account(&payer.pubkey()).await;
+    let source_token_account = env.source_token_account;
+
+    let mut locks = vec![];
+    for (seed, revocation_authority) in [(1, None), (2, None), (3, Some(payer.pubkey()))] {
+        let schedule = Schedule {
+            time_delta: 31_104_000,
+            amount: 200,
+        };
+        let options = LockOptions {
+            revocation_authority,
+            ..LockOptions::default()
+        };
+        locks.push(env.lock(seed, schedule, options).await.unwrap());
+    }
+
+    // Without config, locks can not be released early
+    assert!(env.early_unlock(&owner, &locks[0]).await.is_err());
+
+    env.init_config(
+        &payer,
+        &treasury,
+        &payer.pubkey(),
+        5_000,
+        LockMode::Timestamp,
+    )
+    .await
+    .unwrap();
+    assert_eq!(env.get_config().await.unwrap().admin, payer.pubkey());
+
+    // Only the owner of the destination can release a lock early
+    env.warp_forward(15_552_000).await;
+    let stranger = Keypair::new();
+    assert!(env.early_unlock(&stranger, &locks[0]).await.is_err());
+
+    let info = env.get_lock(&locks[0]).await;
+    let clock = env.get_clock().await;
+    let penalty = early_unlock_penalty(
+        200,
+        info.schedule.release_time - clock.unix_timestamp as u64,
+        5_000,
+    );
+    assert!(penalty > 0 && penalty < 100);
+    env.early_unlock(&owner, &locks[0]).await.unwrap();
+    assert_eq!(env.token_balance(&treasury).await, penalty);
+    assert_eq!(
+        env.token_balance(&source_token_account).await,
+        400 + 200 - penalty
+    );
+
+    // A released lock can not be released again, and revocable locks not at all
+    assert!(env.early_unlock(&owner, &locks[0]).await.is_err());
+    assert!(env.early_unlock(&owner, &locks[2]).await.is_err());
+
+    // Only the admin updates the penalty
+    assert!(env.update_penalty(&stranger, &treasury, 0).await.is_err());
+    env.update_penalty(&payer, &treasury, 0).await.unwrap();
+    env.early_unlock(&owner, &locks[1]).await.unwrap();
+    assert_eq!(env.token_balance(&treasury).await, penalty);
+    assert_eq!(
+        env.token_balance(&source_token_account).await,
+        600 + 200 - penalty
+    );
+}
+
+/// Checks that the locks hold exactly the tokens of their schedules and of the stats, returning
+/// the total
+async fn assert_value_conserved(env: &mut TestEnv, locks: &[LockAddresses]) -> u64 {
+    let mut scheduled = 0;
+    let mut held = 0;
+    for addresses in locks {
+        scheduled += env.get_lock(addresses).await.schedule.amount;
+        held += env.token_balance(&addresses.vesting_token_account).await;
+    }
+    let stats = env.get_stats().await;
+    assert_eq!(scheduled, held);
+    assert_eq!(held, stats.total_locked());
+    assert_eq!(
+        locks.len() as u64,
+        stats
+            .tiers
+            .iter()
+            .map(|tier| tier.active_locks)
+            .sum::<u64>()
+    );
+    held
+}
+
+#[tokio::test]
+async fn test_split_and_merge() {
+    let mut env = setup(100).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+    let source_token_account = env.source_token_account;
+    let wallet = Keypair::new();
+    let gift = env.create_associated_token_account(&wallet.pubkey()).await;
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 100,
+    };
+    let lock = env.lock(1, schedule, LockOptions::default()).await.unwrap();
+    assert_eq!(
+        assert_value_conserved(&mut env, std::slice::from_ref(&lock)).await,
+        100
+    );
+
+    // Splits move part of the amount, only when signed by the destination owner
+    for amount in [0, 100] {
+        assert!(env.split(&owner, &lock, 2, amount, None).await.is_err());
+    }
+    assert!(env.split(&payer, &lock, 2, 30, None).await.is_err());
+    let kept = env.split(&owner, &lock, 2, 30, None).await.unwrap();
+    let gifted = env.split(&owner, &lock, 3, 20, Some(gift)).await.unwrap();
+    assert_eq!(
+        assert_value_conserved(&mut env, &[lock.clone(), kept.clone(), gifted.clone()]).await,
+        100
+    );
+    let original = env.get_lock(&lock).await;
+    let info = env.get_lock(&gifted).await;
+    assert_eq!(info.header.destination_address, gift);
+    assert_eq!(info.schedule.release_time, original.schedule.release_time);
+    assert_eq!(info.schedule.amount, 20);
+    let registry = env.get_registry(&wallet.pubkey()).await.unwrap();
+    assert_eq!(registered(&registry), vec![gifted.vesting_account]);
+
+    // Locks with another destination can not be merged, nor a lock into itself
+    assert!(env
+        .merge(&owner, &lock, std::slice::from_ref(&gifted))
+        .await
+        .is_err());
+    assert!(env
+        .merge(&owner, &lock, std::slice::from_ref(&lock))
+        .await
+        .is_err());
+    assert_eq!(
+        assert_value_conserved(&mut env, &[lock.clone(), kept.clone(), gifted.clone()]).await,
+        100
+    );
+
+    env.merge(&owner, &lock, std::slice::from_ref(&kept))
+        .await
+        .unwrap();
+    assert!(env.get_account(&kept.vesting_account).await.is_none());
+    assert!(env.get_account(&kept.vesting_token_account).await.is_none());
+    assert_eq!(
+        assert_value_conserved(&mut env, &[lock.clone(), gifted.clone()]).await,
+        100
+    );
+    assert_eq!(env.get_lock(&lock).await.schedule.amount, 80);
+
+    // The merged lock releases everything at the original release time
+    env.warp_forward(7_776_000).await;
+    env.unlock(&payer, &lock).await.unwrap();
+    assert_eq!(env.token_balance(&source_token_account).await, 80);
+    env.unlock(&payer, &gifted).await.unwrap();
+    assert_eq!(env.token_balance(&gift).await, 20);
+}
+
+#[tokio::test]
+async fn test_transfer_position() {
+    let mut env = setup(100).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+    let source_token_account = env.source_token_account;
+    let wallet = Keypair::new();
+    let gift = env.create_associated_token_account(&wallet.pubkey()).await;
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 100,
+    };
+    let lock = env.lock(1, schedule, LockOptions::default()).await.unwrap();
+
+    // Only the owner of the destination can transfer the lock
+    assert!(env
+        .transfer_position(&payer, &lock, &gift, true)
+        .await
+        .is_err());
+
+    // A transfer requiring acceptance only changes hands once accepted by the new owner
+    env.transfer_position(&owner, &lock, &gift, true)
+        .await
+        .unwrap();
+    let info = env.get_lock(&lock).await;
+    assert_eq!(info.header.destination_address, source_token_account);
+    assert_eq!(info.header.pending_destination(), Some(gift));
+    assert!(env.accept_position(&payer, &lock).await.is_err());
+    env.accept_position(&wallet, &lock).await.unwrap();
+    let info = env.get_lock(&lock).await;
+    assert_eq!(info.header.destination_address, gift);
+    assert_eq!(info.header.pending_destination(), None);
+    assert_eq!(info.header.owner, wallet.pubkey());
+    let registry = env.get_registry(&wallet.pubkey()).await.unwrap();
+    assert_eq!(registered(&registry), vec![lock.vesting_account]);
+    let registry = env.get_registry(&owner.pubkey()).await.unwrap();
+    assert!(registered(&registry).is_empty());
+
+    // A pending transfer is cancelled by a transfer to the destination of the lock
+    env.transfer_position(&wallet, &lock, &source_token_account, true)
+        .await
+        .unwrap();
+    env.transfer_position(&wallet, &lock, &gift, true)
+        .await
+        .unwrap();
+    assert_eq!(env.get_lock(&lock).await.header.pending_destination(), None);
+    assert!(env.accept_position(&owner, &lock).await.is_err());
+
+    // Without acceptance, the lock changes hands at once
+    env.transfer_position(&wallet, &lock, &source_token_account, false)
+        .await
+        .unwrap();
+    let info = env.get_lock(&lock).await;
+    assert_eq!(info.header.destination_address, source_token_account);
+    let registry = env.get_registry(&wallet.pubkey()).await.unwrap();
+    assert!(registered(&registry).is_empty());
+    let registry = env.get_registry(&owner.pubkey()).await.unwrap();
+    assert_eq!(registered(&registry), vec![lock.vesting_account]);
+
+    // Released locks can not be transferred
+    env.warp_forward(7_776_000).await;
+    env.unlock(&payer, &lock).await.unwrap();
+    assert_eq!(env.token_balance(&source_token_account).await, 100);
+    assert!(env
+        .transfer_position(&owner, &lock, &gift, false)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_transfer_unlocked_stake() {
+    let mut env = setup(100).await;
+    let owner = env.owner.insecure_clone();
+    let wallet = Pubkey::new_unique();
+    let gift = env.create_associated_token_account(&wallet).await;
+    let stake = env.lock_stake(1, 100, None).await.unwrap();
+
+    // The receipts of a stake stay with its owner, who keeps the stake until its withdrawal
+    assert!(env
+        .transfer_position(&owner, &stake, &gift, false)
+        .await
+        .is_err());
+    assert!(env.split(&owner, &stake, 2, 40, Some(gift)).await.is_err());
+    env.split(&owner, &stake, 2, 40, None).await.unwrap();
+
+    // Once the receipts are burned, the stake changes hands
+    env.initialize_unlock(&owner, &stake).await.unwrap();
+    env.transfer_position(&owner, &stake, &gift, false)
+        .await
+        .unwrap();
+    let info = env.get_lock(&stake).await;
+    assert_eq!(info.header.destination_address, gift);
+    assert_eq!(info.header.owner, wallet);
+}
+
+#[tokio::test]
+async fn test_unlock_many() {
+    let mut env = setup(100).await;
+    let payer = env.payer();
+    let source_token_account = env.source_token_account;
+    let wallet = Keypair::new();
+    let beneficiary = env.create_associated_token_account(&wallet.pubkey()).await;
+
+    let mut locks = vec![];
+    for (seed, beneficiary_token_account, time_delta, amount) in [
+        (1, None, 7_776_000, 10),
+        (2, Some(beneficiary), 7_776_000, 20),
+        (3, None, 15_552_000, 30),
+        (4, None, 0, 40),
+    ] {
+        let options = LockOptions {
+            beneficiary_token_account,
+            ..LockOptions::default()
+        };
+        let schedule = Schedule { time_delta, amount };
+        locks.push(env.lock(seed, schedule, options).await.unwrap());
+    }
+    assert!(env.unlock_many(&payer.pubkey(), &[]).await.is_err());
+
+    // Only the matured locks are released, to their own destination
+    env.warp_forward(7_776_000).await;
+    env.unlock_many(&payer.pubkey(), &locks).await.unwrap();
+    assert_eq!(env.token_balance(&source_token_account).await, 10);
+    assert_eq!(env.token_balance(&beneficiary).await, 20);
+    let stats = env.get_stats().await;
+    assert_eq!(stats.tiers[1].total_locked, 0);
+    assert_eq!(stats.tiers[2].total_locked, 30);
+    assert_eq!(stats.tiers[0].total_locked, 40);
+
+    // Released locks are skipped the next time
+    env.warp_forward(7_776_000).await;
+    env.unlock_many(&payer.pubkey(), &locks).await.unwrap();
+    assert_eq!(env.token_balance(&source_token_account).await, 40);
+    assert_eq!(env.token_balance(&beneficiary).await, 20);
+    let stats = env.get_stats().await;
+    assert_eq!(stats.tiers[2].total_locked, 0);
+    assert_eq!(stats.tiers[0].total_locked, 40);
+}
+
+#[tokio::test]
+async fn test_keeper_tip() {
+    let mut env = setup(100).await;
+    let source_token_account = env.source_token_account;
+    let keeper = Keypair::new();
+    env.fund(&keeper.pubkey()).await;
+
+    let mut locks = vec![];
+    for (seed, time_delta, keeper_tip) in [
+        (1, 7_776_000, 1_000_000),
+        (2, 7_776_000, 0),
+        (3, 15_552_000, 2_000_000),
+    ] {
+        let schedule = Schedule {
+            time_delta,
+            amount: 10,
+        };
+        let options = LockOptions {
+            keeper_tip,
+            ..LockOptions::default()
+        };
+        locks.push(env.lock(seed, schedule, options).await.unwrap());
+    }
+
+    // The vesting accounts hold their keeper tip on top of their rent, and their seeds
+    let rent = env.lamports(&locks[1].vesting_account).await;
+    assert_eq!(
+        env.lamports(&locks[0].vesting_account).await,
+        rent + 1_000_000
+    );
+    let info = env.get_lock(&locks[0]).await;
+    assert_eq!(info.header.keeper_tip, 1_000_000);
+    assert_eq!(info.header.seeds, locks[0].seeds);
+
+    // The keeper earns the tips of the locks it releases with `UnlockMany`
+    env.warp_forward(7_776_000).await;
+    let keeper_balance = env.lamports(&keeper.pubkey()).await;
+    env.unlock_many(&keeper.pubkey(), &locks).await.unwrap();
+    assert_eq!(env.token_balance(&source_token_account).await, 90);
+    assert_eq!(env.lamports(&locks[0].vesting_account).await, rent);
+    assert_eq!(
+        env.lamports(&keeper.pubkey()).await,
+        keeper_balance + 1_000_000
+    );
+
+    // Anyone can release a matured lock with `Unlock` and earn its tip
+    env.warp_forward(7_776_000).await;
+    env.unlock(&keeper, &locks[2]).await.unwrap();
+    assert_eq!(env.token_balance(&source_token_account).await, 100);
+    assert_eq!(env.lamports(&locks[2].vesting_account).await, rent);
+    assert_eq!(
+        env.lamports(&keeper.pubkey()).await,
+        keeper_balance + 3_000_000
+    );
+}
+
+#[tokio::test]
+async fn test_auto_renew() {
+    let mut env = setup(100).await;
+    let payer = env.payer();
+    let owner = env.owner.insecure_clone();
+    let source_token_account = env.source_token_account;
+    let renewed = LockOptions {
+        auto_renew: true,
+        ..LockOptions::default()
+    };
+
+    // Unlocked stakes have no lock period to renew
+    let schedule = Schedule {
+        time_delta: 0,
+        amount: 40,
+    };
+    assert!(env.lock(1, schedule, renewed.clone()).await.is_err());
+
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 60,
+    };
+    let addresses = env.lock(2, schedule, renewed.clone()).await.unwrap();
+    let info = env.get_lock(&addresses).await;
+    assert!(info.header.auto_renew);
+    let release_time = info.schedule.release_time;
+
+    // At maturity, the lock renews for another period instead of being released
+    env.warp_forward(7_776_000).await;
+    assert!(env.unlock(&payer, &addresses).await.is_err());
+    let clock = env.get_clock().await;
+    assert_eq!(
+        info.header
+            .release_time(&info.schedule, clock.unix_timestamp as u64),
+        release_time + 7_776_000
+    );
+
+    // Renewals can only be stopped by the owner, during the notice or grace period
+    env.warp_forward(604_800).await;
+    assert!(env.opt_out_renewal(&owner, &addresses).await.is_err());
+    env.warp_forward(7_776_000 - 2 * 604_800).await;
+    let stranger = Keypair::new();
+    assert!(env.opt_out_renewal(&stranger, &addresses).await.is_err());
+    env.opt_out_renewal(&owner, &addresses).await.unwrap();
+    let info = env.get_lock(&addresses).await;
+    assert!(!info.header.auto_renew);
+    assert_eq!(info.schedule.release_time, release_time + 7_776_000);
+
+    // The lock is then released at its next maturity
+    assert!(env.unlock(&payer, &addresses).await.is_err());
+    env.warp_forward(604_800).await;
+    env.unlock(&payer, &addresses).await.unwrap();
+    assert_eq!(env.token_balance(&source_token_account).await, 100);
+
+    // Just after a maturity, the owner can still opt out and release the lock at that maturity
+    let schedule = Schedule {
+        time_delta: 7_776_000,
+        amount: 40,
+    };
+    let addresses = env.lock(3, schedule, renewed).await.unwrap();
+    let release_time = env.get_lock(&addresses).await.schedule.release_time;
+    env.warp_forward(7_776_000 + 3_600).await;
+    assert!(env.unlock(&payer, &addresses).await.is_err());
+    env.opt_out_renewal(&owner, &addresses).await.unwrap();
+    let info = env.get_lock(&addresses).await;
+    assert!(!info.header.auto_renew);
+    assert_eq!(info.schedule.release_time, release_time);
+    env.unlock(&payer, &addresses).await.unwrap();
+    assert_eq!(env.token_balance(&source_token_account).await, 100);
+}